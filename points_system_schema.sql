@@ -5,6 +5,7 @@ ALTER TABLE user ADD FIELD daily_checkin_streak TYPE int DEFAULT 0;
 ALTER TABLE user ADD FIELD last_checkin_date TYPE option<int>;
 ALTER TABLE user ADD FIELD total_invites TYPE int DEFAULT 0;
 ALTER TABLE user ADD FIELD invited_by TYPE option<string>;
+ALTER TABLE user ADD FIELD lucky_boost TYPE option<object>;
 
 -- 创建钱包交易记录表
 DEFINE TABLE wallet_tx SCHEMAFULL;
@@ -16,6 +17,8 @@ DEFINE FIELD currency ON wallet_tx TYPE string ASSERT $value IN ['HP', 'LC'];
 DEFINE FIELD timestamp ON wallet_tx TYPE int;
 DEFINE FIELD related_entity_id ON wallet_tx TYPE option<string>;
 DEFINE FIELD remark ON wallet_tx TYPE option<string>;
+DEFINE FIELD lucky_card_id ON wallet_tx TYPE option<string>;
+DEFINE FIELD boost_multiplier ON wallet_tx TYPE option<float>;
 
 -- 创建礼物表
 DEFINE TABLE gift SCHEMAFULL;
@@ -49,6 +52,7 @@ DEFINE FIELD created_at ON lucky_card TYPE int;
 DEFINE FIELD expires_at ON lucky_card TYPE int;
 DEFINE FIELD is_used ON lucky_card TYPE bool DEFAULT false;
 DEFINE FIELD used_at ON lucky_card TYPE option<int>;
+DEFINE FIELD is_expired ON lucky_card TYPE bool DEFAULT false;
 DEFINE FIELD issued_by_ai_id ON lucky_card TYPE option<string>;
 
 -- 创建积分商城商品表
@@ -0,0 +1,152 @@
+use time::OffsetDateTime;
+
+use crate::models::achievement::{Achievement, AchievementGrant, AchievementMetric, UserAchievement};
+
+use super::points::CREDIT_HP_STATEMENTS;
+use super::surreal::{thrown_error, Database};
+
+impl Database {
+    // ==================== 成就 ====================
+
+    // 创建成就
+    pub async fn create_achievement(&self, achievement: &Achievement) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<Achievement>>(("achievement", &achievement.id))
+            .content(achievement)
+            .await?;
+        Ok(())
+    }
+
+    // 更新成就
+    pub async fn update_achievement(&self, achievement: &Achievement) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<Achievement>>(("achievement", &achievement.id))
+            .content(achievement)
+            .await?;
+        Ok(())
+    }
+
+    // 获取成就
+    pub async fn get_achievement(&self, achievement_id: &str) -> Result<Option<Achievement>, surrealdb::Error> {
+        self.client.select(("achievement", achievement_id)).await
+    }
+
+    // 获取所有成就
+    pub async fn get_achievements(&self) -> Result<Vec<Achievement>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM achievement ORDER BY metric ASC, target ASC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取某指标下启用的成就
+    pub async fn get_active_achievements_by_metric(&self, metric: &AchievementMetric) -> Result<Vec<Achievement>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM achievement WHERE metric = $metric AND is_active = true ORDER BY target ASC")
+            .bind(("metric", metric))
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 成就进度 ====================
+
+    // 获取用户所有成就进度
+    pub async fn get_user_achievements(&self, user_id: &str) -> Result<Vec<UserAchievement>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM user_achievement WHERE user_id = $user_id")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 保存成就进度，已完成的成就不再更新；返回是否写入，用于保证奖励只发放一次
+    pub async fn save_user_achievement(&self, record: &UserAchievement) -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('user_achievement', $record.id) CONTENT $record
+                WHERE completed_at = NONE
+                    AND (progress = NONE OR progress < $record.progress OR $record.completed_at != NONE)
+            ")
+            .bind(("record", record))
+            .await?;
+
+        let saved: Vec<UserAchievement> = result.take(0)?;
+        Ok(!saved.is_empty())
+    }
+
+    // 获取用户已完成但奖励尚未发放的成就
+    pub async fn get_pending_achievement_rewards(&self, user_id: &str) -> Result<Vec<UserAchievement>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM user_achievement WHERE user_id = $user_id AND reward_pending = true")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 发放成就奖励：清除待发放标记与积分、卡券、称号在同一事务中写入，已发放时返回 false
+    pub async fn grant_user_achievement(&self, record_id: &str, grant: &AchievementGrant) -> Result<bool, surrealdb::Error> {
+        let lot = match &grant.tx {
+            Some(tx) => Some(self.hp_lot_for(tx).await?),
+            None => None,
+        };
+
+        let mut statements = String::new();
+        if grant.tx.is_some() {
+            statements.push_str(CREDIT_HP_STATEMENTS);
+        }
+        if grant.coupon.is_some() {
+            statements.push_str("CREATE type::thing('coupon', $coupon.id) CONTENT $coupon;\n");
+        }
+        if grant.title.is_some() {
+            statements.push_str("CREATE type::thing('inventory_item', $title.id) CONTENT $title;\n");
+        }
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                LET $granted = UPDATE type::thing('user_achievement', $record_id) SET
+                    reward_pending = false,
+                    granted_at = $now
+                WHERE reward_pending = true;
+                IF array::len($granted) = 0 {{
+                    THROW 'achievement already granted';
+                }};
+
+                {}
+
+                COMMIT TRANSACTION;
+            ", statements))
+            .bind(("record_id", record_id))
+            .bind(("tx", &grant.tx))
+            .bind(("lot", lot))
+            .bind(("coupon", &grant.coupon))
+            .bind(("title", &grant.title))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+
+        Ok(thrown_error(&mut result, &["achievement already granted"])?.is_none())
+    }
+
+    // 统计用户邀请码被使用的总次数
+    pub async fn count_user_invitees(&self, user_id: &str) -> Result<u32, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("RETURN math::sum((SELECT VALUE array::len(used_by) FROM invite WHERE creator_id = $user_id))")
+            .bind(("user_id", user_id))
+            .await?;
+
+        let total: Option<u32> = result.take(0)?;
+        Ok(total.unwrap_or(0))
+    }
+}
@@ -32,6 +32,33 @@ impl Database {
         Ok(messages.into_iter().next())
     }
     
+    // 私聊会话中某用户发出的消息计数加一，返回加一后的条数
+    // 计数记录按会话和用户确定ID，首次写入时从已有消息数开始，之后单条语句原子自增
+    pub async fn increment_chat_message_count(&self, chat_identify: &str, from_user: &str) -> Result<u32, Error> {
+        let sql = "
+            UPDATE type::thing('chat_message_counter', $counter_id) SET
+                chat_identify = $chat_identify,
+                from_user = $from_user,
+                count = IF count = NONE THEN
+                    (SELECT count() AS count FROM message
+                        WHERE chat_identify = $chat_identify AND from_user = $from_user GROUP ALL)[0].count ?? 0
+                ELSE
+                    count + 1
+                END
+            RETURN VALUE count;
+        ";
+        
+        let mut response = self.client
+            .query(sql)
+            .bind(("counter_id", format!("{}_{}", chat_identify, from_user)))
+            .bind(("chat_identify", chat_identify))
+            .bind(("from_user", from_user))
+            .await?;
+        let count: Option<u32> = response.take(0)?;
+        
+        Ok(count.unwrap_or(0))
+    }
+    
     pub async fn get_chat_messages(&self, chat_identify: &str, limit: u32, offset: u32) -> Result<Vec<Message>, Error> {
         let sql = "
             SELECT * FROM message 
@@ -287,6 +314,16 @@ impl Database {
         Ok(friends.into_iter().next())
     }
     
+    // 双方是否互为好友，任一方拉黑或未通过申请时返回 false
+    pub async fn are_friends(&self, user_id: &str, other_id: &str) -> Result<bool, Error> {
+        let is_normal = |status: Option<i32>| status == Some(FriendStatus::Normal as i32);
+        
+        let forward = self.get_friend(user_id, other_id).await?.map(|friend| friend.status);
+        let backward = self.get_friend(other_id, user_id).await?.map(|friend| friend.status);
+        
+        Ok(is_normal(forward) && is_normal(backward))
+    }
+    
     pub async fn update_friend_status(&self, friend_id: &str, status: i32) -> Result<(), Error> {
         let sql = "UPDATE friend SET status = $status WHERE id = $friend_id";
         
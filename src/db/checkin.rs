@@ -0,0 +1,161 @@
+use time::OffsetDateTime;
+
+use crate::models::checkin::{CheckinRecord, CheckinSchedule};
+use crate::models::earning_rule::EarningCredit;
+use crate::models::lucky_card::LUCKY_BOOST_MAX_BONUS_HP;
+
+use super::earning::LUCKY_BOOST_STATEMENTS;
+use super::points::CREDIT_HP_STATEMENTS;
+use super::surreal::{Database, thrown_error};
+
+impl Database {
+    // ==================== 签到记录 ====================
+
+    // 记录当天签到、更新连续天数并发放签到积分，当天已签到时事务回滚返回 Some(false)；
+    // 幸运卡加成在计算后已变化时返回 None，由调用方重新计算
+    pub async fn record_daily_checkin(&self, record: &CheckinRecord, streak: u32, credit: Option<&EarningCredit>)
+        -> Result<Option<bool>, surrealdb::Error> {
+        let tx = credit.map(|credit| &credit.tx);
+        let bonus = credit.map_or(0, |credit| credit.bonus);
+        let lot = match tx {
+            Some(tx) => Some(self.hp_lot_for(tx).await?),
+            None => None,
+        };
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                CREATE type::thing('checkin_record', $record.id) CONTENT $record;
+
+                UPDATE type::thing('user', $record.user_id) SET
+                    daily_checkin_streak = $streak,
+                    last_checkin_date = $record.checked_at,
+                    updated_at = $record.checked_at;
+
+                {}
+                {}
+
+                COMMIT TRANSACTION;
+            ",
+                if bonus > 0 { LUCKY_BOOST_STATEMENTS } else { "" },
+                if tx.is_some() { CREDIT_HP_STATEMENTS } else { "" },
+            ))
+            .bind(("record", record))
+            .bind(("streak", streak))
+            .bind(("tx", tx))
+            .bind(("bonus", bonus))
+            .bind(("max_bonus", LUCKY_BOOST_MAX_BONUS_HP))
+            .bind(("lot", lot))
+            .await?;
+
+        // 签到记录ID按用户和日期生成，当天已签到时创建失败
+        match thrown_error(&mut result, &["lucky boost changed", "already exists", "already contains"])? {
+            Some("lucky boost changed") => Ok(None),
+            Some(_) => Ok(Some(false)),
+            None => Ok(Some(true)),
+        }
+    }
+
+    // 使用补签卡补签，没有补签卡或当天已签到时事务回滚返回 false
+    pub async fn record_makeup_checkin(&self, record: &CheckinRecord, streak: u32, last_checkin_date: Option<i64>)
+        -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $user = UPDATE type::thing('user', $record.user_id) SET checkin_makeup_cards -= 1
+                    WHERE checkin_makeup_cards > 0;
+                IF array::len($user) = 0 {
+                    THROW 'no makeup card';
+                };
+
+                CREATE type::thing('checkin_record', $record.id) CONTENT $record;
+
+                UPDATE type::thing('user', $record.user_id) SET
+                    daily_checkin_streak = $streak,
+                    last_checkin_date = $last_checkin_date,
+                    updated_at = $now;
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("record", record))
+            .bind(("streak", streak))
+            .bind(("last_checkin_date", last_checkin_date))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+
+        Ok(result.take_errors().is_empty())
+    }
+
+    // 获取用户所有签到日期，按日期倒序
+    pub async fn get_checkin_dates(&self, user_id: &str) -> Result<Vec<String>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT VALUE date FROM checkin_record WHERE user_id = $user_id ORDER BY date DESC")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取用户在日期区间内的签到记录
+    pub async fn get_checkin_records_between(&self, user_id: &str, from: &str, to: &str)
+        -> Result<Vec<CheckinRecord>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM checkin_record
+                WHERE user_id = $user_id AND date >= $from AND date <= $to
+                ORDER BY date ASC
+            ")
+            .bind(("user_id", user_id))
+            .bind(("from", from))
+            .bind(("to", to))
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 用户时区 ====================
+
+    // 修改用户时区，距上次修改不足冷却期时不修改并返回 false
+    pub async fn update_user_timezone(&self, user_id: &str, utc_offset_minutes: i32, now: i64, cooldown_secs: i64)
+        -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('user', $user_id) SET
+                    utc_offset_minutes = $offset,
+                    timezone_updated_at = $now,
+                    updated_at = $now
+                WHERE timezone_updated_at = NONE OR timezone_updated_at <= $cutoff
+            ")
+            .bind(("user_id", user_id))
+            .bind(("offset", utc_offset_minutes))
+            .bind(("now", now))
+            .bind(("cutoff", now - cooldown_secs))
+            .await?;
+
+        let updated: Vec<serde_json::Value> = result.take(0)?;
+        Ok(!updated.is_empty())
+    }
+
+    // ==================== 签到奖励配置 ====================
+
+    // 获取签到奖励配置
+    pub async fn get_checkin_schedule(&self) -> Result<Option<CheckinSchedule>, surrealdb::Error> {
+        self.client.select(("checkin_schedule", CheckinSchedule::ID)).await
+    }
+
+    // 保存签到奖励配置
+    pub async fn save_checkin_schedule(&self, schedule: &CheckinSchedule) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<CheckinSchedule>>(("checkin_schedule", &schedule.id))
+            .content(schedule)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,451 @@
+use crate::models::{Coupon, CouponTemplate};
+use crate::models::coupon::{CouponCampaign, CampaignAudience};
+use crate::models::coupon_code::{CouponCode, CouponCodeBatch, CouponCodeRedemption};
+use crate::models::coupon_transfer::CouponTransfer;
+
+use super::surreal::{thrown_error, Database};
+
+impl Database {
+    // ==================== 卡券模板 ====================
+
+    // 创建卡券模板
+    pub async fn create_coupon_template(&self, template: &CouponTemplate) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<CouponTemplate>>(("coupon_template", &template.id))
+            .content(template)
+            .await?;
+        Ok(())
+    }
+
+    // 更新卡券模板
+    pub async fn update_coupon_template(&self, template: &CouponTemplate) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<CouponTemplate>>(("coupon_template", &template.id))
+            .content(template)
+            .await?;
+        Ok(())
+    }
+
+    // 删除卡券模板
+    pub async fn delete_coupon_template(&self, template_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .delete::<Option<CouponTemplate>>(("coupon_template", template_id))
+            .await?;
+        Ok(())
+    }
+
+    // 获取卡券模板
+    pub async fn get_coupon_template(&self, template_id: &str) -> Result<Option<CouponTemplate>, surrealdb::Error> {
+        self.client.select(("coupon_template", template_id)).await
+    }
+
+    // 获取所有卡券模板
+    pub async fn get_coupon_templates(&self) -> Result<Vec<CouponTemplate>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_template ORDER BY created_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 卡券发放活动 ====================
+
+    // 创建发放活动
+    pub async fn create_coupon_campaign(&self, campaign: &CouponCampaign) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<CouponCampaign>>(("coupon_campaign", &campaign.id))
+            .content(campaign)
+            .await?;
+        Ok(())
+    }
+
+    // 更新发放活动（进度）
+    pub async fn update_coupon_campaign(&self, campaign: &CouponCampaign) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<CouponCampaign>>(("coupon_campaign", &campaign.id))
+            .content(campaign)
+            .await?;
+        Ok(())
+    }
+
+    // 获取发放活动
+    pub async fn get_coupon_campaign(&self, campaign_id: &str) -> Result<Option<CouponCampaign>, surrealdb::Error> {
+        self.client.select(("coupon_campaign", campaign_id)).await
+    }
+
+    // 获取所有发放活动
+    pub async fn get_coupon_campaigns(&self) -> Result<Vec<CouponCampaign>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_campaign ORDER BY created_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取尚未执行完的发放活动（进程重启时中断的活动）
+    pub async fn get_unfinished_coupon_campaigns(&self) -> Result<Vec<CouponCampaign>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_campaign WHERE status IN ['Pending', 'Running'] ORDER BY created_at ASC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取发放活动的目标用户ID
+    pub async fn get_campaign_audience(&self, audience: &CampaignAudience) -> Result<Vec<String>, surrealdb::Error> {
+        let mut result = match audience {
+            CampaignAudience::AllUsers => {
+                self.client
+                    .query("SELECT VALUE meta::id(id) FROM user")
+                    .await?
+            }
+            CampaignAudience::VipLevel { level } => {
+                self.client
+                    .query("SELECT VALUE meta::id(id) FROM user WHERE vip_level = $level")
+                    .bind(("level", level.to_string()))
+                    .await?
+            }
+            CampaignAudience::RegisteredBetween { start, end } => {
+                self.client
+                    .query("SELECT VALUE meta::id(id) FROM user WHERE created_at >= $start AND created_at < $end")
+                    .bind(("start", start))
+                    .bind(("end", end))
+                    .await?
+            }
+            CampaignAudience::UserIds { user_ids } => {
+                self.client
+                    .query("SELECT VALUE meta::id(id) FROM user WHERE meta::id(id) INSIDE $user_ids")
+                    .bind(("user_ids", user_ids))
+                    .await?
+            }
+        };
+
+        result.take(0)
+    }
+
+    // 获取已在该活动中领取过卡券的用户ID
+    pub async fn get_campaign_recipients(&self, campaign_id: &str) -> Result<Vec<String>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT VALUE owner_id FROM coupon WHERE campaign_id = $campaign_id")
+            .bind(("campaign_id", campaign_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 兑换码 ====================
+
+    // 创建兑换码批次
+    pub async fn create_coupon_code_batch(&self, batch: &CouponCodeBatch) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<CouponCodeBatch>>(("coupon_code_batch", &batch.id))
+            .content(batch)
+            .await?;
+        Ok(())
+    }
+
+    // 获取兑换码批次
+    pub async fn get_coupon_code_batch(&self, batch_id: &str) -> Result<Option<CouponCodeBatch>, surrealdb::Error> {
+        self.client.select(("coupon_code_batch", batch_id)).await
+    }
+
+    // 获取所有兑换码批次
+    pub async fn get_coupon_code_batches(&self) -> Result<Vec<CouponCodeBatch>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_code_batch ORDER BY created_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 创建兑换码（兑换码重复时返回错误）
+    pub async fn create_coupon_code(&self, code: &CouponCode) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<CouponCode>>(("coupon_code", &code.id))
+            .content(code)
+            .await?;
+        Ok(())
+    }
+
+    // 根据兑换码查询
+    pub async fn get_coupon_code(&self, code: &str) -> Result<Option<CouponCode>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_code WHERE code = $code LIMIT 1")
+            .bind(("code", code))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取批次下的所有兑换码
+    pub async fn get_batch_coupon_codes(&self, batch_id: &str) -> Result<Vec<CouponCode>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_code WHERE batch_id = $batch_id ORDER BY created_at ASC")
+            .bind(("batch_id", batch_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 用户在某批次的兑换次数
+    pub async fn count_user_batch_redemptions(&self, batch_id: &str, user_id: &str) -> Result<u32, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT count() AS count FROM coupon_code_redemption WHERE batch_id = $batch_id AND user_id = $user_id GROUP ALL")
+            .bind(("batch_id", batch_id))
+            .bind(("user_id", user_id))
+            .await?;
+
+        let count: Option<u32> = result.take("count")?;
+        Ok(count.unwrap_or(0))
+    }
+
+    // 在同一事务中校验每人限领、占用兑换次数并发放卡券，返回触发的限制
+    // 兑换记录ID按“批次_用户_序号”生成，并发领取同一序号时只有一个能成功
+    pub async fn claim_coupon_code(
+        &self,
+        batch: &CouponCodeBatch,
+        coupon: &Coupon,
+        redemption: &CouponCodeRedemption,
+        claimed: u32,
+    ) -> Result<Option<&'static str>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $claimed = (SELECT count() AS count FROM coupon_code_redemption
+                    WHERE batch_id = $batch_id AND user_id = $user_id GROUP ALL)[0].count ?? 0;
+                IF $claimed != $expected_claimed OR $claimed >= $per_user_limit {
+                    THROW 'redemption limit reached';
+                };
+
+                LET $code = UPDATE type::thing('coupon_code', $code_id)
+                    SET redeemed_count += 1
+                    WHERE redeemed_count < $max_redemptions;
+                IF array::len($code) = 0 {
+                    THROW 'code fully redeemed';
+                };
+
+                CREATE coupon CONTENT $coupon;
+                CREATE coupon_code_redemption CONTENT $redemption;
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("batch_id", &batch.id))
+            .bind(("user_id", &redemption.user_id))
+            .bind(("expected_claimed", claimed))
+            .bind(("per_user_limit", batch.per_user_limit))
+            .bind(("code_id", &redemption.code_id))
+            .bind(("max_redemptions", batch.max_redemptions))
+            .bind(("coupon", coupon))
+            .bind(("redemption", redemption))
+            .await?;
+
+        thrown_error(&mut result, &["redemption limit reached", "code fully redeemed"])
+    }
+
+    // 批次的所有兑换记录
+    pub async fn get_batch_redemptions(&self, batch_id: &str) -> Result<Vec<CouponCodeRedemption>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_code_redemption WHERE batch_id = $batch_id")
+            .bind(("batch_id", batch_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 付款占用 ====================
+
+    // 付款前原子占用卡券，卡券已被使用、转赠中或已过期时返回 false
+    pub async fn reserve_coupon(&self, coupon_id: &str, user_id: &str, now: i64) -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('coupon', $coupon_id) SET status = 'Used'
+                WHERE owner_id = $user_id AND status = 'Active' AND expires_at > $now AND pending_transfer_id = NONE
+            ")
+            .bind(("coupon_id", coupon_id))
+            .bind(("user_id", user_id))
+            .bind(("now", now))
+            .await?;
+
+        let reserved: Vec<Coupon> = result.take(0)?;
+        Ok(!reserved.is_empty())
+    }
+
+    // 付款失败后释放占用的卡券
+    pub async fn release_coupon(&self, coupon_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('coupon', $coupon_id) SET status = 'Active' WHERE status = 'Used'")
+            .bind(("coupon_id", coupon_id))
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 过期处理 ====================
+
+    // 将已过期的卡券标记为过期，返回处理数量
+    pub async fn expire_coupons(&self, now: i64) -> Result<usize, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("UPDATE coupon SET status = 'Expired' WHERE status = 'Active' AND expires_at <= $now")
+            .bind(("now", now))
+            .await?;
+
+        let expired: Vec<Coupon> = result.take(0)?;
+        Ok(expired.len())
+    }
+
+    // 获取即将过期且尚未提醒的卡券
+    pub async fn get_coupons_expiring_before(&self, now: i64, before: i64) -> Result<Vec<Coupon>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM coupon
+                WHERE status = 'Active'
+                    AND expires_at > $now
+                    AND expires_at <= $before
+                    AND expiry_notified_at = NONE
+            ")
+            .bind(("now", now))
+            .bind(("before", before))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 记录卡券过期提醒时间，只更新该字段，卡券已被使用或转赠时不写入
+    pub async fn mark_coupon_expiry_notified(&self, coupon_id: &str, now: i64) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('coupon', $id) SET expiry_notified_at = $now WHERE status = 'Active'")
+            .bind(("id", coupon_id))
+            .bind(("now", now))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    // ==================== 卡券转赠 ====================
+
+    // 发起转赠：锁定卡券并创建转赠记录，在同一事务中复核本月转赠次数；
+    // 卡券已被使用、转赠中或已过期时返回 Some("coupon unavailable")，超出次数时返回 Some("transfer limit reached")
+    pub async fn offer_coupon_transfer(&self, transfer: &CouponTransfer, month_start: i64, max_per_month: u32)
+        -> Result<Option<&'static str>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $locked = UPDATE type::thing('coupon', $transfer.coupon_id) SET pending_transfer_id = $transfer.id
+                WHERE owner_id = $transfer.from_user_id
+                    AND status = 'Active'
+                    AND is_transferable = true
+                    AND expires_at > $transfer.created_at
+                    AND pending_transfer_id = NONE;
+                IF array::len($locked) = 0 {
+                    THROW 'coupon unavailable';
+                };
+
+                CREATE type::thing('coupon_transfer', $transfer.id) CONTENT $transfer;
+
+                LET $count = (SELECT count() AS count FROM coupon_transfer
+                    WHERE from_user_id = $transfer.from_user_id
+                        AND created_at >= $month_start
+                        AND status INSIDE ['Pending', 'Accepted']
+                    GROUP ALL)[0].count OR 0;
+                IF $count > $max_per_month {
+                    THROW 'transfer limit reached';
+                };
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("transfer", transfer))
+            .bind(("month_start", month_start))
+            .bind(("max_per_month", max_per_month))
+            .await?;
+
+        thrown_error(&mut result, &["coupon unavailable", "transfer limit reached"])
+    }
+
+    // 结束转赠并解锁卡券，接受时转移所有权；只处理仍待处理且锁定在本次转赠上的卡券。
+    // 转赠已被处理时返回 Some("transfer not pending")，接受时卡券已不可用返回 Some("coupon unavailable")
+    pub async fn resolve_coupon_transfer(&self, transfer: &CouponTransfer, now: i64)
+        -> Result<Option<&'static str>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $resolved = UPDATE type::thing('coupon_transfer', $transfer.id) SET
+                    status = $transfer.status,
+                    responded_at = $transfer.responded_at
+                WHERE status = 'Pending';
+                IF array::len($resolved) = 0 {
+                    THROW 'transfer not pending';
+                };
+
+                LET $accepted = $transfer.status = 'Accepted';
+                LET $unlocked = UPDATE type::thing('coupon', $transfer.coupon_id) SET
+                    pending_transfer_id = NONE,
+                    owner_id = IF $accepted THEN $transfer.to_user_id ELSE owner_id END
+                WHERE status = 'Active'
+                    AND pending_transfer_id = $transfer.id
+                    AND owner_id = $transfer.from_user_id
+                    AND (!$accepted OR expires_at > $now);
+                IF $accepted AND array::len($unlocked) = 0 {
+                    THROW 'coupon unavailable';
+                };
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("transfer", transfer))
+            .bind(("now", now))
+            .await?;
+
+        thrown_error(&mut result, &["transfer not pending", "coupon unavailable"])
+    }
+
+    // 获取转赠记录
+    pub async fn get_coupon_transfer(&self, transfer_id: &str) -> Result<Option<CouponTransfer>, surrealdb::Error> {
+        self.client.select(("coupon_transfer", transfer_id)).await
+    }
+
+    // 获取用户发出和收到的转赠记录
+    pub async fn get_user_coupon_transfers(&self, user_id: &str, limit: usize) -> Result<Vec<CouponTransfer>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM coupon_transfer
+                WHERE from_user_id = $user_id OR to_user_id = $user_id
+                ORDER BY created_at DESC
+                LIMIT $limit
+            ")
+            .bind(("user_id", user_id))
+            .bind(("limit", limit))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取已超时的待处理转赠
+    pub async fn get_expired_pending_transfers(&self, now: i64) -> Result<Vec<CouponTransfer>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM coupon_transfer WHERE status = 'Pending' AND expires_at <= $now")
+            .bind(("now", now))
+            .await?;
+
+        result.take(0)
+    }
+}
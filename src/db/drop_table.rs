@@ -0,0 +1,130 @@
+use crate::models::drop_table::{DropTable, DropPity, DropLog};
+use crate::models::lucky_trigger::{LuckyTrigger, LuckyEventType};
+
+use super::surreal::Database;
+
+impl Database {
+    // ==================== 掉落表 ====================
+
+    // 创建掉落表
+    pub async fn create_drop_table(&self, table: &DropTable) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<DropTable>>(("drop_table", &table.id))
+            .content(table)
+            .await?;
+        Ok(())
+    }
+
+    // 更新掉落表
+    pub async fn update_drop_table(&self, table: &DropTable) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<DropTable>>(("drop_table", &table.id))
+            .content(table)
+            .await?;
+        Ok(())
+    }
+
+    // 获取掉落表
+    pub async fn get_drop_table(&self, table_id: &str) -> Result<Option<DropTable>, surrealdb::Error> {
+        self.client.select(("drop_table", table_id)).await
+    }
+
+    // 获取所有掉落表
+    pub async fn get_drop_tables(&self) -> Result<Vec<DropTable>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM drop_table ORDER BY key ASC, priority DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取场景下当前生效的掉落表（优先级最高的一张）
+    pub async fn get_live_drop_table(&self, key: &str, now: i64) -> Result<Option<DropTable>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM drop_table
+                WHERE key = $key AND is_active = true
+                    AND (starts_at = NONE OR starts_at <= $now)
+                    AND (ends_at = NONE OR ends_at > $now)
+                ORDER BY priority DESC, updated_at DESC
+                LIMIT 1
+            ")
+            .bind(("key", key))
+            .bind(("now", now))
+            .await?;
+
+        let tables: Vec<DropTable> = result.take(0)?;
+        Ok(tables.into_iter().next())
+    }
+
+    // ==================== 保底计数 ====================
+
+    // 获取用户在掉落表上的保底计数
+    pub async fn get_drop_pity(&self, user_id: &str, table_id: &str) -> Result<Option<DropPity>, surrealdb::Error> {
+        self.client.select(("drop_pity", DropPity::record_id(user_id, table_id))).await
+    }
+
+    // ==================== 掉落记录 ====================
+
+    // 创建掉落记录，掉落表启用保底时在同一事务内更新保底计数：
+    // rare_hit 为 Some(true) 时清零，Some(false) 时加一
+    pub async fn create_drop_log(&self, log: &DropLog, rare_hit: Option<bool>) -> Result<(), surrealdb::Error> {
+        let pity_statement = match rare_hit {
+            Some(_) => "
+                UPDATE type::thing('drop_pity', $pity_id) SET
+                    user_id = $log.user_id,
+                    table_id = $log.table_id,
+                    misses = IF $rare_hit THEN 0 ELSE (misses ?? 0) + 1 END,
+                    updated_at = $log.created_at;
+            ",
+            None => "",
+        };
+
+        self.client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                {}
+
+                CREATE type::thing('drop_log', $log.id) CONTENT $log;
+
+                COMMIT TRANSACTION;
+            ", pity_statement))
+            .bind(("log", log))
+            .bind(("pity_id", DropPity::record_id(&log.user_id, &log.table_id)))
+            .bind(("rare_hit", rare_hit.unwrap_or(false)))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    // 获取掉落表的抽取记录
+    pub async fn get_drop_logs(&self, table_id: &str, limit: usize) -> Result<Vec<DropLog>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM drop_log WHERE table_id = $table_id ORDER BY created_at DESC LIMIT $limit")
+            .bind(("table_id", table_id))
+            .bind(("limit", limit))
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 事件触发配置 ====================
+
+    // 获取事件的触发配置
+    pub async fn get_lucky_trigger(&self, event_type: &LuckyEventType) -> Result<Option<LuckyTrigger>, surrealdb::Error> {
+        self.client.select(("lucky_trigger", event_type.as_str())).await
+    }
+
+    // 保存触发配置
+    pub async fn save_lucky_trigger(&self, trigger: &LuckyTrigger) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<LuckyTrigger>>(("lucky_trigger", &trigger.id))
+            .content(trigger)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,218 @@
+use time::OffsetDateTime;
+
+use crate::models::WalletTx;
+use crate::models::lucky_card::LUCKY_BOOST_MAX_BONUS_HP;
+use crate::models::earning_rule::{EarningCredit, EarningRule, EarningSource, HeldReward, HeldRewardStatus};
+
+use super::points::CREDIT_HP_STATEMENTS;
+use super::surreal::{thrown_error, Database};
+
+// 获取上限复核语句：入账前在事务中重新统计各窗口已获取的积分（不含幸运卡加成），超出上限时回滚
+// 需绑定 $tx、$points（计入上限的积分）和 $caps
+pub(crate) const EARNING_CAP_STATEMENTS: &str = "
+    FOR $cap IN $caps {
+        LET $earned = math::sum((SELECT VALUE base_amount ?? amount FROM wallet_tx
+            WHERE user_id = $tx.user_id AND earning_source = $tx.earning_source AND timestamp >= $cap.since))
+            + math::sum((SELECT VALUE amount FROM held_reward
+            WHERE user_id = $tx.user_id AND source = $tx.earning_source AND status = 'Pending' AND created_at >= $cap.since));
+        IF $earned + $points > $cap.max {
+            THROW 'earning cap reached';
+        };
+    };
+";
+
+// 幸运卡加成复核语句：入账时在事务中累加加成已发放的额外积分，
+// 加成已更换、已过期或超出上限时回滚，避免并发入账按过期的加成状态重复计算
+// 需绑定 $tx、$bonus（本次额外积分）和 $max_bonus
+pub(crate) const LUCKY_BOOST_STATEMENTS: &str = "
+    LET $boosted = UPDATE type::thing('user', $tx.user_id) SET lucky_boost.bonus_hp += $bonus
+        WHERE lucky_boost.card_id = $tx.lucky_card_id
+            AND lucky_boost.expires_at > $tx.timestamp
+            AND lucky_boost.bonus_hp + $bonus <= $max_bonus;
+    IF array::len($boosted) = 0 {
+        THROW 'lucky boost changed';
+    };
+";
+
+impl Database {
+    // ==================== 积分获取规则 ====================
+
+    // 获取来源的获取规则
+    pub async fn get_earning_rule(&self, source: &EarningSource) -> Result<Option<EarningRule>, surrealdb::Error> {
+        self.client.select(("earning_rule", source.as_str())).await
+    }
+
+    // 保存获取规则
+    pub async fn save_earning_rule(&self, rule: &EarningRule) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<EarningRule>>(("earning_rule", &rule.id))
+            .content(rule)
+            .await?;
+        Ok(())
+    }
+
+    // 统计用户自某时间起从某来源获得的积分（不含幸运卡加成），待审核的暂扣积分也计入
+    pub async fn sum_user_earnings(&self, user_id: &str, source: &EarningSource, since: i64) -> Result<u32, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                RETURN math::sum((SELECT VALUE base_amount ?? amount FROM wallet_tx
+                    WHERE user_id = $user_id AND earning_source = $source AND timestamp >= $since))
+                    + math::sum((SELECT VALUE amount FROM held_reward
+                    WHERE user_id = $user_id AND source = $source AND status = 'Pending' AND created_at >= $since));
+            ")
+            .bind(("user_id", user_id))
+            .bind(("source", source))
+            .bind(("since", since))
+            .await?;
+
+        let total: Option<u32> = result.take(0)?;
+        Ok(total.unwrap_or(0))
+    }
+
+    // 标记积分获取异常的用户
+    pub async fn flag_user_earnings(&self, user_id: &str, reason: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                UPDATE type::thing('user', $user_id) SET
+                    earning_flagged_at = $now,
+                    earning_flag_reason = $reason,
+                    updated_at = $now
+            ")
+            .bind(("user_id", user_id))
+            .bind(("reason", reason))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+        Ok(())
+    }
+
+    // 解除用户的积分异常标记
+    pub async fn clear_user_earning_flag(&self, user_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                UPDATE type::thing('user', $user_id) SET
+                    earning_flagged_at = NONE,
+                    earning_flag_reason = NONE,
+                    updated_at = $now
+            ")
+            .bind(("user_id", user_id))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+        Ok(())
+    }
+
+    // 在上限内入账获取积分并累加幸运卡加成额度，并发入账使本次超出上限或加成已变化时事务回滚返回 false
+    pub async fn credit_user_earning(&self, credit: &EarningCredit) -> Result<bool, surrealdb::Error> {
+        let lot = self.hp_lot_for(&credit.tx).await?;
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+                {}
+                {}
+                {}
+                COMMIT TRANSACTION;
+            ",
+                EARNING_CAP_STATEMENTS,
+                if credit.bonus > 0 { LUCKY_BOOST_STATEMENTS } else { "" },
+                CREDIT_HP_STATEMENTS,
+            ))
+            .bind(("tx", &credit.tx))
+            .bind(("points", credit.points))
+            .bind(("bonus", credit.bonus))
+            .bind(("max_bonus", LUCKY_BOOST_MAX_BONUS_HP))
+            .bind(("caps", &credit.caps))
+            .bind(("lot", &lot))
+            .await?;
+
+        Ok(thrown_error(&mut result, &["earning cap reached", "lucky boost changed"])?.is_none())
+    }
+
+    // ==================== 暂扣积分 ====================
+
+    // 获取暂扣积分
+    pub async fn get_held_reward(&self, id: &str) -> Result<Option<HeldReward>, surrealdb::Error> {
+        self.client.select(("held_reward", id)).await
+    }
+
+    // 创建暂扣积分
+    pub async fn create_held_reward(&self, reward: &HeldReward) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<HeldReward>>(("held_reward", &reward.id))
+            .content(reward)
+            .await?;
+        Ok(())
+    }
+
+    // 审核暂扣积分，只处理待审核的记录，避免重复入账
+    pub async fn review_held_reward(&self, id: &str, status: &HeldRewardStatus, admin_id: &str) 
+        -> Result<Option<HeldReward>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('held_reward', $id) SET
+                    status = $status,
+                    reviewed_at = $now,
+                    reviewed_by = $admin_id
+                WHERE status = 'Pending'
+            ")
+            .bind(("id", id))
+            .bind(("status", status))
+            .bind(("admin_id", admin_id))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+
+        let rewards: Vec<HeldReward> = result.take(0)?;
+        Ok(rewards.into_iter().next())
+    }
+
+    // 审核通过暂扣积分：更新状态与入账在同一事务中，记录已被审核时返回 None
+    pub async fn approve_held_reward(&self, id: &str, admin_id: &str, tx: &WalletTx) 
+        -> Result<Option<HeldReward>, surrealdb::Error> {
+        let lot = self.hp_lot_for(tx).await?;
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                LET $reward = UPDATE type::thing('held_reward', $id) SET
+                    status = 'Approved',
+                    reviewed_at = $now,
+                    reviewed_by = $admin_id
+                WHERE status = 'Pending';
+                IF array::len($reward) = 0 {{
+                    THROW 'reward already reviewed';
+                }};
+
+                {}
+
+                COMMIT TRANSACTION;
+            ", CREDIT_HP_STATEMENTS))
+            .bind(("id", id))
+            .bind(("admin_id", admin_id))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .bind(("tx", tx))
+            .bind(("lot", &lot))
+            .await?;
+
+        if thrown_error(&mut result, &["reward already reviewed"])?.is_some() {
+            return Ok(None);
+        }
+
+        self.get_held_reward(id).await
+    }
+
+    // 按状态获取暂扣积分
+    pub async fn get_held_rewards(&self, status: &HeldRewardStatus, limit: usize) -> Result<Vec<HeldReward>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM held_reward WHERE status = $status ORDER BY created_at DESC LIMIT $limit")
+            .bind(("status", status))
+            .bind(("limit", limit))
+            .await?;
+
+        result.take(0)
+    }
+}
@@ -0,0 +1,230 @@
+use time::OffsetDateTime;
+
+use crate::models::gift_event::{GiftEvent, GiftEventClaim, GiftEventEntry, GiftEventGrant, GiftEventScore};
+
+use super::points::CREDIT_HP_STATEMENTS;
+use super::surreal::{Database, thrown_error};
+
+impl Database {
+    // ==================== 礼物活动 ====================
+
+    // 创建礼物活动
+    pub async fn create_gift_event(&self, event: &GiftEvent) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<GiftEvent>>(("gift_event", &event.id))
+            .content(event)
+            .await?;
+        Ok(())
+    }
+
+    // 更新礼物活动
+    pub async fn update_gift_event(&self, event: &GiftEvent) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<GiftEvent>>(("gift_event", &event.id))
+            .content(event)
+            .await?;
+        Ok(())
+    }
+
+    // 获取礼物活动
+    pub async fn get_gift_event(&self, event_id: &str) -> Result<Option<GiftEvent>, surrealdb::Error> {
+        self.client.select(("gift_event", event_id)).await
+    }
+
+    // 获取所有礼物活动，最近开始的在前
+    pub async fn get_gift_events(&self) -> Result<Vec<GiftEvent>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM gift_event ORDER BY starts_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取进行中的活动，指定礼物时只返回包含该礼物的活动
+    pub async fn get_running_gift_events(&self, now: i64, gift_id: Option<&str>) -> Result<Vec<GiftEvent>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM gift_event
+                WHERE is_active = true AND starts_at <= $now AND ends_at > $now
+                    AND ($gift_id = NONE OR $gift_id INSIDE gift_ids)
+                ORDER BY ends_at ASC
+            ")
+            .bind(("now", now))
+            .bind(("gift_id", gift_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取在指定时间前结束但尚未结算的活动
+    pub async fn get_unsettled_gift_events(&self, ended_before: i64) -> Result<Vec<GiftEvent>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM gift_event
+                WHERE is_active = true AND ends_at <= $ended_before AND rewards_distributed_at = NONE
+            ")
+            .bind(("ended_before", ended_before))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 标记活动已结算并保存最终排名，同一事务中写入获奖用户的待发放记录；已被标记时返回 false
+    pub async fn settle_gift_event(&self, event_id: &str, standings: &[GiftEventEntry], claims: &[GiftEventClaim], now: i64) 
+        -> Result<bool, surrealdb::Error> {
+        
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $settled = UPDATE type::thing('gift_event', $event_id) SET
+                    rewards_distributed_at = $now,
+                    final_standings = $standings,
+                    updated_at = $now
+                WHERE rewards_distributed_at = NONE;
+                IF array::len($settled) = 0 {
+                    THROW 'gift event already settled';
+                };
+
+                FOR $claim IN $claims {
+                    CREATE type::thing('gift_event_claim', $claim.id) CONTENT $claim;
+                };
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("event_id", event_id))
+            .bind(("standings", standings))
+            .bind(("claims", claims))
+            .bind(("now", now))
+            .await?;
+
+        Ok(thrown_error(&mut result, &["gift event already settled"])?.is_none())
+    }
+
+    // ==================== 活动奖励发放 ====================
+
+    // 获取待发放的活动奖励，指定活动时只返回该活动的记录
+    pub async fn get_pending_gift_event_claims(&self, event_id: Option<&str>) -> Result<Vec<GiftEventClaim>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM gift_event_claim
+                WHERE reward_pending = true AND ($event_id = NONE OR event_id = $event_id)
+                ORDER BY rank ASC
+            ")
+            .bind(("event_id", event_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 发放活动奖励：清除待发放标记与积分、卡券、称号在同一事务中写入，已发放时返回 false
+    pub async fn grant_gift_event_claim(&self, claim_id: &str, grant: &GiftEventGrant) -> Result<bool, surrealdb::Error> {
+        let lot = match &grant.tx {
+            Some(tx) => Some(self.hp_lot_for(tx).await?),
+            None => None,
+        };
+
+        let mut statements = String::new();
+        if grant.tx.is_some() {
+            statements.push_str(CREDIT_HP_STATEMENTS);
+        }
+        if grant.coupon.is_some() {
+            statements.push_str("CREATE type::thing('coupon', $coupon.id) CONTENT $coupon;\n");
+        }
+        if grant.title.is_some() {
+            statements.push_str("CREATE type::thing('inventory_item', $title.id) CONTENT $title;\n");
+        }
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                LET $granted = UPDATE type::thing('gift_event_claim', $claim_id) SET
+                    reward_pending = false,
+                    granted_at = $now
+                WHERE reward_pending = true;
+                IF array::len($granted) = 0 {{
+                    THROW 'event reward already granted';
+                }};
+
+                {}
+
+                COMMIT TRANSACTION;
+            ", statements))
+            .bind(("claim_id", claim_id))
+            .bind(("tx", &grant.tx))
+            .bind(("lot", lot))
+            .bind(("coupon", &grant.coupon))
+            .bind(("title", &grant.title))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+
+        Ok(thrown_error(&mut result, &["event reward already granted"])?.is_none())
+    }
+
+    // ==================== 活动送礼榜 ====================
+
+    // 累加活动送礼分数，后台写入乱序时保留最晚的送礼时间
+    pub async fn increment_gift_event_scores(&self, scores: &[GiftEventScore]) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                FOR $entry IN $scores {
+                    UPDATE type::thing('gift_event_score', $entry.id) SET
+                        event_id = $entry.event_id,
+                        user_id = $entry.user_id,
+                        score = (score OR 0) + $entry.score,
+                        updated_at = math::max([updated_at OR 0, $entry.updated_at]);
+                };
+            ")
+            .bind(("scores", scores))
+            .await?;
+        Ok(())
+    }
+
+    // 分页获取活动送礼榜，同分时先达到的排在前面
+    pub async fn get_gift_event_scores(&self, event_id: &str, limit: usize, offset: usize) -> Result<Vec<GiftEventScore>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM gift_event_score
+                WHERE event_id = $event_id
+                ORDER BY score DESC, updated_at ASC
+                LIMIT $limit START $offset
+            ")
+            .bind(("event_id", event_id))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取用户在活动中的分数
+    pub async fn get_gift_event_score(&self, event_id: &str, user_id: &str) -> Result<Option<GiftEventScore>, surrealdb::Error> {
+        let id = format!("{}_{}", event_id, user_id);
+        self.client.select(("gift_event_score", id.as_str())).await
+    }
+
+    // 统计排在某条分数之前的数量，用于计算名次
+    pub async fn count_gift_event_scores_ahead(&self, score: &GiftEventScore) -> Result<u32, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT count() AS count FROM gift_event_score
+                WHERE event_id = $entry.event_id
+                    AND (score > $entry.score OR (score = $entry.score AND updated_at < $entry.updated_at))
+                GROUP ALL
+            ")
+            .bind(("entry", score))
+            .await?;
+
+        let count: Option<u32> = result.take("count")?;
+        Ok(count.unwrap_or(0))
+    }
+}
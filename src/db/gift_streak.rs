@@ -0,0 +1,195 @@
+use time::OffsetDateTime;
+
+use crate::models::gift::ConsecutiveGiftRecord;
+use crate::models::gift_streak::{GiftStreakClaim, GiftStreakGrant, GiftStreakMilestone};
+
+use super::surreal::{Database, thrown_error};
+
+impl Database {
+    // ==================== 连续送礼里程碑 ====================
+
+    // 创建里程碑
+    pub async fn create_gift_streak_milestone(&self, milestone: &GiftStreakMilestone) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<GiftStreakMilestone>>(("gift_streak_milestone", &milestone.id))
+            .content(milestone)
+            .await?;
+        Ok(())
+    }
+
+    // 更新里程碑
+    pub async fn update_gift_streak_milestone(&self, milestone: &GiftStreakMilestone) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<GiftStreakMilestone>>(("gift_streak_milestone", &milestone.id))
+            .content(milestone)
+            .await?;
+        Ok(())
+    }
+
+    // 获取里程碑
+    pub async fn get_gift_streak_milestone(&self, milestone_id: &str) -> Result<Option<GiftStreakMilestone>, surrealdb::Error> {
+        self.client.select(("gift_streak_milestone", milestone_id)).await
+    }
+
+    // 获取所有里程碑
+    pub async fn get_gift_streak_milestones(&self) -> Result<Vec<GiftStreakMilestone>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM gift_streak_milestone ORDER BY days ASC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取启用的里程碑，按天数升序
+    pub async fn get_active_gift_streak_milestones(&self) -> Result<Vec<GiftStreakMilestone>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM gift_streak_milestone WHERE is_active = true ORDER BY days ASC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 里程碑奖励发放 ====================
+
+    // 写入待发放的里程碑奖励记录，同一段连续送礼中已写入过时返回 false
+    pub async fn create_gift_streak_claim(&self, claim: &GiftStreakClaim) -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("CREATE type::thing('gift_streak_claim', $claim.id) CONTENT $claim")
+            .bind(("claim", claim))
+            .await?;
+
+        Ok(result.take_errors().is_empty())
+    }
+
+    // 获取用户待发放的里程碑奖励
+    pub async fn get_pending_gift_streak_claims(&self, user_id: &str) -> Result<Vec<GiftStreakClaim>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM gift_streak_claim WHERE user_id = $user_id AND reward_pending = true")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取所有用户待发放的里程碑奖励，供定时任务重试
+    pub async fn get_all_pending_gift_streak_claims(&self) -> Result<Vec<GiftStreakClaim>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM gift_streak_claim WHERE reward_pending = true")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 发放里程碑奖励：清除待发放标记与幸运卡、亲密度加成、AI消息在同一事务中写入，
+    // 返回加成后的连续送礼记录，已发放时返回 None
+    pub async fn grant_gift_streak_claim(&self, claim_id: &str, streak_id: &str, grant: &GiftStreakGrant)
+        -> Result<Option<ConsecutiveGiftRecord>, surrealdb::Error> {
+
+        let mut statements = String::new();
+        if grant.lucky_card.is_some() {
+            statements.push_str("CREATE type::thing('lucky_card', $lucky_card.id) CONTENT $lucky_card;\n");
+        }
+        if grant.relationship_boost > 0 {
+            statements.push_str("
+                UPDATE type::thing('consecutive_gift_record', $streak_id) SET
+                    total_emotional_value = (total_emotional_value OR 0) + $boost;
+            ");
+        }
+        if grant.message.is_some() {
+            statements.push_str("CREATE type::thing('message', $message.id) CONTENT $message;\n");
+        }
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                LET $granted = UPDATE type::thing('gift_streak_claim', $claim_id) SET
+                    reward_pending = false,
+                    granted_at = $now
+                WHERE reward_pending = true;
+                IF array::len($granted) = 0 {{
+                    THROW 'streak reward already granted';
+                }};
+
+                {}
+
+                RETURN (SELECT *, meta::id(id) AS id FROM type::thing('consecutive_gift_record', $streak_id))[0];
+
+                COMMIT TRANSACTION;
+            ", statements))
+            .bind(("claim_id", claim_id))
+            .bind(("streak_id", streak_id))
+            .bind(("lucky_card", &grant.lucky_card))
+            .bind(("boost", grant.relationship_boost))
+            .bind(("message", &grant.message))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+
+        if thrown_error(&mut result, &["streak reward already granted"])?.is_some() {
+            return Ok(None);
+        }
+
+        let last = result.num_statements() - 1;
+        result.take(last)
+    }
+
+    // ==================== 连续中断检查 ====================
+
+    // 获取可能已中断且尚未通知的连续送礼记录：最后送礼距今超过一天（任何时区下至少漏掉一天的必要条件）
+    pub async fn get_unnotified_gift_streaks_before(&self, last_gift_before: i64)
+        -> Result<Vec<ConsecutiveGiftRecord>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                SELECT *, meta::id(id) AS id FROM consecutive_gift_record
+                WHERE consecutive_days > 0
+                    AND last_gift_date < $before
+                    AND break_notified_at = NONE
+            ")
+            .bind(("before", last_gift_before))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 标记连续中断已通知，期间再次送礼或已被其他实例标记时返回 false
+    pub async fn mark_gift_streak_break_notified(&self, streak_id: &str, last_gift_date: i64, now: i64)
+        -> Result<bool, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('consecutive_gift_record', $streak_id) SET break_notified_at = $now
+                WHERE break_notified_at = NONE AND last_gift_date = $last_gift_date
+                RETURN meta::id(id) AS id
+            ")
+            .bind(("streak_id", streak_id))
+            .bind(("last_gift_date", last_gift_date))
+            .bind(("now", now))
+            .await?;
+
+        let updated: Vec<serde_json::Value> = result.take(0)?;
+        Ok(!updated.is_empty())
+    }
+
+    // 通知失败时撤销标记，下次检查重试
+    pub async fn release_gift_streak_break_notified(&self, streak_id: &str, now: i64) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                UPDATE type::thing('consecutive_gift_record', $streak_id) SET break_notified_at = NONE
+                WHERE break_notified_at = $now
+            ")
+            .bind(("streak_id", streak_id))
+            .bind(("now", now))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use time::OffsetDateTime;
+
+use crate::models::{User, WalletTx, TxType};
+use crate::models::hp_lot::{HpLot, HpLotDeduction, HpExpiryPolicy, plan_hp_deductions};
+
+use super::surreal::Database;
+
+impl Database {
+    // ==================== 积分有效期策略 ====================
+
+    // 获取交易类型的有效期策略
+    pub async fn get_hp_expiry_policy(&self, tx_type: &TxType) -> Result<Option<HpExpiryPolicy>, surrealdb::Error> {
+        self.client.select(("hp_expiry_policy", tx_type.as_str())).await
+    }
+
+    // 获取生效的有效期策略，未配置时使用默认值
+    pub async fn get_effective_hp_expiry_policy(&self, tx_type: &TxType) -> Result<HpExpiryPolicy, surrealdb::Error> {
+        let policy = self.get_hp_expiry_policy(tx_type).await?;
+        Ok(policy.unwrap_or_else(|| HpExpiryPolicy::default_for(tx_type.clone())))
+    }
+
+    // 保存有效期策略
+    pub async fn save_hp_expiry_policy(&self, policy: &HpExpiryPolicy) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<HpExpiryPolicy>>(("hp_expiry_policy", &policy.id))
+            .content(policy)
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 积分批次 ====================
+
+    // 获取用户未用完的积分批次，按获得时间排序
+    pub async fn get_open_hp_lots(&self, user_id: &str) -> Result<Vec<HpLot>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM hp_lot
+                WHERE user_id = $user_id AND is_expired = false AND remaining > 0
+                ORDER BY earned_at ASC
+            ")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 计算扣减积分时各批次的扣减数量
+    pub async fn plan_user_hp_deductions(&self, user_id: &str, amount: u32) -> Result<Vec<HpLotDeduction>, surrealdb::Error> {
+        let user: Option<User> = self.client.select(("user", user_id)).await?;
+        let hp = user.map(|user| user.hp).unwrap_or(0);
+
+        let lots = self.get_open_hp_lots(user_id).await?;
+        let tracked: u32 = lots.iter().map(|lot| lot.remaining).sum();
+
+        Ok(plan_hp_deductions(&lots, hp.saturating_sub(tracked), amount))
+    }
+
+    // 获取已到期但仍有剩余的积分批次
+    pub async fn get_expired_hp_lots(&self, now: i64) -> Result<Vec<HpLot>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM hp_lot
+                WHERE is_expired = false AND remaining > 0 AND expires_at != NONE AND expires_at <= $now
+                ORDER BY expires_at ASC
+            ")
+            .bind(("now", now))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 过期积分批次：清空剩余数量、扣减用户积分并记录过期交易
+    // 批次在此期间被消费时事务回滚，返回 false
+    pub async fn expire_hp_lot(&self, lot: &HpLot, tx: &WalletTx) -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $lot = UPDATE type::thing('hp_lot', $lot_id) SET remaining = 0, is_expired = true
+                    WHERE is_expired = false AND remaining = $amount;
+                IF array::len($lot) = 0 {
+                    THROW 'hp lot changed';
+                };
+
+                LET $user = (SELECT * FROM type::thing('user', $user_id))[0];
+                UPDATE type::thing('user', $user_id) SET
+                    hp = math::max([$user.hp - $amount, 0]),
+                    updated_at = $now;
+
+                CREATE type::thing('wallet_tx', $tx.id) CONTENT $tx;
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("lot_id", &lot.id))
+            .bind(("user_id", &lot.user_id))
+            .bind(("amount", lot.remaining))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .bind(("tx", tx))
+            .await?;
+
+        Ok(result.take_errors().is_empty())
+    }
+}
@@ -0,0 +1,101 @@
+use time::OffsetDateTime;
+
+use crate::models::PurchaseRecord;
+use crate::models::inventory::{InventoryItem, PurchaseGrant};
+
+use super::surreal::{thrown_error, Database};
+
+impl Database {
+    // ==================== 用户背包 ====================
+
+    // 更新背包物品
+    pub async fn update_inventory_item(&self, item: &InventoryItem) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<InventoryItem>>(("inventory_item", &item.id))
+            .content(item)
+            .await?;
+        Ok(())
+    }
+
+    // 获取背包物品
+    pub async fn get_inventory_item(&self, id: &str) -> Result<Option<InventoryItem>, surrealdb::Error> {
+        self.client.select(("inventory_item", id)).await
+    }
+
+    // 获取用户背包
+    pub async fn get_user_inventory(&self, user_id: &str) -> Result<Vec<InventoryItem>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM inventory_item WHERE user_id = $user_id ORDER BY acquired_at DESC")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 卸下用户当前佩戴的称号
+    pub async fn unequip_user_titles(&self, user_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE inventory_item SET is_equipped = false WHERE user_id = $user_id AND item_type = 'UserTitle' AND is_equipped = true")
+            .bind(("user_id", user_id))
+            .await?;
+        Ok(())
+    }
+
+    // 卸下AI当前的装饰
+    pub async fn unequip_ai_decorations(&self, ai_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                UPDATE inventory_item SET is_equipped = false, equipped_ai_id = NONE
+                WHERE item_type = 'AIDecoration' AND equipped_ai_id = $ai_id
+            ")
+            .bind(("ai_id", ai_id))
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 商品效果 ====================
+
+    // 激活购买记录并在同一事务中发放效果，记录已激活或已过期时事务回滚返回 false
+    pub async fn activate_purchase_record(&self, purchase: &PurchaseRecord, grant: &PurchaseGrant)
+        -> Result<bool, surrealdb::Error> {
+        let mut statements = String::new();
+        if let Some(field) = grant.user_counter {
+            statements.push_str(&format!(
+                "UPDATE type::thing('user', $user_id) SET {} += 1, updated_at = $now;\n", field,
+            ));
+        }
+        if grant.inventory_item.is_some() {
+            statements.push_str("CREATE type::thing('inventory_item', $inventory_item.id) CONTENT $inventory_item;\n");
+        }
+        if grant.coupon.is_some() {
+            statements.push_str("CREATE type::thing('coupon', $coupon.id) CONTENT $coupon;\n");
+        }
+
+        let mut result = self
+            .client
+            .query(format!("
+                BEGIN TRANSACTION;
+
+                LET $activated = UPDATE type::thing('purchase_record', $purchase_id) SET
+                    is_activated = true,
+                    activated_at = $now
+                WHERE is_activated = false AND (expires_at = NONE OR expires_at > $now);
+                IF array::len($activated) = 0 {{
+                    THROW 'purchase not activatable';
+                }};
+
+                {}
+
+                COMMIT TRANSACTION;
+            ", statements))
+            .bind(("purchase_id", &purchase.id))
+            .bind(("user_id", &purchase.user_id))
+            .bind(("inventory_item", &grant.inventory_item))
+            .bind(("coupon", &grant.coupon))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?;
+
+        Ok(thrown_error(&mut result, &["purchase not activatable"])?.is_none())
+    }
+}
@@ -0,0 +1,29 @@
+use super::surreal::Database;
+
+impl Database {
+    // ==================== 定时任务 ====================
+
+    // 获取每日任务上次成功执行的日期（距 UNIX 纪元的天数）
+    pub async fn get_job_last_run_day(&self, job: &str) -> Result<Option<i64>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT VALUE last_run_day FROM type::thing('job_run', $job)")
+            .bind(("job", job))
+            .await?;
+
+        let days: Vec<i64> = result.take(0)?;
+        Ok(days.into_iter().next())
+    }
+
+    // 记录每日任务成功执行的日期
+    pub async fn record_job_run(&self, job: &str, day: i64, now: i64) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('job_run', $job) SET name = $job, last_run_day = $day, last_run_at = $now")
+            .bind(("job", job))
+            .bind(("day", day))
+            .bind(("now", now))
+            .await?
+            .check()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,258 @@
+use time::OffsetDateTime;
+
+use crate::models::leaderboard::{
+    LeaderboardKind, LeaderboardPeriod, LeaderboardScore, LeaderboardSnapshot, LeaderboardTotal,
+};
+
+use super::surreal::Database;
+
+impl Database {
+    // ==================== 排行榜分数 ====================
+
+    // 累加排行榜分数（送礼类榜单）
+    pub async fn increment_leaderboard_scores(&self, scores: &[LeaderboardScore]) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                FOR $entry IN $scores {
+                    UPDATE type::thing('leaderboard_score', $entry.id) SET
+                        kind = $entry.kind,
+                        period = $entry.period,
+                        period_key = $entry.period_key,
+                        subject_id = $entry.subject_id,
+                        owner_id = $entry.owner_id,
+                        score = (score OR 0) + $entry.score,
+                        hidden = $entry.hidden,
+                        updated_at = $entry.updated_at;
+                };
+            ")
+            .bind(("scores", scores))
+            .await?;
+        Ok(())
+    }
+
+    // 提升排行榜分数到不低于给定值（签到类榜单取周期内最高值）
+    pub async fn raise_leaderboard_scores(&self, scores: &[LeaderboardScore]) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                FOR $entry IN $scores {
+                    UPDATE type::thing('leaderboard_score', $entry.id) SET
+                        kind = $entry.kind,
+                        period = $entry.period,
+                        period_key = $entry.period_key,
+                        subject_id = $entry.subject_id,
+                        owner_id = $entry.owner_id,
+                        hidden = $entry.hidden,
+                        updated_at = IF (score OR 0) < $entry.score THEN $entry.updated_at ELSE updated_at END,
+                        score = math::max([score OR 0, $entry.score]);
+                };
+            ")
+            .bind(("scores", scores))
+            .await?;
+        Ok(())
+    }
+
+    // 分页获取排行榜，同分时先达到的排在前面
+    pub async fn get_leaderboard_scores(
+        &self,
+        kind: &LeaderboardKind,
+        period: &LeaderboardPeriod,
+        period_key: &str,
+        limit: usize,
+        offset: usize,
+    ) -> Result<Vec<LeaderboardScore>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM leaderboard_score
+                WHERE kind = $kind AND period = $period AND period_key = $period_key AND hidden = false
+                ORDER BY score DESC, updated_at ASC
+                LIMIT $limit START $offset
+            ")
+            .bind(("kind", kind))
+            .bind(("period", period))
+            .bind(("period_key", period_key))
+            .bind(("limit", limit))
+            .bind(("offset", offset))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取用户在某期排行榜中的分数（AI榜为用户的各个AI）
+    pub async fn get_owner_leaderboard_scores(
+        &self,
+        kind: &LeaderboardKind,
+        period: &LeaderboardPeriod,
+        period_key: &str,
+        owner_id: &str,
+    ) -> Result<Vec<LeaderboardScore>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM leaderboard_score
+                WHERE kind = $kind AND period = $period AND period_key = $period_key
+                    AND owner_id = $owner_id AND hidden = false
+                ORDER BY score DESC
+            ")
+            .bind(("kind", kind))
+            .bind(("period", period))
+            .bind(("period_key", period_key))
+            .bind(("owner_id", owner_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 统计排在某条分数之前的数量，用于计算名次
+    pub async fn count_leaderboard_scores_ahead(&self, score: &LeaderboardScore) -> Result<u32, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT count() AS count FROM leaderboard_score
+                WHERE kind = $entry.kind AND period = $entry.period AND period_key = $entry.period_key
+                    AND hidden = false
+                    AND (score > $entry.score OR (score = $entry.score AND updated_at < $entry.updated_at))
+                GROUP ALL
+            ")
+            .bind(("entry", score))
+            .await?;
+
+        let count: Option<u32> = result.take("count")?;
+        Ok(count.unwrap_or(0))
+    }
+
+    // 设置用户所有排行榜分数的隐藏状态
+    pub async fn set_leaderboard_hidden(&self, owner_id: &str, hidden: bool) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE leaderboard_score SET hidden = $hidden WHERE owner_id = $owner_id")
+            .bind(("owner_id", owner_id))
+            .bind(("hidden", hidden))
+            .await?;
+        Ok(())
+    }
+
+    // 设置用户是否在排行榜中展示
+    pub async fn set_user_leaderboard_opt_out(&self, user_id: &str, opt_out: bool) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('user', $user_id) SET leaderboard_opt_out = $opt_out, updated_at = $now")
+            .bind(("user_id", user_id))
+            .bind(("opt_out", opt_out))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    // 获取仍有分数、且不是当期的期数，用于补存漏掉的快照
+    pub async fn get_stale_leaderboard_period_keys(
+        &self,
+        kind: &LeaderboardKind,
+        period: &LeaderboardPeriod,
+        current_key: &str,
+    ) -> Result<Vec<String>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT period_key FROM leaderboard_score
+                WHERE kind = $kind AND period = $period AND period_key != $current_key
+                GROUP BY period_key
+            ")
+            .bind(("kind", kind))
+            .bind(("period", period))
+            .bind(("current_key", current_key))
+            .await?;
+
+        let keys: Vec<String> = result.take("period_key")?;
+        Ok(keys)
+    }
+
+    // 删除某期排行榜分数，快照保存后调用
+    pub async fn delete_leaderboard_scores(
+        &self,
+        kind: &LeaderboardKind,
+        period: &LeaderboardPeriod,
+        period_key: &str,
+    ) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("DELETE leaderboard_score WHERE kind = $kind AND period = $period AND period_key = $period_key")
+            .bind(("kind", kind))
+            .bind(("period", period))
+            .bind(("period_key", period_key))
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 排行榜快照 ====================
+
+    // 获取某期排行榜快照
+    pub async fn get_leaderboard_snapshot(
+        &self,
+        kind: &LeaderboardKind,
+        period: &LeaderboardPeriod,
+        period_key: &str,
+    ) -> Result<Option<LeaderboardSnapshot>, surrealdb::Error> {
+        let id = LeaderboardSnapshot::record_id(kind, period, period_key);
+        self.client.select(("leaderboard_snapshot", id.as_str())).await
+    }
+
+    // 保存排行榜快照
+    pub async fn create_leaderboard_snapshot(&self, snapshot: &LeaderboardSnapshot) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<LeaderboardSnapshot>>(("leaderboard_snapshot", &snapshot.id))
+            .content(snapshot)
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 总榜补录 ====================
+
+    // 是否已有总榜分数
+    pub async fn has_all_time_leaderboard_scores(&self) -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT VALUE score FROM leaderboard_score WHERE period = 'AllTime' LIMIT 1")
+            .await?;
+
+        let scores: Vec<u32> = result.take(0)?;
+        Ok(!scores.is_empty())
+    }
+
+    // 按用户统计历史送礼情感价值
+    pub async fn get_gift_value_totals_by_user(&self) -> Result<Vec<LeaderboardTotal>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT user_id AS subject_id, math::sum(total_emotional_value) AS score
+                FROM consecutive_gift_record GROUP BY subject_id
+            ")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 按AI统计历史收到的礼物情感价值
+    pub async fn get_gift_value_totals_by_ai(&self) -> Result<Vec<LeaderboardTotal>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT ai_id AS subject_id, math::sum(total_emotional_value) AS score
+                FROM consecutive_gift_record GROUP BY subject_id
+            ")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取当前连续签到天数大于0的用户
+    pub async fn get_checkin_streak_totals(&self) -> Result<Vec<LeaderboardTotal>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT meta::id(id) AS subject_id, daily_checkin_streak AS score
+                FROM user WHERE daily_checkin_streak > 0
+            ")
+            .await?;
+
+        result.take(0)
+    }
+}
@@ -0,0 +1,270 @@
+use serde::Deserialize;
+use serde_json::Value;
+use time::OffsetDateTime;
+use time::format_description::well_known::Rfc3339;
+use time::macros::format_description;
+
+use crate::db::Database;
+use crate::models::{Coupon, CouponKind, CouponStatus, CouponTemplate, VipLevel};
+use crate::models::coupon::NEVER_EXPIRES_AT;
+use crate::models::drop_table::DropPity;
+
+// 旧版卡券记录：类型、状态均为字符串，时间为 OffsetDateTime::to_string() 文本
+#[derive(Deserialize)]
+struct LegacyCoupon {
+    id: String,
+    coupon_type: String,
+    sub_type: String,
+    value: f32,
+    duration_days: Option<u32>,
+    status: String,
+    owner_id: String,
+    issued_at: Value,
+    expires_at: Value,
+    is_transferable: bool,
+}
+
+// 旧版卡券模板记录
+#[derive(Deserialize)]
+struct LegacyCouponTemplate {
+    id: String,
+    name: String,
+    description: String,
+    coupon_type: String,
+    sub_type: String,
+    value: f32,
+    duration_days: Option<u32>,
+    is_transferable: bool,
+    created_at: Value,
+    is_active: bool,
+}
+
+// 根据旧的 coupon_type / sub_type / value 推断卡券类型
+// sub_type 形如 "pro_2d"、"premium_7d"、"95_discount"、"cash_10"
+fn legacy_kind(coupon_type: &str, sub_type: &str, value: f32, days: Option<u32>) -> Option<CouponKind> {
+    match coupon_type {
+        "experience" => {
+            let mut parts = sub_type.split('_');
+            let level_name = parts.next().unwrap_or_default();
+            let mut chars = level_name.chars();
+            let level_name = match chars.next() {
+                Some(first) => first.to_uppercase().collect::<String>() + &chars.as_str().to_lowercase(),
+                None => String::new(),
+            };
+            let level = VipLevel::from_name(&level_name)?;
+
+            // 体验天数优先取 duration_days，否则从 "_7d" 后缀中解析
+            let days = days.or_else(|| {
+                parts.next()
+                    .and_then(|suffix| suffix.strip_suffix('d'))
+                    .and_then(|days| days.parse().ok())
+            })?;
+
+            Some(CouponKind::Experience { level, days })
+        }
+        "discount" => {
+            let rate = if value > 0.0 && value < 1.0 {
+                value
+            } else {
+                sub_type.split('_').next()?.parse::<f32>().ok()? / 100.0
+            };
+            Some(CouponKind::Discount { rate })
+        }
+        "cash" => {
+            let amount = sub_type
+                .strip_prefix("cash_")
+                .and_then(|amount| amount.parse::<f32>().ok())
+                .unwrap_or(value);
+            Some(CouponKind::Cash { amount })
+        }
+        _ => None,
+    }
+}
+
+// 将旧的时间字段（时间戳或时间文本）转换为unix时间戳
+fn legacy_timestamp(value: &Value) -> Option<i64> {
+    match value {
+        Value::Number(number) => number.as_i64(),
+        Value::String(text) => OffsetDateTime::parse(text, &Rfc3339)
+            .or_else(|_| OffsetDateTime::parse(
+                text,
+                // OffsetDateTime::to_string() 的输出格式
+                format_description!("[year]-[month]-[day] [hour padding:none]:[minute]:[second].[subsecond] [offset_hour sign:mandatory]:[offset_minute]:[offset_second]"),
+            ))
+            .ok()
+            .map(|time| time.unix_timestamp()),
+        _ => None,
+    }
+}
+
+impl Database {
+    // 将旧版卡券和卡券模板记录转换为类型化结构，返回转换的记录数
+    pub async fn migrate_legacy_coupons(&self) -> Result<usize, surrealdb::Error> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut migrated = 0;
+
+        let mut result = self.client
+            .query("SELECT *, meta::id(id) AS id FROM coupon WHERE kind = NONE")
+            .await?;
+        let legacy_coupons: Vec<LegacyCoupon> = result.take(0)?;
+
+        for legacy in legacy_coupons {
+            let Some(kind) = legacy_kind(&legacy.coupon_type, &legacy.sub_type, legacy.value, legacy.duration_days) else {
+                eprintln!("Skipping coupon {} with unknown type {}/{}", legacy.id, legacy.coupon_type, legacy.sub_type);
+                continue;
+            };
+
+            let issued_at = legacy_timestamp(&legacy.issued_at).unwrap_or(now);
+            let expires_at = legacy_timestamp(&legacy.expires_at).unwrap_or(NEVER_EXPIRES_AT);
+
+            let mut coupon = Coupon::new(
+                legacy.id,
+                kind,
+                legacy.owner_id,
+                issued_at,
+                expires_at,
+                legacy.is_transferable,
+            );
+            coupon.status = match legacy.status.as_str() {
+                "used" => CouponStatus::Used,
+                "expired" => CouponStatus::Expired,
+                _ => CouponStatus::Active,
+            };
+
+            self.update_coupon(&coupon).await?;
+            migrated += 1;
+        }
+
+        let mut result = self.client
+            .query("SELECT *, meta::id(id) AS id FROM coupon_template WHERE kind = NONE")
+            .await?;
+        let legacy_templates: Vec<LegacyCouponTemplate> = result.take(0)?;
+
+        for legacy in legacy_templates {
+            // 旧模板的 duration_days 同时表示体验天数和有效期
+            let Some(kind) = legacy_kind(&legacy.coupon_type, &legacy.sub_type, legacy.value, legacy.duration_days) else {
+                eprintln!("Skipping coupon template {} with unknown type {}/{}", legacy.id, legacy.coupon_type, legacy.sub_type);
+                continue;
+            };
+
+            let template = CouponTemplate {
+                id: legacy.id.clone(),
+                name: legacy.name,
+                description: legacy.description,
+                kind,
+                duration_days: legacy.duration_days,
+                is_transferable: legacy.is_transferable,
+                created_at: legacy_timestamp(&legacy.created_at).unwrap_or(now),
+                is_active: legacy.is_active,
+            };
+
+            let _: Option<CouponTemplate> = self.client
+                .update(("coupon_template", &legacy.id))
+                .content(&template)
+                .await?;
+            migrated += 1;
+        }
+
+        Ok(migrated)
+    }
+
+    // 为销量字段上线前的商品补写 sold_count = 0，避免热度排序和分页游标遇到 NONE
+    pub async fn backfill_shop_item_sold_count(&self) -> Result<usize, surrealdb::Error> {
+        let mut result = self.client
+            .query("UPDATE shop_item SET sold_count = 0 WHERE sold_count = NONE RETURN meta::id(id) AS id")
+            .await?;
+        let updated: Vec<Value> = result.take(0)?;
+        Ok(updated.len())
+    }
+
+    // 为获取规则上线前的积分收入交易补写 earning_source，按交易类型和备注前缀识别来源，
+    // 使上限统计包含历史收入；无法识别的交易不计入任何来源
+    pub async fn backfill_wallet_tx_earning_sources(&self) -> Result<usize, surrealdb::Error> {
+        let legacy_sources = [
+            ("DailyCheckin", "PointsEarned", "每日签到奖励"),
+            ("Dialogue", "PointsEarned", "对话互动奖励"),
+            ("Invitation", "Reward", "邀请新用户奖励"),
+            ("FirstPayment", "Reward", "首次充值奖励"),
+            ("GiftReceived", "GiftReceive", ""),
+        ];
+
+        let mut updated = 0;
+        for (source, tx_type, remark_prefix) in legacy_sources {
+            let mut result = self.client
+                .query("
+                    UPDATE wallet_tx SET earning_source = $source
+                    WHERE earning_source = NONE AND currency = 'HP' AND tx_type = $tx_type
+                        AND string::starts_with(remark ?? '', $remark_prefix)
+                    RETURN meta::id(id) AS id
+                ")
+                .bind(("source", source))
+                .bind(("tx_type", tx_type))
+                .bind(("remark_prefix", remark_prefix))
+                .await?;
+            let rows: Vec<Value> = result.take(0)?;
+            updated += rows.len();
+        }
+
+        Ok(updated)
+    }
+
+    // 为已有的积分收入交易补写不含幸运卡加成的 base_amount，加成交易按倍率折算
+    pub async fn backfill_wallet_tx_base_amounts(&self) -> Result<usize, surrealdb::Error> {
+        let mut result = self.client
+            .query("
+                UPDATE wallet_tx SET base_amount = IF boost_multiplier > 0 THEN
+                        <int> math::round(amount / boost_multiplier)
+                    ELSE
+                        amount
+                    END
+                WHERE earning_source != NONE AND base_amount = NONE
+                RETURN meta::id(id) AS id
+            ")
+            .await?;
+
+        let updated: Vec<Value> = result.take(0)?;
+        Ok(updated.len())
+    }
+
+    // 用户间送礼的光币分成曾记为 GiftReceive，改为单独的 GiftShareReceive，与AI收礼的积分奖励区分
+    pub async fn migrate_gift_share_tx_type(&self) -> Result<usize, surrealdb::Error> {
+        let mut result = self.client
+            .query("
+                UPDATE wallet_tx SET tx_type = 'GiftShareReceive'
+                WHERE tx_type = 'GiftReceive' AND currency = 'LC'
+                RETURN meta::id(id) AS id
+            ")
+            .await?;
+        let updated: Vec<Value> = result.take(0)?;
+        Ok(updated.len())
+    }
+
+    // 保底计数记录曾使用随机ID，改为按用户和掉落表生成的ID，使计数可在固定记录上原子更新
+    pub async fn migrate_drop_pity_ids(&self) -> Result<usize, surrealdb::Error> {
+        let mut result = self.client
+            .query("SELECT * FROM drop_pity WHERE meta::id(id) != string::concat(user_id, '_', table_id)")
+            .await?;
+        let legacy: Vec<DropPity> = result.take(0)?;
+
+        for pity in &legacy {
+            self.client
+                .query("
+                    BEGIN TRANSACTION;
+                    DELETE type::thing('drop_pity', $legacy_id);
+                    CREATE type::thing('drop_pity', $pity_id) SET
+                        user_id = $pity.user_id,
+                        table_id = $pity.table_id,
+                        misses = $pity.misses,
+                        updated_at = $pity.updated_at;
+                    COMMIT TRANSACTION;
+                ")
+                .bind(("legacy_id", &pity.id))
+                .bind(("pity_id", DropPity::record_id(&pity.user_id, &pity.table_id)))
+                .bind(("pity", pity))
+                .await?
+                .check()?;
+        }
+
+        Ok(legacy.len())
+    }
+}
@@ -2,5 +2,21 @@ pub mod surreal;
 pub mod points;
 pub mod promoter;
 pub mod chat;
+pub mod subscription;
+pub mod migration;
+pub mod coupon;
+pub mod notification;
+pub mod drop_table;
+pub mod inventory;
+pub mod shop_promotion;
+pub mod wishlist;
+pub mod hp_lot;
+pub mod earning;
+pub mod checkin;
+pub mod achievement;
+pub mod leaderboard;
+pub mod gift_streak;
+pub mod gift_event;
+pub mod job_run;
 
 pub use surreal::Database;
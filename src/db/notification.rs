@@ -0,0 +1,36 @@
+use crate::models::Notification;
+
+use super::surreal::Database;
+
+impl Database {
+    // 创建站内通知
+    pub async fn create_notification(&self, notification: &Notification) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<Notification>>(("notification", &notification.id))
+            .content(notification)
+            .await?;
+        Ok(())
+    }
+
+    // 获取用户的站内通知
+    pub async fn get_user_notifications(&self, user_id: &str, limit: usize) -> Result<Vec<Notification>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM notification WHERE user_id = $user_id ORDER BY created_at DESC LIMIT $limit")
+            .bind(("user_id", user_id))
+            .bind(("limit", limit))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 将用户的通知标记为已读
+    pub async fn mark_notifications_read(&self, user_id: &str, ids: &[String]) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE notification SET is_read = true WHERE user_id = $user_id AND meta::id(id) INSIDE $ids")
+            .bind(("user_id", user_id))
+            .bind(("ids", ids))
+            .await?;
+        Ok(())
+    }
+}
@@ -4,11 +4,26 @@ use time::OffsetDateTime;
 
 use crate::models::{
     User, WalletTx, TxType, CurrencyType, Gift, GiftRecord, 
-    LuckyCard, CardLevel, ShopItem, PurchaseRecord, ShopItemCategory, MonthlyRedemptionStat,
+    LuckyCard, LuckyBoost, ShopItem, PurchaseRecord, MonthlyRedemptionStat,
 };
-use crate::models::gift::{ConsecutiveGiftRecord, GiftFeedbackTemplate, GiftCategory};
+use crate::models::gift::{
+    ConsecutiveGiftRecord, GiftComboRule, GiftFeedbackTemplate, GiftCategory, GiftStreakUpdate, UserGiftRecord, UserGiftSetting,
+};
+use crate::models::point_shop::ShopItemSearch;
+use crate::models::hp_lot::HpLot;
+use crate::models::shop_promotion::{PriceQuote, ShopBundle};
+
+use super::surreal::{thrown_error, Database};
+
+// 积分入账语句：增加用户积分并写入交易记录和积分批次，需绑定 $tx 和 $lot
+pub(crate) const CREDIT_HP_STATEMENTS: &str = "
+    UPDATE type::thing('user', $tx.user_id) SET hp += $tx.amount, updated_at = $tx.timestamp;
+    CREATE type::thing('wallet_tx', $tx.id) CONTENT $tx;
+    CREATE type::thing('hp_lot', $lot.id) CONTENT $lot;
+";
 
-use super::surreal::Database;
+// 扣减方案在事务外按当前批次计算，批次被并发消费时重新计算并重试的次数上限
+const MAX_HP_LOT_ATTEMPTS: usize = 3;
 
 impl Database {
     // ==================== 用户积分操作 ====================
@@ -28,79 +43,44 @@ impl Database {
             remark,
         );
         
+        self.credit_user_hp(&tx).await
+    }
+    
+    // 按交易记录增加用户积分，同时按交易类型的有效期创建积分批次
+    pub async fn credit_user_hp(&self, tx: &WalletTx) -> Result<(), surrealdb::Error> {
+        let lot = self.hp_lot_for(tx).await?;
+        
         // 更新用户积分并添加交易记录
-        let result = self
-            .client
-            .query("
+        self.client
+            .query(format!("
                 BEGIN TRANSACTION;
-                
-                LET $user = SELECT * FROM user:$user_id;
-                
-                UPDATE user:$user_id SET 
-                    hp = $user.hp + $amount,
-                    updated_at = $now;
-                
-                CREATE wallet_tx:$tx_id CONTENT $tx;
-                
+                {}
                 COMMIT TRANSACTION;
-            ")
-            .bind(("user_id", user_id))
-            .bind(("amount", amount))
-            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
-            .bind(("tx_id", &tx.id))
-            .bind(("tx", &tx))
-            .await?;
+            ", CREDIT_HP_STATEMENTS))
+            .bind(("tx", tx))
+            .bind(("lot", &lot))
+            .await?
+            .check()?;
         
         Ok(())
     }
     
-    // 扣减用户积分
-    pub async fn deduct_user_hp(&self, user_id: &str, amount: u32, tx_type: TxType, 
-                               related_entity_id: Option<String>, remark: Option<String>) 
-        -> Result<bool, surrealdb::Error> {
-        
-        // 创建交易记录
-        let tx = WalletTx::new(
-            user_id.to_string(),
-            tx_type,
-            amount,
-            CurrencyType::HP,
-            related_entity_id,
-            remark,
-        );
-        
-        // 检查用户积分是否足够，如果足够则扣减并添加交易记录
-        let mut result = self
-            .client
-            .query("
-                BEGIN TRANSACTION;
-                
-                LET $user = SELECT * FROM user:$user_id;
-                
-                IF $user.hp >= $amount THEN
-                    UPDATE user:$user_id SET 
-                        hp = $user.hp - $amount,
-                        updated_at = $now;
-                    
-                    CREATE wallet_tx:$tx_id CONTENT $tx;
-                    
-                    RETURN true;
-                ELSE
-                    RETURN false;
-                END;
-                
-                COMMIT TRANSACTION;
-            ")
+    // 按交易类型的有效期生成交易对应的积分批次
+    pub(crate) async fn hp_lot_for(&self, tx: &WalletTx) -> Result<HpLot, surrealdb::Error> {
+        let policy = self.get_effective_hp_expiry_policy(&tx.tx_type).await?;
+        Ok(HpLot::new(tx, policy.expires_at(tx.timestamp)))
+    }
+    
+    // 更新用户的幸运卡加成
+    pub async fn set_user_lucky_boost(&self, user_id: &str, boost: Option<&LuckyBoost>) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('user', $user_id) SET lucky_boost = $boost, updated_at = $now")
             .bind(("user_id", user_id))
-            .bind(("amount", amount))
+            .bind(("boost", boost))
             .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
-            .bind(("tx_id", &tx.id))
-            .bind(("tx", &tx))
             .await?;
         
-        // 返回是否扣减成功
-        let success: Option<bool> = result.take(0)?;
-        Ok(success.unwrap_or(false))
+        Ok(())
     }
     
     // 获取用户积分交易记录
@@ -295,52 +275,104 @@ impl Database {
         self.client.select(("gift", gift_id)).await
     }
     
-    // 赠送礼物
-    pub async fn send_gift(&self, gift_id: &str, sender_id: &str, receiver_ai_id: &str, message: Option<String>) 
-        -> Result<bool, surrealdb::Error> {
-        
-        // 获取礼物信息
-        let gift: Option<Gift> = self.client.select(("gift", gift_id)).await?;
+    // 赠送礼物：扣除光币、写入送礼记录并更新连续送礼记录
+    // 连续天数按用户当地日期在事务中计算，累计数量用增量更新，不会覆盖并发送礼或里程碑加成；余额不足时返回 None
+    pub async fn send_gift(&self, record: &GiftRecord, tx: &WalletTx, streak_id: &str, emotional_value: u32, 
+                           utc_offset_secs: i64) 
+        -> Result<Option<GiftStreakUpdate>, surrealdb::Error> {
         
-        if let Some(gift) = gift {
-            // 检查礼物是否可用
-            let now = OffsetDateTime::now_utc().unix_timestamp();
-            if !gift.is_active || (gift.is_limited && gift.available_until.unwrap_or(0) <= now) {
-                return Ok(false);
-            }
-            
-            // 创建礼物记录
-            let gift_record = GiftRecord::new(
-                gift_id.to_string(),
-                sender_id.to_string(),
-                receiver_ai_id.to_string(),
-                message,
-            );
-            
-            // 扣减用户金币并创建礼物记录
-            let deduct_result = self.deduct_user_lc(
-                sender_id, 
-                gift.price_lc, 
-                TxType::GiftSend, 
-                Some(gift_record.id.clone()),
-                Some(format!("赠送礼物: {}", gift.name)),
-            ).await?;
-            
-            if deduct_result {
-                // 创建礼物记录
-                self.client
-                    .create::<Option<GiftRecord>>(("gift_record", &gift_record.id))
-                    .content(&gift_record)
-                    .await?;
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
                 
-                // 更新连续送礼记录
-                self.update_consecutive_gift_record(sender_id, receiver_ai_id, &gift).await?;
+                LET $sender = UPDATE type::thing('user', $record.sender_id) SET
+                    lc_balance -= $tx.amount,
+                    updated_at = $record.sent_at
+                WHERE lc_balance >= $tx.amount;
+                IF array::len($sender) = 0 {
+                    THROW 'insufficient balance';
+                };
                 
-                return Ok(true);
-            }
+                LET $previous = (SELECT * FROM type::thing('consecutive_gift_record', $streak_id))[0];
+                LET $previous_days = $previous.consecutive_days OR 0;
+                LET $today = math::floor(($record.sent_at + $offset) / 86400);
+                LET $missed = $today - math::floor((($previous.last_gift_date OR 0) + $offset) / 86400) - 1;
+                LET $freezes_used = IF $previous_days > 0 AND $missed > 0 AND $missed <= $sender[0].gift_streak_freezes
+                    THEN $missed ELSE 0 END;
+                LET $days = IF $previous_days > 0 AND $missed < 0 THEN $previous_days
+                    ELSE IF $previous_days > 0 AND ($missed = 0 OR $freezes_used > 0) THEN $previous_days + 1
+                    ELSE 1 END;
+                
+                IF $freezes_used > 0 {
+                    UPDATE type::thing('user', $record.sender_id) SET gift_streak_freezes -= $freezes_used;
+                };
+                
+                CREATE type::thing('wallet_tx', $tx.id) CONTENT $tx;
+                CREATE type::thing('gift_record', $record.id) CONTENT $record;
+                
+                UPDATE type::thing('consecutive_gift_record', $streak_id) SET
+                    user_id = $record.sender_id,
+                    ai_id = $record.receiver_ai_id,
+                    consecutive_days = $days,
+                    last_gift_date = $record.sent_at,
+                    total_gifts_sent = (total_gifts_sent OR 0) + $quantity,
+                    total_emotional_value = (total_emotional_value OR 0) + $emotional_value,
+                    break_notified_at = NONE;
+                
+                RETURN {
+                    streak: (SELECT *, meta::id(id) AS id FROM type::thing('consecutive_gift_record', $streak_id))[0],
+                    previous_days: $previous_days,
+                    previous_emotional_value: $previous.total_emotional_value OR 0,
+                    freezes_used: $freezes_used,
+                    break_notified: $previous.break_notified_at != NONE,
+                };
+                
+                COMMIT TRANSACTION;
+            ")
+            .bind(("record", record))
+            .bind(("tx", tx))
+            .bind(("streak_id", streak_id))
+            .bind(("offset", utc_offset_secs))
+            .bind(("quantity", record.quantity))
+            .bind(("emotional_value", emotional_value))
+            .await?;
+        
+        if thrown_error(&mut result, &["insufficient balance"])?.is_some() {
+            return Ok(None);
         }
         
-        Ok(false)
+        let last = result.num_statements() - 1;
+        result.take(last)
+    }
+    
+    // 记录用户送礼时间，返回是否为当地当天首次送礼（并发送礼时只有一次返回 true）
+    pub async fn mark_first_gift_of_day(&self, user_id: &str, sent_at: i64, day_start: i64) -> Result<bool, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('user', $user_id) SET last_gift_date = $sent_at
+                WHERE last_gift_date = NONE OR last_gift_date < $day_start
+            ")
+            .bind(("user_id", user_id))
+            .bind(("sent_at", sent_at))
+            .bind(("day_start", day_start))
+            .await?;
+        
+        let updated: Vec<User> = result.take(0)?;
+        Ok(!updated.is_empty())
+    }
+    
+    // 统计用户累计赠送的礼物数量（按数量计）
+    pub async fn count_user_gifts_sent(&self, user_id: &str) -> Result<u32, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("RETURN math::sum((SELECT VALUE quantity OR 1 FROM gift_record WHERE sender_id = $user_id))")
+            .bind(("user_id", user_id))
+            .await?;
+        
+        let count: Option<u32> = result.take(0)?;
+        Ok(count.unwrap_or(0))
     }
     
     // 获取用户赠送的礼物记录
@@ -381,67 +413,93 @@ impl Database {
         Ok(result.take(0)?)
     }
     
-    // 更新连续送礼记录
-    pub async fn update_consecutive_gift_record(&self, user_id: &str, ai_id: &str, gift: &Gift) 
-        -> Result<ConsecutiveGiftRecord, surrealdb::Error> {
+    // 用户间送礼：扣减送礼方光币、按分成给收礼方入账、记录双方交易和礼物在同一事务中完成，余额不足时返回 false
+    pub async fn send_user_gift(&self, record: &UserGiftRecord, sender_tx: &WalletTx, receiver_tx: &WalletTx) 
+        -> Result<bool, surrealdb::Error> {
         
-        // 查询是否存在连续送礼记录
         let mut result = self
             .client
             .query("
-                SELECT * FROM consecutive_gift_record 
-                WHERE user_id = $user_id AND ai_id = $ai_id
-                LIMIT 1
+                BEGIN TRANSACTION;
+                
+                LET $sender = UPDATE type::thing('user', $record.sender_id) SET
+                    lc_balance -= $sender_tx.amount,
+                    updated_at = $record.sent_at
+                WHERE lc_balance >= $sender_tx.amount;
+                IF array::len($sender) = 0 {
+                    THROW 'insufficient balance';
+                };
+                
+                UPDATE type::thing('user', $record.receiver_id) SET
+                    lc_balance += $receiver_tx.amount,
+                    updated_at = $record.sent_at;
+                
+                CREATE type::thing('wallet_tx', $sender_tx.id) CONTENT $sender_tx;
+                IF $receiver_tx.amount > 0 {
+                    CREATE type::thing('wallet_tx', $receiver_tx.id) CONTENT $receiver_tx;
+                };
+                CREATE type::thing('user_gift_record', $record.id) CONTENT $record;
+                
+                COMMIT TRANSACTION;
+            ")
+            .bind(("record", record))
+            .bind(("sender_tx", sender_tx))
+            .bind(("receiver_tx", receiver_tx))
+            .await?;
+        
+        Ok(thrown_error(&mut result, &["insufficient balance"])?.is_none())
+    }
+    
+    // 获取用户送给其他用户的礼物记录
+    pub async fn get_user_gifts_sent_to_users(&self, user_id: &str, limit: usize) 
+        -> Result<Vec<UserGiftRecord>, surrealdb::Error> {
+        
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM user_gift_record 
+                WHERE sender_id = $user_id
+                ORDER BY sent_at DESC
+                LIMIT $limit
             ")
             .bind(("user_id", user_id))
-            .bind(("ai_id", ai_id))
+            .bind(("limit", limit))
             .await?;
         
-        let records: Vec<ConsecutiveGiftRecord> = result.take(0)?;
-        let now = OffsetDateTime::now_utc().unix_timestamp();
-        let today_start = now - (now % 86400); // 当天0点的时间戳
+        result.take(0)
+    }
+    
+    // 获取用户收到的其他用户的礼物记录
+    pub async fn get_user_received_gifts(&self, user_id: &str, limit: usize) 
+        -> Result<Vec<UserGiftRecord>, surrealdb::Error> {
         
-        if let Some(mut record) = records.into_iter().next() {
-            // 检查是否是连续的（同一天内多次送礼只算一次）
-            let last_gift_day = record.last_gift_date - (record.last_gift_date % 86400);
-            
-            if last_gift_day < today_start {
-                // 不是同一天
-                if last_gift_day + 86400 >= today_start {
-                    // 是连续的（昨天送过礼物）
-                    record.consecutive_days += 1;
-                } else {
-                    // 不是连续的
-                    record.consecutive_days = 1;
-                }
-                record.last_gift_date = now;
-            }
-            
-            // 更新总计数据
-            record.total_gifts_sent += 1;
-            record.total_emotional_value += gift.emotional_value;
-            
-            // 更新记录
-            self.client
-                .update::<Option<ConsecutiveGiftRecord>>(("consecutive_gift_record", &record.id))
-                .content(&record)
-                .await?;
-            
-            Ok(record)
-        } else {
-            // 创建新记录
-            let record = ConsecutiveGiftRecord::new(
-                user_id.to_string(),
-                ai_id.to_string(),
-            );
-            
-            self.client
-                .create::<Option<ConsecutiveGiftRecord>>(("consecutive_gift_record", &record.id))
-                .content(&record)
-                .await?;
-            
-            Ok(record)
-        }
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM user_gift_record 
+                WHERE receiver_id = $user_id
+                ORDER BY sent_at DESC
+                LIMIT $limit
+            ")
+            .bind(("user_id", user_id))
+            .bind(("limit", limit))
+            .await?;
+        
+        result.take(0)
+    }
+    
+    // 获取用户间送礼分成配置
+    pub async fn get_user_gift_setting(&self) -> Result<Option<UserGiftSetting>, surrealdb::Error> {
+        self.client.select(("user_gift_setting", UserGiftSetting::ID)).await
+    }
+    
+    // 保存用户间送礼分成配置
+    pub async fn save_user_gift_setting(&self, setting: &UserGiftSetting) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<UserGiftSetting>>(("user_gift_setting", &setting.id))
+            .content(setting)
+            .await?;
+        Ok(())
     }
     
     // 获取用户与AI的连续送礼记录
@@ -463,6 +521,20 @@ impl Database {
         Ok(records.into_iter().next())
     }
     
+    // 获取礼物分类的连击规则
+    pub async fn get_gift_combo_rule(&self, category: &GiftCategory) -> Result<Option<GiftComboRule>, surrealdb::Error> {
+        self.client.select(("gift_combo_rule", category.as_str())).await
+    }
+    
+    // 保存礼物连击规则
+    pub async fn save_gift_combo_rule(&self, rule: &GiftComboRule) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<GiftComboRule>>(("gift_combo_rule", &rule.id))
+            .content(rule)
+            .await?;
+        Ok(())
+    }
+    
     // 创建礼物反馈模板
     pub async fn create_gift_feedback_template(&self, template: &GiftFeedbackTemplate) 
         -> Result<(), surrealdb::Error> {
@@ -494,17 +566,18 @@ impl Database {
     // ==================== 幸运卡系统操作 ====================
     
     // 创建幸运卡
-    pub async fn create_lucky_card(&self, level: CardLevel, owner_id: &str, issued_by_ai_id: Option<String>) 
-        -> Result<LuckyCard, surrealdb::Error> {
-        
-        let lucky_card = LuckyCard::new(level, owner_id.to_string(), issued_by_ai_id);
-        
+    pub async fn create_lucky_card(&self, lucky_card: &LuckyCard) -> Result<(), surrealdb::Error> {
         self.client
             .create::<Option<LuckyCard>>(("lucky_card", &lucky_card.id))
-            .content(&lucky_card)
+            .content(lucky_card)
             .await?;
         
-        Ok(lucky_card)
+        Ok(())
+    }
+    
+    // 获取幸运卡
+    pub async fn get_lucky_card(&self, card_id: &str) -> Result<Option<LuckyCard>, surrealdb::Error> {
+        self.client.select(("lucky_card", card_id)).await
     }
     
     // 获取用户有效的幸运卡
@@ -530,6 +603,21 @@ impl Database {
         Ok(result.take(0)?)
     }
     
+    // 将已过期未使用的幸运卡标记为过期，返回处理数量
+    pub async fn expire_lucky_cards(&self, now: i64) -> Result<usize, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE lucky_card SET is_expired = true
+                WHERE is_used = false AND is_expired != true AND expires_at <= $now
+            ")
+            .bind(("now", now))
+            .await?;
+
+        let expired: Vec<LuckyCard> = result.take(0)?;
+        Ok(expired.len())
+    }
+    
     // 使用幸运卡
     pub async fn use_lucky_card(&self, card_id: &str) 
         -> Result<Option<f32>, surrealdb::Error> {
@@ -586,65 +674,6 @@ impl Database {
         Ok(result.take(0)?)
     }
     
-    // 购买商品
-    pub async fn purchase_shop_item(&self, user_id: &str, item_id: &str) 
-        -> Result<bool, surrealdb::Error> {
-        
-        // 获取商品信息
-        let item: Option<ShopItem> = self.client.select(("shop_item", item_id)).await?;
-        
-        if let Some(item) = item {
-            // 检查商品是否可用
-            if !item.is_available() {
-                return Ok(false);
-            }
-            
-            // 创建购买记录
-            let purchase_record = PurchaseRecord::new(
-                user_id.to_string(),
-                item_id.to_string(),
-                item.price_hp,
-                None, // 暂不设置过期时间
-                None, // 无备注
-            );
-            
-            // 扣减用户积分并创建购买记录
-            let deduct_result = self.deduct_user_hp(
-                user_id, 
-                item.price_hp, 
-                TxType::PointsSpent, 
-                Some(purchase_record.id.clone()),
-                Some(format!("购买商品: {}", item.name)),
-            ).await?;
-            
-            if deduct_result {
-                // 创建购买记录
-                self.client
-                    .create::<Option<PurchaseRecord>>(("purchase_record", &purchase_record.id))
-                    .content(&purchase_record)
-                    .await?;
-                
-                // 如果商品有库存限制，则减少库存
-                if let Some(stock) = item.stock {
-                    if stock > 0 {
-                        self.client
-                            .query("
-                                UPDATE shop_item:$item_id SET 
-                                    stock = $stock - 1
-                            ")
-                            .bind(("item_id", item_id))
-                            .bind(("stock", stock))
-                            .await?;
-                    }
-                }
-                
-                return Ok(true);
-            }
-        }
-        
-        Ok(false)
-    }
-    
     // 获取用户购买记录
     pub async fn get_user_purchases(&self, user_id: &str, limit: usize) 
         -> Result<Vec<PurchaseRecord>, surrealdb::Error> {
@@ -664,6 +693,21 @@ impl Database {
         Ok(result.take(0)?)
     }
     
+    // 获取购买记录
+    pub async fn get_purchase_record(&self, purchase_id: &str) -> Result<Option<PurchaseRecord>, surrealdb::Error> {
+        self.client.select(("purchase_record", purchase_id)).await
+    }
+    
+    // 更新购买记录
+    pub async fn update_purchase_record(&self, record: &PurchaseRecord) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<PurchaseRecord>>(("purchase_record", &record.id))
+            .content(record)
+            .await?;
+        
+        Ok(())
+    }
+    
     // ==================== 积分商城操作扩展 ====================
     
     // 获取所有商品（包括不可见的，用于管理员）
@@ -679,31 +723,57 @@ impl Database {
         Ok(result.take(0)?)
     }
     
-    // 根据分类获取可用商品
-    pub async fn get_available_shop_items_by_category(&self, category: &ShopItemCategory) 
+    // 搜索可用商品，按排序字段和ID做游标分页
+    pub async fn search_shop_items(&self, search: &ShopItemSearch) 
         -> Result<Vec<ShopItem>, surrealdb::Error> {
         
-        let now = OffsetDateTime::now_utc().unix_timestamp();
-        let category_str = match category {
-            ShopItemCategory::Coupon => "Coupon",
-            ShopItemCategory::Decoration => "Decoration",
-            ShopItemCategory::Function => "Function",
-        };
+        let mut sql = String::from("
+            SELECT * FROM shop_item 
+            WHERE 
+                visible = true AND
+                ((is_limited = false) OR 
+                (is_limited = true AND available_until > $now)) AND
+                (stock IS NONE OR stock > 0)
+        ");
+        
+        if search.keyword.is_some() {
+            sql.push_str(" AND (string::lowercase(name) CONTAINS $keyword OR string::lowercase(description) CONTAINS $keyword)");
+        }
+        if search.category.is_some() {
+            sql.push_str(" AND category = $category");
+        }
+        if search.item_type.is_some() {
+            sql.push_str(" AND item_type = $item_type");
+        }
+        if search.min_price.is_some() {
+            sql.push_str(" AND price_hp >= $min_price");
+        }
+        if search.max_price.is_some() {
+            sql.push_str(" AND price_hp <= $max_price");
+        }
+        
+        let field = search.sort.field();
+        let (direction, compare) = if search.sort.is_descending() { ("DESC", "<") } else { ("ASC", ">") };
+        
+        if search.cursor.is_some() {
+            sql.push_str(&format!(
+                " AND ({field} {compare} $cursor_value OR ({field} = $cursor_value AND meta::id(id) {compare} $cursor_id))"
+            ));
+        }
+        sql.push_str(&format!(" ORDER BY {field} {direction}, id {direction} LIMIT $limit"));
         
         let mut result = self
             .client
-            .query("
-                SELECT * FROM shop_item 
-                WHERE 
-                    category = $category AND
-                    visible = true AND
-                    ((is_limited = false) OR 
-                    (is_limited = true AND available_until > $now)) AND
-                    (stock IS NONE OR stock > 0)
-                ORDER BY price_hp ASC
-            ")
-            .bind(("category", category_str))
-            .bind(("now", now))
+            .query(sql)
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .bind(("keyword", search.keyword.as_ref().map(|keyword| keyword.to_lowercase())))
+            .bind(("category", search.category.as_ref().map(|category| format!("{:?}", category))))
+            .bind(("item_type", search.item_type.as_ref().map(|item_type| format!("{:?}", item_type))))
+            .bind(("min_price", search.min_price))
+            .bind(("max_price", search.max_price))
+            .bind(("cursor_value", search.cursor.as_ref().map(|cursor| cursor.value)))
+            .bind(("cursor_id", search.cursor.as_ref().map(|cursor| cursor.id.clone())))
+            .bind(("limit", search.limit))
             .await?;
         
         Ok(result.take(0)?)
@@ -736,27 +806,9 @@ impl Database {
             .await
     }
     
-    // 计算用户商品的折扣价格
-    pub async fn calculate_discounted_price(&self, user_id: &str, item_id: &str) 
-        -> Result<u32, surrealdb::Error> {
-        
-        // 获取用户信息
-        let user: Option<User> = self.client.select(("user", user_id)).await?;
-        
-        // 获取商品信息
-        let item: Option<ShopItem> = self.client.select(("shop_item", item_id)).await?;
-        
-        if let (Some(user), Some(item)) = (user, item) {
-            // 计算折扣价格
-            Ok(item.get_discounted_price(&user.vip_level))
-        } else {
-            Err(surrealdb::Error::Api(surrealdb::error::Api::Query(String::from("用户或商品不存在"))))
-        }
-    }
-    
-    // 兑换商品（扩展版，支持VIP折扣和月度限制）
-    pub async fn redeem_shop_item(&self, user_id: &str, item_id: &str, remark: Option<String>) 
-        -> Result<bool, surrealdb::Error> {
+    // 兑换商品，成交价由 PricingService 计算
+    pub async fn redeem_shop_item(&self, user_id: &str, item_id: &str, quote: &PriceQuote, remark: Option<String>) 
+        -> Result<Option<PurchaseRecord>, surrealdb::Error> {
         
         // 获取商品信息
         let item: Option<ShopItem> = self.client.select(("shop_item", item_id)).await?;
@@ -764,142 +816,273 @@ impl Database {
         if let Some(item) = item {
             // 检查商品是否可用
             if !item.is_available() {
-                return Ok(false);
+                return Ok(None);
             }
             
             // 获取用户信息
             let user: Option<User> = self.client.select(("user", user_id)).await?;
             
             if let Some(user) = user {
-                // 计算折扣价格
-                let price_to_pay = item.get_discounted_price(&user.vip_level);
-                
-                // 检查月度兑换限制
-                if let Some(monthly_limit) = item.monthly_limit {
-                    // 获取月度兑换统计
-                    let now = OffsetDateTime::now_utc();
-                    let year_month = format!("{}-{:02}", now.year(), now.month() as u8);
-                    let stat_id = format!("{}:{}", user_id, year_month);
-                    
-                    let mut stat: Option<MonthlyRedemptionStat> = self.client
-                        .select(("monthly_redemption_stat", &stat_id))
-                        .await?;
-                    
-                    if let Some(ref stat) = stat {
-                        let item_type_str = format!("{:?}", item.item_type);
-                        if !stat.check_monthly_limit(&item_type_str, monthly_limit) {
-                            return Ok(false); // 达到月度兑换上限
-                        }
-                    }
-                }
-                
                 // 创建购买记录
-                let purchase_record = PurchaseRecord::new(
+                let mut purchase_record = PurchaseRecord::new(
                     user_id.to_string(),
                     item_id.to_string(),
-                    price_to_pay,
+                    quote.effective_price,
                     None, // 暂不设置过期时间
                     remark,
                 );
+                purchase_record.flash_deal_id = quote.flash_deal_id().map(str::to_string);
+                purchase_record.coupon_id = quote.coupon_id.clone();
                 
-                // 扣减用户积分并创建购买记录
-                let deduct_result = self.deduct_user_hp(
-                    user_id, 
-                    price_to_pay, 
-                    TxType::PointsSpent, 
-                    Some(purchase_record.id.clone()),
-                    Some(format!("兑换商品: {}", item.name)),
-                ).await?;
+                let remark = format!("兑换商品: {}", item.name);
+                let records = vec![purchase_record];
                 
-                if deduct_result {
-                    // 创建购买记录
-                    self.client
-                        .create::<Option<PurchaseRecord>>(("purchase_record", &purchase_record.id))
-                        .content(&purchase_record)
-                        .await?;
+                if self.redeem_purchases(&user, &[item], &records, quote, remark).await? {
+                    // 商品效果由 FulfilmentService 在激活时发放
+                    return Ok(records.into_iter().next());
+                }
+            }
+        }
+        
+        Ok(None)
+    }
+    
+    // 兑换组合包，为包内每个商品生成一条按比例分摊价格的购买记录
+    pub async fn redeem_shop_bundle(&self, user: &User, bundle: &ShopBundle, items: &[ShopItem], 
+                                    quote: &PriceQuote, remark: Option<String>) 
+        -> Result<Option<Vec<PurchaseRecord>>, surrealdb::Error> {
+        
+        // 使用折扣券时按券后价格分摊
+        let records: Vec<PurchaseRecord> = items
+            .iter()
+            .zip(ShopBundle::split_price(quote.effective_price, items))
+            .map(|(item, price)| {
+                let mut record = PurchaseRecord::new(
+                    user.id.clone(),
+                    item.id.clone(),
+                    price,
+                    None,
+                    remark.clone(),
+                );
+                record.bundle_id = Some(bundle.id.clone());
+                record.coupon_id = quote.coupon_id.clone();
+                record
+            })
+            .collect();
+        
+        let remark = format!("兑换组合包: {}", bundle.name);
+        
+        if self.redeem_purchases(user, items, &records, quote, remark).await? {
+            return Ok(Some(records));
+        }
+        
+        Ok(None)
+    }
+    
+    // 在同一事务中检查限购、积分和秒杀/组合包余量，扣减积分与库存并创建购买记录
+    async fn redeem_purchases(&self, user: &User, items: &[ShopItem], records: &[PurchaseRecord], 
+                              quote: &PriceQuote, remark: String) 
+        -> Result<bool, surrealdb::Error> {
+        
+        let related_id = match quote.bundle_id() {
+            Some(bundle_id) => bundle_id.to_string(),
+            None => records[0].id.clone(),
+        };
+        
+        let tx = WalletTx::new(
+            user.id.clone(),
+            TxType::PointsSpent,
+            quote.effective_price,
+            CurrencyType::HP,
+            Some(related_id),
+            Some(remark),
+        );
+        
+        // 限购窗口的起始时间与上限，终身限购从0开始统计
+        let now = OffsetDateTime::now_utc();
+        let limits: Vec<serde_json::Value> = items
+            .iter()
+            .flat_map(|item| {
+                item.effective_limits(&user.vip_level)
+                    .windows()
+                    .into_iter()
+                    .map(|(window, max)| serde_json::json!({
+                        "item_id": item.id,
+                        "since": window.start(now).unwrap_or(0),
+                        "max": max,
+                    }))
+            })
+            .collect();
+        
+        let item_ids: Vec<&str> = items.iter().map(|item| item.id.as_str()).collect();
+        
+        // 达到限购、积分不足、卡券不可用或已售罄时事务回滚
+        let expected = [
+            "purchase limit reached", "insufficient hp", "hp lot changed", "flash deal sold out",
+            "coupon not available", "bundle sold out", "item sold out",
+        ];
+        
+        // 按先进先出扣减积分批次，批次被并发消费时重新计算扣减方案
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            let deductions = self.plan_user_hp_deductions(&user.id, quote.effective_price).await?;
+            
+            let mut result = self
+                .client
+                .query("
+                    BEGIN TRANSACTION;
                     
-                    // 更新月度兑换统计
-                    let now = OffsetDateTime::now_utc();
-                    let year_month = format!("{}-{:02}", now.year(), now.month() as u8);
-                    let stat_id = format!("{}:{}", user_id, year_month);
+                    LET $user = (SELECT * FROM type::thing('user', $user_id))[0];
                     
-                    let mut stat: Option<MonthlyRedemptionStat> = self.client
-                        .select(("monthly_redemption_stat", &stat_id))
-                        .await?;
+                    FOR $limit IN $limits {
+                        LET $used = (SELECT count() AS count FROM purchase_record
+                            WHERE user_id = $user_id AND item_id = $limit.item_id AND purchased_at >= $limit.since
+                            GROUP ALL)[0].count ?? 0;
+                        IF $used >= $limit.max {
+                            THROW 'purchase limit reached';
+                        };
+                    };
                     
-                    if let Some(mut stat) = stat {
-                        // 更新现有统计
-                        let item_type_str = format!("{:?}", item.item_type);
-                        stat.record_redemption(&item_type_str, price_to_pay);
-                        
-                        self.client
-                            .update::<Option<MonthlyRedemptionStat>>(("monthly_redemption_stat", &stat_id))
-                            .content(&stat)
-                            .await?;
-                    } else {
-                        // 创建新的统计记录
-                        let mut new_stat = MonthlyRedemptionStat::new(user_id.to_string());
-                        let item_type_str = format!("{:?}", item.item_type);
-                        new_stat.record_redemption(&item_type_str, price_to_pay);
-                        
-                        self.client
-                            .create::<Option<MonthlyRedemptionStat>>(("monthly_redemption_stat", &new_stat.id))
-                            .content(&new_stat)
-                            .await?;
-                    }
+                    IF $user.hp < $amount {
+                        THROW 'insufficient hp';
+                    };
                     
-                    // 如果商品有库存限制，则减少库存
-                    if let Some(stock) = item.stock {
-                        if stock > 0 {
-                            self.client
-                                .query("
-                                    UPDATE shop_item:$item_id SET 
-                                        stock = $stock - 1
-                                ")
-                                .bind(("item_id", item_id))
-                                .bind(("stock", stock))
-                                .await?;
-                        }
-                    }
+                    FOR $deduction IN $deductions {
+                        LET $lot = UPDATE type::thing('hp_lot', $deduction.lot_id) SET remaining -= $deduction.amount
+                            WHERE is_expired = false AND remaining >= $deduction.amount;
+                        IF array::len($lot) = 0 {
+                            THROW 'hp lot changed';
+                        };
+                    };
                     
-                    // 处理卡券类商品
-                    if item.category == ShopItemCategory::Coupon {
-                        if let Some(coupon_id) = item.linked_coupon_id {
-                            // 获取卡券模板信息
-                            let coupon_template: Option<crate::models::coupon::CouponTemplate> = 
-                                self.client.select(("coupon_template", &coupon_id)).await?;
-                            
-                            if let Some(template) = coupon_template {
-                                // 创建用户卡券
-                                let new_coupon = crate::models::coupon::Coupon::new_from_template(
-                                    template,
-                                    user_id.to_string(),
-                                );
-                                
-                                self.create_coupon(&new_coupon).await?;
-                            }
-                        }
-                    }
+                    IF $flash_deal_id != NONE {
+                        LET $deal = UPDATE type::thing('flash_deal', $flash_deal_id) SET sold += 1 WHERE sold < stock;
+                        IF array::len($deal) = 0 {
+                            THROW 'flash deal sold out';
+                        };
+                    };
                     
-                    return Ok(true);
-                }
+                    IF $coupon_id != NONE {
+                        LET $coupon = UPDATE type::thing('coupon', $coupon_id) SET status = 'Used'
+                            WHERE owner_id = $user_id AND status = 'Active' AND expires_at > $now AND pending_transfer_id = NONE;
+                        IF array::len($coupon) = 0 {
+                            THROW 'coupon not available';
+                        };
+                    };
+                    
+                    IF $bundle_id != NONE {
+                        LET $bundle = UPDATE type::thing('shop_bundle', $bundle_id) SET sold += 1 WHERE stock = NONE OR sold < stock;
+                        IF array::len($bundle) = 0 {
+                            THROW 'bundle sold out';
+                        };
+                    };
+                    
+                    FOR $item_id IN $item_ids {
+                        LET $item = (SELECT stock FROM type::thing('shop_item', $item_id))[0];
+                        IF $item.stock != NONE {
+                            LET $stocked = UPDATE type::thing('shop_item', $item_id) SET stock -= 1 WHERE stock > 0;
+                            IF array::len($stocked) = 0 {
+                                THROW 'item sold out';
+                            };
+                        };
+                        UPDATE type::thing('shop_item', $item_id) SET sold_count += 1;
+                    };
+                    
+                    UPDATE type::thing('user', $user_id) SET 
+                        hp -= $amount,
+                        updated_at = $now;
+                    
+                    CREATE type::thing('wallet_tx', $tx.id) CONTENT $tx;
+                    
+                    FOR $record IN $records {
+                        CREATE type::thing('purchase_record', $record.id) CONTENT $record;
+                    };
+                    
+                    COMMIT TRANSACTION;
+                ")
+                .bind(("user_id", &user.id))
+                .bind(("limits", &limits))
+                .bind(("amount", quote.effective_price))
+                .bind(("deductions", deductions))
+                .bind(("flash_deal_id", quote.flash_deal_id()))
+                .bind(("bundle_id", quote.bundle_id()))
+                .bind(("coupon_id", &quote.coupon_id))
+                .bind(("item_ids", &item_ids))
+                .bind(("now", now.unix_timestamp()))
+                .bind(("tx", &tx))
+                .bind(("records", records))
+                .await?;
+            
+            match thrown_error(&mut result, &expected)? {
+                None => break,
+                Some("hp lot changed") if attempt < MAX_HP_LOT_ATTEMPTS => continue,
+                Some(_) => return Ok(false),
             }
         }
         
-        Ok(false)
+        // 更新月度兑换统计
+        for (item, record) in items.iter().zip(records) {
+            self.record_monthly_redemption(&user.id, item, record.price_paid).await?;
+        }
+        
+        Ok(true)
     }
     
-    // 获取用户月度兑换统计
-    pub async fn get_user_monthly_redemption_stat(&self, user_id: &str) 
-        -> Result<Option<MonthlyRedemptionStat>, surrealdb::Error> {
+    // 更新月度兑换统计
+    async fn record_monthly_redemption(&self, user_id: &str, item: &ShopItem, points_spent: u32) 
+        -> Result<(), surrealdb::Error> {
         
         let now = OffsetDateTime::now_utc();
         let year_month = format!("{}-{:02}", now.year(), now.month() as u8);
         let stat_id = format!("{}:{}", user_id, year_month);
         
-        self.client
+        let stat: Option<MonthlyRedemptionStat> = self.client
             .select(("monthly_redemption_stat", &stat_id))
-            .await
+            .await?;
+        
+        let item_type_str = format!("{:?}", item.item_type);
+        
+        if let Some(mut stat) = stat {
+            // 更新现有统计
+            stat.record_redemption(&item_type_str, points_spent);
+            
+            self.client
+                .update::<Option<MonthlyRedemptionStat>>(("monthly_redemption_stat", &stat_id))
+                .content(&stat)
+                .await?;
+        } else {
+            // 创建新的统计记录
+            let mut new_stat = MonthlyRedemptionStat::new(user_id.to_string());
+            new_stat.record_redemption(&item_type_str, points_spent);
+            
+            self.client
+                .create::<Option<MonthlyRedemptionStat>>(("monthly_redemption_stat", &new_stat.id))
+                .content(&new_stat)
+                .await?;
+        }
+        
+        Ok(())
+    }
+    
+    // 统计用户某商品的兑换次数，since为空时统计全部
+    pub async fn count_user_item_purchases(&self, user_id: &str, item_id: &str, since: Option<i64>) 
+        -> Result<u32, surrealdb::Error> {
+        
+        let mut result = self
+            .client
+            .query("
+                SELECT count() AS count FROM purchase_record 
+                WHERE user_id = $user_id AND item_id = $item_id AND purchased_at >= $since
+                GROUP ALL
+            ")
+            .bind(("user_id", user_id))
+            .bind(("item_id", item_id))
+            .bind(("since", since.unwrap_or(0)))
+            .await?;
+        
+        let count: Option<u32> = result.take("count")?;
+        Ok(count.unwrap_or(0))
     }
     
     // 获取用户历史兑换统计
@@ -920,95 +1103,4 @@ impl Database {
         
         Ok(result.take(0)?)
     }
-    
-    // ==================== 签到系统操作 ====================
-    
-    // 用户签到
-    pub async fn user_daily_checkin(&self, user_id: &str) 
-        -> Result<(bool, u32, u32), surrealdb::Error> {
-        
-        let now = OffsetDateTime::now_utc().unix_timestamp();
-        let today_start = now - (now % 86400);
-        
-        // 获取用户信息
-        let user: Option<User> = self.client.select(("user", user_id)).await?;
-        
-        if let Some(user) = user {
-            // 检查今天是否已经签到
-            if let Some(last_checkin) = user.last_checkin_date {
-                if last_checkin >= today_start {
-                    return Ok((false, user.daily_checkin_streak, user.hp));
-                }
-            }
-            
-            // 检查是否连续签到
-            let is_consecutive = if let Some(last_checkin) = user.last_checkin_date {
-                // 如果上次签到是昨天，则连续签到天数+1
-                last_checkin >= (today_start - 86400)
-            } else {
-                // 首次签到
-                false
-            };
-            
-            // 计算新的连续签到天数
-            let new_streak = if is_consecutive {
-                user.daily_checkin_streak + 1
-            } else {
-                1
-            };
-            
-            // 计算签到奖励积分
-            let base_points = 10; // 基础签到积分
-            let streak_bonus = match new_streak {
-                1..=6 => new_streak as u32, // 1-6天连续签到，每天额外+1
-                7 => 10,                    // 7天连续签到，额外+10
-                8..=13 => 8,                // 8-13天连续签到，额外+8
-                14 => 15,                   // 14天连续签到，额外+15
-                15..=29 => 10,              // 15-29天连续签到，额外+10
-                30 => 30,                   // 30天连续签到，额外+30
-                _ => 15,                    // 30天以上连续签到，额外+15
-            };
-            
-            let total_points = base_points + streak_bonus;
-            
-            // 更新用户签到信息并增加积分
-            let mut result = self
-                .client
-                .query("
-                    BEGIN TRANSACTION;
-                    
-                    UPDATE user:$user_id SET 
-                        daily_checkin_streak = $new_streak,
-                        last_checkin_date = $now,
-                        hp = hp + $total_points,
-                        updated_at = $now;
-                    
-                    COMMIT TRANSACTION;
-                ")
-                .bind(("user_id", user_id))
-                .bind(("new_streak", new_streak))
-                .bind(("now", now))
-                .bind(("total_points", total_points))
-                .await?;
-            
-            // 创建积分交易记录
-            let tx = WalletTx::new(
-                user_id.to_string(),
-                TxType::PointsEarned,
-                total_points,
-                CurrencyType::HP,
-                None,
-                Some(format!("每日签到奖励 (连续{}天)", new_streak)),
-            );
-            
-            self.client
-                .create::<Option<WalletTx>>(("wallet_tx", &tx.id))
-                .content(&tx)
-                .await?;
-            
-            return Ok((true, new_streak, user.hp + total_points));
-        }
-        
-        Ok((false, 0, 0))
-    }
 }
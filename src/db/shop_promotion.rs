@@ -0,0 +1,201 @@
+use crate::models::shop_promotion::{ShopSale, FlashDeal, ShopBundle};
+
+use super::surreal::Database;
+
+impl Database {
+    // ==================== 限时促销 ====================
+
+    // 创建限时促销
+    pub async fn create_shop_sale(&self, sale: &ShopSale) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<ShopSale>>(("shop_sale", &sale.id))
+            .content(sale)
+            .await?;
+        Ok(())
+    }
+
+    // 更新限时促销
+    pub async fn update_shop_sale(&self, sale: &ShopSale) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<ShopSale>>(("shop_sale", &sale.id))
+            .content(sale)
+            .await?;
+        Ok(())
+    }
+
+    // 获取限时促销
+    pub async fn get_shop_sale(&self, id: &str) -> Result<Option<ShopSale>, surrealdb::Error> {
+        self.client.select(("shop_sale", id)).await
+    }
+
+    // 获取所有限时促销
+    pub async fn get_shop_sales(&self) -> Result<Vec<ShopSale>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM shop_sale ORDER BY starts_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取当前生效的限时促销
+    pub async fn get_live_shop_sales(&self, now: i64) -> Result<Vec<ShopSale>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM shop_sale
+                WHERE is_active = true AND starts_at <= $now AND ends_at > $now
+            ")
+            .bind(("now", now))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 记录促销已通知心愿单用户
+    pub async fn mark_shop_sale_wishlist_notified(&self, id: &str, now: i64) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('shop_sale', $id) SET wishlist_notified_at = $now")
+            .bind(("id", id))
+            .bind(("now", now))
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 秒杀 ====================
+
+    // 创建秒杀
+    pub async fn create_flash_deal(&self, deal: &FlashDeal) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<FlashDeal>>(("flash_deal", &deal.id))
+            .content(deal)
+            .await?;
+        Ok(())
+    }
+
+    // 更新秒杀（不覆盖已售数量）
+    pub async fn update_flash_deal(&self, deal: &FlashDeal) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                UPDATE type::thing('flash_deal', $id) MERGE {
+                    price_hp: $deal.price_hp,
+                    stock: $deal.stock,
+                    starts_at: $deal.starts_at,
+                    ends_at: $deal.ends_at,
+                    is_active: $deal.is_active,
+                    updated_at: $deal.updated_at
+                }
+            ")
+            .bind(("id", &deal.id))
+            .bind(("deal", deal))
+            .await?;
+        Ok(())
+    }
+
+    // 获取秒杀
+    pub async fn get_flash_deal(&self, id: &str) -> Result<Option<FlashDeal>, surrealdb::Error> {
+        self.client.select(("flash_deal", id)).await
+    }
+
+    // 获取所有秒杀
+    pub async fn get_flash_deals(&self) -> Result<Vec<FlashDeal>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM flash_deal ORDER BY starts_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取当前进行中且有余量的秒杀
+    pub async fn get_live_flash_deals(&self, now: i64) -> Result<Vec<FlashDeal>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM flash_deal
+                WHERE is_active = true AND starts_at <= $now AND ends_at > $now AND sold < stock
+                ORDER BY price_hp ASC
+            ")
+            .bind(("now", now))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 记录秒杀已通知心愿单用户
+    pub async fn mark_flash_deal_wishlist_notified(&self, id: &str, now: i64) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('flash_deal', $id) SET wishlist_notified_at = $now")
+            .bind(("id", id))
+            .bind(("now", now))
+            .await?;
+        Ok(())
+    }
+
+    // ==================== 组合包 ====================
+
+    // 创建组合包
+    pub async fn create_shop_bundle(&self, bundle: &ShopBundle) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<ShopBundle>>(("shop_bundle", &bundle.id))
+            .content(bundle)
+            .await?;
+        Ok(())
+    }
+
+    // 更新组合包（不覆盖已售数量）
+    pub async fn update_shop_bundle(&self, bundle: &ShopBundle) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("
+                UPDATE type::thing('shop_bundle', $id) MERGE {
+                    name: $bundle.name,
+                    description: $bundle.description,
+                    image_url: $bundle.image_url,
+                    item_ids: $bundle.item_ids,
+                    price_hp: $bundle.price_hp,
+                    stock: $bundle.stock,
+                    starts_at: $bundle.starts_at,
+                    ends_at: $bundle.ends_at,
+                    is_active: $bundle.is_active,
+                    updated_at: $bundle.updated_at
+                }
+            ")
+            .bind(("id", &bundle.id))
+            .bind(("bundle", bundle))
+            .await?;
+        Ok(())
+    }
+
+    // 获取组合包
+    pub async fn get_shop_bundle(&self, id: &str) -> Result<Option<ShopBundle>, surrealdb::Error> {
+        self.client.select(("shop_bundle", id)).await
+    }
+
+    // 获取所有组合包
+    pub async fn get_shop_bundles(&self) -> Result<Vec<ShopBundle>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM shop_bundle ORDER BY created_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取当前在售的组合包
+    pub async fn get_live_shop_bundles(&self, now: i64) -> Result<Vec<ShopBundle>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM shop_bundle
+                WHERE is_active = true
+                    AND (starts_at = NONE OR starts_at <= $now)
+                    AND (ends_at = NONE OR ends_at > $now)
+                    AND (stock = NONE OR sold < stock)
+                ORDER BY created_at DESC
+            ")
+            .bind(("now", now))
+            .await?;
+
+        result.take(0)
+    }
+}
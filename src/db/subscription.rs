@@ -0,0 +1,211 @@
+use crate::models::{VipPlan, VipSubscription, PaymentOrder, OrderType, User};
+use crate::models::user::VipStatus;
+
+use super::surreal::{Database, thrown_error};
+
+impl Database {
+    // ==================== VIP套餐 ====================
+
+    // 创建VIP套餐
+    pub async fn create_vip_plan(&self, plan: &VipPlan) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<VipPlan>>(("vip_plan", &plan.id))
+            .content(plan)
+            .await?;
+        Ok(())
+    }
+
+    // 更新VIP套餐
+    pub async fn update_vip_plan(&self, plan: &VipPlan) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<VipPlan>>(("vip_plan", &plan.id))
+            .content(plan)
+            .await?;
+        Ok(())
+    }
+
+    // 获取VIP套餐
+    pub async fn get_vip_plan(&self, plan_id: &str) -> Result<Option<VipPlan>, surrealdb::Error> {
+        self.client.select(("vip_plan", plan_id)).await
+    }
+
+    // 获取所有上架的VIP套餐
+    pub async fn get_active_vip_plans(&self) -> Result<Vec<VipPlan>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM vip_plan WHERE is_active = true ORDER BY price ASC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取所有VIP套餐（管理员用）
+    pub async fn get_all_vip_plans(&self) -> Result<Vec<VipPlan>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM vip_plan ORDER BY created_at DESC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== VIP订阅 ====================
+
+    // 更新订阅
+    pub async fn update_vip_subscription(&self, subscription: &VipSubscription) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<VipSubscription>>(("vip_subscription", &subscription.id))
+            .content(subscription)
+            .await?;
+        Ok(())
+    }
+
+    // 只更新用户的VIP等级和时段表，与订阅记录在同一事务中写入；
+    // 时段表在读取后被其他请求修改时返回 false，由调用方重新读取后重试
+    pub async fn save_user_vip_subscriptions(&self, user: &User, previous_schedule: &[VipStatus],
+                                             subscriptions: &[VipSubscription])
+        -> Result<bool, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                BEGIN TRANSACTION;
+
+                LET $updated = UPDATE type::thing('user', $user.id) SET
+                    vip_level = $user.vip_level,
+                    vip_schedule = $user.vip_schedule,
+                    updated_at = $user.updated_at
+                WHERE vip_schedule = $previous_schedule;
+                IF array::len($updated) = 0 {
+                    THROW 'vip schedule changed';
+                };
+
+                FOR $subscription IN $subscriptions {
+                    UPDATE type::thing('vip_subscription', $subscription.id) CONTENT $subscription;
+                };
+
+                COMMIT TRANSACTION;
+            ")
+            .bind(("user", user))
+            .bind(("previous_schedule", previous_schedule))
+            .bind(("subscriptions", subscriptions))
+            .await?;
+
+        Ok(thrown_error(&mut result, &["vip schedule changed"])?.is_none())
+    }
+
+    // 获取用户当前生效的订阅
+    pub async fn get_user_vip_subscription(&self, user_id: &str)
+        -> Result<Option<VipSubscription>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM vip_subscription
+                WHERE user_id = $user_id AND status IN ['Active', 'PastDue']
+                ORDER BY created_at DESC
+                LIMIT 1
+            ")
+            .bind(("user_id", user_id))
+            .await?;
+
+        let subscriptions: Vec<VipSubscription> = result.take(0)?;
+        Ok(subscriptions.into_iter().next())
+    }
+
+    // 获取需要自动续费的订阅（周期在指定时间前结束）
+    pub async fn get_subscriptions_due_for_renewal(&self, before: i64)
+        -> Result<Vec<VipSubscription>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM vip_subscription
+                WHERE
+                    auto_renew = true AND
+                    status IN ['Active', 'PastDue'] AND
+                    current_period_end <= $before
+                ORDER BY current_period_end ASC
+            ")
+            .bind(("before", before))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取已过期但尚未关闭的订阅（未开启续费且周期已结束，或宽限期已结束）
+    pub async fn get_lapsed_subscriptions(&self, now: i64)
+        -> Result<Vec<VipSubscription>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM vip_subscription
+                WHERE
+                    (status = 'Active' AND auto_renew = false AND current_period_end <= $now) OR
+                    (status = 'PastDue' AND grace_until != NONE AND grace_until <= $now)
+            ")
+            .bind(("now", now))
+            .await?;
+
+        result.take(0)
+    }
+
+    // ==================== 付款订单 ====================
+
+    // 创建付款订单
+    pub async fn create_payment_order(&self, order: &PaymentOrder) -> Result<(), surrealdb::Error> {
+        self.client
+            .create::<Option<PaymentOrder>>(("payment_order", &order.id))
+            .content(order)
+            .await?;
+        Ok(())
+    }
+
+    // 更新付款订单
+    pub async fn update_payment_order(&self, order: &PaymentOrder) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<PaymentOrder>>(("payment_order", &order.id))
+            .content(order)
+            .await?;
+        Ok(())
+    }
+
+    // 获取用户的付款订单
+    pub async fn get_user_payment_orders(&self, user_id: &str, limit: usize)
+        -> Result<Vec<PaymentOrder>, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM payment_order
+                WHERE user_id = $user_id
+                ORDER BY created_at DESC
+                LIMIT $limit
+            ")
+            .bind(("user_id", user_id))
+            .bind(("limit", limit))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 用户是否已有付款成功的指定类型订单
+    pub async fn has_paid_order(&self, user_id: &str, order_types: &[OrderType])
+        -> Result<bool, surrealdb::Error> {
+
+        let mut result = self
+            .client
+            .query("
+                SELECT * FROM payment_order
+                WHERE user_id = $user_id AND status = 'Paid' AND order_type IN $order_types
+                LIMIT 1
+            ")
+            .bind(("user_id", user_id))
+            .bind(("order_types", order_types))
+            .await?;
+
+        let orders: Vec<PaymentOrder> = result.take(0)?;
+        Ok(!orders.is_empty())
+    }
+}
@@ -5,7 +5,7 @@ use time::OffsetDateTime;
 use std::env;
 
 use crate::models::{AuditLog, EmailVerification, User, AI, Invite, VipLevelConfig, VipLevel};
-use crate::models::coupon::Coupon;
+use crate::models::coupon::{Coupon, CouponStatus};
 
 #[derive(Clone)]
 pub struct Database {
@@ -61,6 +61,14 @@ impl Database {
             .await
     }
 
+    pub async fn get_all_users(&self) -> Result<Vec<User>, surrealdb::Error> {
+        let mut users = self
+            .client
+            .query("SELECT * FROM user")
+            .await?;
+        users.take(0)
+    }
+
     pub async fn create_user(&self, user: &User) -> Result<(), surrealdb::Error> {
         self.client
             .create::<Option<User>>(("user", &user.id))
@@ -113,6 +121,22 @@ impl Database {
         }
     }
 
+    // 将已过期的邀请码标记为过期，返回处理数量
+    pub async fn expire_invites(&self, now: i64) -> Result<usize, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("UPDATE invite SET is_expired = true, updated_at = $now WHERE is_expired != true AND expires_at <= $now")
+            .bind(("now", now))
+            .await?;
+
+        let expired: Vec<Invite> = result.take(0)?;
+        Ok(expired.len())
+    }
+
+    pub async fn get_ai(&self, id: &str) -> Result<Option<AI>, surrealdb::Error> {
+        self.client.select(("ai", id)).await
+    }
+
     pub async fn get_user_ais(&self, user_id: &str) -> Result<Vec<AI>, surrealdb::Error> {
         let mut ais = self
             .client
@@ -204,11 +228,31 @@ impl Database {
         }
     }
 
-    pub async fn get_user_coupons(&self, user_id: &str) -> Result<Vec<Coupon>, surrealdb::Error> {
+    // 按状态、类型筛选用户卡券；可用卡券额外排除已过期但尚未被定时任务处理的
+    pub async fn get_user_coupons(
+        &self,
+        user_id: &str,
+        status: &CouponStatus,
+        coupon_type: Option<&str>,
+        order_by: &str,
+    ) -> Result<Vec<Coupon>, surrealdb::Error> {
+        let mut sql = String::from("SELECT * FROM coupon WHERE owner_id = $user_id");
+        match status {
+            CouponStatus::Active => sql.push_str(" AND status = 'Active' AND expires_at > $now"),
+            CouponStatus::Expired => sql.push_str(" AND (status = 'Expired' OR (status = 'Active' AND expires_at <= $now))"),
+            CouponStatus::Used => sql.push_str(" AND status = 'Used'"),
+        }
+        if coupon_type.is_some() {
+            sql.push_str(" AND kind.type = $coupon_type");
+        }
+        sql.push_str(&format!(" ORDER BY {}", order_by));
+
         let mut coupons = self
             .client
-            .query("SELECT * FROM coupon WHERE owner_id = $user_id AND status = 'active'")
+            .query(sql)
             .bind(("user_id", user_id))
+            .bind(("now", OffsetDateTime::now_utc().unix_timestamp()))
+            .bind(("coupon_type", coupon_type))
             .await?;
         Ok(coupons.take(0)?)
     }
@@ -298,3 +342,34 @@ impl Database {
         Ok(())
     }
 }
+
+// 检查事务执行结果：成功返回 None，触发预期的 THROW 时返回对应消息，其余数据库错误照常返回
+#[allow(clippy::result_large_err)]
+pub(crate) fn thrown_error(
+    result: &mut surrealdb::Response,
+    expected: &[&'static str],
+) -> Result<Option<&'static str>, surrealdb::Error> {
+    let mut errors: Vec<(usize, surrealdb::Error)> = result.take_errors().into_iter().collect();
+    if errors.is_empty() {
+        return Ok(None);
+    }
+
+    if let Some(message) = errors.iter()
+        .find_map(|(_, e)| expected.iter().find(|message| e.to_string().contains(*message)))
+    {
+        return Ok(Some(*message));
+    }
+
+    // 事务中其余语句只会报“事务失败”，优先返回真正出错的那条
+    errors.sort_by_key(|(index, _)| *index);
+    let index = errors.iter()
+        .position(|(_, e)| !e.to_string().contains("failed transaction"))
+        .unwrap_or(0);
+    Err(errors.swap_remove(index).1)
+}
+
+// 是否为唯一索引或记录ID冲突
+pub(crate) fn is_unique_conflict(error: &surrealdb::Error) -> bool {
+    let message = error.to_string();
+    message.contains("already contains") || message.contains("already exists")
+}
@@ -0,0 +1,146 @@
+use crate::models::wishlist::{WishlistEntry, WishlistNotice};
+
+use super::surreal::{Database, is_unique_conflict};
+
+impl Database {
+    // ==================== 心愿单 ====================
+
+    // 保存心愿单条目
+    pub async fn save_wishlist_entry(&self, entry: &WishlistEntry) -> Result<(), surrealdb::Error> {
+        self.client
+            .update::<Option<WishlistEntry>>(("wishlist_entry", &entry.id))
+            .content(entry)
+            .await?;
+        Ok(())
+    }
+
+    // 获取用户对某商品的心愿单条目
+    pub async fn get_wishlist_entry(&self, user_id: &str, item_id: &str) -> Result<Option<WishlistEntry>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM wishlist_entry WHERE user_id = $user_id AND item_id = $item_id LIMIT 1")
+            .bind(("user_id", user_id))
+            .bind(("item_id", item_id))
+            .await?;
+
+        let entries: Vec<WishlistEntry> = result.take(0)?;
+        Ok(entries.into_iter().next())
+    }
+
+    // 删除心愿单条目
+    pub async fn delete_wishlist_entry(&self, entry_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .delete::<Option<WishlistEntry>>(("wishlist_entry", entry_id))
+            .await?;
+        Ok(())
+    }
+
+    // 获取用户心愿单
+    pub async fn get_user_wishlist(&self, user_id: &str) -> Result<Vec<WishlistEntry>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM wishlist_entry WHERE user_id = $user_id ORDER BY created_at DESC")
+            .bind(("user_id", user_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 获取关注某商品的心愿单条目
+    pub async fn get_item_wishlist_entries(&self, item_id: &str) -> Result<Vec<WishlistEntry>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM wishlist_entry WHERE item_id = $item_id")
+            .bind(("item_id", item_id))
+            .await?;
+
+        result.take(0)
+    }
+
+    // 生成心愿单提醒，同一事件的提醒已存在时忽略，返回新生成的数量
+    pub async fn enqueue_wishlist_notices(&self, notices: &[WishlistNotice]) -> Result<usize, surrealdb::Error> {
+        let mut created = 0;
+        for notice in notices {
+            let result = self.client
+                .query("CREATE type::thing('wishlist_notice', $notice.id) CONTENT $notice")
+                .bind(("notice", notice))
+                .await?
+                .check();
+
+            match result {
+                Ok(_) => created += 1,
+                Err(e) if is_unique_conflict(&e) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(created)
+    }
+
+    // 获取尚未发送的心愿单提醒
+    pub async fn get_pending_wishlist_notices(&self) -> Result<Vec<WishlistNotice>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("SELECT * FROM wishlist_notice WHERE sent_at = NONE ORDER BY created_at ASC")
+            .await?;
+
+        result.take(0)
+    }
+
+    // 冷却期已过时占用心愿单条目的提醒时间，返回占用前的提醒时间；冷却期内或条目已删除时返回 None
+    pub async fn claim_wishlist_entry_notice(&self, entry_id: &str, now: i64, cooldown_secs: i64) 
+        -> Result<Option<Option<i64>>, surrealdb::Error> {
+        let mut result = self
+            .client
+            .query("
+                UPDATE type::thing('wishlist_entry', $id) SET last_notified_at = $now
+                WHERE last_notified_at = NONE OR last_notified_at <= $cutoff
+                RETURN BEFORE
+            ")
+            .bind(("id", entry_id))
+            .bind(("now", now))
+            .bind(("cutoff", now - cooldown_secs))
+            .await?;
+
+        let claimed: Vec<WishlistEntry> = result.take(0)?;
+        Ok(claimed.into_iter().next().map(|entry| entry.last_notified_at))
+    }
+
+    // 提醒发送失败时恢复条目原来的提醒时间
+    pub async fn release_wishlist_entry_notice(&self, entry_id: &str, claimed_at: i64, previous: Option<i64>) 
+        -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('wishlist_entry', $id) SET last_notified_at = $previous WHERE last_notified_at = $claimed_at")
+            .bind(("id", entry_id))
+            .bind(("claimed_at", claimed_at))
+            .bind(("previous", previous))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    // 记录心愿单提醒已发送
+    pub async fn mark_wishlist_notice_sent(&self, notice_id: &str, now: i64) -> Result<(), surrealdb::Error> {
+        self.client
+            .query("UPDATE type::thing('wishlist_notice', $id) SET sent_at = $now")
+            .bind(("id", notice_id))
+            .bind(("now", now))
+            .await?
+            .check()?;
+        Ok(())
+    }
+
+    // 删除心愿单提醒（条目已移出心愿单）
+    pub async fn delete_wishlist_notice(&self, notice_id: &str) -> Result<(), surrealdb::Error> {
+        self.client
+            .delete::<Option<WishlistNotice>>(("wishlist_notice", notice_id))
+            .await?;
+        Ok(())
+    }
+
+    // 获取心愿单条目
+    pub async fn get_wishlist_entry_by_id(&self, entry_id: &str) -> Result<Option<WishlistEntry>, surrealdb::Error> {
+        self.client
+            .select(("wishlist_entry", entry_id))
+            .await
+    }
+}
@@ -8,10 +8,12 @@ mod routes;
 mod models;
 mod db;
 mod middleware {
+    pub mod admin;
     pub mod auth;
 }
 mod utils;
 mod services;
+mod tasks;
 
 #[tokio::main]
 async fn main() {
@@ -23,6 +25,55 @@ async fn main() {
         .await
         .expect("Failed to initialize database");
     
+    // 转换旧版卡券数据
+    match db.migrate_legacy_coupons().await {
+        Ok(0) => {}
+        Ok(count) => println!("Migrated {} legacy coupon records", count),
+        Err(e) => eprintln!("Failed to migrate legacy coupons: {:?}", e),
+    }
+    
+    // 补写旧商品的销量字段
+    match db.backfill_shop_item_sold_count().await {
+        Ok(0) => {}
+        Ok(count) => println!("Backfilled sold_count on {} shop items", count),
+        Err(e) => eprintln!("Failed to backfill shop item sold_count: {:?}", e),
+    }
+    
+    // 补写旧积分收入交易的来源，使获取上限统计包含历史收入
+    match db.backfill_wallet_tx_earning_sources().await {
+        Ok(0) => {}
+        Ok(count) => println!("Backfilled earning source on {} wallet transactions", count),
+        Err(e) => eprintln!("Failed to backfill wallet transaction earning sources: {:?}", e),
+    }
+    
+    // 补写积分收入交易中计入获取上限的积分，需在补写来源之后执行
+    match db.backfill_wallet_tx_base_amounts().await {
+        Ok(0) => {}
+        Ok(count) => println!("Backfilled base amount on {} wallet transactions", count),
+        Err(e) => eprintln!("Failed to backfill wallet transaction base amounts: {:?}", e),
+    }
+    
+    // 区分用户间送礼的光币分成交易
+    match db.migrate_gift_share_tx_type().await {
+        Ok(0) => {}
+        Ok(count) => println!("Migrated {} gift share transactions", count),
+        Err(e) => eprintln!("Failed to migrate gift share transactions: {:?}", e),
+    }
+    
+    // 保底计数记录改用固定ID
+    match db.migrate_drop_pity_ids().await {
+        Ok(0) => {}
+        Ok(count) => println!("Migrated {} drop pity records", count),
+        Err(e) => eprintln!("Failed to migrate drop pity records: {:?}", e),
+    }
+    
+    // 首次启用排行榜时补录总榜
+    match services::LeaderboardService::new(db.clone()).backfill_all_time().await {
+        Ok(0) => {}
+        Ok(count) => println!("Backfilled {} all-time leaderboard scores", count),
+        Err(e) => eprintln!("Failed to backfill leaderboards: {:?}", e),
+    }
+    
     // 创建应用路由
     let app = routes::create_routes(db.clone());
     
@@ -48,6 +99,12 @@ async fn main() {
         services::websocket::start_server(&ws_addr, ws_db).await;
     });
     
+    // 启动定时任务
+    let task_db = db.clone();
+    tokio::spawn(async move {
+        tasks::run_scheduler(task_db).await;
+    });
+    
     // 启动HTTP服务器
     let addr = format!("{}:{}", host, port).parse::<SocketAddr>().unwrap();
     println!("HTTP Server running on http://{}", addr);
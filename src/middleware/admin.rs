@@ -0,0 +1,33 @@
+use axum::http::StatusCode;
+
+use crate::db::Database;
+use crate::models::{AuditAction, AuditLog, User};
+
+// 验证管理员权限
+pub async fn require_admin(db: &Database, user_id: &str) -> Result<User, StatusCode> {
+    let admin = db.get_user_by_id(user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    if !admin.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+
+    Ok(admin)
+}
+
+// 记录管理员操作
+pub async fn write_audit_log(db: &Database, admin_id: String, details: String) -> Result<(), StatusCode> {
+    let log = AuditLog::new(
+        admin_id,
+        AuditAction::AdminAction,
+        details,
+        "".to_string(),
+        "".to_string(),
+    );
+
+    db.create_audit_log(&log)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)
+}
@@ -0,0 +1,258 @@
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::{Coupon, CouponTemplate, CurrencyType, ShopItem, TxType, WalletTx};
+use crate::models::inventory::InventoryItem;
+
+// 成就的统计指标，由对应事件触发重新计算
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum AchievementMetric {
+    GiftsSent,          // 累计送出礼物数
+    CheckinStreak,      // 连续签到天数
+    InvitesUsed,        // 邀请码被使用的次数
+    AisAwakened,        // 唤醒的AI数量
+}
+
+// 成就完成奖励，可同时发放积分、卡券和称号
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct AchievementReward {
+    #[serde(default)]
+    pub hp: u32,
+    pub coupon_template_id: Option<String>,
+    pub title_item_id: Option<String>,      // 称号类商城商品ID
+}
+
+// 管理员配置的成就
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Achievement {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub badge_url: Option<String>,          // 徽章图片
+    pub metric: AchievementMetric,
+    pub target: u32,                        // 指标达到该值时完成
+    pub reward: AchievementReward,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl Achievement {
+    pub fn new(name: String, description: String, metric: AchievementMetric, target: u32, reward: AchievementReward) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            badge_url: None,
+            metric,
+            target,
+            reward,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.name.trim().is_empty() {
+            return Err("成就名称不能为空");
+        }
+
+        if self.target == 0 {
+            return Err("成就目标必须大于0");
+        }
+
+        Ok(())
+    }
+}
+
+// 用户的成就进度，每个用户每个成就一条
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserAchievement {
+    pub id: String,
+    pub user_id: String,
+    pub achievement_id: String,
+    pub progress: u32,                      // 不超过成就目标
+    pub completed_at: Option<i64>,
+    #[serde(default)]
+    pub reward_pending: bool,               // 已完成但奖励尚未发放，发放失败时由下次评估重试
+    #[serde(default)]
+    pub granted_at: Option<i64>,
+    pub updated_at: i64,
+}
+
+impl UserAchievement {
+    pub fn new(user_id: String, achievement: &Achievement, progress: u32) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let progress = progress.min(achievement.target);
+        let completed = progress >= achievement.target;
+
+        Self {
+            id: Self::record_id(&user_id, &achievement.id),
+            user_id,
+            achievement_id: achievement.id.clone(),
+            progress,
+            completed_at: completed.then_some(now),
+            reward_pending: completed,
+            granted_at: None,
+            updated_at: now,
+        }
+    }
+
+    pub fn record_id(user_id: &str, achievement_id: &str) -> String {
+        format!("{}_{}", user_id, achievement_id)
+    }
+}
+
+// 成就奖励的具体内容，与发放标记在同一事务中写入
+#[derive(Debug, Clone, Default)]
+pub struct AchievementGrant {
+    pub tx: Option<WalletTx>,                   // 奖励积分
+    pub coupon: Option<Coupon>,
+    pub title: Option<InventoryItem>,           // 放入背包的称号
+}
+
+impl AchievementGrant {
+    // 卡券模板或称号商品已删除时跳过对应奖励
+    pub fn new(
+        user_id: &str,
+        achievement: &Achievement,
+        template: Option<&CouponTemplate>,
+        title_item: Option<&ShopItem>,
+    ) -> Self {
+        let tx = (achievement.reward.hp > 0).then(|| WalletTx::new(
+            user_id.to_string(),
+            TxType::Reward,
+            achievement.reward.hp,
+            CurrencyType::HP,
+            Some(achievement.id.clone()),
+            Some(format!("成就奖励: {}", achievement.name)),
+        ));
+
+        Self {
+            tx,
+            coupon: template.map(|template| Coupon::new_from_template(template, user_id.to_string())),
+            title: title_item.map(|item| InventoryItem::granted(item, user_id.to_string(), achievement.id.clone())),
+        }
+    }
+}
+
+// 成就列表中的一项，附带用户进度
+#[derive(Debug, Serialize, Clone)]
+pub struct AchievementProgress {
+    pub achievement: Achievement,
+    pub progress: u32,
+    pub completed_at: Option<i64>,
+}
+
+// 个人主页展示的徽章
+#[derive(Debug, Serialize, Clone)]
+pub struct Badge {
+    pub achievement_id: String,
+    pub name: String,
+    pub description: String,
+    pub badge_url: Option<String>,
+    pub earned_at: i64,
+}
+
+impl Badge {
+    pub fn new(achievement: &Achievement, earned_at: i64) -> Self {
+        Self {
+            achievement_id: achievement.id.clone(),
+            name: achievement.name.clone(),
+            description: achievement.description.clone(),
+            badge_url: achievement.badge_url.clone(),
+            earned_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{CouponKind, ShopItemCategory, ShopItemType};
+
+    fn achievement(target: u32, reward: AchievementReward) -> Achievement {
+        Achievement::new("送礼达人".to_string(), "累计送出礼物".to_string(), AchievementMetric::GiftsSent, target, reward)
+    }
+
+    #[test]
+    fn progress_below_target_is_not_completed() {
+        let achievement = achievement(10, AchievementReward::default());
+        let record = UserAchievement::new("u1".to_string(), &achievement, 3);
+
+        assert_eq!(record.progress, 3);
+        assert!(record.completed_at.is_none());
+        assert!(!record.reward_pending);
+        assert_eq!(record.id, UserAchievement::record_id("u1", &achievement.id));
+    }
+
+    #[test]
+    fn completion_clamps_progress_and_marks_reward_pending() {
+        let achievement = achievement(10, AchievementReward::default());
+        let record = UserAchievement::new("u1".to_string(), &achievement, 25);
+
+        assert_eq!(record.progress, 10);
+        assert!(record.completed_at.is_some());
+        assert!(record.reward_pending);
+        assert!(record.granted_at.is_none());
+    }
+
+    #[test]
+    fn grant_credits_hp_reward_to_user() {
+        let reward = AchievementReward { hp: 50, ..Default::default() };
+        let achievement = achievement(1, reward);
+        let grant = AchievementGrant::new("u1", &achievement, None, None);
+
+        let tx = grant.tx.expect("hp reward");
+        assert_eq!(tx.user_id, "u1");
+        assert_eq!(tx.amount, 50);
+        assert_eq!(tx.tx_type, TxType::Reward);
+        assert_eq!(tx.related_entity_id.as_deref(), Some(achievement.id.as_str()));
+        assert!(grant.coupon.is_none());
+        assert!(grant.title.is_none());
+    }
+
+    #[test]
+    fn grant_without_hp_has_no_transaction() {
+        let grant = AchievementGrant::new("u1", &achievement(1, AchievementReward::default()), None, None);
+
+        assert!(grant.tx.is_none());
+    }
+
+    #[test]
+    fn grant_issues_coupon_and_title_to_user() {
+        let template = CouponTemplate::new(
+            "九五折券".to_string(),
+            String::new(),
+            CouponKind::Discount { rate: 0.95 },
+            Some(7),
+            false,
+        );
+        let title_item = ShopItem::new(
+            "送礼达人".to_string(),
+            String::new(),
+            ShopItemType::UserTitle,
+            ShopItemCategory::Decoration,
+            0,
+            None,
+            false,
+            None,
+            None,
+            false,
+            None,
+            None,
+            None,
+        );
+        let achievement = achievement(1, AchievementReward::default());
+        let grant = AchievementGrant::new("u1", &achievement, Some(&template), Some(&title_item));
+
+        assert_eq!(grant.coupon.expect("coupon").owner_id, "u1");
+        let title = grant.title.expect("title");
+        assert_eq!(title.user_id, "u1");
+        assert_eq!(title.item_id, title_item.id);
+        assert_eq!(title.purchase_id, achievement.id);
+    }
+}
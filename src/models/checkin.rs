@@ -0,0 +1,148 @@
+use serde::{Serialize, Deserialize};
+use time::{Date, Duration, OffsetDateTime, UtcOffset};
+use time::macros::format_description;
+
+// 未设置时区的用户按北京时间（UTC+8）计算签到日
+pub const DEFAULT_UTC_OFFSET_MINUTES: i32 = 8 * 60;
+
+// 可补签的最早天数
+pub const MAKEUP_WINDOW_DAYS: i64 = 30;
+
+// 两次修改时区的最小间隔天数，避免反复切换时区在同一天多次签到
+pub const TIMEZONE_CHANGE_COOLDOWN_DAYS: i64 = 7;
+
+// 时间戳在指定时区下的日期
+pub fn local_date(timestamp: i64, utc_offset_minutes: i32) -> Date {
+    let offset = UtcOffset::from_whole_seconds(utc_offset_minutes * 60).unwrap_or(UtcOffset::UTC);
+    OffsetDateTime::from_unix_timestamp(timestamp)
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+        .to_offset(offset)
+        .date()
+}
+
+// 指定时区下某天零点的时间戳
+pub fn local_midnight(date: Date, utc_offset_minutes: i32) -> i64 {
+    let offset = UtcOffset::from_whole_seconds(utc_offset_minutes * 60).unwrap_or(UtcOffset::UTC);
+    date.midnight().assume_offset(offset).unix_timestamp()
+}
+
+// 指定时区下某天中午的时间戳
+pub fn local_midday(date: Date, utc_offset_minutes: i32) -> i64 {
+    local_midnight(date, utc_offset_minutes) + 12 * 60 * 60
+}
+
+// 解析 YYYY-MM-DD 格式的日期
+pub fn parse_date(date: &str) -> Option<Date> {
+    Date::parse(date, format_description!("[year]-[month]-[day]")).ok()
+}
+
+// 签到记录，每个用户每天一条，ID 由用户和日期组成以防止重复签到
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckinRecord {
+    pub id: String,
+    pub user_id: String,
+    pub date: String,                   // 用户本地日期 YYYY-MM-DD
+    pub points: u32,                    // 签到应得积分，补签为0
+    pub is_makeup: bool,
+    pub checked_at: i64,
+}
+
+impl CheckinRecord {
+    pub fn new(user_id: String, date: Date, points: u32, is_makeup: bool) -> Self {
+        Self {
+            id: Self::record_id(&user_id, date),
+            user_id,
+            date: date.to_string(),
+            points,
+            is_makeup,
+            checked_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn record_id(user_id: &str, date: Date) -> String {
+        format!("{}_{}", user_id, date)
+    }
+
+    // 根据签到日期（倒序）计算截至今天的连续签到天数，今天未签到时从昨天开始计算
+    pub fn current_streak(dates: &[Date], today: Date) -> u32 {
+        let mut expected = match dates.first() {
+            Some(&date) if date == today => today,
+            _ => today - Duration::days(1),
+        };
+
+        let mut streak = 0;
+        for &date in dates {
+            if date > expected {
+                continue;
+            }
+            if date < expected {
+                break;
+            }
+            streak += 1;
+            expected -= Duration::days(1);
+        }
+
+        streak
+    }
+}
+
+// 连续签到奖励配置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CheckinSchedule {
+    pub id: String,
+    pub base_points: u32,               // 每次签到的基础积分
+    pub streak_bonuses: Vec<u32>,       // 连续第N天的额外积分，下标0为第1天
+    pub overflow_bonus: u32,            // 超出列表天数后的每日额外积分
+    pub updated_at: i64,
+}
+
+impl CheckinSchedule {
+    pub const ID: &'static str = "default";
+
+    // 默认奖励：1-6天每天+N，第7天+10，8-13天+8，第14天+15，15-29天+10，第30天+30，之后每天+15
+    pub fn default_schedule() -> Self {
+        let mut streak_bonuses = vec![1, 2, 3, 4, 5, 6, 10, 8, 8, 8, 8, 8, 8, 15];
+        streak_bonuses.extend([10; 15]);
+        streak_bonuses.push(30);
+
+        Self {
+            id: Self::ID.to_string(),
+            base_points: 10,
+            streak_bonuses,
+            overflow_bonus: 15,
+            updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn points_for(&self, streak: u32) -> u32 {
+        let bonus = match streak {
+            0 => 0,
+            _ => self.streak_bonuses
+                .get(streak as usize - 1)
+                .copied()
+                .unwrap_or(self.overflow_bonus),
+        };
+
+        self.base_points + bonus
+    }
+}
+
+// 签到日历中的一天
+#[derive(Debug, Serialize, Clone)]
+pub struct CheckinDay {
+    pub date: String,
+    pub points: u32,
+    pub is_makeup: bool,
+}
+
+// 月度签到日历
+#[derive(Debug, Serialize, Clone)]
+pub struct CheckinCalendar {
+    pub year: i32,
+    pub month: u8,
+    pub today: String,                  // 用户本地的今天
+    pub streak: u32,
+    pub makeup_cards: u32,              // 剩余补签卡
+    pub days: Vec<CheckinDay>,
+}
+
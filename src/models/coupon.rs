@@ -1,64 +1,107 @@
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
-use time::{OffsetDateTime, Duration};
+use time::OffsetDateTime;
+use crate::models::VipLevel;
 
-#[derive(Serialize, Deserialize)]
+// 卡券类型及其参数
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum CouponKind {
+    Experience { level: VipLevel, days: u32 },  // 体验券：免费体验指定会员等级若干天
+    Discount { rate: f32 },                     // 折扣券：如 0.95 即95折
+    Cash { amount: f32 },                       // 现金券：按面额抵扣（美元）
+}
+
+impl CouponKind {
+    // 卡券名称，用于通知
+    pub fn description(&self) -> String {
+        match self {
+            CouponKind::Experience { level, days } => format!("{} {}天体验券", level.to_string(), days),
+            CouponKind::Discount { rate } => format!("{}折折扣券", (rate * 100.0).round()),
+            CouponKind::Cash { amount } => format!("${} 现金券", amount),
+        }
+    }
+}
+
+// 无限期卡券的过期时间（9999-12-31T23:59:59Z）
+pub const NEVER_EXPIRES_AT: i64 = 253402300799;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum CouponStatus {
+    Active,     // 可使用
+    Used,       // 已使用
+    Expired,    // 已过期
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Coupon {
     pub id: String,
-    pub coupon_type: String,       // "experience", "discount", "cash"
-    pub sub_type: String,          // 如 "pro_2d", "premium_7d", "95_discount", "cash_10"
-    pub value: f32,                // 折扣或金额
-    pub duration_days: Option<u32>,// 体验时长
-    pub status: String,            // "active", "used", "expired"
+    pub kind: CouponKind,
+    pub status: CouponStatus,
     pub owner_id: String,          // 所属用户
-    pub issued_at: String,
-    pub expires_at: String,
+    pub issued_at: i64,
+    pub expires_at: i64,
     pub is_transferable: bool,
+    pub template_id: Option<String>,   // 来源模板
+    pub campaign_id: Option<String>,   // 来源发放活动
+    pub expiry_notified_at: Option<i64>,  // 到期提醒发送时间
+    pub pending_transfer_id: Option<String>,  // 转赠中（等待对方接受）时不可使用
 }
 
 impl Coupon {
-    pub fn new(id: String, coupon_type: String, sub_type: String, value: f32, duration_days: Option<u32>, owner_id: String, issued_at: String, expires_at: String, is_transferable: bool) -> Self {
+    pub fn new(id: String, kind: CouponKind, owner_id: String, issued_at: i64, expires_at: i64, is_transferable: bool) -> Self {
         Self {
             id,
-            coupon_type,
-            sub_type,
-            value,
-            duration_days,
-            status: "active".to_string(),
+            kind,
+            status: CouponStatus::Active,
             owner_id,
             issued_at,
             expires_at,
             is_transferable,
+            template_id: None,
+            campaign_id: None,
+            expiry_notified_at: None,
+            pending_transfer_id: None,
         }
     }
     
     // 从模板创建卡券
-    pub fn new_from_template(template: CouponTemplate, owner_id: String) -> Self {
-        let now = OffsetDateTime::now_utc();
-        let issued_at = now.to_string();
+    pub fn new_from_template(template: &CouponTemplate, owner_id: String) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
         
         // 计算过期时间
         let expires_at = match template.duration_days {
-            Some(days) => {
-                let expire_time = now + Duration::days(days as i64);
-                expire_time.to_string()
-            },
-            None => "9999-12-31T23:59:59Z".to_string() // 无限期
+            Some(days) => now + days as i64 * 24 * 60 * 60,
+            None => NEVER_EXPIRES_AT, // 无限期
         };
         
         Self {
             id: Uuid::new_v4().to_string(),
-            coupon_type: template.coupon_type,
-            sub_type: template.sub_type,
-            value: template.value,
-            duration_days: template.duration_days,
-            status: "active".to_string(),
+            kind: template.kind.clone(),
+            status: CouponStatus::Active,
             owner_id,
-            issued_at,
+            issued_at: now,
             expires_at,
             is_transferable: template.is_transferable,
+            template_id: Some(template.id.clone()),
+            campaign_id: None,
+            expiry_notified_at: None,
+            pending_transfer_id: None,
         }
     }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at <= OffsetDateTime::now_utc().unix_timestamp()
+    }
+
+    // 是否可以使用
+    pub fn is_usable(&self) -> bool {
+        self.status == CouponStatus::Active && !self.is_expired() && self.pending_transfer_id.is_none()
+    }
+
+    pub fn mark_as_used(&mut self) {
+        self.status = CouponStatus::Used;
+    }
 }
 
 #[derive(Serialize, Deserialize)]
@@ -71,6 +114,7 @@ pub struct RedeemCouponPayload {
 pub struct TransferCouponPayload {
     pub coupon_id: String,
     pub new_owner_id: String,
+    pub message: Option<String>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -81,27 +125,119 @@ pub struct IssueCouponPayload {
 #[derive(Serialize, Deserialize)]
 pub struct CouponData {
     pub id: String,
-    pub coupon_type: String,
-    pub sub_type: String,
-    pub value: f32,
-    pub duration_days: Option<u32>,
+    pub kind: CouponKind,
     pub owner_id: String,
-    pub issued_at: String,
-    pub expires_at: String,
+    pub issued_at: i64,
+    pub expires_at: i64,
     pub is_transferable: bool,
 }
 
 // 卡券模板
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CouponTemplate {
     pub id: String,
     pub name: String,
     pub description: String,
-    pub coupon_type: String,
-    pub sub_type: String,
-    pub value: f32,
-    pub duration_days: Option<u32>,
+    pub kind: CouponKind,
+    pub duration_days: Option<u32>,  // 发放后的有效天数，None为长期有效
     pub is_transferable: bool,
-    pub created_at: String,
+    pub created_at: i64,
     pub is_active: bool,
 }
+
+impl CouponTemplate {
+    pub fn new(name: String, description: String, kind: CouponKind, duration_days: Option<u32>, is_transferable: bool) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            kind,
+            duration_days,
+            is_transferable,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+            is_active: true,
+        }
+    }
+}
+
+// 发放活动的目标用户
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum CampaignAudience {
+    AllUsers,                                   // 全部用户
+    VipLevel { level: VipLevel },               // 指定会员等级
+    RegisteredBetween { start: i64, end: i64 }, // 指定时间段内注册的用户
+    UserIds { user_ids: Vec<String> },          // 指定用户列表
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum CampaignStatus {
+    Pending,    // 等待执行
+    Running,    // 发放中
+    Completed,  // 已完成
+    Failed,     // 执行失败
+}
+
+// 卡券发放活动
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouponCampaign {
+    pub id: String,
+    pub name: String,
+    pub template_id: String,
+    pub audience: CampaignAudience,
+    pub status: CampaignStatus,
+    pub total_users: u32,           // 目标用户数
+    pub processed_users: u32,       // 已处理用户数
+    pub issued_count: u32,          // 成功发放数
+    pub skipped_count: u32,         // 已领取过而跳过的用户数
+    pub failed_count: u32,          // 发放失败数
+    pub error: Option<String>,
+    pub created_by: String,
+    pub created_at: i64,
+    pub started_at: Option<i64>,
+    pub finished_at: Option<i64>,
+}
+
+impl CouponCampaign {
+    pub fn new(name: String, template_id: String, audience: CampaignAudience, created_by: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            template_id,
+            audience,
+            status: CampaignStatus::Pending,
+            total_users: 0,
+            processed_users: 0,
+            issued_count: 0,
+            skipped_count: 0,
+            failed_count: 0,
+            error: None,
+            created_by,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    // 开始或恢复执行，恢复时按已发放记录重新统计进度
+    pub fn start(&mut self, total_users: u32) {
+        self.status = CampaignStatus::Running;
+        self.total_users = total_users;
+        self.processed_users = 0;
+        self.issued_count = 0;
+        self.skipped_count = 0;
+        self.failed_count = 0;
+        self.started_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+    }
+
+    pub fn finish(&mut self) {
+        self.status = CampaignStatus::Completed;
+        self.finished_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.status = CampaignStatus::Failed;
+        self.error = Some(error);
+        self.finished_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+    }
+}
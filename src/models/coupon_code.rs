@@ -0,0 +1,113 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+use crate::models::VipLevel;
+
+// 兑换码批次，同一批次的兑换码共用领取规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouponCodeBatch {
+    pub id: String,
+    pub name: String,
+    pub template_id: String,
+    pub code_count: u32,                        // 生成的兑换码数量
+    pub max_redemptions: u32,                   // 每个兑换码最多可兑换次数
+    pub per_user_limit: u32,                    // 每个用户在本批次最多兑换次数
+    pub valid_from: i64,
+    pub valid_until: i64,
+    pub required_vip_level: Option<VipLevel>,   // 最低会员等级要求
+    pub is_active: bool,
+    pub created_by: String,
+    pub created_at: i64,
+}
+
+impl CouponCodeBatch {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: String,
+        template_id: String,
+        code_count: u32,
+        max_redemptions: u32,
+        per_user_limit: u32,
+        valid_from: i64,
+        valid_until: i64,
+        required_vip_level: Option<VipLevel>,
+        created_by: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            template_id,
+            code_count,
+            max_redemptions,
+            per_user_limit,
+            valid_from,
+            valid_until,
+            required_vip_level,
+            is_active: true,
+            created_by,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    // 当前是否在有效期内
+    pub fn is_valid_now(&self) -> bool {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.is_active && self.valid_from <= now && now < self.valid_until
+    }
+}
+
+// 兑换码
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouponCode {
+    pub id: String,
+    pub code: String,               // 如 RAINBOW-7X3K-9QPL
+    pub batch_id: String,
+    pub redeemed_count: u32,
+    pub created_at: i64,
+}
+
+impl CouponCode {
+    pub fn new(code: String, batch_id: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            code,
+            batch_id,
+            redeemed_count: 0,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
+
+// 兑换记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouponCodeRedemption {
+    pub id: String,
+    pub code_id: String,
+    pub batch_id: String,
+    pub user_id: String,
+    pub coupon_id: String,          // 兑换得到的卡券
+    pub redeemed_at: i64,
+}
+
+impl CouponCodeRedemption {
+    pub fn new(code_id: String, batch_id: String, user_id: String, coupon_id: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            code_id,
+            batch_id,
+            user_id,
+            coupon_id,
+            redeemed_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
+
+// 批次兑换统计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouponCodeBatchStats {
+    pub batch_id: String,
+    pub code_count: u32,
+    pub redeemed_codes: u32,        // 至少被兑换过一次的兑换码数
+    pub total_redemptions: u32,
+    pub unique_users: u32,
+}
@@ -0,0 +1,66 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+use crate::models::CouponKind;
+
+// 转赠邀请的有效期
+pub const TRANSFER_OFFER_TTL_SECS: i64 = 3 * 24 * 60 * 60;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum CouponTransferStatus {
+    Pending,    // 等待对方接受
+    Accepted,   // 已接受，卡券归属已变更
+    Declined,   // 对方已拒绝
+    Expired,    // 超时未处理
+}
+
+// 卡券转赠记录
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CouponTransfer {
+    pub id: String,
+    pub coupon_id: String,
+    pub coupon_kind: CouponKind,        // 转赠时的卡券类型快照
+    pub from_user_id: String,
+    pub to_user_id: String,
+    pub message: Option<String>,        // 转赠留言
+    pub status: CouponTransferStatus,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub responded_at: Option<i64>,
+}
+
+impl CouponTransfer {
+    pub fn new(
+        coupon_id: String,
+        coupon_kind: CouponKind,
+        from_user_id: String,
+        to_user_id: String,
+        message: Option<String>,
+    ) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            coupon_id,
+            coupon_kind,
+            from_user_id,
+            to_user_id,
+            message,
+            status: CouponTransferStatus::Pending,
+            created_at: now,
+            expires_at: now + TRANSFER_OFFER_TTL_SECS,
+            responded_at: None,
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.status == CouponTransferStatus::Pending
+            && self.expires_at > OffsetDateTime::now_utc().unix_timestamp()
+    }
+
+    // 结束转赠邀请
+    pub fn resolve(&mut self, status: CouponTransferStatus) {
+        self.status = status;
+        self.responded_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+    }
+}
@@ -0,0 +1,359 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+use rand::Rng;
+use crate::models::{CardLevel, VipLevel};
+
+// 幸运卡掉落表的标识
+pub const LUCKY_CARD_DROP_KEY: &str = "lucky_card";
+
+// 掉落奖励
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum DropReward {
+    Nothing,                                    // 未掉落
+    LuckyCard {
+        level: CardLevel,
+        min_multiplier: Option<f32>,            // 不填则使用卡片等级默认倍率
+        max_multiplier: Option<f32>,
+    },
+    Hp { amount: u32 },
+    Coupon { template_id: String },
+}
+
+// 掉落表条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropEntry {
+    pub reward: DropReward,
+    pub weight: u32,
+    #[serde(default)]
+    pub is_rare: bool,                          // 保底可命中的稀有条目（如B级以上幸运卡）
+}
+
+impl DropEntry {
+    pub fn new(reward: DropReward, weight: u32, is_rare: bool) -> Self {
+        Self { reward, weight, is_rare }
+    }
+}
+
+// 按会员等级覆盖掉落条目
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropOverride {
+    pub vip_level: VipLevel,
+    pub entries: Vec<DropEntry>,
+}
+
+// 掉落表
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropTable {
+    pub id: String,
+    pub key: String,                            // 掉落场景，如 lucky_card
+    pub name: String,
+    pub entries: Vec<DropEntry>,
+    pub vip_overrides: Vec<DropOverride>,
+    pub pity_threshold: Option<u32>,            // 连续未中稀有条目N次后保底
+    pub priority: i32,                          // 同一场景多张表同时生效时取优先级最高的
+    pub starts_at: Option<i64>,
+    pub ends_at: Option<i64>,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+// 单次抽取结果
+#[derive(Debug, Clone)]
+pub struct DropOutcome {
+    pub entry_index: usize,
+    pub reward: DropReward,
+    pub is_rare: bool,
+    pub pity_triggered: bool,
+}
+
+// 公示概率
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropOdds {
+    pub reward: DropReward,
+    pub probability: f64,
+}
+
+impl DropTable {
+    pub fn new(key: String, name: String, entries: Vec<DropEntry>) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            key,
+            name,
+            entries,
+            vip_overrides: vec![],
+            pity_threshold: None,
+            priority: 0,
+            starts_at: None,
+            ends_at: None,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    // 未配置掉落表时使用的默认幸运卡掉落：20%掉落，A-E按 5/10/20/30/35 分配
+    pub fn default_lucky_card() -> Self {
+        let card = |level: CardLevel, weight: u32, is_rare: bool| DropEntry::new(
+            DropReward::LuckyCard { level, min_multiplier: None, max_multiplier: None },
+            weight,
+            is_rare,
+        );
+
+        let mut table = Self::new(
+            LUCKY_CARD_DROP_KEY.to_string(),
+            "默认幸运卡掉落".to_string(),
+            vec![
+                DropEntry::new(DropReward::Nothing, 400, false),
+                card(CardLevel::A, 5, true),
+                card(CardLevel::B, 10, true),
+                card(CardLevel::C, 20, false),
+                card(CardLevel::D, 30, false),
+                card(CardLevel::E, 35, false),
+            ],
+        );
+        table.id = "default".to_string();
+        table
+    }
+
+    // 校验配置
+    pub fn validate(&self) -> Result<(), &'static str> {
+        let valid_entries = |entries: &[DropEntry]| {
+            entries.iter().any(|entry| entry.weight > 0)
+                && entries.iter().all(|entry| match &entry.reward {
+                    DropReward::LuckyCard { min_multiplier: Some(min), max_multiplier: Some(max), .. } => {
+                        *min >= 1.0 && min <= max
+                    }
+                    DropReward::LuckyCard { min_multiplier, max_multiplier, .. } => {
+                        min_multiplier.is_none() && max_multiplier.is_none()
+                    }
+                    DropReward::Hp { amount } => *amount > 0,
+                    _ => true,
+                })
+        };
+
+        if !valid_entries(&self.entries) || !self.vip_overrides.iter().all(|o| valid_entries(&o.entries)) {
+            return Err("掉落条目配置无效");
+        }
+
+        if let (Some(start), Some(end)) = (self.starts_at, self.ends_at) {
+            if start >= end {
+                return Err("生效时间无效");
+            }
+        }
+
+        if self.pity_threshold == Some(0) {
+            return Err("保底次数必须大于0");
+        }
+
+        Ok(())
+    }
+
+    // 获取会员等级对应的掉落条目
+    pub fn entries_for(&self, vip_level: &VipLevel) -> &[DropEntry] {
+        self.vip_overrides
+            .iter()
+            .find(|o| &o.vip_level == vip_level)
+            .map(|o| o.entries.as_slice())
+            .unwrap_or(&self.entries)
+    }
+
+    // 公示概率
+    pub fn odds(&self, vip_level: &VipLevel) -> Vec<DropOdds> {
+        let entries = self.entries_for(vip_level);
+        let total: u32 = entries.iter().map(|entry| entry.weight).sum();
+
+        entries
+            .iter()
+            .filter(|entry| entry.weight > 0)
+            .map(|entry| DropOdds {
+                reward: entry.reward.clone(),
+                probability: entry.weight as f64 / total as f64,
+            })
+            .collect()
+    }
+
+    // 抽取一次。pity_misses 为当前连续未中稀有条目的次数，
+    // 随机数由调用方传入，使用相同种子可复现结果
+    pub fn roll<R: Rng>(&self, rng: &mut R, vip_level: &VipLevel, pity_misses: u32) -> DropOutcome {
+        let entries = self.entries_for(vip_level);
+
+        let pity_triggered = self.pity_threshold.is_some_and(|threshold| pity_misses >= threshold)
+            && entries.iter().any(|entry| entry.is_rare && entry.weight > 0);
+
+        let candidates: Vec<(usize, &DropEntry)> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.weight > 0 && (!pity_triggered || entry.is_rare))
+            .collect();
+
+        let total: u32 = candidates.iter().map(|(_, entry)| entry.weight).sum();
+        let mut point = rng.gen_range(0..total);
+
+        for (index, entry) in &candidates {
+            if point < entry.weight {
+                return DropOutcome {
+                    entry_index: *index,
+                    reward: entry.reward.clone(),
+                    is_rare: entry.is_rare,
+                    pity_triggered,
+                };
+            }
+            point -= entry.weight;
+        }
+
+        unreachable!("roll point is always below the total weight")
+    }
+}
+
+// 用户在某张掉落表上的保底计数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropPity {
+    pub id: String,
+    pub user_id: String,
+    pub table_id: String,
+    pub misses: u32,
+    pub updated_at: i64,
+}
+
+impl DropPity {
+    // 保底计数记录ID按用户和掉落表生成，计数只在该记录上原子增减
+    pub fn record_id(user_id: &str, table_id: &str) -> String {
+        format!("{}_{}", user_id, table_id)
+    }
+}
+
+// 掉落记录，用于公平性审计
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DropLog {
+    pub id: String,
+    pub table_id: String,
+    pub table_key: String,
+    pub user_id: String,
+    pub vip_level: VipLevel,
+    pub seed: u64,                              // 本次抽取的随机种子，可用于复现
+    pub entry_index: usize,
+    pub reward: DropReward,
+    pub pity_misses: u32,                       // 抽取前的连续未中次数
+    pub pity_triggered: bool,
+    pub granted_entity_id: Option<String>,      // 发放的幸运卡/卡券ID
+    pub created_at: i64,
+}
+
+impl DropLog {
+    pub fn new(
+        table: &DropTable,
+        user_id: String,
+        vip_level: VipLevel,
+        seed: u64,
+        pity_misses: u32,
+        outcome: &DropOutcome,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            table_id: table.id.clone(),
+            table_key: table.key.clone(),
+            user_id,
+            vip_level,
+            seed,
+            entry_index: outcome.entry_index,
+            reward: outcome.reward.clone(),
+            pity_misses,
+            pity_triggered: outcome.pity_triggered,
+            granted_entity_id: None,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    const SEED: u64 = 20240601;
+    const ROLLS: usize = 100_000;
+
+    #[test]
+    fn roll_distribution_matches_published_odds() {
+        let table = DropTable::default_lucky_card();
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        let mut hits = vec![0usize; table.entries.len()];
+        for _ in 0..ROLLS {
+            hits[table.roll(&mut rng, &VipLevel::Free, 0).entry_index] += 1;
+        }
+
+        let odds = table.odds(&VipLevel::Free);
+        for (index, odds) in odds.iter().enumerate() {
+            let observed = hits[index] as f64 / ROLLS as f64;
+            assert!(
+                (observed - odds.probability).abs() < 0.01,
+                "entry {} observed {:.4}, expected {:.4}", index, observed, odds.probability,
+            );
+        }
+    }
+
+    #[test]
+    fn same_seed_reproduces_roll() {
+        let table = DropTable::default_lucky_card();
+
+        let first: Vec<usize> = (0..100)
+            .map(|seed| table.roll(&mut StdRng::seed_from_u64(seed), &VipLevel::Free, 0).entry_index)
+            .collect();
+        let second: Vec<usize> = (0..100)
+            .map(|seed| table.roll(&mut StdRng::seed_from_u64(seed), &VipLevel::Free, 0).entry_index)
+            .collect();
+
+        assert_eq!(first, second);
+    }
+
+    #[test]
+    fn pity_triggers_at_threshold() {
+        let mut table = DropTable::default_lucky_card();
+        table.pity_threshold = Some(10);
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        for _ in 0..1000 {
+            let outcome = table.roll(&mut rng, &VipLevel::Free, 10);
+            assert!(outcome.pity_triggered);
+            assert!(outcome.is_rare);
+        }
+
+        let below: Vec<DropOutcome> = (0..1000).map(|_| table.roll(&mut rng, &VipLevel::Free, 9)).collect();
+        assert!(below.iter().all(|outcome| !outcome.pity_triggered));
+        assert!(below.iter().any(|outcome| !outcome.is_rare));
+    }
+
+    #[test]
+    fn pity_ignored_without_rare_entries() {
+        let mut table = DropTable::new(
+            "test".to_string(),
+            "test".to_string(),
+            vec![DropEntry::new(DropReward::Nothing, 1, false), DropEntry::new(DropReward::Hp { amount: 5 }, 1, false)],
+        );
+        table.pity_threshold = Some(1);
+
+        let outcome = table.roll(&mut StdRng::seed_from_u64(SEED), &VipLevel::Free, 5);
+        assert!(!outcome.pity_triggered);
+    }
+
+    #[test]
+    fn vip_override_replaces_entries() {
+        let mut table = DropTable::default_lucky_card();
+        table.vip_overrides.push(DropOverride {
+            vip_level: VipLevel::Premium,
+            entries: vec![DropEntry::new(DropReward::Hp { amount: 10 }, 1, false)],
+        });
+        let mut rng = StdRng::seed_from_u64(SEED);
+
+        for _ in 0..100 {
+            assert_eq!(table.roll(&mut rng, &VipLevel::Premium, 0).reward, DropReward::Hp { amount: 10 });
+        }
+    }
+}
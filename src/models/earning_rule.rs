@@ -0,0 +1,152 @@
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use crate::models::{TxType, WalletTx};
+use crate::models::point_shop::LimitWindow;
+
+// 积分来源，每种来源单独配置获取上限
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum EarningSource {
+    DailyCheckin,       // 每日签到
+    Dialogue,           // 对话互动
+    Invitation,         // 邀请注册
+    GiftReceived,       // AI收到礼物
+    FirstPayment,       // 首次充值
+}
+
+impl EarningSource {
+    pub fn all() -> [EarningSource; 5] {
+        [
+            EarningSource::DailyCheckin,
+            EarningSource::Dialogue,
+            EarningSource::Invitation,
+            EarningSource::GiftReceived,
+            EarningSource::FirstPayment,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EarningSource::DailyCheckin => "DailyCheckin",
+            EarningSource::Dialogue => "Dialogue",
+            EarningSource::Invitation => "Invitation",
+            EarningSource::GiftReceived => "GiftReceived",
+            EarningSource::FirstPayment => "FirstPayment",
+        }
+    }
+}
+
+// 积分获取规则，每种来源一条
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EarningRule {
+    pub id: String,                     // 与来源同名
+    pub source: EarningSource,
+    pub daily_cap: Option<u32>,         // None 表示不限
+    pub weekly_cap: Option<u32>,
+    pub lifetime_cap: Option<u32>,
+    pub velocity_limit: Option<u32>,    // 一小时内获取超过该值时标记用户并暂扣积分
+    pub updated_at: i64,
+}
+
+impl EarningRule {
+    // 未配置时的默认规则
+    pub fn default_for(source: EarningSource) -> Self {
+        let (daily_cap, weekly_cap, lifetime_cap, velocity_limit) = match source {
+            EarningSource::DailyCheckin => (None, None, None, None),
+            EarningSource::Dialogue => (Some(30), None, None, None),
+            EarningSource::Invitation => (Some(150), Some(500), Some(5000), Some(100)),
+            EarningSource::GiftReceived => (Some(1000), None, None, Some(500)),
+            EarningSource::FirstPayment => (None, None, None, None),
+        };
+
+        Self {
+            id: source.as_str().to_string(),
+            source,
+            daily_cap,
+            weekly_cap,
+            lifetime_cap,
+            velocity_limit,
+            updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    // 已配置的上限窗口
+    pub fn windows(&self) -> Vec<(LimitWindow, u32)> {
+        [
+            (LimitWindow::Daily, self.daily_cap),
+            (LimitWindow::Weekly, self.weekly_cap),
+            (LimitWindow::Lifetime, self.lifetime_cap),
+        ]
+        .into_iter()
+        .filter_map(|(window, cap)| cap.map(|cap| (window, cap)))
+        .collect()
+    }
+}
+
+// 入账时在事务中复核的上限窗口：since 起获取的积分加上本次不得超过 max
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct EarningCap {
+    pub since: i64,
+    pub max: u32,
+}
+
+// 通过规则判定、待入账的获取积分
+#[derive(Debug, Clone)]
+pub struct EarningCredit {
+    pub tx: WalletTx,                   // 含幸运卡加成
+    pub points: u32,                    // 计入上限的积分（不含加成）
+    pub bonus: u32,                     // 幸运卡加成的额外积分，入账时累加到加成已发放额度
+    pub caps: Vec<EarningCap>,
+}
+
+// 暂扣积分的审核状态
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum HeldRewardStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+// 被标记用户的积分奖励，审核通过后才入账
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HeldReward {
+    pub id: String,
+    pub user_id: String,
+    pub source: EarningSource,
+    pub tx_type: TxType,
+    pub amount: u32,
+    pub related_entity_id: Option<String>,
+    pub remark: Option<String>,
+    pub reason: String,                 // 暂扣原因
+    pub status: HeldRewardStatus,
+    pub created_at: i64,
+    pub reviewed_at: Option<i64>,
+    pub reviewed_by: Option<String>,
+}
+
+impl HeldReward {
+    pub fn new(
+        user_id: String,
+        source: EarningSource,
+        tx_type: TxType,
+        amount: u32,
+        related_entity_id: Option<String>,
+        remark: Option<String>,
+        reason: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            source,
+            tx_type,
+            amount,
+            related_entity_id,
+            remark,
+            reason,
+            status: HeldRewardStatus::Pending,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+            reviewed_at: None,
+            reviewed_by: None,
+        }
+    }
+}
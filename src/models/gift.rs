@@ -18,6 +18,40 @@ pub enum GiftCategory {
     Limited,    // 限定礼物
 }
 
+impl GiftCategory {
+    pub fn all() -> [GiftCategory; 5] {
+        [
+            GiftCategory::Light,
+            GiftCategory::Medium,
+            GiftCategory::Advanced,
+            GiftCategory::Rare,
+            GiftCategory::Limited,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GiftCategory::Light => "Light",
+            GiftCategory::Medium => "Medium",
+            GiftCategory::Advanced => "Advanced",
+            GiftCategory::Rare => "Rare",
+            GiftCategory::Limited => "Limited",
+        }
+    }
+}
+
+// 客户端播放礼物特效所需的资源
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftAnimation {
+    pub animation_url: String,              // 特效资源地址
+    pub format: String,                     // 资源格式，如 lottie、svga、webp
+    pub duration_ms: u32,
+    #[serde(default)]
+    pub full_screen: bool,                  // 是否全屏播放
+    pub sound_url: Option<String>,
+    pub combo_animation_url: Option<String>, // 连击时播放的特效，为空时重复播放普通特效
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Gift {
     pub id: String,
@@ -33,6 +67,8 @@ pub struct Gift {
     pub available_until: Option<i64>, // 限定礼物的有效期
     pub boost_value: Option<u32>,    // 提升值（针对Boost类型礼物）
     pub is_active: bool,             // 是否激活（可用于管理员控制礼物上下架）
+    #[serde(default)]
+    pub animation: Option<GiftAnimation>, // 特效资源
 }
 
 impl Gift {
@@ -62,6 +98,7 @@ impl Gift {
             available_until,
             boost_value,
             is_active: true,
+            animation: None,
         }
     }
 }
@@ -74,6 +111,14 @@ pub struct GiftRecord {
     pub receiver_ai_id: String,
     pub sent_at: i64,
     pub message: Option<String>,     // 赠送礼物时的留言
+    #[serde(default = "default_gift_quantity")]
+    pub quantity: u32,               // 一次赠送的数量
+    #[serde(default)]
+    pub combo_bonus: u32,            // 连击额外获得的情感价值
+}
+
+fn default_gift_quantity() -> u32 {
+    1
 }
 
 impl GiftRecord {
@@ -90,10 +135,168 @@ impl GiftRecord {
             receiver_ai_id,
             sent_at: OffsetDateTime::now_utc().unix_timestamp(),
             message,
+            quantity: 1,
+            combo_bonus: 0,
+        }
+    }
+
+    pub fn with_quantity(mut self, quantity: u32, combo_bonus: u32) -> Self {
+        self.quantity = quantity;
+        self.combo_bonus = combo_bonus;
+        self
+    }
+}
+
+// 用户间送礼的分成配置，收礼方按比例获得光币，其余为平台手续费
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserGiftSetting {
+    pub id: String,
+    pub recipient_share_percent: u32,   // 收礼方获得的比例
+    pub updated_at: i64,
+}
+
+impl UserGiftSetting {
+    pub const ID: &'static str = "default";
+
+    pub fn default_setting() -> Self {
+        Self {
+            id: Self::ID.to_string(),
+            recipient_share_percent: 70,
+            updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.recipient_share_percent > 100 {
+            return Err("收礼方分成比例不能超过100");
         }
+
+        Ok(())
     }
+
+    // 按比例拆分礼物价格，返回（收礼方所得，平台手续费）
+    pub fn split(&self, price_lc: u32) -> (u32, u32) {
+        let received = (price_lc as u64 * self.recipient_share_percent as u64 / 100) as u32;
+        (received, price_lc - received)
+    }
+}
+
+// 用户间送礼记录，可以是好友私聊或群聊中送出
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserGiftRecord {
+    pub id: String,
+    pub gift_id: String,
+    pub sender_id: String,
+    pub receiver_id: String,
+    pub group_id: Option<String>,       // 在群聊中送出时的群ID
+    pub quantity: u32,
+    pub price_lc: u32,                  // 送礼方支付的光币
+    pub received_lc: u32,               // 收礼方获得的光币
+    pub platform_fee_lc: u32,           // 平台手续费
+    pub message: Option<String>,
+    pub sent_at: i64,
 }
 
+impl UserGiftRecord {
+    pub fn new(
+        gift_id: String,
+        sender_id: String,
+        receiver_id: String,
+        quantity: u32,
+        price_lc: u32,
+        setting: &UserGiftSetting,
+        message: Option<String>,
+    ) -> Self {
+        let (received_lc, platform_fee_lc) = setting.split(price_lc);
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            gift_id,
+            sender_id,
+            receiver_id,
+            group_id: None,
+            quantity,
+            price_lc,
+            received_lc,
+            platform_fee_lc,
+            message,
+            sent_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn in_group(mut self, group_id: Option<String>) -> Self {
+        self.group_id = group_id;
+        self
+    }
+}
+
+// 连击档位：一次赠送达到数量时情感价值额外加成
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftComboTier {
+    pub min_quantity: u32,
+    pub bonus_percent: u32,
+}
+
+// 礼物连击规则，每个礼物分类一条
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftComboRule {
+    pub id: String,                  // 与分类同名
+    pub category: GiftCategory,
+    pub max_quantity: u32,           // 单次最多赠送数量
+    pub tiers: Vec<GiftComboTier>,
+    pub updated_at: i64,
+}
+
+impl GiftComboRule {
+    // 未配置时的默认规则：越贵重的分类连击加成越高
+    pub fn default_for(category: GiftCategory) -> Self {
+        let (max_quantity, tiers) = match category {
+            GiftCategory::Light | GiftCategory::Medium => (999, vec![(10, 5), (99, 10), (520, 15)]),
+            GiftCategory::Advanced => (999, vec![(10, 10), (99, 15), (520, 20)]),
+            GiftCategory::Rare => (99, vec![(10, 15), (99, 20)]),
+            GiftCategory::Limited => (99, vec![(10, 20), (99, 30)]),
+        };
+
+        Self {
+            id: category.as_str().to_string(),
+            category,
+            max_quantity,
+            tiers: tiers
+                .into_iter()
+                .map(|(min_quantity, bonus_percent)| GiftComboTier { min_quantity, bonus_percent })
+                .collect(),
+            updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.max_quantity == 0 {
+            return Err("单次赠送数量上限必须大于0");
+        }
+
+        if self.tiers.iter().any(|tier| tier.min_quantity < 2) {
+            return Err("连击档位数量至少为2");
+        }
+
+        Ok(())
+    }
+
+    // 按命中的最高档位计算连击加成
+    pub fn combo_bonus(&self, base_value: u32, quantity: u32) -> u32 {
+        let percent = self.tiers
+            .iter()
+            .filter(|tier| quantity >= tier.min_quantity)
+            .map(|tier| tier.bonus_percent)
+            .max()
+            .unwrap_or(0);
+
+        (base_value as u64 * percent as u64 / 100) as u32
+    }
+}
+
+// 亲密等级所需的累计情感价值
+pub const RELATIONSHIP_LEVEL_THRESHOLDS: [u32; 6] = [100, 300, 600, 1000, 2000, 5000];
+
 // 连续送礼记录
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ConsecutiveGiftRecord {
@@ -104,22 +307,60 @@ pub struct ConsecutiveGiftRecord {
     pub last_gift_date: i64,         // 最后一次送礼日期
     pub total_gifts_sent: u32,       // 总共送出的礼物数量
     pub total_emotional_value: u32,  // 总情感价值
+    #[serde(default)]
+    pub break_notified_at: Option<i64>, // 已通知连续中断的时间，再次送礼时清空
 }
 
 impl ConsecutiveGiftRecord {
-    pub fn new(
-        user_id: String,
-        ai_id: String,
-    ) -> Self {
-        Self {
-            id: Uuid::new_v4().to_string(),
-            user_id,
-            ai_id,
-            consecutive_days: 1,
-            last_gift_date: OffsetDateTime::now_utc().unix_timestamp(),
-            total_gifts_sent: 1,
-            total_emotional_value: 0,
-        }
+    // 新建记录的ID，每个用户与AI一条，并发首次送礼时写入同一条记录
+    pub fn record_id(user_id: &str, ai_id: &str) -> String {
+        format!("{}_{}", user_id, ai_id)
+    }
+    
+    // 时间戳在用户时区下的日序号，与送礼事务中计算连续天数的日界一致
+    pub fn local_day(timestamp: i64, utc_offset_secs: i64) -> i64 {
+        (timestamp + utc_offset_secs).div_euclid(86400)
+    }
+    
+    // 上次送礼到今天之间漏掉的天数（用户当地日期），昨天或今天送过礼时为0
+    pub fn missed_days(&self, now: i64, utc_offset_secs: i64) -> u32 {
+        let today = Self::local_day(now, utc_offset_secs);
+        let last_gift_day = Self::local_day(self.last_gift_date, utc_offset_secs);
+        (today - last_gift_day - 1).max(0) as u32
+    }
+    
+    pub fn sent_on_day_of(&self, now: i64, utc_offset_secs: i64) -> bool {
+        self.consecutive_days > 0
+            && Self::local_day(self.last_gift_date, utc_offset_secs) == Self::local_day(now, utc_offset_secs)
+    }
+    
+    // 根据累计情感价值计算亲密等级
+    pub fn relationship_level(&self) -> u32 {
+        Self::level_for(self.total_emotional_value)
+    }
+
+    pub fn level_for(emotional_value: u32) -> u32 {
+        RELATIONSHIP_LEVEL_THRESHOLDS
+            .iter()
+            .filter(|threshold| emotional_value >= **threshold)
+            .count() as u32
+    }
+}
+
+// 送礼事务中更新连续送礼记录的结果
+#[derive(Debug, Deserialize, Clone)]
+pub struct GiftStreakUpdate {
+    pub streak: ConsecutiveGiftRecord,
+    pub previous_days: u32,
+    pub previous_emotional_value: u32,
+    pub freezes_used: u32,          // 漏掉的天数不超过冻结卡数量时消耗冻结卡保持连续
+    pub break_notified: bool,       // 本次送礼前定时检查是否已通知过连续中断
+}
+
+impl GiftStreakUpdate {
+    // 本次送礼前的亲密等级
+    pub fn previous_level(&self) -> u32 {
+        ConsecutiveGiftRecord::level_for(self.previous_emotional_value)
     }
 }
 
@@ -0,0 +1,205 @@
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::{Coupon, CouponTemplate, CurrencyType, Gift, ShopItem, TxType, WalletTx};
+use crate::models::inventory::InventoryItem;
+
+// 活动结束后等待后台累计的送礼分数写入完成再结算
+pub const SETTLEMENT_GRACE_SECS: i64 = 10 * 60;
+
+// 活动结束后按排名发放的奖励，名次区间包含首尾
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftEventReward {
+    pub rank_from: u32,
+    pub rank_to: u32,
+    #[serde(default)]
+    pub hp: u32,
+    pub coupon_template_id: Option<String>,
+    pub title_item_id: Option<String>,      // 称号类商城商品ID
+}
+
+// 限时礼物活动（春节、情人节、七夕等），活动期间主题礼物自动上架，活动结束后按送礼榜发放奖励
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftEvent {
+    pub id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub banner_url: Option<String>,
+    pub gift_ids: Vec<String>,              // 活动主题礼物
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub rewards: Vec<GiftEventReward>,
+    pub is_active: bool,
+    pub rewards_distributed_at: Option<i64>,
+    #[serde(default)]
+    pub final_standings: Vec<GiftEventEntry>, // 发放奖励时保存的最终排名
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl GiftEvent {
+    pub fn new(name: String, gift_ids: Vec<String>, starts_at: i64, ends_at: i64, rewards: Vec<GiftEventReward>) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description: None,
+            banner_url: None,
+            gift_ids,
+            starts_at,
+            ends_at,
+            rewards,
+            is_active: true,
+            rewards_distributed_at: None,
+            final_standings: Vec::new(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.name.trim().is_empty() {
+            return Err("活动名称不能为空");
+        }
+
+        if self.gift_ids.is_empty() {
+            return Err("活动至少包含一个礼物");
+        }
+
+        if self.ends_at <= self.starts_at {
+            return Err("活动结束时间必须晚于开始时间");
+        }
+
+        if self.rewards.iter().any(|reward| reward.rank_from == 0 || reward.rank_to < reward.rank_from) {
+            return Err("奖励名次区间无效");
+        }
+
+        Ok(())
+    }
+
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.ends_at <= now
+    }
+
+    // 活动结束且已过结算等待期
+    pub fn is_settleable(&self, now: i64) -> bool {
+        self.has_ended(now - SETTLEMENT_GRACE_SECS)
+    }
+
+    // 发放奖励时需要统计的最低名次
+    pub fn rewarded_ranks(&self) -> u32 {
+        self.rewards.iter().map(|reward| reward.rank_to).max().unwrap_or(0)
+    }
+
+    pub fn reward_for(&self, rank: u32) -> Option<&GiftEventReward> {
+        self.rewards.iter().find(|reward| reward.rank_from <= rank && rank <= reward.rank_to)
+    }
+}
+
+// 用户在活动中的送礼分数（活动礼物的情感价值累计）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftEventScore {
+    pub id: String,
+    pub event_id: String,
+    pub user_id: String,
+    pub score: u32,
+    pub updated_at: i64,
+}
+
+impl GiftEventScore {
+    // 以送礼时间计分，后台写入延迟不影响同分时的先后
+    pub fn new(event_id: String, user_id: String, score: u32, sent_at: i64) -> Self {
+        Self {
+            id: format!("{}_{}", event_id, user_id),
+            event_id,
+            user_id,
+            score,
+            updated_at: sent_at,
+        }
+    }
+}
+
+// 活动排行榜中的一行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftEventEntry {
+    pub rank: u32,
+    pub user_id: String,
+    pub score: u32,
+}
+
+// 结算时为每个获奖用户写入的待发放记录，发放失败时由定时结算任务重试
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftEventClaim {
+    pub id: String,
+    pub event_id: String,
+    pub user_id: String,
+    pub rank: u32,
+    pub reward_pending: bool,
+    pub granted_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl GiftEventClaim {
+    pub fn new(event_id: &str, entry: &GiftEventEntry, now: i64) -> Self {
+        Self {
+            // 每个活动每个用户一条，防止重复发放
+            id: format!("{}_{}", event_id, entry.user_id),
+            event_id: event_id.to_string(),
+            user_id: entry.user_id.clone(),
+            rank: entry.rank,
+            reward_pending: true,
+            granted_at: None,
+            created_at: now,
+        }
+    }
+}
+
+// 活动排名奖励的具体内容，与发放标记在同一事务中写入
+#[derive(Debug, Clone, Default)]
+pub struct GiftEventGrant {
+    pub tx: Option<WalletTx>,                   // 奖励积分
+    pub coupon: Option<Coupon>,
+    pub title: Option<InventoryItem>,           // 放入背包的称号
+}
+
+impl GiftEventGrant {
+    // 卡券模板或称号商品已删除时跳过对应奖励
+    pub fn new(
+        event: &GiftEvent,
+        claim: &GiftEventClaim,
+        reward: &GiftEventReward,
+        template: Option<&CouponTemplate>,
+        title_item: Option<&ShopItem>,
+    ) -> Self {
+        let tx = (reward.hp > 0).then(|| WalletTx::new(
+            claim.user_id.clone(),
+            TxType::Reward,
+            reward.hp,
+            CurrencyType::HP,
+            Some(event.id.clone()),
+            Some(format!("活动奖励: {} 第{}名", event.name, claim.rank)),
+        ));
+
+        Self {
+            tx,
+            coupon: template.map(|template| Coupon::new_from_template(template, claim.user_id.clone())),
+            title: title_item.map(|item| InventoryItem::granted(item, claim.user_id.clone(), event.id.clone())),
+        }
+    }
+}
+
+// 进行中的活动及其主题礼物
+#[derive(Debug, Serialize, Clone)]
+pub struct GiftEventCatalogue {
+    pub event: GiftEvent,
+    pub gifts: Vec<Gift>,
+}
+
+// 活动结果：排行榜、自己的名次和奖励发放状态
+#[derive(Debug, Serialize, Clone)]
+pub struct GiftEventResults {
+    pub event: GiftEvent,
+    pub entries: Vec<GiftEventEntry>,
+    pub mine: Option<GiftEventEntry>,
+}
@@ -0,0 +1,157 @@
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use crate::models::{AI, CardLevel, LuckyCard};
+use crate::models::chat::{Message, MessageType};
+use crate::models::gift::ConsecutiveGiftRecord;
+
+// 连续送礼里程碑奖励，可同时发放幸运卡、AI专属消息和亲密度加成
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GiftStreakReward {
+    pub lucky_card_level: Option<CardLevel>,
+    pub ai_message: Option<String>,         // AI发给用户的专属消息，支持 {ai_name}、{days} 占位符
+    #[serde(default)]
+    pub relationship_boost: u32,            // 额外增加的情感价值，计入亲密等级
+}
+
+impl GiftStreakReward {
+    pub fn is_empty(&self) -> bool {
+        self.lucky_card_level.is_none() && self.ai_message.is_none() && self.relationship_boost == 0
+    }
+}
+
+// 管理员配置的连续送礼里程碑
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftStreakMilestone {
+    pub id: String,
+    pub name: String,
+    pub days: u32,                          // 对同一个AI连续送礼达到的天数
+    pub reward: GiftStreakReward,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl GiftStreakMilestone {
+    pub fn new(name: String, days: u32, reward: GiftStreakReward) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            days,
+            reward,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.name.trim().is_empty() {
+            return Err("里程碑名称不能为空");
+        }
+
+        if self.days < 2 {
+            return Err("里程碑天数至少为2天");
+        }
+
+        if self.reward.is_empty() {
+            return Err("里程碑奖励不能为空");
+        }
+
+        Ok(())
+    }
+}
+
+// 里程碑奖励发放记录，同一段连续送礼中每个里程碑只发放一次；
+// 先写入待发放记录，奖励在发放事务中与清除待发放标记一起写入，失败时下次送礼或定时检查重试
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct GiftStreakClaim {
+    pub id: String,
+    pub user_id: String,
+    pub ai_id: String,
+    pub milestone_id: String,
+    pub days: u32,
+    #[serde(default)]
+    pub reward_pending: bool,               // 旧记录均已发放，默认 false
+    pub granted_at: Option<i64>,
+    #[serde(default)]
+    pub created_at: i64,
+}
+
+impl GiftStreakClaim {
+    pub fn new(streak: &ConsecutiveGiftRecord, milestone: &GiftStreakMilestone, utc_offset_secs: i64) -> Self {
+        let day = ConsecutiveGiftRecord::local_day(streak.last_gift_date, utc_offset_secs);
+        Self {
+            // 达到里程碑的当天（用户当地日期）在一段连续送礼中是唯一的，作为记录ID的一部分防止重复发放
+            id: format!("{}_{}_{}", streak.id, milestone.id, day),
+            user_id: streak.user_id.clone(),
+            ai_id: streak.ai_id.clone(),
+            milestone_id: milestone.id.clone(),
+            days: streak.consecutive_days,
+            reward_pending: true,
+            granted_at: None,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
+
+// 里程碑奖励发放事务中写入的内容
+#[derive(Debug, Clone)]
+pub struct GiftStreakGrant {
+    pub lucky_card: Option<LuckyCard>,
+    pub relationship_boost: u32,
+    pub message: Option<Message>,
+}
+
+impl GiftStreakGrant {
+    pub fn new(claim: &GiftStreakClaim, milestone: &GiftStreakMilestone, ai: &AI) -> Self {
+        let reward = &milestone.reward;
+
+        let lucky_card = reward.lucky_card_level
+            .as_ref()
+            .map(|level| LuckyCard::new(level.clone(), claim.user_id.clone(), Some(ai.id.clone())));
+
+        let message = reward.ai_message.as_ref().map(|template| {
+            let content = template
+                .replace("{ai_name}", &ai.name)
+                .replace("{days}", &claim.days.to_string());
+
+            let extends = serde_json::json!({
+                "gift_streak_milestone_id": milestone.id,
+                "days": claim.days,
+                "lucky_card_id": lucky_card.as_ref().map(|card| card.id.clone()),
+            });
+
+            Message::new(
+                ai.id.clone(),
+                claim.user_id.clone(),
+                content,
+                MessageType::Event,
+                false,
+                None,
+                Some(extends.to_string()),
+                None,
+            )
+        });
+
+        Self {
+            lucky_card,
+            relationship_boost: reward.relationship_boost,
+            message,
+        }
+    }
+}
+
+// 当前连续送礼状态和下一个里程碑
+#[derive(Debug, Serialize, Clone)]
+pub struct GiftStreakStatus {
+    pub ai_id: String,
+    pub consecutive_days: u32,              // 已中断时为0
+    pub sent_today: bool,
+    pub freezes_available: u32,
+    pub freezes_needed: u32,                // 今天送礼保持连续需要消耗的冻结卡数
+    pub next_milestone: Option<GiftStreakMilestone>,
+    pub days_to_next_milestone: Option<u32>,
+}
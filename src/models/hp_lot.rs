@@ -0,0 +1,128 @@
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use crate::models::{WalletTx, TxType};
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+// 积分批次：每笔积分收入单独记录剩余数量和过期时间，消费时按获得时间先进先出扣减
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HpLot {
+    pub id: String,
+    pub user_id: String,
+    pub tx_id: String,                      // 入账的交易记录
+    pub tx_type: TxType,
+    pub amount: u32,
+    pub remaining: u32,
+    pub earned_at: i64,
+    pub expires_at: Option<i64>,            // None 表示永不过期
+    pub is_expired: bool,
+}
+
+impl HpLot {
+    pub fn new(tx: &WalletTx, expires_at: Option<i64>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id: tx.user_id.clone(),
+            tx_id: tx.id.clone(),
+            tx_type: tx.tx_type.clone(),
+            amount: tx.amount,
+            remaining: tx.amount,
+            earned_at: tx.timestamp,
+            expires_at,
+            is_expired: false,
+        }
+    }
+}
+
+// 单个批次的扣减数量
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HpLotDeduction {
+    pub lot_id: String,
+    pub amount: u32,
+}
+
+// 按先进先出计算扣减方案
+// 启用批次前的历史积分没有批次记录，视为最早获得的积分优先扣减
+pub fn plan_hp_deductions(lots: &[HpLot], untracked: u32, amount: u32) -> Vec<HpLotDeduction> {
+    let mut left = amount.saturating_sub(untracked);
+    let mut lots: Vec<&HpLot> = lots.iter().filter(|lot| lot.remaining > 0 && !lot.is_expired).collect();
+    lots.sort_by_key(|lot| lot.earned_at);
+
+    let mut deductions = Vec::new();
+    for lot in lots {
+        if left == 0 {
+            break;
+        }
+
+        let take = lot.remaining.min(left);
+        deductions.push(HpLotDeduction { lot_id: lot.id.clone(), amount: take });
+        left -= take;
+    }
+
+    deductions
+}
+
+// 积分有效期策略，每种交易类型一条
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct HpExpiryPolicy {
+    pub id: String,                         // 与交易类型同名
+    pub tx_type: TxType,
+    pub valid_days: Option<u32>,            // None 表示永不过期
+    pub updated_at: i64,
+}
+
+impl HpExpiryPolicy {
+    // 未配置时的默认有效期：签到等积分获取和奖励有效12个月，其余不过期
+    pub fn default_for(tx_type: TxType) -> Self {
+        let valid_days = match tx_type {
+            TxType::PointsEarned | TxType::Reward => Some(365),
+            _ => None,
+        };
+
+        Self {
+            id: tx_type.as_str().to_string(),
+            tx_type,
+            valid_days,
+            updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn expires_at(&self, earned_at: i64) -> Option<i64> {
+        self.valid_days.map(|days| earned_at + days as i64 * SECONDS_PER_DAY)
+    }
+}
+
+// 即将过期的积分提示：最近一个过期日及当天过期的积分总数
+#[derive(Debug, Serialize, Clone)]
+pub struct HpExpiryNotice {
+    pub amount: u32,
+    pub expires_at: i64,
+    pub message: String,
+}
+
+impl HpExpiryNotice {
+    pub fn from_lots(lots: &[HpLot]) -> Option<Self> {
+        let first = lots
+            .iter()
+            .filter(|lot| lot.remaining > 0 && !lot.is_expired)
+            .filter_map(|lot| lot.expires_at)
+            .min()?;
+
+        // 同一天过期的批次合并展示
+        let day = first.div_euclid(SECONDS_PER_DAY);
+        let amount = lots
+            .iter()
+            .filter(|lot| lot.remaining > 0 && !lot.is_expired)
+            .filter(|lot| lot.expires_at.is_some_and(|at| at.div_euclid(SECONDS_PER_DAY) == day))
+            .map(|lot| lot.remaining)
+            .sum();
+
+        let date = OffsetDateTime::from_unix_timestamp(first).ok()?.date();
+        Some(Self {
+            amount,
+            expires_at: first,
+            message: format!("{}积分将于{}过期", amount, date),
+        })
+    }
+}
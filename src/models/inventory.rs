@@ -0,0 +1,90 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+use crate::models::{Coupon, ShopItem, ShopItemType, PurchaseRecord};
+
+// 用户背包中的物品（称号、AI装饰、已解锁剧情）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InventoryItem {
+    pub id: String,
+    pub user_id: String,
+    pub item_id: String,                    // 商城商品ID，剧情类商品即剧情内容ID
+    pub item_type: ShopItemType,
+    pub name: String,
+    pub image_url: Option<String>,
+    pub purchase_id: String,                // 非购买获得时为来源ID（如成就ID）
+    pub is_equipped: bool,
+    pub equipped_ai_id: Option<String>,     // 装饰当前装备的AI
+    pub acquired_at: i64,
+    pub expires_at: Option<i64>,
+}
+
+impl InventoryItem {
+    pub fn new(item: &ShopItem, purchase: &PurchaseRecord) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id: purchase.user_id.clone(),
+            item_id: item.id.clone(),
+            item_type: item.item_type.clone(),
+            name: item.name.clone(),
+            image_url: item.image_url.clone(),
+            purchase_id: purchase.id.clone(),
+            is_equipped: false,
+            equipped_ai_id: None,
+            acquired_at: OffsetDateTime::now_utc().unix_timestamp(),
+            expires_at: purchase.expires_at,
+        }
+    }
+
+    // 成就等奖励发放的物品，永久有效
+    pub fn granted(item: &ShopItem, user_id: String, source_id: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            item_id: item.id.clone(),
+            item_type: item.item_type.clone(),
+            name: item.name.clone(),
+            image_url: item.image_url.clone(),
+            purchase_id: source_id,
+            is_equipped: false,
+            equipped_ai_id: None,
+            acquired_at: OffsetDateTime::now_utc().unix_timestamp(),
+            expires_at: None,
+        }
+    }
+
+    // 是否为可装备的物品
+    pub fn is_equippable(&self) -> bool {
+        matches!(self.item_type, ShopItemType::UserTitle | ShopItemType::AIDecoration)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_at <= OffsetDateTime::now_utc().unix_timestamp())
+    }
+}
+
+// 激活购买记录时发放的效果，与激活状态在同一事务中写入
+#[derive(Debug, Clone, Default)]
+pub struct PurchaseGrant {
+    pub user_counter: Option<&'static str>,     // 加一的用户计数字段（AI名额、LIO券、补签卡、冻结卡）
+    pub inventory_item: Option<InventoryItem>,  // 放入背包的称号、装饰或剧情
+    pub coupon: Option<Coupon>,                 // 关联卡券
+}
+
+impl PurchaseGrant {
+    pub fn new(item: &ShopItem, purchase: &PurchaseRecord) -> Self {
+        let mut grant = Self::default();
+        match item.item_type {
+            ShopItemType::AISlotExpansion => grant.user_counter = Some("ai_slots"),
+            ShopItemType::LIOAccessTicket => grant.user_counter = Some("lio_tickets"),
+            ShopItemType::CheckinMakeupCard => grant.user_counter = Some("checkin_makeup_cards"),
+            ShopItemType::GiftStreakFreeze => grant.user_counter = Some("gift_streak_freezes"),
+            ShopItemType::UserTitle | ShopItemType::AIDecoration | ShopItemType::ExclusiveStory => {
+                // 称号、装饰进入背包等待装备，剧情以背包记录作为解锁凭证
+                grant.inventory_item = Some(InventoryItem::new(item, purchase));
+            }
+        }
+        grant
+    }
+}
@@ -7,6 +7,8 @@ pub struct Invite {
     pub creator_id: String,        // 创建者ID
     pub usage_limit: u32,         // 本周使用上限
     pub expires_at: i64,          // 过期时间戳
+    #[serde(default)]
+    pub is_expired: bool,         // 已被过期任务标记
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -23,6 +25,7 @@ impl Invite {
             creator_id,
             usage_limit,
             expires_at,
+            is_expired: false,
             created_at: now,
             updated_at: now,
         }
@@ -0,0 +1,172 @@
+use serde::{Serialize, Deserialize};
+use time::{Duration, OffsetDateTime};
+
+// 每期排行榜快照保留的名次
+pub const LEADERBOARD_SNAPSHOT_SIZE: usize = 100;
+
+// 排行榜类型
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum LeaderboardKind {
+    TopGifters,         // 送礼情感价值最高的用户
+    LongestStreak,      // 连续签到天数最长的用户
+    PopularAis,         // 收到礼物情感价值最高的AI
+}
+
+impl LeaderboardKind {
+    pub fn all() -> [LeaderboardKind; 3] {
+        [
+            LeaderboardKind::TopGifters,
+            LeaderboardKind::LongestStreak,
+            LeaderboardKind::PopularAis,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardKind::TopGifters => "TopGifters",
+            LeaderboardKind::LongestStreak => "LongestStreak",
+            LeaderboardKind::PopularAis => "PopularAis",
+        }
+    }
+}
+
+// 排行榜周期，按UTC时间划分
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum LeaderboardPeriod {
+    Daily,
+    Weekly,
+    AllTime,
+}
+
+impl LeaderboardPeriod {
+    pub fn all() -> [LeaderboardPeriod; 3] {
+        [
+            LeaderboardPeriod::Daily,
+            LeaderboardPeriod::Weekly,
+            LeaderboardPeriod::AllTime,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LeaderboardPeriod::Daily => "Daily",
+            LeaderboardPeriod::Weekly => "Weekly",
+            LeaderboardPeriod::AllTime => "AllTime",
+        }
+    }
+
+    // 时间所在周期的标识：日榜为 2024-01-31，周榜为 ISO 周 2024-W05，总榜为 all
+    pub fn key(&self, at: OffsetDateTime) -> String {
+        match self {
+            LeaderboardPeriod::Daily => at.date().to_string(),
+            LeaderboardPeriod::Weekly => {
+                let (year, week, _) = at.date().to_iso_week_date();
+                format!("{}-W{:02}", year, week)
+            }
+            LeaderboardPeriod::AllTime => "all".to_string(),
+        }
+    }
+
+    // 刚结束的上一期标识，总榜没有周期
+    pub fn previous_key(&self, now: OffsetDateTime) -> Option<String> {
+        match self {
+            LeaderboardPeriod::Daily => Some(self.key(now - Duration::days(1))),
+            LeaderboardPeriod::Weekly => Some(self.key(now - Duration::weeks(1))),
+            LeaderboardPeriod::AllTime => None,
+        }
+    }
+}
+
+// 排行榜分数，每个榜单每期每个对象一条，送礼和签到时增量更新
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeaderboardScore {
+    pub id: String,
+    pub kind: LeaderboardKind,
+    pub period: LeaderboardPeriod,
+    pub period_key: String,
+    pub subject_id: String,             // 用户ID或AI ID
+    pub owner_id: String,               // 所属用户，用户榜即用户本人
+    pub score: u32,
+    pub hidden: bool,                   // 所属用户关闭了排行榜展示
+    pub updated_at: i64,
+}
+
+impl LeaderboardScore {
+    pub fn new(
+        kind: LeaderboardKind,
+        period: LeaderboardPeriod,
+        subject_id: String,
+        owner_id: String,
+        score: u32,
+        hidden: bool,
+    ) -> Self {
+        let now = OffsetDateTime::now_utc();
+        let period_key = period.key(now);
+
+        Self {
+            id: format!("{}_{}_{}_{}", kind.as_str(), period.as_str(), period_key, subject_id),
+            kind,
+            period,
+            period_key,
+            subject_id,
+            owner_id,
+            score,
+            hidden,
+            updated_at: now.unix_timestamp(),
+        }
+    }
+}
+
+// 排行榜中的一行
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeaderboardEntry {
+    pub rank: u32,
+    pub subject_id: String,
+    pub owner_id: String,
+    pub score: u32,
+}
+
+// 周期结束时保存的排行榜快照
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LeaderboardSnapshot {
+    pub id: String,
+    pub kind: LeaderboardKind,
+    pub period: LeaderboardPeriod,
+    pub period_key: String,
+    pub entries: Vec<LeaderboardEntry>,
+    pub created_at: i64,
+}
+
+impl LeaderboardSnapshot {
+    pub fn new(kind: LeaderboardKind, period: LeaderboardPeriod, period_key: String, entries: Vec<LeaderboardEntry>) -> Self {
+        Self {
+            id: Self::record_id(&kind, &period, &period_key),
+            kind,
+            period,
+            period_key,
+            entries,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    pub fn record_id(kind: &LeaderboardKind, period: &LeaderboardPeriod, period_key: &str) -> String {
+        format!("{}_{}_{}", kind.as_str(), period.as_str(), period_key)
+    }
+}
+
+// 排行榜分页结果
+#[derive(Debug, Serialize, Clone)]
+pub struct LeaderboardPage {
+    pub kind: LeaderboardKind,
+    pub period: LeaderboardPeriod,
+    pub period_key: String,
+    pub entries: Vec<LeaderboardEntry>,
+    pub mine: Vec<LeaderboardEntry>,    // 自己的名次，AI榜为自己的各个AI
+}
+
+// 补录总榜时的统计结果
+#[derive(Debug, Deserialize, Clone)]
+pub struct LeaderboardTotal {
+    pub subject_id: String,
+    pub score: u32,
+}
@@ -2,6 +2,11 @@ use serde::{Serialize, Deserialize};
 use uuid::Uuid;
 use time::OffsetDateTime;
 
+// 幸运卡使用后的加成时长
+pub const LUCKY_BOOST_DURATION_SECS: i64 = 60 * 60;
+// 单次加成期内最多额外获得的积分
+pub const LUCKY_BOOST_MAX_BONUS_HP: u32 = 500;
+
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum CardLevel {
     A,  // 8-9.99倍
@@ -46,17 +51,25 @@ pub struct LuckyCard {
     pub is_used: bool,
     pub used_at: Option<i64>,
     pub issued_by_ai_id: Option<String>,  // 由哪个AI发放
+    #[serde(default)]
+    pub is_expired: bool,         // 已被过期任务标记
 }
 
 impl LuckyCard {
     pub fn new(level: CardLevel, owner_id: String, issued_by_ai_id: Option<String>) -> Self {
-        let now = OffsetDateTime::now_utc().unix_timestamp();
         let min = level.min_multiplier();
         let max = level.max_multiplier();
         
         // 在范围内生成随机倍率
         let multiplier = min + (max - min) * rand::random::<f32>();
         
+        Self::with_multiplier(level, owner_id, issued_by_ai_id, multiplier)
+    }
+    
+    // 使用指定倍率创建（倍率由掉落表决定）
+    pub fn with_multiplier(level: CardLevel, owner_id: String, issued_by_ai_id: Option<String>, multiplier: f32) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        
         Self {
             id: Uuid::new_v4().to_string(),
             level,
@@ -67,6 +80,7 @@ impl LuckyCard {
             is_used: false,
             used_at: None,
             issued_by_ai_id,
+            is_expired: false,
         }
     }
     
@@ -91,3 +105,45 @@ impl LuckyCard {
         Ok(self.multiplier)
     }
 }
+
+// 幸运卡加成窗口，期间获得的积分按倍率放大
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LuckyBoost {
+    pub card_id: String,
+    pub multiplier: f32,
+    pub started_at: i64,
+    pub expires_at: i64,
+    pub bonus_hp: u32,            // 本次加成已额外发放的积分
+}
+
+impl LuckyBoost {
+    pub fn new(card_id: String, multiplier: f32) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        Self {
+            card_id,
+            multiplier,
+            started_at: now,
+            expires_at: now + LUCKY_BOOST_DURATION_SECS,
+            bonus_hp: 0,
+        }
+    }
+
+    // 加成是否仍然有效（未过期且未达到上限）
+    pub fn is_active(&self) -> bool {
+        self.expires_at > OffsetDateTime::now_utc().unix_timestamp()
+            && self.bonus_hp < LUCKY_BOOST_MAX_BONUS_HP
+    }
+
+    // 计算本次额外积分，不超过剩余额度
+    pub fn apply(&mut self, base_points: u32) -> u32 {
+        if !self.is_active() {
+            return 0;
+        }
+
+        let bonus = (base_points as f32 * (self.multiplier - 1.0)).round().max(0.0) as u32;
+        let bonus = bonus.min(LUCKY_BOOST_MAX_BONUS_HP - self.bonus_hp);
+        self.bonus_hp += bonus;
+        bonus
+    }
+}
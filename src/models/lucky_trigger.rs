@@ -0,0 +1,98 @@
+use serde::{Serialize, Deserialize};
+use time::OffsetDateTime;
+use crate::models::drop_table::LUCKY_CARD_DROP_KEY;
+
+// 可触发幸运卡掉落的事件
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum LuckyEventType {
+    ChatMilestone,          // 与AI的对话条数达到里程碑
+    FirstGiftOfDay,         // 当天首次送礼
+    CheckinStreak,          // 连续签到天数
+    RelationshipLevelUp,    // 与AI的亲密等级提升
+}
+
+impl LuckyEventType {
+    pub fn all() -> [LuckyEventType; 4] {
+        [
+            LuckyEventType::ChatMilestone,
+            LuckyEventType::FirstGiftOfDay,
+            LuckyEventType::CheckinStreak,
+            LuckyEventType::RelationshipLevelUp,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LuckyEventType::ChatMilestone => "ChatMilestone",
+            LuckyEventType::FirstGiftOfDay => "FirstGiftOfDay",
+            LuckyEventType::CheckinStreak => "CheckinStreak",
+            LuckyEventType::RelationshipLevelUp => "RelationshipLevelUp",
+        }
+    }
+}
+
+// 事件触发的幸运卡掉落配置，每种事件一条
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LuckyTrigger {
+    pub id: String,                     // 与事件类型同名
+    pub event_type: LuckyEventType,
+    pub is_enabled: bool,
+    pub drop_key: String,               // 使用的掉落表场景
+    pub thresholds: Vec<u32>,           // 命中这些值时触发（消息条数/签到天数/等级），为空则每次都触发
+    pub message_template: String,       // AI公告文案，{level} 和 {multiplier} 会被替换
+    pub updated_at: i64,
+}
+
+impl LuckyTrigger {
+    // 未配置时的默认触发规则
+    pub fn default_for(event_type: LuckyEventType) -> Self {
+        let (thresholds, message_template) = match event_type {
+            LuckyEventType::ChatMilestone => (
+                vec![50, 100, 200, 500, 1000],
+                "我们已经聊了这么多啦！送你一张{level}级幸运卡，{multiplier}倍积分加成~",
+            ),
+            LuckyEventType::FirstGiftOfDay => (
+                vec![],
+                "谢谢你今天的礼物！回赠你一张{level}级幸运卡，{multiplier}倍积分加成~",
+            ),
+            LuckyEventType::CheckinStreak => (
+                vec![3, 7, 14, 30],
+                "连续签到好厉害！奖励你一张{level}级幸运卡，{multiplier}倍积分加成~",
+            ),
+            LuckyEventType::RelationshipLevelUp => (
+                vec![],
+                "我们的关系更亲密了！送你一张{level}级幸运卡，{multiplier}倍积分加成~",
+            ),
+        };
+
+        Self {
+            id: event_type.as_str().to_string(),
+            event_type,
+            is_enabled: true,
+            drop_key: LUCKY_CARD_DROP_KEY.to_string(),
+            thresholds,
+            message_template: message_template.to_string(),
+            updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+
+    // 事件值是否满足触发条件
+    pub fn matches(&self, value: u32) -> bool {
+        self.is_enabled && (self.thresholds.is_empty() || self.thresholds.contains(&value))
+    }
+}
+
+// 触发事件
+#[derive(Debug, Clone)]
+pub struct LuckyEvent {
+    pub event_type: LuckyEventType,
+    pub user_id: String,
+    pub ai_id: Option<String>,          // 相关AI，为空时由用户的第一个AI发布公告
+    pub value: u32,
+}
+
+impl LuckyEvent {
+    pub fn new(event_type: LuckyEventType, user_id: String, ai_id: Option<String>, value: u32) -> Self {
+        Self { event_type, user_id, ai_id, value }
+    }
+}
@@ -10,6 +10,23 @@ pub mod lucky_card;
 pub mod point_shop;
 pub mod promoter;
 pub mod chat;
+pub mod vip_plan;
+pub mod order;
+pub mod notification;
+pub mod coupon_code;
+pub mod coupon_transfer;
+pub mod drop_table;
+pub mod lucky_trigger;
+pub mod inventory;
+pub mod shop_promotion;
+pub mod wishlist;
+pub mod hp_lot;
+pub mod earning_rule;
+pub mod checkin;
+pub mod achievement;
+pub mod leaderboard;
+pub mod gift_streak;
+pub mod gift_event;
 
 pub use user::{User, VipLevel, PromoterType, FrontendUserRole, VipLevelConfig};
 pub use ai::{AI, AIType, AIStatus};
@@ -18,7 +35,11 @@ pub use audit_log::{AuditLog, AuditAction};
 pub use verification::{EmailVerification, VerificationType};
 pub use wallet_tx::{WalletTx, TxType, CurrencyType};
 pub use gift::{Gift, GiftRecord, GiftEffectType};
-pub use lucky_card::{LuckyCard, CardLevel};
+pub use lucky_card::{LuckyCard, LuckyBoost, CardLevel};
 pub use point_shop::{ShopItem, ShopItemType, PurchaseRecord, ShopItemCategory, MonthlyRedemptionStat};
 pub use promoter::{Promoter, PromotionRecord, CommissionLog, WithdrawalRequest, VerificationStatus, CommissionStatus, CommissionType};
 pub use chat::{Message, MessageType, Group, GroupSetting, GroupUser, GroupApply, Friend, ChatFile};
+pub use vip_plan::{VipPlan, VipSubscription, SubscriptionStatus};
+pub use order::{PaymentOrder, OrderType, OrderStatus};
+pub use coupon::{Coupon, CouponKind, CouponStatus, CouponTemplate};
+pub use notification::{Notification, NotificationType};
@@ -0,0 +1,52 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum NotificationType {
+    CouponIssued,       // 收到卡券
+    CouponExpiring,     // 卡券即将过期
+    CouponTransfer,     // 卡券转赠（收到邀请、被接受或拒绝）
+    LuckyCardIssued,    // 获得幸运卡
+    WishlistRestock,    // 心愿单商品补货
+    WishlistOnSale,     // 心愿单商品促销
+    AchievementUnlocked, // 达成成就
+    GiftStreakMilestone, // 连续送礼达到里程碑
+    GiftStreakFreezeUsed, // 消耗冻结卡保持连续送礼
+    GiftStreakBroken,   // 连续送礼中断
+    GiftEventReward,    // 礼物活动排名奖励
+}
+
+// 站内通知
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Notification {
+    pub id: String,
+    pub user_id: String,
+    pub notification_type: NotificationType,
+    pub title: String,
+    pub content: String,
+    pub related_entity_id: Option<String>,  // 相关实体ID (如卡券ID)
+    pub is_read: bool,
+    pub created_at: i64,
+}
+
+impl Notification {
+    pub fn new(
+        user_id: String,
+        notification_type: NotificationType,
+        title: String,
+        content: String,
+        related_entity_id: Option<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            notification_type,
+            title,
+            content,
+            related_entity_id,
+            is_read: false,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
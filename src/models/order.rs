@@ -0,0 +1,81 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum OrderType {
+    VipPurchase,    // 购买VIP套餐
+    VipRenewal,     // VIP自动续费
+    LcRecharge,     // 光币充值
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum OrderStatus {
+    Pending,
+    Paid,
+    Failed,
+}
+
+// 付款订单（所有经过支付网关的扣款）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PaymentOrder {
+    pub id: String,
+    pub user_id: String,
+    pub order_type: OrderType,
+    pub related_entity_id: Option<String>,  // 相关实体ID (如VIP套餐ID)
+    pub original_amount: f32,               // 优惠前金额
+    pub discount_amount: f32,               // 卡券抵扣金额
+    pub amount: f32,                        // 实付金额
+    pub currency: String,
+    pub coupon_id: Option<String>,          // 使用的卡券
+    pub status: OrderStatus,
+    pub gateway_tx_id: Option<String>,      // 支付网关的交易ID
+    pub failure_reason: Option<String>,
+    pub created_at: i64,
+    pub paid_at: Option<i64>,
+}
+
+impl PaymentOrder {
+    pub fn new(
+        user_id: String,
+        order_type: OrderType,
+        related_entity_id: Option<String>,
+        amount: f32,
+        currency: String,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            order_type,
+            related_entity_id,
+            original_amount: amount,
+            discount_amount: 0.0,
+            amount,
+            currency,
+            coupon_id: None,
+            status: OrderStatus::Pending,
+            gateway_tx_id: None,
+            failure_reason: None,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+            paid_at: None,
+        }
+    }
+
+    // 使用卡券后的价格
+    pub fn apply_coupon(&mut self, coupon_id: String, discount_amount: f32) {
+        self.discount_amount = discount_amount;
+        self.amount = self.original_amount - discount_amount;
+        self.coupon_id = Some(coupon_id);
+    }
+
+    pub fn mark_as_paid(&mut self, gateway_tx_id: String) {
+        self.status = OrderStatus::Paid;
+        self.gateway_tx_id = Some(gateway_tx_id);
+        self.paid_at = Some(OffsetDateTime::now_utc().unix_timestamp());
+    }
+
+    pub fn mark_as_failed(&mut self, reason: String) {
+        self.status = OrderStatus::Failed;
+        self.failure_reason = Some(reason);
+    }
+}
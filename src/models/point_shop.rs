@@ -1,6 +1,7 @@
 use serde::{Serialize, Deserialize};
 use uuid::Uuid;
-use time::OffsetDateTime;
+use time::{Duration, OffsetDateTime};
+use crate::models::VipLevel;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum ShopItemType {
@@ -9,6 +10,8 @@ pub enum ShopItemType {
     LIOAccessTicket,    // LIO访问券
     AISlotExpansion,    // AI扩展名额
     ExclusiveStory,     // 限定剧情解锁
+    CheckinMakeupCard,  // 补签卡
+    GiftStreakFreeze,   // 连续送礼冻结卡
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -18,6 +21,163 @@ pub enum ShopItemCategory {
     Function,           // 功能类（名额、故事解锁、关系改名）
 }
 
+// 限购统计窗口（按UTC自然日/周/月计算）
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum LimitWindow {
+    Lifetime,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl LimitWindow {
+    // 窗口起始时间，终身限购返回 None
+    pub fn start(&self, now: OffsetDateTime) -> Option<i64> {
+        let today = now.date();
+        let date = match self {
+            LimitWindow::Lifetime => return None,
+            LimitWindow::Daily => today,
+            LimitWindow::Weekly => today - Duration::days(today.weekday().number_days_from_monday() as i64),
+            LimitWindow::Monthly => today.replace_day(1).expect("day 1 is valid for every month"),
+        };
+
+        Some(date.midnight().assume_utc().unix_timestamp())
+    }
+}
+
+// 单个商品的限购规则，None 表示该窗口不限
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct PurchaseLimits {
+    pub lifetime: Option<u32>,
+    pub daily: Option<u32>,
+    pub weekly: Option<u32>,
+    pub monthly: Option<u32>,
+}
+
+impl PurchaseLimits {
+    // 已配置的限购窗口
+    pub fn windows(&self) -> Vec<(LimitWindow, u32)> {
+        [
+            (LimitWindow::Lifetime, self.lifetime),
+            (LimitWindow::Daily, self.daily),
+            (LimitWindow::Weekly, self.weekly),
+            (LimitWindow::Monthly, self.monthly),
+        ]
+        .into_iter()
+        .filter_map(|(window, limit)| limit.map(|limit| (window, limit)))
+        .collect()
+    }
+}
+
+// 按会员等级覆盖限购规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PurchaseLimitOverride {
+    pub vip_level: VipLevel,
+    pub limits: PurchaseLimits,
+}
+
+// 单个窗口的剩余额度
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LimitAllowance {
+    pub window: LimitWindow,
+    pub limit: u32,
+    pub used: u32,
+    pub remaining: u32,
+}
+
+// 用户对某商品的剩余可兑换次数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PurchaseAllowance {
+    pub windows: Vec<LimitAllowance>,
+    pub remaining: Option<u32>,     // 各窗口中最小的剩余额度，None 表示不限
+}
+
+impl PurchaseAllowance {
+    pub fn new(windows: Vec<LimitAllowance>) -> Self {
+        let remaining = windows.iter().map(|window| window.remaining).min();
+        Self { windows, remaining }
+    }
+
+    pub fn is_exhausted(&self) -> bool {
+        self.remaining == Some(0)
+    }
+}
+
+// 商品列表排序方式
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ShopItemSort {
+    PriceAsc,
+    PriceDesc,
+    Newest,
+    Popular,
+}
+
+impl ShopItemSort {
+    // 排序字段
+    pub fn field(&self) -> &'static str {
+        match self {
+            ShopItemSort::PriceAsc | ShopItemSort::PriceDesc => "price_hp",
+            ShopItemSort::Newest => "created_at",
+            ShopItemSort::Popular => "sold_count",
+        }
+    }
+
+    pub fn is_descending(&self) -> bool {
+        !matches!(self, ShopItemSort::PriceAsc)
+    }
+
+    // 商品在该排序下的取值，用于生成游标
+    pub fn value_of(&self, item: &ShopItem) -> i64 {
+        match self {
+            ShopItemSort::PriceAsc | ShopItemSort::PriceDesc => item.price_hp as i64,
+            ShopItemSort::Newest => item.created_at,
+            ShopItemSort::Popular => item.sold_count as i64,
+        }
+    }
+}
+
+// 分页游标：上一页最后一件商品的排序值和ID
+#[derive(Debug, Clone)]
+pub struct ShopItemCursor {
+    pub value: i64,
+    pub id: String,
+}
+
+impl ShopItemCursor {
+    pub fn after(sort: &ShopItemSort, item: &ShopItem) -> Self {
+        Self {
+            value: sort.value_of(item),
+            id: item.id.clone(),
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.value, self.id)
+    }
+
+    pub fn parse(cursor: &str) -> Option<Self> {
+        let (value, id) = cursor.split_once('_')?;
+        Some(Self {
+            value: value.parse().ok()?,
+            id: id.to_string(),
+        })
+    }
+}
+
+// 商品搜索条件
+#[derive(Debug, Clone)]
+pub struct ShopItemSearch {
+    pub keyword: Option<String>,            // 匹配名称或描述，不区分大小写
+    pub category: Option<ShopItemCategory>,
+    pub item_type: Option<ShopItemType>,
+    pub min_price: Option<u32>,             // 按标价筛选
+    pub max_price: Option<u32>,
+    pub sort: ShopItemSort,
+    pub cursor: Option<ShopItemCursor>,
+    pub limit: usize,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ShopItem {
     pub id: String,
@@ -35,6 +195,12 @@ pub struct ShopItem {
     pub linked_coupon_id: Option<String>, // 新增：关联的卡券ID
     pub monthly_limit: Option<u32>, // 新增：月度兑换上限
     pub vip_discount: Option<bool>, // 新增：是否支持VIP折扣
+    #[serde(default)]
+    pub limits: PurchaseLimits,     // 按商品计算的限购规则
+    #[serde(default)]
+    pub vip_limit_overrides: Vec<PurchaseLimitOverride>,
+    #[serde(default)]
+    pub sold_count: u32,            // 累计兑换次数，用于按热度排序
 }
 
 impl ShopItem {
@@ -69,7 +235,25 @@ impl ShopItem {
             linked_coupon_id,
             monthly_limit,
             vip_discount,
+            limits: PurchaseLimits::default(),
+            vip_limit_overrides: vec![],
+            sold_count: 0,
+        }
+    }
+    
+    // 获取会员等级对应的限购规则，未设置月度限购时沿用 monthly_limit
+    pub fn effective_limits(&self, vip_level: &VipLevel) -> PurchaseLimits {
+        let mut limits = self.vip_limit_overrides
+            .iter()
+            .find(|o| &o.vip_level == vip_level)
+            .map(|o| o.limits.clone())
+            .unwrap_or_else(|| self.limits.clone());
+        
+        if limits.monthly.is_none() {
+            limits.monthly = self.monthly_limit;
         }
+        
+        limits
     }
     
     // 检查商品是否可用
@@ -98,22 +282,27 @@ impl ShopItem {
         true
     }
     
-    // 计算VIP用户的折扣价格
-    pub fn get_discounted_price(&self, vip_level: &crate::models::VipLevel) -> u32 {
+    // 计算VIP用户的折扣价格，price_hp 可以是促销后的价格，会员折扣叠加在促销价之上
+    pub fn get_discounted_price(&self, price_hp: u32, discount_percent: u32) -> u32 {
         if self.vip_discount.unwrap_or(false) {
-            match vip_level {
-                crate::models::VipLevel::Free => self.price_hp,
-                crate::models::VipLevel::Pro => (self.price_hp as f32 * 0.9) as u32, // 10% 折扣
-                crate::models::VipLevel::Premium => (self.price_hp as f32 * 0.85) as u32, // 15% 折扣
-                crate::models::VipLevel::Ultimate => (self.price_hp as f32 * 0.8) as u32, // 20% 折扣
-                crate::models::VipLevel::Team => (self.price_hp as f32 * 0.7) as u32, // 30% 折扣
-            }
+            apply_percent_off(price_hp, discount_percent)
         } else {
-            self.price_hp
+            price_hp
         }
     }
 }
 
+// 按百分比减价：四舍五入到整数积分，且非免费商品最低1积分
+pub fn apply_percent_off(price_hp: u32, percent_off: u32) -> u32 {
+    if price_hp == 0 {
+        return 0;
+    }
+    
+    let percent_paid = 100 - percent_off.min(100) as u64;
+    let price = (price_hp as u64 * percent_paid + 50) / 100;
+    (price as u32).max(1)
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct PurchaseRecord {
     pub id: String,
@@ -125,6 +314,12 @@ pub struct PurchaseRecord {
     pub activated_at: Option<i64>,
     pub expires_at: Option<i64>,    // 某些物品可能有使用期限
     pub remark: Option<String>,     // 新增：备注信息
+    #[serde(default)]
+    pub flash_deal_id: Option<String>, // 秒杀成交的秒杀ID
+    #[serde(default)]
+    pub bundle_id: Option<String>,  // 组合包成交的组合包ID
+    #[serde(default)]
+    pub coupon_id: Option<String>,  // 兑换时使用的折扣券
 }
 
 impl PurchaseRecord {
@@ -145,6 +340,9 @@ impl PurchaseRecord {
             activated_at: None,
             expires_at,
             remark,
+            flash_deal_id: None,
+            bundle_id: None,
+            coupon_id: None,
         }
     }
     
@@ -200,13 +398,4 @@ impl MonthlyRedemptionStat {
         self.total_points_spent += points_spent;
         self.updated_at = OffsetDateTime::now_utc().unix_timestamp();
     }
-    
-    // 检查是否达到月度兑换上限
-    pub fn check_monthly_limit(&self, item_type: &str, limit: u32) -> bool {
-        if let Some(count) = self.item_type_counts.get(item_type) {
-            *count < limit
-        } else {
-            true
-        }
-    }
 }
@@ -0,0 +1,411 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+use crate::models::{ShopItem, Coupon, CouponKind};
+use crate::models::point_shop::apply_percent_off;
+
+// 促销价格调整方式
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum PriceAdjustment {
+    Percentage { percent_off: u32 },            // 按百分比减价，如 20 即8折
+    Fixed { price_hp: u32 },                    // 一口价
+}
+
+impl PriceAdjustment {
+    pub fn apply(&self, price_hp: u32) -> u32 {
+        match self {
+            PriceAdjustment::Percentage { percent_off } => apply_percent_off(price_hp, *percent_off),
+            PriceAdjustment::Fixed { price_hp: fixed } => (*fixed).min(price_hp),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        match self {
+            PriceAdjustment::Percentage { percent_off } => (1..100).contains(percent_off),
+            PriceAdjustment::Fixed { price_hp } => *price_hp > 0,
+        }
+    }
+}
+
+// 生效时间窗口校验
+fn validate_window(starts_at: i64, ends_at: i64) -> Result<(), &'static str> {
+    if starts_at >= ends_at {
+        return Err("生效时间无效");
+    }
+    Ok(())
+}
+
+// 限时促销：在时间窗口内对指定商品打折或一口价
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShopSale {
+    pub id: String,
+    pub name: String,
+    pub item_ids: Vec<String>,
+    pub adjustment: PriceAdjustment,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub is_active: bool,
+    #[serde(default)]
+    pub wishlist_notified_at: Option<i64>,      // 已通知心愿单用户的时间
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ShopSale {
+    pub fn new(name: String, item_ids: Vec<String>, adjustment: PriceAdjustment, starts_at: i64, ends_at: i64) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            item_ids,
+            adjustment,
+            starts_at,
+            ends_at,
+            is_active: true,
+            wishlist_notified_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.item_ids.is_empty() || !self.adjustment.is_valid() {
+            return Err("促销配置无效");
+        }
+        validate_window(self.starts_at, self.ends_at)
+    }
+
+    pub fn covers(&self, item_id: &str, now: i64) -> bool {
+        self.is_active
+            && self.starts_at <= now
+            && now < self.ends_at
+            && self.item_ids.iter().any(|id| id == item_id)
+    }
+}
+
+// 秒杀：单个商品在时间窗口内以特价限量出售
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FlashDeal {
+    pub id: String,
+    pub item_id: String,
+    pub price_hp: u32,
+    pub stock: u32,                             // 本次秒杀的独立库存
+    pub sold: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+    pub is_active: bool,
+    #[serde(default)]
+    pub wishlist_notified_at: Option<i64>,      // 已通知心愿单用户的时间
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl FlashDeal {
+    pub fn new(item_id: String, price_hp: u32, stock: u32, starts_at: i64, ends_at: i64) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            item_id,
+            price_hp,
+            stock,
+            sold: 0,
+            starts_at,
+            ends_at,
+            is_active: true,
+            wishlist_notified_at: None,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.price_hp == 0 || self.stock == 0 {
+            return Err("秒杀配置无效");
+        }
+        validate_window(self.starts_at, self.ends_at)
+    }
+
+    pub fn remaining(&self) -> u32 {
+        self.stock.saturating_sub(self.sold)
+    }
+
+    pub fn is_live(&self, now: i64) -> bool {
+        self.is_active && self.starts_at <= now && now < self.ends_at && self.remaining() > 0
+    }
+}
+
+// 组合包：多个商品以组合价一起出售
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ShopBundle {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub image_url: Option<String>,
+    pub item_ids: Vec<String>,
+    pub price_hp: u32,
+    pub stock: Option<u32>,                     // None 表示不限量
+    pub sold: u32,
+    pub starts_at: Option<i64>,
+    pub ends_at: Option<i64>,
+    pub is_active: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl ShopBundle {
+    pub fn new(name: String, description: String, item_ids: Vec<String>, price_hp: u32) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            description,
+            image_url: None,
+            item_ids,
+            price_hp,
+            stock: None,
+            sold: 0,
+            starts_at: None,
+            ends_at: None,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.item_ids.len() < 2 || self.price_hp == 0 {
+            return Err("组合包至少包含两个商品且价格大于0");
+        }
+        if let (Some(start), Some(end)) = (self.starts_at, self.ends_at) {
+            validate_window(start, end)?;
+        }
+        Ok(())
+    }
+
+    pub fn is_live(&self, now: i64) -> bool {
+        self.is_active
+            && self.starts_at.is_none_or(|start| start <= now)
+            && self.ends_at.is_none_or(|end| now < end)
+            && self.stock.is_none_or(|stock| self.sold < stock)
+    }
+
+    // 按原价比例把成交价分摊到各商品，余数计入最后一件
+    pub fn split_price(price_hp: u32, items: &[ShopItem]) -> Vec<u32> {
+        let total: u64 = items.iter().map(|item| item.price_hp as u64).sum();
+        let mut shares: Vec<u32> = items
+            .iter()
+            .map(|item| match total {
+                0 => 0,
+                _ => (price_hp as u64 * item.price_hp as u64 / total) as u32,
+            })
+            .collect();
+
+        let allocated: u32 = shares.iter().sum();
+        if let Some(last) = shares.last_mut() {
+            *last += price_hp - allocated;
+        }
+
+        shares
+    }
+}
+
+// 成交价来源
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "type")]
+pub enum PriceSource {
+    Regular,
+    Sale { sale_id: String, name: String },
+    FlashDeal { deal_id: String, remaining: u32 },
+    Bundle { bundle_id: String },
+}
+
+// 价格报价：原价与实际成交价
+// 叠加顺序：促销价（多个促销取最低）→ 会员折扣 → 折扣券；秒杀价和组合价不再叠加会员折扣
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PriceQuote {
+    pub original_price: u32,
+    pub base_price: u32,                        // 促销、秒杀或组合包价格
+    pub vip_discount: u32,                      // 会员折扣减免的积分
+    pub coupon_discount: u32,                   // 折扣券减免的积分
+    pub coupon_id: Option<String>,
+    pub effective_price: u32,
+    pub source: PriceSource,
+}
+
+impl PriceQuote {
+    fn new(original_price: u32, base_price: u32, source: PriceSource) -> Self {
+        Self {
+            original_price,
+            base_price,
+            vip_discount: 0,
+            coupon_discount: 0,
+            coupon_id: None,
+            effective_price: base_price,
+            source,
+        }
+    }
+
+    // 计算商品报价，秒杀价低于促销叠加会员折扣后的价格时按秒杀价成交
+    pub fn for_item(item: &ShopItem, vip_discount_percent: u32, sales: &[ShopSale], deal: Option<&FlashDeal>, now: i64) -> Self {
+        let mut quote = Self::new(item.price_hp, item.price_hp, PriceSource::Regular);
+
+        for sale in sales.iter().filter(|sale| sale.covers(&item.id, now)) {
+            let sale_price = sale.adjustment.apply(item.price_hp);
+            if sale_price < quote.base_price {
+                quote.base_price = sale_price;
+                quote.source = PriceSource::Sale { sale_id: sale.id.clone(), name: sale.name.clone() };
+            }
+        }
+
+        let vip_price = item.get_discounted_price(quote.base_price, vip_discount_percent);
+        quote.vip_discount = quote.base_price - vip_price;
+        quote.effective_price = vip_price;
+
+        if let Some(deal) = deal.filter(|deal| deal.item_id == item.id && deal.is_live(now)) {
+            if deal.price_hp < quote.effective_price {
+                let source = PriceSource::FlashDeal { deal_id: deal.id.clone(), remaining: deal.remaining() };
+                quote = Self::new(item.price_hp, deal.price_hp, source);
+            }
+        }
+
+        quote
+    }
+
+    // 组合包按组合价成交，原价为各商品原价之和
+    pub fn for_bundle(bundle: &ShopBundle, items: &[ShopItem]) -> Self {
+        Self::new(
+            items.iter().map(|item| item.price_hp).sum(),
+            bundle.price_hp,
+            PriceSource::Bundle { bundle_id: bundle.id.clone() },
+        )
+    }
+
+    // 在最终价格上叠加折扣券，积分商城只接受折扣券
+    pub fn apply_coupon(&mut self, coupon: &Coupon) -> Result<(), &'static str> {
+        let CouponKind::Discount { rate } = coupon.kind else {
+            return Err("该卡券不能用于积分商城");
+        };
+
+        let percent_off = ((1.0 - rate) * 100.0).round() as u32;
+        let price = apply_percent_off(self.effective_price, percent_off);
+        self.coupon_discount = self.effective_price - price;
+        self.coupon_id = Some(coupon.id.clone());
+        self.effective_price = price;
+
+        Ok(())
+    }
+
+    pub fn flash_deal_id(&self) -> Option<&str> {
+        match &self.source {
+            PriceSource::FlashDeal { deal_id, .. } => Some(deal_id),
+            _ => None,
+        }
+    }
+
+    pub fn bundle_id(&self) -> Option<&str> {
+        match &self.source {
+            PriceSource::Bundle { bundle_id } => Some(bundle_id),
+            _ => None,
+        }
+    }
+}
+
+// 带报价的商品，用于商城列表展示原价与实际价格
+#[derive(Debug, Serialize, Clone)]
+pub struct PricedShopItem {
+    #[serde(flatten)]
+    pub item: ShopItem,
+    pub price: PriceQuote,
+}
+
+// 带报价的组合包
+#[derive(Debug, Serialize, Clone)]
+pub struct PricedShopBundle {
+    #[serde(flatten)]
+    pub bundle: ShopBundle,
+    pub items: Vec<ShopItem>,
+    pub price: PriceQuote,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ShopItemCategory, ShopItemType};
+
+    const NOW: i64 = 1_700_000_000;
+
+    fn item(price_hp: u32) -> ShopItem {
+        ShopItem::new(
+            "AI皮肤".to_string(),
+            String::new(),
+            ShopItemType::AIDecoration,
+            ShopItemCategory::Decoration,
+            price_hp,
+            None,
+            false,
+            None,
+            None,
+            true,
+            None,
+            None,
+            Some(true),
+        )
+    }
+
+    fn sale(item: &ShopItem, percent_off: u32) -> ShopSale {
+        ShopSale::new("限时八折".to_string(), vec![item.id.clone()], PriceAdjustment::Percentage { percent_off }, NOW - 60, NOW + 60)
+    }
+
+    fn deal(item: &ShopItem, price_hp: u32) -> FlashDeal {
+        FlashDeal::new(item.id.clone(), price_hp, 10, NOW - 60, NOW + 60)
+    }
+
+    #[test]
+    fn vip_discount_and_coupon_stack_on_sale_price() {
+        let item = item(1000);
+        let mut quote = PriceQuote::for_item(&item, 10, &[sale(&item, 20)], None, NOW);
+
+        assert_eq!(quote.base_price, 800);
+        assert_eq!(quote.vip_discount, 80);
+        assert_eq!(quote.effective_price, 720);
+
+        let coupon = Coupon::new("c1".to_string(), CouponKind::Discount { rate: 0.9 }, "u1".to_string(), NOW, NOW + 60, false);
+        quote.apply_coupon(&coupon).expect("discount coupon");
+
+        assert_eq!(quote.coupon_discount, 72);
+        assert_eq!(quote.effective_price, 648);
+    }
+
+    #[test]
+    fn flash_deal_price_is_not_vip_discounted() {
+        let item = item(1000);
+        let deal = deal(&item, 700);
+        let quote = PriceQuote::for_item(&item, 10, &[sale(&item, 20)], Some(&deal), NOW);
+
+        assert_eq!(quote.flash_deal_id(), Some(deal.id.as_str()));
+        assert_eq!(quote.vip_discount, 0);
+        assert_eq!(quote.effective_price, 700);
+    }
+
+    #[test]
+    fn flash_deal_above_vip_price_is_ignored() {
+        let item = item(1000);
+        let deal = deal(&item, 750);
+        let quote = PriceQuote::for_item(&item, 10, &[sale(&item, 20)], Some(&deal), NOW);
+
+        assert!(quote.flash_deal_id().is_none());
+        assert_eq!(quote.effective_price, 720);
+    }
+
+    #[test]
+    fn bundle_price_is_not_vip_discounted() {
+        let items = vec![item(1000), item(500)];
+        let bundle = ShopBundle::new("组合包".to_string(), String::new(), items.iter().map(|item| item.id.clone()).collect(), 1200);
+        let quote = PriceQuote::for_bundle(&bundle, &items);
+
+        assert_eq!(quote.original_price, 1500);
+        assert_eq!(quote.vip_discount, 0);
+        assert_eq!(quote.effective_price, 1200);
+    }
+}
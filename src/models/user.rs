@@ -3,6 +3,8 @@ use time;
 use uuid;
 use crate::db::Database;
 use crate::models::ai::AIType;
+use crate::models::LuckyBoost;
+use crate::models::checkin;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub enum VipLevel {
@@ -24,6 +26,39 @@ impl VipLevel {
         }
     }
 
+    pub fn from_name(name: &str) -> Option<VipLevel> {
+        match name {
+            "Free" => Some(VipLevel::Free),
+            "Pro" => Some(VipLevel::Pro),
+            "Premium" => Some(VipLevel::Premium),
+            "Ultimate" => Some(VipLevel::Ultimate),
+            "Team" => Some(VipLevel::Team),
+            _ => None,
+        }
+    }
+
+    // 等级高低，用于多个VIP时段重叠时取最高等级
+    pub fn rank(&self) -> u8 {
+        match self {
+            VipLevel::Free => 0,
+            VipLevel::Pro => 1,
+            VipLevel::Premium => 2,
+            VipLevel::Ultimate => 3,
+            VipLevel::Team => 4,
+        }
+    }
+
+    // 积分商城默认会员折扣（百分比），未在会员配置中设置时使用
+    pub fn default_shop_discount_percent(&self) -> u32 {
+        match self {
+            VipLevel::Free => 0,
+            VipLevel::Pro => 10,
+            VipLevel::Premium => 15,
+            VipLevel::Ultimate => 20,
+            VipLevel::Team => 30,
+        }
+    }
+
     pub async fn max_ai_partners(&self, db: &Database) -> Result<u32, surrealdb::Error> {
         let config = db.get_vip_config(self).await?;
         Ok(config.max_ai_partners)
@@ -51,6 +86,8 @@ pub struct VipLevelConfig {
     pub max_work_ai: u32,
     pub max_service_ai: u32,
     pub free_mapping_quota: u32,
+    #[serde(default)]
+    pub shop_discount_percent: Option<u32>,     // 积分商城会员折扣（百分比），如 10 即9折
 }
 
 impl VipLevelConfig {
@@ -75,8 +112,15 @@ impl VipLevelConfig {
             max_work_ai,
             max_service_ai,
             free_mapping_quota,
+            shop_discount_percent: None,
         }
     }
+
+    // 积分商城会员折扣，未配置时使用等级默认值
+    pub fn shop_discount(&self) -> u32 {
+        self.shop_discount_percent
+            .unwrap_or_else(|| self.level.default_shop_discount_percent())
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -136,6 +180,26 @@ pub struct User {
     pub last_checkin_date: Option<i64>,
     pub total_invites: u32,
     pub is_email_verified: bool,
+    #[serde(default)]
+    pub lucky_boost: Option<LuckyBoost>,    // 当前幸运卡加成
+    #[serde(default)]
+    pub lio_tickets: u32,                   // 积分商城兑换的LIO访问券
+    #[serde(default)]
+    pub earning_flagged_at: Option<i64>,    // 积分获取异常被标记的时间，标记期间奖励暂扣待审核
+    #[serde(default)]
+    pub earning_flag_reason: Option<String>,
+    #[serde(default)]
+    pub utc_offset_minutes: Option<i32>,    // 用户时区，用于计算签到日
+    #[serde(default)]
+    pub timezone_updated_at: Option<i64>,   // 上次修改时区的时间
+    #[serde(default)]
+    pub checkin_makeup_cards: u32,          // 积分商城兑换的补签卡
+    #[serde(default)]
+    pub leaderboard_opt_out: bool,          // 不在排行榜中展示
+    #[serde(default)]
+    pub gift_streak_freezes: u32,           // 积分商城兑换的连续送礼冻结卡
+    #[serde(default)]
+    pub last_gift_date: Option<i64>,        // 最近一次送礼时间，用于判断当天首次送礼
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -170,6 +234,16 @@ impl User {
             last_checkin_date: None,
             total_invites: 0,
             is_email_verified: false,
+            lucky_boost: None,
+            lio_tickets: 0,
+            earning_flagged_at: None,
+            earning_flag_reason: None,
+            utc_offset_minutes: None,
+            timezone_updated_at: None,
+            checkin_makeup_cards: 0,
+            leaderboard_opt_out: false,
+            gift_streak_freezes: 0,
+            last_gift_date: None,
             created_at: now,
             updated_at: now,
         }
@@ -180,6 +254,29 @@ impl User {
         Ok(self.ai_partner_count < max_ai_partners)
     }
 
+    // 用户时区相对UTC的偏移分钟数
+    pub fn utc_offset(&self) -> i32 {
+        self.utc_offset_minutes.unwrap_or(checkin::DEFAULT_UTC_OFFSET_MINUTES)
+    }
+
+    // 时间戳在用户时区下的日期
+    pub fn local_date(&self, timestamp: i64) -> time::Date {
+        checkin::local_date(timestamp, self.utc_offset())
+    }
+
+    // 时间戳所在的用户当地日期零点
+    pub fn local_day_start(&self, timestamp: i64) -> i64 {
+        checkin::local_midnight(self.local_date(timestamp), self.utc_offset())
+    }
+
+    // 当前仍有效的连续签到天数，今天和昨天都未签到时已中断
+    pub fn active_checkin_streak(&self, today: time::Date) -> u32 {
+        match self.last_checkin_date.map(|at| self.local_date(at)) {
+            Some(date) if date >= today - time::Duration::days(1) => self.daily_checkin_streak,
+            _ => 0,
+        }
+    }
+
     pub fn is_backend_user(&self) -> bool {
         !self.backend_roles.is_empty()
     }
@@ -256,4 +353,74 @@ impl User {
     pub fn use_free_mapping(&mut self) {
         self.free_mapping_used += 1;
     }
+
+    // 追加一段VIP时段：同等级已有未到期时段时顺延，否则从现在开始
+    pub fn append_vip_period(&mut self, level: &VipLevel, duration_secs: i64) -> VipStatus {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let start = self.vip_schedule
+            .iter()
+            .filter(|s| s.level == level.to_string() && s.end > now)
+            .map(|s| s.end)
+            .max()
+            .unwrap_or(now);
+
+        let status = VipStatus {
+            level: level.to_string().to_owned(),
+            start,
+            end: start + duration_secs,
+        };
+        self.vip_schedule.push(status.clone());
+        self.refresh_vip_level();
+        status
+    }
+
+    // 续费宽限期内保留VIP：在已有时段之后补一段截止到宽限期结束的时段
+    pub fn hold_vip_until(&mut self, level: &VipLevel, until: i64) {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let start = self.vip_schedule
+            .iter()
+            .filter(|s| s.level == level.to_string() && s.end > now)
+            .map(|s| s.end)
+            .max()
+            .unwrap_or(now);
+
+        if start < until {
+            self.vip_schedule.push(VipStatus {
+                level: level.to_string().to_owned(),
+                start,
+                end: until,
+            });
+        }
+        self.refresh_vip_level();
+    }
+
+    // 宽限期内续费成功后移除保留时段，避免新周期顺延到宽限期之后
+    pub fn release_vip_hold(&mut self, level: &VipLevel, until: i64) {
+        let level = level.to_string();
+        self.vip_schedule.retain(|s| !(s.level == level && s.end == until));
+        self.refresh_vip_level();
+    }
+
+    // 当前时间生效的最高VIP等级
+    pub fn current_scheduled_vip_level(&self) -> Option<VipLevel> {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        self.vip_schedule
+            .iter()
+            .filter(|s| s.start <= now && s.end > now)
+            .filter_map(|s| VipLevel::from_name(&s.level))
+            .max_by_key(|level| level.rank())
+    }
+
+    // 根据VIP时段表刷新当前VIP等级，没有生效时段时回退到Pro体验或Free
+    pub fn refresh_vip_level(&mut self) {
+        let now = time::OffsetDateTime::now_utc().unix_timestamp();
+        let has_pro_experience = self.pro_experience_expiration.is_some_and(|end| end > now);
+
+        self.vip_level = match self.current_scheduled_vip_level() {
+            Some(level) => level,
+            None if has_pro_experience => VipLevel::Pro,
+            None => VipLevel::Free,
+        };
+        self.updated_at = now;
+    }
 }
@@ -0,0 +1,158 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+use crate::models::VipLevel;
+
+// VIP套餐（可购买的会员产品）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VipPlan {
+    pub id: String,
+    pub name: String,
+    pub level: VipLevel,
+    pub period_days: u32,            // 每个计费周期的天数（月付30，年付365）
+    pub price: f32,                  // 每个周期的价格
+    pub currency: String,
+    pub is_active: bool,             // 是否上架
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl VipPlan {
+    pub fn new(name: String, level: VipLevel, period_days: u32, price: f32, currency: String) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            level,
+            period_days,
+            price,
+            currency,
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    // 校验套餐配置：价格和周期必须为正数，且不能是免费等级
+    pub fn validate(&self) -> Result<(), &'static str> {
+        if self.level == VipLevel::Free {
+            return Err("VIP套餐等级不能为Free");
+        }
+        if self.period_days == 0 {
+            return Err("计费周期天数必须大于0");
+        }
+        if !self.price.is_finite() || self.price <= 0.0 {
+            return Err("套餐价格必须大于0");
+        }
+        if self.name.trim().is_empty() {
+            return Err("套餐名称不能为空");
+        }
+        Ok(())
+    }
+
+    // 一个计费周期的秒数
+    pub fn period_secs(&self) -> i64 {
+        self.period_days as i64 * 24 * 60 * 60
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub enum SubscriptionStatus {
+    Active,     // 正常订阅中
+    PastDue,    // 续费扣款失败，处于宽限期
+    Cancelled,  // 已被新订阅替换
+    Expired,    // 已到期
+}
+
+// 用户的VIP订阅
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct VipSubscription {
+    pub id: String,
+    pub user_id: String,
+    pub plan_id: String,
+    pub level: VipLevel,
+    pub status: SubscriptionStatus,
+    pub auto_renew: bool,
+    pub current_period_start: i64,
+    pub current_period_end: i64,
+    pub grace_until: Option<i64>,        // 续费失败后的宽限期截止时间
+    pub renewal_attempts: u32,           // 当前周期续费失败次数
+    pub last_attempt_at: Option<i64>,    // 最近一次续费尝试时间
+    pub last_order_id: Option<String>,   // 最近一次付款订单
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+impl VipSubscription {
+    pub fn new(
+        user_id: String,
+        plan: &VipPlan,
+        auto_renew: bool,
+        period_start: i64,
+        period_end: i64,
+        order_id: String,
+    ) -> Self {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            plan_id: plan.id.clone(),
+            level: plan.level.clone(),
+            status: SubscriptionStatus::Active,
+            auto_renew,
+            current_period_start: period_start,
+            current_period_end: period_end,
+            grace_until: None,
+            renewal_attempts: 0,
+            last_attempt_at: None,
+            last_order_id: Some(order_id),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+
+    // 续费成功，进入下一个周期
+    pub fn renewed(&mut self, period_start: i64, period_end: i64, order_id: String) {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.current_period_start = period_start;
+        self.current_period_end = period_end;
+        self.status = SubscriptionStatus::Active;
+        self.grace_until = None;
+        self.renewal_attempts = 0;
+        self.last_attempt_at = Some(now);
+        self.last_order_id = Some(order_id);
+        self.updated_at = now;
+    }
+
+    // 续费失败，进入宽限期
+    pub fn renewal_failed(&mut self, grace_secs: i64) {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.status = SubscriptionStatus::PastDue;
+        if self.grace_until.is_none() {
+            self.grace_until = Some(self.current_period_end + grace_secs);
+        }
+        self.renewal_attempts += 1;
+        self.last_attempt_at = Some(now);
+        self.updated_at = now;
+    }
+
+    // 宽限期是否已结束
+    pub fn grace_expired(&self, now: i64) -> bool {
+        self.status == SubscriptionStatus::PastDue
+            && self.grace_until.is_some_and(|until| until <= now)
+    }
+
+    pub fn expire(&mut self) {
+        self.status = SubscriptionStatus::Expired;
+        self.auto_renew = false;
+        self.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+    }
+
+    pub fn cancel(&mut self) {
+        self.status = SubscriptionStatus::Cancelled;
+        self.auto_renew = false;
+        self.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+    }
+}
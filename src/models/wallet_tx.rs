@@ -1,15 +1,39 @@
 use serde::{Serialize, Deserialize};
 use time::OffsetDateTime;
 use uuid::Uuid;
+use crate::models::LuckyBoost;
+use crate::models::earning_rule::EarningSource;
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub enum TxType {
     Recharge,       // 充值
     GiftSend,       // 礼物赠送
-    GiftReceive,    // 礼物接收
+    GiftReceive,    // AI收到礼物时主人获得的积分
+    GiftShareReceive, // 用户间送礼，收礼用户获得的光币分成
     Reward,         // 奖励
     PointsEarned,   // 积分获取
     PointsSpent,    // 积分消费
+    PointsExpired,  // 积分过期
+}
+
+impl TxType {
+    // 会产生积分收入的交易类型，可配置有效期
+    pub fn hp_credit_types() -> [TxType; 3] {
+        [TxType::PointsEarned, TxType::Reward, TxType::GiftReceive]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TxType::Recharge => "Recharge",
+            TxType::GiftSend => "GiftSend",
+            TxType::GiftReceive => "GiftReceive",
+            TxType::GiftShareReceive => "GiftShareReceive",
+            TxType::Reward => "Reward",
+            TxType::PointsEarned => "PointsEarned",
+            TxType::PointsSpent => "PointsSpent",
+            TxType::PointsExpired => "PointsExpired",
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -28,6 +52,14 @@ pub struct WalletTx {
     pub timestamp: i64,
     pub related_entity_id: Option<String>,  // 相关实体ID (如礼物ID、积分商品ID等)
     pub remark: Option<String>,
+    #[serde(default)]
+    pub lucky_card_id: Option<String>,      // 加成所用的幸运卡
+    #[serde(default)]
+    pub boost_multiplier: Option<f32>,
+    #[serde(default)]
+    pub earning_source: Option<EarningSource>,  // 积分来源，用于统计获取上限
+    #[serde(default)]
+    pub base_amount: Option<u32>,           // 计入获取上限的积分，不含幸运卡加成
 }
 
 impl WalletTx {
@@ -48,6 +80,25 @@ impl WalletTx {
             timestamp: OffsetDateTime::now_utc().unix_timestamp(),
             related_entity_id,
             remark,
+            lucky_card_id: None,
+            boost_multiplier: None,
+            earning_source: None,
+            base_amount: None,
         }
     }
+
+    // 记录幸运卡加成
+    pub fn with_lucky_boost(mut self, boost: &LuckyBoost) -> Self {
+        self.lucky_card_id = Some(boost.card_id.clone());
+        self.boost_multiplier = Some(boost.multiplier);
+        self
+    }
+
+    // 记录积分来源
+    // 在加成前调用，当前金额即为计入获取上限的积分
+    pub fn with_earning_source(mut self, source: EarningSource) -> Self {
+        self.earning_source = Some(source);
+        self.base_amount = Some(self.amount);
+        self
+    }
 }
@@ -0,0 +1,68 @@
+use serde::{Serialize, Deserialize};
+use uuid::Uuid;
+use time::OffsetDateTime;
+
+use crate::models::NotificationType;
+
+// 心愿单条目：商品补货或促销时通知用户
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WishlistEntry {
+    pub id: String,
+    pub user_id: String,
+    pub item_id: String,
+    pub notify_email: bool,                 // 是否同时发送邮件
+    pub last_notified_at: Option<i64>,
+    pub created_at: i64,
+}
+
+impl WishlistEntry {
+    pub fn new(user_id: String, item_id: String, notify_email: bool) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            user_id,
+            item_id,
+            notify_email,
+            last_notified_at: None,
+            created_at: OffsetDateTime::now_utc().unix_timestamp(),
+        }
+    }
+}
+
+// 待发送的心愿单提醒：同一事件对同一条目只生成一条，冷却期内的提醒留待下次发送
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WishlistNotice {
+    pub id: String,                         // {事件}_{心愿单条目ID}
+    pub entry_id: String,
+    pub user_id: String,
+    pub item_id: String,
+    pub notify_email: bool,
+    pub notification_type: NotificationType,
+    pub title: String,
+    pub content: String,
+    pub created_at: i64,
+    pub sent_at: Option<i64>,
+}
+
+impl WishlistNotice {
+    pub fn new(
+        event_key: &str,
+        entry: &WishlistEntry,
+        notification_type: NotificationType,
+        title: String,
+        content: String,
+        now: i64,
+    ) -> Self {
+        Self {
+            id: format!("{}_{}", event_key, entry.id),
+            entry_id: entry.id.clone(),
+            user_id: entry.user_id.clone(),
+            item_id: entry.item_id.clone(),
+            notify_email: entry.notify_email,
+            notification_type,
+            title,
+            content,
+            created_at: now,
+            sent_at: None,
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use axum::{
+    extract::{State, Path},
+    http::StatusCode,
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::models::achievement::{Achievement, AchievementMetric, AchievementProgress, AchievementReward, Badge};
+use crate::services::AchievementService;
+use crate::middleware::admin::{require_admin, write_audit_log};
+use crate::middleware::auth::AuthenticatedUser;
+
+// 路由配置
+pub fn achievement_routes() -> Router<Database> {
+    Router::new()
+        .route("/list", get(get_achievements))
+        .route("/badges/:user_id", get(get_user_badges))
+}
+
+// 管理员路由配置
+pub fn admin_achievement_routes() -> Router<Database> {
+    Router::new()
+        .route("/list", get(admin_get_achievements))
+        .route("/create", post(admin_create_achievement))
+        .route("/update", post(admin_update_achievement))
+}
+
+// ==================== 请求和响应结构 ====================
+
+#[derive(Deserialize)]
+pub struct CreateAchievementRequest {
+    name: String,
+    description: String,
+    badge_url: Option<String>,
+    metric: AchievementMetric,
+    target: u32,
+    reward: Option<AchievementReward>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateAchievementRequest {
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    badge_url: Option<String>,
+    target: Option<u32>,                    // 只影响之后的进度计算，已完成的成就不受影响
+    reward: Option<AchievementReward>,
+    is_active: Option<bool>,
+}
+
+// ==================== 用户接口 ====================
+
+// 获取所有成就及自己的进度
+pub async fn get_achievements(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<AchievementProgress>>, StatusCode> {
+    let list = AchievementService::new(db)
+        .list_progress(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(list))
+}
+
+// 查看用户已获得的徽章
+pub async fn get_user_badges(
+    State(db): State<Database>,
+    _auth_user: AuthenticatedUser,
+    Path(user_id): Path<String>,
+) -> Result<Json<Vec<Badge>>, StatusCode> {
+    let badges = AchievementService::new(db)
+        .get_badges(&user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(badges))
+}
+
+// ==================== 管理员接口 ====================
+
+// 获取所有成就
+pub async fn admin_get_achievements(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<Achievement>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let achievements = db.get_achievements()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(achievements))
+}
+
+// 创建成就
+pub async fn admin_create_achievement(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateAchievementRequest>,
+) -> Result<Json<Achievement>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut achievement = Achievement::new(
+        payload.name,
+        payload.description,
+        payload.metric,
+        payload.target,
+        payload.reward.unwrap_or_default(),
+    );
+    achievement.badge_url = payload.badge_url;
+
+    achievement.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    AchievementService::new(db.clone())
+        .validate_reward(&achievement)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    db.create_achievement(&achievement)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Created achievement: {} ({})", achievement.name, achievement.id)).await?;
+
+    Ok(Json(achievement))
+}
+
+// 更新成就
+pub async fn admin_update_achievement(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateAchievementRequest>,
+) -> Result<Json<Achievement>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut achievement = db.get_achievement(&payload.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Some(name) = payload.name {
+        achievement.name = name;
+    }
+
+    if let Some(description) = payload.description {
+        achievement.description = description;
+    }
+
+    if payload.badge_url.is_some() {
+        achievement.badge_url = payload.badge_url;
+    }
+
+    if let Some(target) = payload.target {
+        achievement.target = target;
+    }
+
+    if let Some(reward) = payload.reward {
+        achievement.reward = reward;
+    }
+
+    if let Some(is_active) = payload.is_active {
+        achievement.is_active = is_active;
+    }
+
+    achievement.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    AchievementService::new(db.clone())
+        .validate_reward(&achievement)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    achievement.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.update_achievement(&achievement)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated achievement: {} ({})", achievement.name, achievement.id)).await?;
+
+    Ok(Json(achievement))
+}
+
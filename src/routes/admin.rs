@@ -110,7 +110,7 @@ pub async fn view_audit_logs(
 // ==================== 礼物管理接口 ====================
 
 use crate::models::{Gift, GiftEffectType};
-use crate::models::gift::{GiftCategory, GiftFeedbackTemplate};
+use crate::models::gift::{GiftAnimation, GiftCategory, GiftComboRule, GiftComboTier, GiftFeedbackTemplate};
 use crate::services::PointsService;
 
 #[derive(Serialize)]
@@ -130,6 +130,7 @@ pub struct CreateGiftPayload {
     is_limited: bool,
     available_until: Option<i64>,
     boost_value: Option<u32>,
+    animation: Option<GiftAnimation>,
 }
 
 #[derive(Deserialize)]
@@ -145,6 +146,7 @@ pub struct UpdateGiftPayload {
     is_limited: bool,
     available_until: Option<i64>,
     boost_value: Option<u32>,
+    animation: Option<GiftAnimation>,
     is_active: bool,
 }
 
@@ -197,7 +199,7 @@ pub async fn admin_create_gift(
     }
 
     // 创建礼物
-    let gift = Gift::new(
+    let mut gift = Gift::new(
         payload.name,
         payload.description,
         payload.price_lc,
@@ -209,6 +211,7 @@ pub async fn admin_create_gift(
         payload.available_until,
         payload.boost_value,
     );
+    gift.animation = payload.animation;
 
     // 保存礼物
     let points_service = PointsService::new(db.clone());
@@ -273,6 +276,7 @@ pub async fn admin_update_gift(
         is_limited: payload.is_limited,
         available_until: payload.available_until,
         boost_value: payload.boost_value,
+        animation: payload.animation,
         is_active: payload.is_active,
     };
 
@@ -440,4 +444,60 @@ pub async fn admin_get_feedback_templates(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(Json(FeedbackTemplateListResponse { templates }))
-}
\ No newline at end of file
+}
+// ==================== 礼物连击规则管理 ====================
+
+use crate::middleware::admin::{require_admin, write_audit_log};
+
+#[derive(Deserialize)]
+pub struct UpdateGiftComboRulePayload {
+    category: GiftCategory,
+    max_quantity: u32,
+    tiers: Vec<GiftComboTier>,
+}
+
+#[derive(Serialize)]
+pub struct GiftComboRuleListResponse {
+    rules: Vec<GiftComboRule>,
+}
+
+// 获取各分类的礼物连击规则
+#[axum::debug_handler]
+pub async fn admin_get_gift_combo_rules(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<GiftComboRuleListResponse>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let points_service = PointsService::new(db);
+    let rules = points_service.get_gift_combo_rules()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(GiftComboRuleListResponse { rules }))
+}
+
+// 更新礼物分类的连击规则
+#[axum::debug_handler]
+pub async fn admin_update_gift_combo_rule(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateGiftComboRulePayload>,
+) -> Result<Json<GiftComboRule>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut rule = GiftComboRule::default_for(payload.category);
+    rule.max_quantity = payload.max_quantity;
+    rule.tiers = payload.tiers;
+    rule.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let points_service = PointsService::new(db.clone());
+    points_service.save_gift_combo_rule(&rule)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 记录审计日志
+    write_audit_log(&db, admin.id, format!("Updated gift combo rule for category: {:?}", rule.category)).await?;
+
+    Ok(Json(rule))
+}
@@ -10,6 +10,8 @@ use crate::{
     models::{AI, AIType, User},
     db::Database,
     middleware::auth::AuthenticatedUser,
+    models::achievement::AchievementMetric,
+    services::AchievementService,
 };
 
 #[derive(Deserialize)]
@@ -60,6 +62,8 @@ pub async fn initiate_ai(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    AchievementService::fire(db, auth_user.user_id, AchievementMetric::AisAwakened);
+
     Ok(Json(InitiateAIResponse { ai_id: ai.id }))
 }
 
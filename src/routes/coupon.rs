@@ -1,28 +1,59 @@
 
 use axum::{
     Json,
-    http::StatusCode,
-    extract::State,
+    http::{StatusCode, header},
+    extract::{State, Path, Query},
+    response::IntoResponse,
+    routing::{get, post},
+    Router,
 };
+use serde::{Deserialize, Serialize};
 
-use crate::models::coupon::{Coupon, RedeemCouponPayload, TransferCouponPayload, IssueCouponPayload};
+use crate::models::coupon::{
+    Coupon, CouponKind, CouponStatus, CouponTemplate, CouponCampaign, CampaignAudience,
+    RedeemCouponPayload, TransferCouponPayload, IssueCouponPayload,
+};
+use crate::models::coupon_code::{CouponCodeBatch, CouponCodeBatchStats};
+use crate::models::coupon_transfer::CouponTransfer;
+use crate::models::VipLevel;
 use crate::db::Database;
+use crate::services::CouponService;
+use crate::services::coupon_service::DEFAULT_CODE_PREFIX;
 use std::sync::Arc;
+use crate::middleware::admin::{require_admin, write_audit_log};
 use crate::middleware::auth::AuthenticatedUser;
-use crate::models::user::{VipStatus, BackendUserRole};
-use time::OffsetDateTime;
+use crate::routes::nullable;
+use crate::models::user::BackendUserRole;
 
-// 获取当前时间戳的辅助函数
-fn now() -> i64 {
-    OffsetDateTime::now_utc().unix_timestamp()
+#[derive(Deserialize)]
+pub struct MyCouponsQuery {
+    pub status: Option<CouponStatus>,   // 默认只返回可用卡券
+    pub coupon_type: Option<String>,    // Experience / Discount / Cash
+    pub sort: Option<String>,           // expires_asc（默认）/ expires_desc / newest
 }
 
 pub async fn get_my_coupons(
     State(db): State<Database>,
-    auth_user: AuthenticatedUser
+    auth_user: AuthenticatedUser,
+    Query(query): Query<MyCouponsQuery>,
 ) -> Result<Json<Vec<Coupon>>, StatusCode> {
-    // 查询用户的未过期卡券
-    let coupons = db.get_user_coupons(&auth_user.user_id)
+    let status = query.status.unwrap_or(CouponStatus::Active);
+
+    let coupon_type = query.coupon_type.as_deref();
+    if let Some(coupon_type) = coupon_type {
+        if !["Experience", "Discount", "Cash"].contains(&coupon_type) {
+            return Err(StatusCode::BAD_REQUEST);
+        }
+    }
+
+    let order_by = match query.sort.as_deref() {
+        None | Some("expires_asc") => "expires_at ASC",
+        Some("expires_desc") => "expires_at DESC",
+        Some("newest") => "issued_at DESC",
+        Some(_) => return Err(StatusCode::BAD_REQUEST),
+    };
+
+    let coupons = db.get_user_coupons(&auth_user.user_id, &status, coupon_type, order_by)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(Json(coupons))
@@ -39,7 +70,7 @@ pub async fn redeem_coupon(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    if coupon.owner_id != auth_user.user_id || coupon.status != "active" {
+    if coupon.owner_id != auth_user.user_id || !coupon.is_usable() {
         return Err(StatusCode::FORBIDDEN);
     }
 
@@ -47,7 +78,7 @@ pub async fn redeem_coupon(
     apply_coupon_logic(&mut coupon, &auth_user, db.clone()).await?;
 
     // 更新卡券状态
-    coupon.status = "used".to_string();
+    coupon.mark_as_used();
     db.update_coupon(&coupon)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
@@ -55,27 +86,86 @@ pub async fn redeem_coupon(
     Ok(StatusCode::OK)
 }
 
+// 发起卡券转赠，需对方接受后才会变更归属
 pub async fn transfer_coupon(
     State(db): State<Database>,
     auth_user: AuthenticatedUser,
     Json(payload): Json<TransferCouponPayload>
-) -> Result<StatusCode, StatusCode> {
-    // 转赠卡券
-    let mut coupon = db.get_coupon(&payload.coupon_id)
+) -> Result<Json<CouponTransfer>, StatusCode> {
+    let coupon_service = CouponService::new(db);
+
+    let transfer = coupon_service.offer_transfer(
+        &auth_user.user_id,
+        &payload.new_owner_id,
+        &payload.coupon_id,
+        payload.message,
+    )
         .await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-        .ok_or(StatusCode::NOT_FOUND)?;
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
 
-    if coupon.owner_id != auth_user.user_id || !coupon.is_transferable {
-        return Err(StatusCode::FORBIDDEN);
-    }
+    Ok(Json(transfer))
+}
 
-    coupon.owner_id = payload.new_owner_id.clone();
-    db.update_coupon(&coupon)
+// 接受卡券转赠
+pub async fn accept_coupon_transfer(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(transfer_id): Path<String>,
+) -> Result<Json<CouponTransfer>, StatusCode> {
+    let coupon_service = CouponService::new(db);
+
+    let transfer = coupon_service.respond_transfer(&auth_user.user_id, &transfer_id, true)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(transfer))
+}
+
+// 拒绝卡券转赠，卡券退回原主人
+pub async fn decline_coupon_transfer(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(transfer_id): Path<String>,
+) -> Result<Json<CouponTransfer>, StatusCode> {
+    let coupon_service = CouponService::new(db);
+
+    let transfer = coupon_service.respond_transfer(&auth_user.user_id, &transfer_id, false)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(transfer))
+}
+
+// 我发出和收到的转赠记录
+pub async fn get_my_coupon_transfers(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<CouponTransfer>>, StatusCode> {
+    let transfers = db.get_user_coupon_transfers(&auth_user.user_id, 100)
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
-    Ok(StatusCode::OK)
+    Ok(Json(transfers))
+}
+
+#[derive(Deserialize)]
+pub struct ClaimCodePayload {
+    pub code: String,
+}
+
+// 使用兑换码领取卡券
+pub async fn claim_coupon_code(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<ClaimCodePayload>
+) -> Result<Json<Coupon>, StatusCode> {
+    let coupon_service = CouponService::new(db);
+
+    let coupon = coupon_service.claim_code(&auth_user.user_id, &payload.code)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    Ok(Json(coupon))
 }
 
 pub async fn issue_coupon_admin(
@@ -97,10 +187,7 @@ pub async fn issue_coupon_admin(
     for coupon_data in payload.coupons {
         let coupon = Coupon::new(
             coupon_data.id,
-            coupon_data.coupon_type,
-            coupon_data.sub_type,
-            coupon_data.value,
-            coupon_data.duration_days,
+            coupon_data.kind,
             coupon_data.owner_id,
             coupon_data.issued_at,
             coupon_data.expires_at,
@@ -115,32 +202,350 @@ pub async fn issue_coupon_admin(
 }
 
 pub async fn apply_coupon_logic(coupon: &mut Coupon, auth_user: &AuthenticatedUser, db: Database) -> Result<(), StatusCode> {
-    // 示例：根据卡券类型应用不同的逻辑
-    match coupon.coupon_type.as_str() {
-        "experience" => {
+    // 根据卡券类型应用不同的逻辑
+    match &coupon.kind {
+        CouponKind::Experience { level, days } => {
             // 应用体验券逻辑
             let mut user = db.get_user_by_id(&auth_user.user_id)
                 .await
                 .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
                 .ok_or(StatusCode::NOT_FOUND)?;
 
-            user.vip_schedule.push(VipStatus {
-                level: coupon.sub_type.clone(),
-                start: now(),
-                end: now() + coupon.duration_days.unwrap_or(0) as i64,
-            });
+            user.append_vip_period(level, *days as i64 * 24 * 60 * 60);
 
             db.update_user(&user)
                 .await
                 .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
         }
-        "discount" => {
-            // 应用折扣券逻辑
-        }
-        "cash" => {
-            // 应用现金券逻辑
+        CouponKind::Discount { .. } | CouponKind::Cash { .. } => {
+            // 折扣券和现金券只能在付款时使用（见 CheckoutService）
+            return Err(StatusCode::BAD_REQUEST);
         }
-        _ => return Err(StatusCode::BAD_REQUEST),
     }
     Ok(())
 }
+
+// ==================== 卡券模板与发放活动（管理员） ====================
+
+// 管理员路由配置
+pub fn admin_coupon_routes() -> Router<Database> {
+    Router::new()
+        .route("/templates", get(admin_get_coupon_templates))
+        .route("/template/create", post(admin_create_coupon_template))
+        .route("/template/update", post(admin_update_coupon_template))
+        .route("/template/delete/:id", post(admin_delete_coupon_template))
+        .route("/campaigns", get(admin_get_coupon_campaigns))
+        .route("/campaign/create", post(admin_create_coupon_campaign))
+        .route("/campaign/:id", get(admin_get_coupon_campaign))
+        .route("/code-batches", get(admin_get_code_batches))
+        .route("/code-batch/create", post(admin_create_code_batch))
+        .route("/code-batch/:id/stats", get(admin_get_code_batch_stats))
+        .route("/code-batch/:id/export", get(admin_export_code_batch))
+}
+
+#[derive(Deserialize)]
+pub struct CreateCouponTemplateRequest {
+    name: String,
+    description: String,
+    kind: CouponKind,
+    duration_days: Option<u32>,
+    is_transferable: bool,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCouponTemplateRequest {
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    kind: Option<CouponKind>,
+    #[serde(default, deserialize_with = "nullable")]
+    duration_days: Option<Option<u32>>,
+    is_transferable: Option<bool>,
+    is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateCouponCampaignRequest {
+    name: String,
+    template_id: String,
+    audience: CampaignAudience,
+}
+
+#[derive(Deserialize)]
+pub struct CreateCodeBatchRequest {
+    name: String,
+    template_id: String,
+    count: u32,
+    max_redemptions: u32,
+    per_user_limit: u32,
+    valid_from: i64,
+    valid_until: i64,
+    required_vip_level: Option<VipLevel>,
+    prefix: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct CodeBatchResponse {
+    batch: CouponCodeBatch,
+    codes: Vec<String>,
+}
+
+// 获取所有卡券模板
+pub async fn admin_get_coupon_templates(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<CouponTemplate>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let templates = db.get_coupon_templates()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(templates))
+}
+
+// 创建卡券模板
+pub async fn admin_create_coupon_template(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateCouponTemplateRequest>,
+) -> Result<Json<CouponTemplate>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    CouponService::validate_kind(&payload.kind).map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let template = CouponTemplate::new(
+        payload.name,
+        payload.description,
+        payload.kind,
+        payload.duration_days,
+        payload.is_transferable,
+    );
+
+    db.create_coupon_template(&template)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Created coupon template: {}", template.name)).await?;
+
+    Ok(Json(template))
+}
+
+// 更新卡券模板
+pub async fn admin_update_coupon_template(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateCouponTemplateRequest>,
+) -> Result<Json<CouponTemplate>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut template = db.get_coupon_template(&payload.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Some(name) = payload.name {
+        template.name = name;
+    }
+
+    if let Some(description) = payload.description {
+        template.description = description;
+    }
+
+    if let Some(kind) = payload.kind {
+        CouponService::validate_kind(&kind).map_err(|_| StatusCode::BAD_REQUEST)?;
+        template.kind = kind;
+    }
+
+    if let Some(duration_days) = payload.duration_days {
+        template.duration_days = duration_days;
+    }
+
+    if let Some(is_transferable) = payload.is_transferable {
+        template.is_transferable = is_transferable;
+    }
+
+    if let Some(is_active) = payload.is_active {
+        template.is_active = is_active;
+    }
+
+    db.update_coupon_template(&template)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated coupon template: {}", template.name)).await?;
+
+    Ok(Json(template))
+}
+
+// 删除卡券模板（已发放的卡券不受影响）
+pub async fn admin_delete_coupon_template(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let template = db.get_coupon_template(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    db.delete_coupon_template(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Deleted coupon template: {}", template.name)).await?;
+
+    Ok(StatusCode::OK)
+}
+
+// 获取所有发放活动
+pub async fn admin_get_coupon_campaigns(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<CouponCampaign>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let campaigns = db.get_coupon_campaigns()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(campaigns))
+}
+
+// 获取发放活动进度
+pub async fn admin_get_coupon_campaign(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<CouponCampaign>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let campaign = db.get_coupon_campaign(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(campaign))
+}
+
+// 创建发放活动，卡券在后台批量发放
+pub async fn admin_create_coupon_campaign(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateCouponCampaignRequest>,
+) -> Result<Json<CouponCampaign>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let coupon_service = CouponService::new(db.clone());
+    let campaign = coupon_service
+        .start_campaign(&admin.id, payload.name, &payload.template_id, payload.audience)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    write_audit_log(&db, admin.id, format!("Started coupon campaign: {}", campaign.name)).await?;
+
+    Ok(Json(campaign))
+}
+
+// 获取所有兑换码批次
+pub async fn admin_get_code_batches(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<CouponCodeBatch>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let batches = db.get_coupon_code_batches()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(batches))
+}
+
+// 批量生成兑换码
+pub async fn admin_create_code_batch(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateCodeBatchRequest>,
+) -> Result<Json<CodeBatchResponse>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    // 前缀只允许大写字母和数字
+    let prefix = payload.prefix.unwrap_or_else(|| DEFAULT_CODE_PREFIX.to_string()).to_uppercase();
+    if prefix.is_empty() || prefix.len() > 16 || !prefix.chars().all(|c| c.is_ascii_alphanumeric()) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let batch = CouponCodeBatch::new(
+        payload.name,
+        payload.template_id,
+        payload.count,
+        payload.max_redemptions,
+        payload.per_user_limit,
+        payload.valid_from,
+        payload.valid_until,
+        payload.required_vip_level,
+        admin.id.clone(),
+    );
+
+    let coupon_service = CouponService::new(db.clone());
+    let (batch, codes) = coupon_service.generate_code_batch(batch, &prefix)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    write_audit_log(&db, admin.id, format!("Generated {} coupon codes in batch: {}", codes.len(), batch.name)).await?;
+
+    Ok(Json(CodeBatchResponse {
+        batch,
+        codes: codes.into_iter().map(|code| code.code).collect(),
+    }))
+}
+
+// 获取兑换码批次统计
+pub async fn admin_get_code_batch_stats(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<CouponCodeBatchStats>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let batch = db.get_coupon_code_batch(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let coupon_service = CouponService::new(db);
+    let stats = coupon_service.get_batch_stats(&batch)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(stats))
+}
+
+// 导出兑换码批次为CSV
+pub async fn admin_export_code_batch(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<impl IntoResponse, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let batch = db.get_coupon_code_batch(&id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    let coupon_service = CouponService::new(db);
+    let csv = coupon_service.export_batch_csv(&batch)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (header::CONTENT_DISPOSITION, format!("attachment; filename=\"coupon_codes_{}.csv\"", batch.id)),
+        ],
+        csv,
+    ))
+}
@@ -0,0 +1,281 @@
+use axum::{
+    extract::{State, Path},
+    http::StatusCode,
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::models::drop_table::{DropTable, DropEntry, DropOverride, DropOdds, DropLog};
+use crate::models::lucky_trigger::{LuckyTrigger, LuckyEventType};
+use crate::services::{DropService, LuckyEventService};
+use crate::middleware::admin::{require_admin, write_audit_log};
+use crate::routes::nullable;
+use crate::middleware::auth::AuthenticatedUser;
+
+// 路由配置
+pub fn drop_routes() -> Router<Database> {
+    Router::new()
+        .route("/odds/:key", get(get_drop_odds))
+}
+
+// 管理员路由配置
+pub fn admin_drop_routes() -> Router<Database> {
+    Router::new()
+        .route("/tables", get(admin_get_drop_tables))
+        .route("/table/create", post(admin_create_drop_table))
+        .route("/table/update", post(admin_update_drop_table))
+        .route("/table/:id/logs", get(admin_get_drop_logs))
+        .route("/triggers", get(admin_get_lucky_triggers))
+        .route("/trigger/update", post(admin_update_lucky_trigger))
+}
+
+// ==================== 请求和响应结构 ====================
+
+#[derive(Serialize)]
+pub struct DropOddsResponse {
+    table_id: String,
+    name: String,
+    pity_threshold: Option<u32>,
+    odds: Vec<DropOdds>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateDropTableRequest {
+    key: String,
+    name: String,
+    entries: Vec<DropEntry>,
+    vip_overrides: Option<Vec<DropOverride>>,
+    pity_threshold: Option<u32>,
+    priority: Option<i32>,
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateDropTableRequest {
+    id: String,
+    name: Option<String>,
+    entries: Option<Vec<DropEntry>>,
+    vip_overrides: Option<Vec<DropOverride>>,
+    #[serde(default, deserialize_with = "nullable")]
+    pity_threshold: Option<Option<u32>>,           // 传 null 取消保底
+    priority: Option<i32>,
+    #[serde(default, deserialize_with = "nullable")]
+    starts_at: Option<Option<i64>>,                // 传 null 清除开始时间
+    #[serde(default, deserialize_with = "nullable")]
+    ends_at: Option<Option<i64>>,                  // 传 null 清除结束时间
+    is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateLuckyTriggerRequest {
+    event_type: LuckyEventType,
+    is_enabled: Option<bool>,
+    drop_key: Option<String>,
+    thresholds: Option<Vec<u32>>,
+    message_template: Option<String>,
+}
+
+// ==================== 用户接口 ====================
+
+// 获取场景当前的公示概率
+pub async fn get_drop_odds(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(key): Path<String>,
+) -> Result<Json<DropOddsResponse>, StatusCode> {
+    let user = db.get_user_by_id(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    let table = DropService::new(db)
+        .get_live_table(&key)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(DropOddsResponse {
+        odds: table.odds(&user.vip_level),
+        table_id: table.id,
+        name: table.name,
+        pity_threshold: table.pity_threshold,
+    }))
+}
+
+// ==================== 管理员接口 ====================
+
+// 获取所有掉落表
+pub async fn admin_get_drop_tables(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<DropTable>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let tables = db.get_drop_tables()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(tables))
+}
+
+// 创建掉落表
+pub async fn admin_create_drop_table(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateDropTableRequest>,
+) -> Result<Json<DropTable>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut table = DropTable::new(payload.key, payload.name, payload.entries);
+    table.vip_overrides = payload.vip_overrides.unwrap_or_default();
+    table.pity_threshold = payload.pity_threshold;
+    table.priority = payload.priority.unwrap_or(0);
+    table.starts_at = payload.starts_at;
+    table.ends_at = payload.ends_at;
+
+    table.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    db.create_drop_table(&table)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Created drop table: {} ({})", table.name, table.key)).await?;
+
+    Ok(Json(table))
+}
+
+// 更新掉落表
+pub async fn admin_update_drop_table(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateDropTableRequest>,
+) -> Result<Json<DropTable>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut table = db.get_drop_table(&payload.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Some(name) = payload.name {
+        table.name = name;
+    }
+
+    if let Some(entries) = payload.entries {
+        table.entries = entries;
+    }
+
+    if let Some(vip_overrides) = payload.vip_overrides {
+        table.vip_overrides = vip_overrides;
+    }
+
+    if let Some(pity_threshold) = payload.pity_threshold {
+        table.pity_threshold = pity_threshold;
+    }
+
+    if let Some(priority) = payload.priority {
+        table.priority = priority;
+    }
+
+    if let Some(starts_at) = payload.starts_at {
+        table.starts_at = starts_at;
+    }
+
+    if let Some(ends_at) = payload.ends_at {
+        table.ends_at = ends_at;
+    }
+
+    if let Some(is_active) = payload.is_active {
+        table.is_active = is_active;
+    }
+
+    table.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    table.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.update_drop_table(&table)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated drop table: {} ({})", table.name, table.key)).await?;
+
+    Ok(Json(table))
+}
+
+// 获取掉落表的抽取记录
+pub async fn admin_get_drop_logs(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(table_id): Path<String>,
+) -> Result<Json<Vec<DropLog>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let logs = db.get_drop_logs(&table_id, 500)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(logs))
+}
+
+// 获取所有事件的触发配置（含未配置时的默认规则）
+pub async fn admin_get_lucky_triggers(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<LuckyTrigger>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let service = LuckyEventService::new(db);
+    let mut triggers = Vec::new();
+    for event_type in LuckyEventType::all() {
+        let trigger = service.get_trigger(event_type)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        triggers.push(trigger);
+    }
+
+    Ok(Json(triggers))
+}
+
+// 更新事件的触发配置
+pub async fn admin_update_lucky_trigger(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateLuckyTriggerRequest>,
+) -> Result<Json<LuckyTrigger>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut trigger = LuckyEventService::new(db.clone())
+        .get_trigger(payload.event_type)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if let Some(is_enabled) = payload.is_enabled {
+        trigger.is_enabled = is_enabled;
+    }
+
+    if let Some(drop_key) = payload.drop_key {
+        trigger.drop_key = drop_key;
+    }
+
+    if let Some(thresholds) = payload.thresholds {
+        trigger.thresholds = thresholds;
+    }
+
+    if let Some(message_template) = payload.message_template {
+        trigger.message_template = message_template;
+    }
+
+    trigger.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.save_lucky_trigger(&trigger)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated lucky card trigger: {}", trigger.id)).await?;
+
+    Ok(Json(trigger))
+}
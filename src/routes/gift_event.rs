@@ -0,0 +1,212 @@
+use axum::{
+    extract::{State, Path, Query},
+    http::StatusCode,
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::models::gift_event::{GiftEvent, GiftEventCatalogue, GiftEventResults, GiftEventReward};
+use crate::services::GiftEventService;
+use crate::middleware::admin::{require_admin, write_audit_log};
+use crate::middleware::auth::AuthenticatedUser;
+
+// 每页最多返回的名次
+const MAX_PAGE_SIZE: usize = 100;
+
+// 路由配置
+pub fn gift_event_routes() -> Router<Database> {
+    Router::new()
+        .route("/active", get(get_active_gift_events))
+        .route("/:id/results", get(get_gift_event_results))
+}
+
+// 管理员路由配置
+pub fn admin_gift_event_routes() -> Router<Database> {
+    Router::new()
+        .route("/list", get(admin_get_gift_events))
+        .route("/create", post(admin_create_gift_event))
+        .route("/update", post(admin_update_gift_event))
+}
+
+// ==================== 请求和响应结构 ====================
+
+#[derive(Deserialize)]
+pub struct GiftEventResultsQuery {
+    page: Option<usize>,
+    limit: Option<usize>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateGiftEventRequest {
+    name: String,
+    description: Option<String>,
+    banner_url: Option<String>,
+    gift_ids: Vec<String>,
+    starts_at: i64,
+    ends_at: i64,
+    rewards: Option<Vec<GiftEventReward>>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateGiftEventRequest {
+    id: String,
+    name: Option<String>,
+    description: Option<String>,
+    banner_url: Option<String>,
+    gift_ids: Option<Vec<String>>,
+    starts_at: Option<i64>,
+    ends_at: Option<i64>,
+    rewards: Option<Vec<GiftEventReward>>,
+    is_active: Option<bool>,
+}
+
+// ==================== 用户接口 ====================
+
+// 获取进行中的活动及其主题礼物
+pub async fn get_active_gift_events(
+    State(db): State<Database>,
+    _auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<GiftEventCatalogue>>, StatusCode> {
+    let catalogues = GiftEventService::new(db)
+        .get_active_catalogues()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(catalogues))
+}
+
+// 获取活动送礼榜和自己的名次，活动结束后首次查询时发放排名奖励
+pub async fn get_gift_event_results(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(event_id): Path<String>,
+    Query(query): Query<GiftEventResultsQuery>,
+) -> Result<Json<GiftEventResults>, StatusCode> {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20).min(MAX_PAGE_SIZE);
+
+    GiftEventService::new(db)
+        .get_results(&event_id, &auth_user.user_id, page, limit)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::NOT_FOUND)
+}
+
+// ==================== 管理员接口 ====================
+
+// 获取所有礼物活动
+pub async fn admin_get_gift_events(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<GiftEvent>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let events = db.get_gift_events()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(events))
+}
+
+// 创建礼物活动
+pub async fn admin_create_gift_event(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateGiftEventRequest>,
+) -> Result<Json<GiftEvent>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut event = GiftEvent::new(
+        payload.name,
+        payload.gift_ids,
+        payload.starts_at,
+        payload.ends_at,
+        payload.rewards.unwrap_or_default(),
+    );
+    event.description = payload.description;
+    event.banner_url = payload.banner_url;
+
+    event.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    GiftEventService::new(db.clone())
+        .validate_references(&event)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    db.create_gift_event(&event)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Created gift event: {} ({})", event.name, event.id)).await?;
+
+    Ok(Json(event))
+}
+
+// 更新礼物活动，奖励发放后不能再修改
+pub async fn admin_update_gift_event(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateGiftEventRequest>,
+) -> Result<Json<GiftEvent>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut event = db.get_gift_event(&payload.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if event.rewards_distributed_at.is_some() {
+        return Err(StatusCode::CONFLICT);
+    }
+
+    if let Some(name) = payload.name {
+        event.name = name;
+    }
+
+    if payload.description.is_some() {
+        event.description = payload.description;
+    }
+
+    if payload.banner_url.is_some() {
+        event.banner_url = payload.banner_url;
+    }
+
+    if let Some(gift_ids) = payload.gift_ids {
+        event.gift_ids = gift_ids;
+    }
+
+    if let Some(starts_at) = payload.starts_at {
+        event.starts_at = starts_at;
+    }
+
+    if let Some(ends_at) = payload.ends_at {
+        event.ends_at = ends_at;
+    }
+
+    if let Some(rewards) = payload.rewards {
+        event.rewards = rewards;
+    }
+
+    if let Some(is_active) = payload.is_active {
+        event.is_active = is_active;
+    }
+
+    event.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    GiftEventService::new(db.clone())
+        .validate_references(&event)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    event.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.update_gift_event(&event)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated gift event: {} ({})", event.name, event.id)).await?;
+
+    Ok(Json(event))
+}
+
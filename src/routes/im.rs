@@ -10,7 +10,7 @@ use uuid::Uuid;
 use crate::db::Database;
 use crate::middleware::auth::AuthenticatedUser;
 use crate::models::chat::{Message, MessageType, Group, GroupUser, GroupApply, Friend, ChatFile};
-use crate::services::FileStorage;
+use crate::services::{FileStorage, LuckyEventService};
 use std::sync::Arc;
 
 // 请求和响应数据结构
@@ -111,7 +111,10 @@ async fn send_message(
     
     // 保存消息到数据库
     match db.create_message(&message).await {
-        Ok(_) => Ok(Json(message)),
+        Ok(_) => {
+            LuckyEventService::on_chat_message(db.clone(), &message);
+            Ok(Json(message))
+        },
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -10,6 +10,8 @@ use crate::{
     middleware::auth::AuthenticatedUser,
     db::Database,
     models::{Invite, User, VipLevel},
+    models::achievement::AchievementMetric,
+    services::AchievementService,
 };
 
 #[derive(Serialize)]
@@ -86,5 +88,7 @@ pub async fn use_invite(
         .await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
+    AchievementService::fire(db, invite.creator_id, AchievementMetric::InvitesUsed);
+
     Ok(StatusCode::OK)
 }
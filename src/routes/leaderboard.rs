@@ -0,0 +1,49 @@
+use axum::{
+    extract::{State, Query},
+    http::StatusCode,
+    Json,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+
+use crate::db::Database;
+use crate::models::leaderboard::{LeaderboardKind, LeaderboardPage, LeaderboardPeriod};
+use crate::services::LeaderboardService;
+use crate::middleware::auth::AuthenticatedUser;
+
+// 每页最多返回的名次
+const MAX_PAGE_SIZE: usize = 100;
+
+// 路由配置
+pub fn leaderboard_routes() -> Router<Database> {
+    Router::new()
+        .route("/", get(get_leaderboard))
+}
+
+#[derive(Deserialize)]
+pub struct LeaderboardQuery {
+    kind: LeaderboardKind,
+    period: LeaderboardPeriod,
+    period_key: Option<String>,             // 往期标识，为空时为当期
+    page: Option<usize>,
+    limit: Option<usize>,
+}
+
+// 获取排行榜及自己的名次
+pub async fn get_leaderboard(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Query(query): Query<LeaderboardQuery>,
+) -> Result<Json<LeaderboardPage>, StatusCode> {
+    let page = query.page.unwrap_or(1);
+    let limit = query.limit.unwrap_or(20).min(MAX_PAGE_SIZE);
+
+    let leaderboard = LeaderboardService::new(db)
+        .get_leaderboard(&auth_user.user_id, query.kind, query.period, query.period_key, page, limit)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(leaderboard))
+}
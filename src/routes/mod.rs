@@ -11,6 +11,12 @@ pub mod audit;
 pub mod im;
 pub mod friend;
 pub mod group;
+pub mod vip;
+pub mod notification;
+pub mod drop_table;
+pub mod achievement;
+pub mod leaderboard;
+pub mod gift_event;
 
 use axum::{
     Router,
@@ -34,6 +40,8 @@ pub fn create_routes(db: Database) -> Router {
         .route("/profile", get(user::get_profile))
         .route("/stats", get(user::get_stats))
         .route("/apply-for-promoter", post(user::apply_for_promoter))
+        .route("/timezone", post(user::update_timezone))
+        .route("/leaderboard-privacy", post(user::update_leaderboard_privacy))
         .route("/set_vip_config", post(user::set_vip_config))
         .layer(middleware::map_response(auth_middleware))
         .with_state(db.clone());
@@ -48,18 +56,25 @@ pub fn create_routes(db: Database) -> Router {
         .route("/my", get(coupon::get_my_coupons))
         .route("/redeem", post(coupon::redeem_coupon))
         .route("/transfer", post(coupon::transfer_coupon))
+        .route("/transfer/accept/:id", post(coupon::accept_coupon_transfer))
+        .route("/transfer/decline/:id", post(coupon::decline_coupon_transfer))
+        .route("/transfers", get(coupon::get_my_coupon_transfers))
+        .route("/claim", post(coupon::claim_coupon_code))
         .route("/issue/admin", post(coupon::issue_coupon_admin))
         .layer(middleware::map_response(auth_middleware))
         .with_state(db.clone());
 
     let points_routes = Router::new()
         .route("/daily-checkin", post(points::daily_checkin))
+        .route("/checkin/calendar", get(points::get_checkin_calendar))
+        .route("/checkin/makeup", post(points::makeup_checkin))
         .route("/wallet/transactions", get(points::get_wallet_transactions))
         .route("/wallet/balance", get(points::get_wallet_info))
         .route("/gift/send", post(points::send_gift))
         .route("/gift/available", get(points::get_available_gifts))
         .route("/gift/sent", get(points::get_sent_gifts))
         .route("/gift/received/:ai_id", get(points::get_ai_received_gifts))
+        .route("/gift/send-to-user", post(points::send_gift_to_user))
         .route("/lucky-card/use/:id", post(points::use_lucky_card))
         .route("/lucky-card/my", get(points::get_valid_lucky_cards))
         .nest("/points", points::points_routes())
@@ -93,7 +108,15 @@ pub fn create_routes(db: Database) -> Router {
         .route("/gift/delete/:id", post(admin::admin_delete_gift))
         .route("/gift/feedback/create", post(admin::admin_create_feedback_template))
         .route("/gift/feedback/:category", get(admin::admin_get_feedback_templates))
+        .route("/gift/combo-rules", get(admin::admin_get_gift_combo_rules))
+        .route("/gift/combo-rule/update", post(admin::admin_update_gift_combo_rule))
         .nest("/promoter", promoter::admin_promoter_routes())
+        .nest("/vip", vip::admin_vip_routes())
+        .nest("/coupon", coupon::admin_coupon_routes())
+        .nest("/drop", drop_table::admin_drop_routes())
+        .nest("/points", points::admin_points_routes())
+        .nest("/achievement", achievement::admin_achievement_routes())
+        .nest("/gift-event", gift_event::admin_gift_event_routes())
         .layer(middleware::map_response(auth_middleware))
         .with_state(db.clone());
 
@@ -103,6 +126,42 @@ pub fn create_routes(db: Database) -> Router {
         .layer(middleware::map_response(auth_middleware))
         .with_state(db.clone());
         
+    // 添加VIP订阅路由
+    let vip_routes = Router::new()
+        .merge(vip::vip_routes())
+        .layer(middleware::map_response(auth_middleware))
+        .with_state(db.clone());
+        
+    // 添加站内通知路由
+    let notification_routes = Router::new()
+        .merge(notification::notification_routes())
+        .layer(middleware::map_response(auth_middleware))
+        .with_state(db.clone());
+        
+    // 添加随机掉落路由
+    let drop_routes = Router::new()
+        .merge(drop_table::drop_routes())
+        .layer(middleware::map_response(auth_middleware))
+        .with_state(db.clone());
+        
+    // 添加成就路由
+    let achievement_routes = Router::new()
+        .merge(achievement::achievement_routes())
+        .layer(middleware::map_response(auth_middleware))
+        .with_state(db.clone());
+        
+    // 添加排行榜路由
+    let leaderboard_routes = Router::new()
+        .merge(leaderboard::leaderboard_routes())
+        .layer(middleware::map_response(auth_middleware))
+        .with_state(db.clone());
+        
+    // 添加礼物活动路由
+    let gift_event_routes = Router::new()
+        .merge(gift_event::gift_event_routes())
+        .layer(middleware::map_response(auth_middleware))
+        .with_state(db.clone());
+        
     // 创建文件存储服务
     let file_storage = Arc::new(FileStorage::new("./uploads"));
     
@@ -132,8 +191,23 @@ pub fn create_routes(db: Database) -> Router {
         .nest("/invite", invite_routes)
         .nest("/admin", admin_routes)
         .nest("/promoter", promoter_routes)
+        .nest("/vip", vip_routes)
+        .nest("/notification", notification_routes)
+        .nest("/drop", drop_routes)
+        .nest("/achievement", achievement_routes)
+        .nest("/leaderboard", leaderboard_routes)
+        .nest("/gift-event", gift_event_routes)
         .nest("/im", im_routes)
         .nest("/friend", friend_routes)
         .nest("/group", group_routes)
         .with_state(db)
 }
+
+// 区分请求中未传的字段和显式传 null 的字段：未传为 None，传 null 为 Some(None)，用于清空可选配置
+pub(crate) fn nullable<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: serde::Deserialize<'de>,
+{
+    serde::Deserialize::deserialize(deserializer).map(Some)
+}
@@ -0,0 +1,54 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::db::Database;
+use crate::models::Notification;
+use crate::middleware::auth::AuthenticatedUser;
+
+// 路由配置
+pub fn notification_routes() -> Router<Database> {
+    Router::new()
+        .route("/my", get(get_my_notifications))
+        .route("/read", post(mark_notifications_read))
+}
+
+#[derive(Serialize)]
+pub struct NotificationListResponse {
+    notifications: Vec<Notification>,
+}
+
+#[derive(Deserialize)]
+pub struct MarkReadRequest {
+    ids: Vec<String>,
+}
+
+// 获取我的站内通知
+pub async fn get_my_notifications(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<NotificationListResponse>, StatusCode> {
+    let notifications = db.get_user_notifications(&auth_user.user_id, 100)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(NotificationListResponse { notifications }))
+}
+
+// 标记通知为已读
+pub async fn mark_notifications_read(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<MarkReadRequest>,
+) -> Result<StatusCode, StatusCode> {
+    db.mark_notifications_read(&auth_user.user_id, &payload.ids)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::OK)
+}
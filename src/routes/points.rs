@@ -1,5 +1,5 @@
 use axum::{
-    extract::{State, Path},
+    extract::{State, Path, Query},
     http::StatusCode,
     Json,
     routing::{get, post},
@@ -10,10 +10,16 @@ use time::OffsetDateTime;
 
 use crate::db::Database;
 use crate::models::{
-    WalletTx, CurrencyType, Gift, GiftRecord, LuckyCard, TxType,
+    WalletTx, CurrencyType, Gift, GiftRecord, LuckyCard, LuckyBoost, TxType, PaymentOrder, OrderStatus,
 };
-use crate::models::gift::ConsecutiveGiftRecord;
-use crate::services::PointsService;
+use crate::models::gift::{ConsecutiveGiftRecord, UserGiftRecord, UserGiftSetting};
+use crate::models::gift_streak::{GiftStreakMilestone, GiftStreakReward, GiftStreakStatus};
+use crate::models::checkin::{CheckinCalendar, CheckinSchedule};
+use crate::models::hp_lot::{HpExpiryNotice, HpExpiryPolicy};
+use crate::models::earning_rule::{EarningRule, EarningSource, HeldReward, HeldRewardStatus};
+use crate::services::{PointsService, CheckoutService, EarningService, GiftStreakService};
+use crate::services::points_service::InvalidGiftRequest;
+use crate::middleware::admin::{require_admin, write_audit_log};
 use crate::middleware::auth::AuthenticatedUser;
 
 // ==================== 请求和响应结构 ====================
@@ -30,10 +36,28 @@ pub struct DailyCheckinResponse {
     current_hp: u32,
 }
 
+#[derive(Deserialize)]
+pub struct CheckinCalendarQuery {
+    year: Option<i32>,
+    month: Option<u8>,                      // 为空时默认用户本地的当月
+}
+
+#[derive(Deserialize)]
+pub struct MakeupCheckinRequest {
+    date: String,                           // YYYY-MM-DD
+}
+
+#[derive(Serialize)]
+pub struct MakeupCheckinResponse {
+    success: bool,
+    streak: u32,
+}
+
 #[derive(Deserialize)]
 pub struct SendGiftRequest {
     gift_id: String,
     receiver_ai_id: String,
+    quantity: Option<u32>,                  // 一次赠送的数量，默认1个
     message: Option<String>,
 }
 
@@ -42,11 +66,32 @@ pub struct SendGiftResponse {
     success: bool,
 }
 
+#[derive(Deserialize)]
+pub struct SendUserGiftRequest {
+    gift_id: String,
+    receiver_id: String,
+    quantity: Option<u32>,                  // 一次赠送的数量，默认1个
+    group_id: Option<String>,               // 在群聊中送出时的群ID，为空时为好友私聊
+    message: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SendUserGiftResponse {
+    success: bool,
+    record: Option<UserGiftRecord>,
+}
+
 #[derive(Deserialize)]
 pub struct UseLuckyCardRequest {
     card_id: String,
 }
 
+#[derive(Serialize)]
+pub struct LuckyCardsResponse {
+    cards: Vec<LuckyCard>,
+    active_boost: Option<LuckyBoost>,   // 当前生效的加成
+}
+
 #[derive(Serialize)]
 pub struct UseLuckyCardResponse {
     success: bool,
@@ -56,18 +101,70 @@ pub struct UseLuckyCardResponse {
 #[derive(Deserialize)]
 pub struct RechargeLCRequest {
     amount: u32,
+    coupon_id: Option<String>,
 }
 
 #[derive(Serialize)]
 pub struct RechargeLCResponse {
     success: bool,
     new_balance: u32,
+    order: PaymentOrder,
 }
 
 #[derive(Serialize)]
 pub struct WalletInfoResponse {
     hp: u32,
     lc_balance: u32,
+    hp_expiring: Option<HpExpiryNotice>,    // 最近一批即将过期的积分
+}
+
+#[derive(Deserialize)]
+pub struct UpdateHpExpiryPolicyRequest {
+    tx_type: TxType,
+    valid_days: Option<u32>,                // 为空表示永不过期
+}
+
+#[derive(Deserialize)]
+pub struct UpdateEarningRuleRequest {
+    source: EarningSource,
+    daily_cap: Option<u32>,                 // 为空表示不限
+    weekly_cap: Option<u32>,
+    lifetime_cap: Option<u32>,
+    velocity_limit: Option<u32>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateCheckinScheduleRequest {
+    base_points: u32,
+    streak_bonuses: Vec<u32>,               // 连续第N天的额外积分
+    overflow_bonus: u32,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateUserGiftSettingRequest {
+    recipient_share_percent: u32,
+}
+
+#[derive(Deserialize)]
+pub struct CreateGiftStreakMilestoneRequest {
+    name: String,
+    days: u32,
+    reward: GiftStreakReward,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateGiftStreakMilestoneRequest {
+    id: String,
+    name: Option<String>,
+    days: Option<u32>,                      // 已发放的奖励不受影响
+    reward: Option<GiftStreakReward>,
+    is_active: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct HeldRewardsQuery {
+    status: Option<HeldRewardStatus>,
+    limit: Option<usize>,
 }
 
 #[derive(Serialize)]
@@ -103,6 +200,37 @@ pub async fn daily_checkin(
     }
 }
 
+// 获取签到日历
+pub async fn get_checkin_calendar(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Query(query): Query<CheckinCalendarQuery>,
+) -> Result<Json<CheckinCalendar>, StatusCode> {
+    let points_service = PointsService::new(db);
+    
+    points_service.get_checkin_calendar(&auth_user.user_id, query.year, query.month)
+        .await
+        .map(Json)
+        .map_err(|_| StatusCode::BAD_REQUEST)
+}
+
+// 使用补签卡补签
+pub async fn makeup_checkin(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<MakeupCheckinRequest>,
+) -> Result<Json<MakeupCheckinResponse>, StatusCode> {
+    let points_service = PointsService::new(db);
+    
+    match points_service.makeup_checkin(&auth_user.user_id, &payload.date).await {
+        Ok(streak) => Ok(Json(MakeupCheckinResponse {
+            success: true,
+            streak,
+        })),
+        Err(_) => Err(StatusCode::BAD_REQUEST),
+    }
+}
+
 // 获取钱包信息
 pub async fn get_wallet_info(
     State(db): State<Database>,
@@ -110,11 +238,16 @@ pub async fn get_wallet_info(
 ) -> Result<Json<WalletInfoResponse>, StatusCode> {
     let points_service = PointsService::new(db);
     
+    let hp_expiring = points_service.get_hp_expiry_notice(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
     match points_service.get_user_wallet(&auth_user.user_id).await {
         Ok((hp, lc_balance)) => {
             Ok(Json(WalletInfoResponse {
                 hp,
                 lc_balance,
+                hp_expiring,
             }))
         },
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
@@ -166,23 +299,23 @@ pub async fn recharge_lc(
     auth_user: AuthenticatedUser,
     Json(payload): Json<RechargeLCRequest>,
 ) -> Result<Json<RechargeLCResponse>, StatusCode> {
-    let points_service = PointsService::new(db.clone());
-    
-    // 这里应该有支付流程，暂时简化处理
-    match points_service.recharge_lc(&auth_user.user_id, payload.amount).await {
-        Ok(()) => {
-            // 获取新的余额
-            let user = db.get_user_by_id(&auth_user.user_id).await
-                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
-                .ok_or(StatusCode::NOT_FOUND)?;
-            
-            Ok(Json(RechargeLCResponse {
-                success: true,
-                new_balance: user.lc_balance,
-            }))
-        },
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let checkout_service = CheckoutService::new(db.clone());
+
+    let order = checkout_service
+        .recharge_lc(&auth_user.user_id, payload.amount, payload.coupon_id.as_deref())
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    // 获取新的余额
+    let user = db.get_user_by_id(&auth_user.user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    Ok(Json(RechargeLCResponse {
+        success: order.status == OrderStatus::Paid,
+        new_balance: user.lc_balance,
+        order,
+    }))
 }
 
 // 获取可用礼物列表
@@ -206,12 +339,15 @@ pub async fn send_gift(
 ) -> Result<Json<SendGiftResponse>, StatusCode> {
     let points_service = PointsService::new(db);
     
-    match points_service.send_gift(&auth_user.user_id, &payload.gift_id, &payload.receiver_ai_id, payload.message).await {
+    let quantity = payload.quantity.unwrap_or(1);
+    
+    match points_service.send_gift(&payload.gift_id, &auth_user.user_id, &payload.receiver_ai_id, quantity, payload.message).await {
         Ok(success) => {
             Ok(Json(SendGiftResponse {
                 success,
             }))
         },
+        Err(e) if e.is::<InvalidGiftRequest>() => Err(StatusCode::BAD_REQUEST),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
 }
@@ -229,6 +365,61 @@ pub async fn get_sent_gifts(
     }
 }
 
+// 向好友或群成员赠送礼物
+pub async fn send_gift_to_user(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<SendUserGiftRequest>,
+) -> Result<Json<SendUserGiftResponse>, StatusCode> {
+    let points_service = PointsService::new(db);
+    
+    let quantity = payload.quantity.unwrap_or(1);
+    
+    match points_service.send_gift_to_user(
+        &payload.gift_id,
+        &auth_user.user_id,
+        &payload.receiver_id,
+        quantity,
+        payload.group_id,
+        payload.message,
+    ).await {
+        Ok(record) => {
+            Ok(Json(SendUserGiftResponse {
+                success: record.is_some(),
+                record,
+            }))
+        },
+        Err(e) if e.is::<InvalidGiftRequest>() => Err(StatusCode::BAD_REQUEST),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// 获取用户送给其他用户的礼物记录
+pub async fn get_user_gifts_sent_to_users(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<UserGiftRecord>>, StatusCode> {
+    let points_service = PointsService::new(db);
+    
+    match points_service.get_user_gifts_sent_to_users(&auth_user.user_id, 50).await {
+        Ok(records) => Ok(Json(records)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
+// 获取用户收到的其他用户的礼物记录
+pub async fn get_user_received_gifts(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<UserGiftRecord>>, StatusCode> {
+    let points_service = PointsService::new(db);
+    
+    match points_service.get_user_received_gifts(&auth_user.user_id, 50).await {
+        Ok(records) => Ok(Json(records)),
+        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
+    }
+}
+
 // 获取AI收到的礼物记录
 pub async fn get_ai_received_gifts(
     State(db): State<Database>,
@@ -256,13 +447,17 @@ pub async fn get_ai_received_gifts(
 pub async fn get_valid_lucky_cards(
     State(db): State<Database>,
     auth_user: AuthenticatedUser,
-) -> Result<Json<Vec<LuckyCard>>, StatusCode> {
+) -> Result<Json<LuckyCardsResponse>, StatusCode> {
     let points_service = PointsService::new(db);
     
-    match points_service.get_user_valid_lucky_cards(&auth_user.user_id).await {
-        Ok(cards) => Ok(Json(cards)),
-        Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
-    }
+    let cards = points_service.get_user_valid_lucky_cards(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let active_boost = points_service.get_active_lucky_boost(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(LuckyCardsResponse { cards, active_boost }))
 }
 
 // 使用幸运卡
@@ -300,6 +495,20 @@ pub async fn get_consecutive_gift_record(
     Ok(Json(ConsecutiveGiftResponse { record }))
 }
 
+// 获取与AI的连续送礼状态和下一个里程碑
+pub async fn get_gift_streak(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(ai_id): Path<String>,
+) -> Result<Json<GiftStreakStatus>, StatusCode> {
+    let status = GiftStreakService::new(db)
+        .get_status(&auth_user.user_id, &ai_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(status))
+}
+
 // 获取礼物反馈
 #[axum::debug_handler]
 pub async fn get_gift_feedback(
@@ -342,12 +551,363 @@ pub async fn get_gift_feedback(
     }))
 }
 
+// ==================== 管理员接口 ====================
+
+// 获取各交易类型的积分有效期
+pub async fn admin_get_hp_expiry_policies(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<HpExpiryPolicy>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let mut policies = Vec::new();
+    for tx_type in TxType::hp_credit_types() {
+        let policy = db.get_effective_hp_expiry_policy(&tx_type)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        policies.push(policy);
+    }
+
+    Ok(Json(policies))
+}
+
+// 更新交易类型的积分有效期，只影响之后入账的积分
+pub async fn admin_update_hp_expiry_policy(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateHpExpiryPolicyRequest>,
+) -> Result<Json<HpExpiryPolicy>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    if !TxType::hp_credit_types().contains(&payload.tx_type) || payload.valid_days == Some(0) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let mut policy = db.get_effective_hp_expiry_policy(&payload.tx_type)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    policy.valid_days = payload.valid_days;
+    policy.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.save_hp_expiry_policy(&policy)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(
+        &db,
+        admin.id,
+        format!("Updated HP expiry policy {}: {:?} days", policy.id, policy.valid_days),
+    ).await?;
+
+    Ok(Json(policy))
+}
+
+// 获取签到奖励配置
+pub async fn admin_get_checkin_schedule(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<CheckinSchedule>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let schedule = PointsService::new(db)
+        .get_checkin_schedule()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(schedule))
+}
+
+// 更新签到奖励配置，从下一次签到开始生效
+pub async fn admin_update_checkin_schedule(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateCheckinScheduleRequest>,
+) -> Result<Json<CheckinSchedule>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    if payload.streak_bonuses.len() > 366 {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let schedule = CheckinSchedule {
+        id: CheckinSchedule::ID.to_string(),
+        base_points: payload.base_points,
+        streak_bonuses: payload.streak_bonuses,
+        overflow_bonus: payload.overflow_bonus,
+        updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+    };
+
+    db.save_checkin_schedule(&schedule)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(
+        &db,
+        admin.id,
+        format!(
+            "Updated checkin schedule: base {}, {} streak bonuses, overflow {}",
+            schedule.base_points, schedule.streak_bonuses.len(), schedule.overflow_bonus
+        ),
+    ).await?;
+
+    Ok(Json(schedule))
+}
+
+// 获取用户间送礼分成配置
+pub async fn admin_get_user_gift_setting(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<UserGiftSetting>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let setting = PointsService::new(db)
+        .get_user_gift_setting()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(setting))
+}
+
+// 更新用户间送礼分成比例，其余部分为平台手续费
+pub async fn admin_update_user_gift_setting(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateUserGiftSettingRequest>,
+) -> Result<Json<UserGiftSetting>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let setting = UserGiftSetting {
+        id: UserGiftSetting::ID.to_string(),
+        recipient_share_percent: payload.recipient_share_percent,
+        updated_at: OffsetDateTime::now_utc().unix_timestamp(),
+    };
+    setting.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    db.save_user_gift_setting(&setting)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(
+        &db,
+        admin.id,
+        format!("Updated user gift recipient share: {}%", setting.recipient_share_percent),
+    ).await?;
+
+    Ok(Json(setting))
+}
+
+// 获取所有连续送礼里程碑
+pub async fn admin_get_gift_streak_milestones(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<GiftStreakMilestone>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let milestones = db.get_gift_streak_milestones()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(milestones))
+}
+
+// 创建连续送礼里程碑
+pub async fn admin_create_gift_streak_milestone(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateGiftStreakMilestoneRequest>,
+) -> Result<Json<GiftStreakMilestone>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let milestone = GiftStreakMilestone::new(payload.name, payload.days, payload.reward);
+    milestone.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    db.create_gift_streak_milestone(&milestone)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(
+        &db,
+        admin.id,
+        format!("Created gift streak milestone: {} ({} days)", milestone.name, milestone.days),
+    ).await?;
+
+    Ok(Json(milestone))
+}
+
+// 更新连续送礼里程碑
+pub async fn admin_update_gift_streak_milestone(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateGiftStreakMilestoneRequest>,
+) -> Result<Json<GiftStreakMilestone>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut milestone = db.get_gift_streak_milestone(&payload.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Some(name) = payload.name {
+        milestone.name = name;
+    }
+
+    if let Some(days) = payload.days {
+        milestone.days = days;
+    }
+
+    if let Some(reward) = payload.reward {
+        milestone.reward = reward;
+    }
+
+    if let Some(is_active) = payload.is_active {
+        milestone.is_active = is_active;
+    }
+
+    milestone.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    milestone.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.update_gift_streak_milestone(&milestone)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(
+        &db,
+        admin.id,
+        format!("Updated gift streak milestone: {} ({})", milestone.name, milestone.id),
+    ).await?;
+
+    Ok(Json(milestone))
+}
+
+// 获取各来源的积分获取规则
+pub async fn admin_get_earning_rules(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<EarningRule>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let service = EarningService::new(db);
+    let mut rules = Vec::new();
+    for source in EarningSource::all() {
+        let rule = service.get_rule(source)
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        rules.push(rule);
+    }
+
+    Ok(Json(rules))
+}
+
+// 更新来源的积分获取规则
+pub async fn admin_update_earning_rule(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateEarningRuleRequest>,
+) -> Result<Json<EarningRule>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut rule = EarningService::new(db.clone())
+        .get_rule(payload.source)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    rule.daily_cap = payload.daily_cap;
+    rule.weekly_cap = payload.weekly_cap;
+    rule.lifetime_cap = payload.lifetime_cap;
+    rule.velocity_limit = payload.velocity_limit;
+    rule.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.save_earning_rule(&rule)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated earning rule {}", rule.id)).await?;
+
+    Ok(Json(rule))
+}
+
+// 获取暂扣积分，默认只看待审核的
+pub async fn admin_get_held_rewards(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Query(query): Query<HeldRewardsQuery>,
+) -> Result<Json<Vec<HeldReward>>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let status = query.status.unwrap_or(HeldRewardStatus::Pending);
+    let rewards = db.get_held_rewards(&status, query.limit.unwrap_or(50))
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(rewards))
+}
+
+// 审核通过暂扣积分并入账
+pub async fn admin_approve_held_reward(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<HeldReward>, StatusCode> {
+    review_held_reward(db, auth_user, id, true).await
+}
+
+// 驳回暂扣积分
+pub async fn admin_reject_held_reward(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<HeldReward>, StatusCode> {
+    review_held_reward(db, auth_user, id, false).await
+}
+
+async fn review_held_reward(
+    db: Database,
+    auth_user: AuthenticatedUser,
+    id: String,
+    approved: bool,
+) -> Result<Json<HeldReward>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let reward = EarningService::new(db.clone())
+        .review(&id, approved, &admin.id)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    write_audit_log(
+        &db,
+        admin.id,
+        format!("Reviewed held reward {} ({:?})", reward.id, reward.status),
+    ).await?;
+
+    Ok(Json(reward))
+}
+
+// 解除用户的积分异常标记，之后的奖励恢复正常入账
+pub async fn admin_clear_earning_flag(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(user_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    db.clear_user_earning_flag(&user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Cleared earning flag for user {}", user_id)).await?;
+
+    Ok(StatusCode::OK)
+}
+
 // ==================== 路由配置 ====================
 
 pub fn points_routes() -> Router<Database> {
     Router::new()
         // 积分相关路由
         .route("/checkin", post(daily_checkin))
+        .route("/checkin/calendar", get(get_checkin_calendar))
+        .route("/checkin/makeup", post(makeup_checkin))
         .route("/hp/transactions", get(get_hp_transactions))
         
         // 钱包相关路由
@@ -362,9 +922,33 @@ pub fn points_routes() -> Router<Database> {
         .route("/gift/sent", get(get_sent_gifts))
         .route("/gift/received/:ai_id", get(get_ai_received_gifts))
         .route("/gift/consecutive/:ai_id", get(get_consecutive_gift_record))
+        .route("/gift/streak/:ai_id", get(get_gift_streak))
         .route("/gift/feedback/:gift_id/:ai_id", get(get_gift_feedback))
+        .route("/gift/send-to-user", post(send_gift_to_user))
+        .route("/gift/user/sent", get(get_user_gifts_sent_to_users))
+        .route("/gift/user/received", get(get_user_received_gifts))
         
         // 幸运卡系统路由
         .route("/lucky-card/use", post(use_lucky_card))
         .route("/lucky-card/my", get(get_valid_lucky_cards))
 }
+
+// 管理员路由配置
+pub fn admin_points_routes() -> Router<Database> {
+    Router::new()
+        .route("/hp-expiry", get(admin_get_hp_expiry_policies))
+        .route("/hp-expiry/update", post(admin_update_hp_expiry_policy))
+        .route("/checkin-schedule", get(admin_get_checkin_schedule))
+        .route("/checkin-schedule/update", post(admin_update_checkin_schedule))
+        .route("/user-gift-setting", get(admin_get_user_gift_setting))
+        .route("/user-gift-setting/update", post(admin_update_user_gift_setting))
+        .route("/gift-streak-milestones", get(admin_get_gift_streak_milestones))
+        .route("/gift-streak-milestone/create", post(admin_create_gift_streak_milestone))
+        .route("/gift-streak-milestone/update", post(admin_update_gift_streak_milestone))
+        .route("/earning-rules", get(admin_get_earning_rules))
+        .route("/earning-rule/update", post(admin_update_earning_rule))
+        .route("/held-rewards", get(admin_get_held_rewards))
+        .route("/held-reward/:id/approve", post(admin_approve_held_reward))
+        .route("/held-reward/:id/reject", post(admin_reject_held_reward))
+        .route("/earning-flag/:user_id/clear", post(admin_clear_earning_flag))
+}
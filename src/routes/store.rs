@@ -1,14 +1,28 @@
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     routing::{get, post},
     Json, Router,
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use time::OffsetDateTime;
 
 use crate::db::Database;
-use crate::models::{ShopItem, ShopItemCategory, PurchaseRecord, MonthlyRedemptionStat};
+use crate::models::{ShopItem, ShopItemCategory, ShopItemType, PurchaseRecord, MonthlyRedemptionStat};
+use crate::models::point_shop::{
+    PurchaseAllowance, PurchaseLimits, PurchaseLimitOverride, ShopItemSearch, ShopItemSort, ShopItemCursor,
+};
+use crate::models::inventory::InventoryItem;
+use crate::models::wishlist::WishlistEntry;
+use crate::models::shop_promotion::{
+    ShopSale, FlashDeal, ShopBundle, PriceAdjustment, PriceQuote, PricedShopItem, PricedShopBundle,
+};
+use crate::services::{FulfilmentService, PointsService, PricingService, WishlistService};
 use crate::middleware::auth::AuthenticatedUser;
+use crate::routes::nullable;
 
 // 创建商城路由
 pub fn create_store_routes() -> Router<Database> {
@@ -17,8 +31,17 @@ pub fn create_store_routes() -> Router<Database> {
         .route("/items/:category", get(get_store_items_by_category))
         .route("/item/:id", get(get_store_item))
         .route("/redeem", post(redeem_item))
+        .route("/bundles", get(get_store_bundles))
+        .route("/bundle/redeem", post(redeem_bundle))
         .route("/my-history", get(get_user_redemption_history))
         .route("/my-purchases", get(get_user_purchases))
+        .route("/purchase/:id/activate", post(activate_purchase))
+        .route("/inventory", get(get_user_inventory))
+        .route("/inventory/:id/equip", post(equip_inventory_item))
+        .route("/inventory/:id/unequip", post(unequip_inventory_item))
+        .route("/wishlist", get(get_user_wishlist))
+        .route("/wishlist/add", post(add_wishlist_item))
+        .route("/wishlist/remove/:item_id", post(remove_wishlist_item))
 }
 
 // 创建管理员路由
@@ -29,6 +52,15 @@ pub fn create_admin_store_routes() -> Router<Database> {
         .route("/update", post(admin_update_item))
         .route("/delete/:id", post(admin_delete_item))
         .route("/redemptions", post(admin_get_redemptions))
+        .route("/sales", get(admin_get_sales))
+        .route("/sale/create", post(admin_create_sale))
+        .route("/sale/update", post(admin_update_sale))
+        .route("/flash-deals", get(admin_get_flash_deals))
+        .route("/flash-deal/create", post(admin_create_flash_deal))
+        .route("/flash-deal/update", post(admin_update_flash_deal))
+        .route("/bundles", get(admin_get_bundles))
+        .route("/bundle/create", post(admin_create_bundle))
+        .route("/bundle/update", post(admin_update_bundle))
 }
 
 // ==================== 用户接口 ====================
@@ -39,34 +71,158 @@ pub struct StoreItemsResponse {
     pub items: Vec<ShopItem>,
 }
 
-// 获取所有可用商品
+// 商品列表每页默认数量与上限
+const DEFAULT_PAGE_SIZE: usize = 20;
+const MAX_PAGE_SIZE: usize = 100;
+
+// 商品搜索参数
+#[derive(Deserialize)]
+pub struct StoreItemsQuery {
+    pub q: Option<String>,                  // 搜索名称或描述
+    pub category: Option<String>,           // coupon / decoration / function
+    pub item_type: Option<ShopItemType>,
+    pub min_price: Option<u32>,
+    pub max_price: Option<u32>,
+    pub affordable: Option<bool>,           // 只看当前积分买得起的商品（按实际价格）
+    pub sort: Option<ShopItemSort>,         // price_asc（默认）/ price_desc / newest / popular
+    pub cursor: Option<String>,
+    pub limit: Option<usize>,
+}
+
+// 带报价的商品列表响应（包含原价与实际价格）
+#[derive(Serialize)]
+pub struct PricedItemsResponse {
+    pub items: Vec<PricedShopItem>,
+    pub next_cursor: Option<String>,
+}
+
+fn parse_category(category: &str) -> Option<ShopItemCategory> {
+    match category {
+        "coupon" => Some(ShopItemCategory::Coupon),
+        "decoration" => Some(ShopItemCategory::Decoration),
+        "function" => Some(ShopItemCategory::Function),
+        _ => None,
+    }
+}
+
+// 带 ETag 的 JSON 响应，客户端缓存未变化时返回 304
+fn etag_json<T: Serialize>(headers: &HeaderMap, body: &T) -> Result<Response, StatusCode> {
+    let bytes = serde_json::to_vec(body).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    let mut hasher = DefaultHasher::new();
+    bytes.hash(&mut hasher);
+    let etag = format!("W/\"{:016x}\"", hasher.finish());
+    
+    // 价格按用户计算，只允许私有缓存
+    let cache_headers = [
+        (header::ETAG, etag.clone()),
+        (header::CACHE_CONTROL, "private, no-cache".to_string()),
+    ];
+    
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    
+    if not_modified {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
+    }
+    
+    Ok((cache_headers, [(header::CONTENT_TYPE, "application/json".to_string())], bytes).into_response())
+}
+
+// 搜索商品并按用户计算报价
+async fn search_items(
+    db: Database,
+    user_id: &str,
+    query: StoreItemsQuery,
+    category: Option<ShopItemCategory>,
+    headers: &HeaderMap,
+) -> Result<Response, StatusCode> {
+    let user = db.get_user_by_id(user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    let cursor = match query.cursor.as_deref() {
+        Some(cursor) => Some(ShopItemCursor::parse(cursor).ok_or(StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
+    
+    let limit = query.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let mut search = ShopItemSearch {
+        keyword: query.q.filter(|q| !q.trim().is_empty()).map(|q| q.trim().to_string()),
+        category,
+        item_type: query.item_type,
+        min_price: query.min_price,
+        max_price: query.max_price,
+        sort: query.sort.unwrap_or(ShopItemSort::PriceAsc),
+        cursor,
+        limit: limit + 1,
+    };
+    
+    // 买得起按实际价格（含促销、秒杀和会员折扣）判断，报价后过滤，不足一页时继续向后取
+    let affordable = query.affordable == Some(true);
+    let pricing = PricingService::new(db.clone());
+    let mut items: Vec<PricedShopItem> = Vec::new();
+    let mut next_cursor = None;
+    
+    'pages: loop {
+        let batch = db.search_shop_items(&search).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        let exhausted = batch.len() < search.limit;
+        
+        let priced = pricing.quote_items(&user, batch).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        
+        for priced in priced {
+            search.cursor = Some(ShopItemCursor::after(&search.sort, &priced.item));
+            if affordable && priced.price.effective_price > user.hp {
+                continue;
+            }
+            
+            // 多取一条判断是否还有下一页
+            if items.len() == limit {
+                next_cursor = items.last()
+                    .map(|last| ShopItemCursor::after(&search.sort, &last.item).encode());
+                break 'pages;
+            }
+            items.push(priced);
+        }
+        
+        if exhausted || !affordable {
+            break;
+        }
+    }
+    
+    etag_json(headers, &PricedItemsResponse { items, next_cursor })
+}
+
+// 搜索可用商品
 pub async fn get_store_items(
     State(db): State<Database>,
     auth_user: AuthenticatedUser,
-) -> Result<Json<StoreItemsResponse>, StatusCode> {
-    let items = db.get_available_shop_items().await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    headers: HeaderMap,
+    Query(query): Query<StoreItemsQuery>,
+) -> Result<Response, StatusCode> {
+    let category = match query.category.as_deref() {
+        Some(category) => Some(parse_category(category).ok_or(StatusCode::BAD_REQUEST)?),
+        None => None,
+    };
     
-    Ok(Json(StoreItemsResponse { items }))
+    search_items(db, &auth_user.user_id, query, category, &headers).await
 }
 
 // 按分类获取商品
 pub async fn get_store_items_by_category(
     State(db): State<Database>,
     auth_user: AuthenticatedUser,
+    headers: HeaderMap,
     Path(category): Path<String>,
-) -> Result<Json<StoreItemsResponse>, StatusCode> {
-    let category = match category.as_str() {
-        "coupon" => ShopItemCategory::Coupon,
-        "decoration" => ShopItemCategory::Decoration,
-        "function" => ShopItemCategory::Function,
-        _ => return Err(StatusCode::BAD_REQUEST),
-    };
+    Query(query): Query<StoreItemsQuery>,
+) -> Result<Response, StatusCode> {
+    let category = parse_category(&category).ok_or(StatusCode::BAD_REQUEST)?;
     
-    let items = db.get_available_shop_items_by_category(&category).await
-        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-    
-    Ok(Json(StoreItemsResponse { items }))
+    search_items(db, &auth_user.user_id, query, Some(category), &headers).await
 }
 
 // 获取单个商品信息响应
@@ -74,6 +230,8 @@ pub async fn get_store_items_by_category(
 pub struct StoreItemResponse {
     pub item: ShopItem,
     pub discounted_price: u32,
+    pub price: PriceQuote,
+    pub allowance: PurchaseAllowance,
 }
 
 // 获取单个商品信息
@@ -91,13 +249,23 @@ pub async fn get_store_item(
         return Err(StatusCode::NOT_FOUND);
     }
     
-    // 计算折扣价格
-    let discounted_price = db.calculate_discounted_price(&auth_user.user_id, &id).await
+    let user = db.get_user_by_id(&auth_user.user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    // 计算成交价格
+    let price = PricingService::new(db.clone()).quote_item(&user, &item).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // 计算剩余可兑换次数
+    let allowance = PointsService::new(db).get_purchase_allowance(&user, &item).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
     Ok(Json(StoreItemResponse { 
         item, 
-        discounted_price,
+        discounted_price: price.effective_price,
+        price,
+        allowance,
     }))
 }
 
@@ -106,6 +274,7 @@ pub async fn get_store_item(
 pub struct RedeemItemRequest {
     pub item_id: String,
     pub remark: Option<String>,
+    pub coupon_id: Option<String>,   // 可选的折扣券
 }
 
 // 兑换商品响应
@@ -141,8 +310,21 @@ pub async fn redeem_item(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
     
-    // 计算折扣价格
-    let price_to_pay = item.get_discounted_price(&user.vip_level);
+    // 计算成交价格
+    let pricing = PricingService::new(db.clone());
+    let mut quote = pricing.quote_item(&user, &item).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    if let Some(coupon_id) = &request.coupon_id {
+        if pricing.apply_coupon(&user.id, &mut quote, coupon_id).await.is_err() {
+            return Ok(Json(RedeemItemResponse {
+                success: false,
+                message: "卡券不可用".to_string(),
+                purchase_id: None,
+            }));
+        }
+    }
+    let price_to_pay = quote.effective_price;
     
     // 检查用户积分是否足够
     if user.hp < price_to_pay {
@@ -153,38 +335,33 @@ pub async fn redeem_item(
         }));
     }
     
-    // 检查月度兑换限制
-    if let Some(monthly_limit) = item.monthly_limit {
-        let stat = db.get_user_monthly_redemption_stat(&auth_user.user_id).await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        
-        if let Some(stat) = stat {
-            let item_type_str = format!("{:?}", item.item_type);
-            if !stat.check_monthly_limit(&item_type_str, monthly_limit) {
-                return Ok(Json(RedeemItemResponse {
-                    success: false,
-                    message: format!("已达到本月兑换上限 ({} 次)", monthly_limit),
-                    purchase_id: None,
-                }));
-            }
-        }
+    // 检查限购，兑换事务内会再次校验
+    let allowance = PointsService::new(db.clone()).get_purchase_allowance(&user, &item).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    if allowance.is_exhausted() {
+        return Ok(Json(RedeemItemResponse {
+            success: false,
+            message: "已达到兑换上限".to_string(),
+            purchase_id: None,
+        }));
     }
     
     // 执行兑换
-    let result = db.redeem_shop_item(&auth_user.user_id, &request.item_id, request.remark).await
+    let result = db.redeem_shop_item(&auth_user.user_id, &request.item_id, &quote, request.remark).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
-    if result {
-        // 获取最新的购买记录
-        let purchases = db.get_user_purchases(&auth_user.user_id, 1).await
-            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
-        
-        let purchase_id = purchases.first().map(|p| p.id.clone());
+    if let Some(purchase) = result {
+        // 兑换后立即激活并发放商品效果
+        let message = match FulfilmentService::new(db).activate_purchase(&auth_user.user_id, &purchase.id).await {
+            Ok(_) => format!("成功兑换商品: {}", item.name),
+            Err(_) => format!("成功兑换商品: {}，激活失败，请稍后在购买记录中重新激活", item.name),
+        };
         
         Ok(Json(RedeemItemResponse {
             success: true,
-            message: format!("成功兑换商品: {}", item.name),
-            purchase_id,
+            message,
+            purchase_id: Some(purchase.id),
         }))
     } else {
         Ok(Json(RedeemItemResponse {
@@ -195,6 +372,124 @@ pub async fn redeem_item(
     }
 }
 
+// 组合包列表响应
+#[derive(Serialize)]
+pub struct StoreBundlesResponse {
+    pub bundles: Vec<PricedShopBundle>,
+}
+
+// 获取在售组合包
+pub async fn get_store_bundles(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<StoreBundlesResponse>, StatusCode> {
+    let bundles = PricingService::new(db).get_live_bundles().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(StoreBundlesResponse { bundles }))
+}
+
+// 兑换组合包请求
+#[derive(Deserialize)]
+pub struct RedeemBundleRequest {
+    pub bundle_id: String,
+    pub remark: Option<String>,
+    pub coupon_id: Option<String>,   // 可选的折扣券
+}
+
+// 兑换组合包响应
+#[derive(Serialize)]
+pub struct RedeemBundleResponse {
+    pub success: bool,
+    pub message: String,
+    pub purchase_ids: Vec<String>,
+}
+
+// 兑换组合包
+pub async fn redeem_bundle(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<RedeemBundleRequest>,
+) -> Result<Json<RedeemBundleResponse>, StatusCode> {
+    let bundle = db.get_shop_bundle(&request.bundle_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    let pricing = PricingService::new(db.clone());
+    let items = pricing.get_bundle_items(&bundle).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // 检查组合包是否在售
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let items = match items {
+        Some(items) if bundle.is_live(now) => items,
+        _ => {
+            return Ok(Json(RedeemBundleResponse {
+                success: false,
+                message: "组合包不可用或已售罄".to_string(),
+                purchase_ids: vec![],
+            }));
+        }
+    };
+    
+    let user = db.get_user_by_id(&auth_user.user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    // 检查用户积分是否足够
+    let mut quote = PriceQuote::for_bundle(&bundle, &items);
+    if let Some(coupon_id) = &request.coupon_id {
+        if pricing.apply_coupon(&user.id, &mut quote, coupon_id).await.is_err() {
+            return Ok(Json(RedeemBundleResponse {
+                success: false,
+                message: "卡券不可用".to_string(),
+                purchase_ids: vec![],
+            }));
+        }
+    }
+    
+    if user.hp < quote.effective_price {
+        return Ok(Json(RedeemBundleResponse {
+            success: false,
+            message: format!("积分不足，需要 {} 积分", quote.effective_price),
+            purchase_ids: vec![],
+        }));
+    }
+    
+    // 执行兑换，限购在兑换事务内校验
+    let result = db.redeem_shop_bundle(&user, &bundle, &items, &quote, request.remark).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    let Some(purchases) = result else {
+        return Ok(Json(RedeemBundleResponse {
+            success: false,
+            message: "兑换失败，可能已达到兑换上限或已售罄".to_string(),
+            purchase_ids: vec![],
+        }));
+    };
+    
+    // 兑换后逐个激活并发放商品效果
+    let fulfilment = FulfilmentService::new(db);
+    let mut activation_failed = false;
+    for purchase in &purchases {
+        if fulfilment.activate_purchase(&auth_user.user_id, &purchase.id).await.is_err() {
+            activation_failed = true;
+        }
+    }
+    
+    let message = if activation_failed {
+        format!("成功兑换组合包: {}，部分商品激活失败，请稍后在购买记录中重新激活", bundle.name)
+    } else {
+        format!("成功兑换组合包: {}", bundle.name)
+    };
+    
+    Ok(Json(RedeemBundleResponse {
+        success: true,
+        message,
+        purchase_ids: purchases.into_iter().map(|purchase| purchase.id).collect(),
+    }))
+}
+
 // 用户兑换历史响应
 #[derive(Serialize)]
 pub struct UserRedemptionHistoryResponse {
@@ -255,6 +550,130 @@ pub async fn get_user_purchases(
     }))
 }
 
+// 激活购买记录
+pub async fn activate_purchase(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<PurchaseRecord>, StatusCode> {
+    let purchase = FulfilmentService::new(db).activate_purchase(&auth_user.user_id, &id).await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    Ok(Json(purchase))
+}
+
+// 用户背包响应
+#[derive(Serialize)]
+pub struct UserInventoryResponse {
+    pub items: Vec<InventoryItem>,
+}
+
+// 获取用户背包
+pub async fn get_user_inventory(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<UserInventoryResponse>, StatusCode> {
+    let items = db.get_user_inventory(&auth_user.user_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(UserInventoryResponse { items }))
+}
+
+// 装备物品请求
+#[derive(Deserialize)]
+pub struct EquipItemRequest {
+    pub ai_id: Option<String>,   // 装饰需要指定AI
+}
+
+// 装备称号或AI装饰
+pub async fn equip_inventory_item(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+    Json(request): Json<EquipItemRequest>,
+) -> Result<Json<InventoryItem>, StatusCode> {
+    let item = FulfilmentService::new(db)
+        .equip(&auth_user.user_id, &id, request.ai_id.as_deref())
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    Ok(Json(item))
+}
+
+// 卸下称号或AI装饰
+pub async fn unequip_inventory_item(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(id): Path<String>,
+) -> Result<Json<InventoryItem>, StatusCode> {
+    let item = FulfilmentService::new(db)
+        .unequip(&auth_user.user_id, &id)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    Ok(Json(item))
+}
+
+// 心愿单商品
+#[derive(Serialize)]
+pub struct WishlistItemResponse {
+    #[serde(flatten)]
+    pub entry: WishlistEntry,
+    pub item: ShopItem,
+}
+
+// 获取用户心愿单
+pub async fn get_user_wishlist(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<WishlistItemResponse>>, StatusCode> {
+    let items = WishlistService::new(db)
+        .list(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(items
+        .into_iter()
+        .map(|(entry, item)| WishlistItemResponse { entry, item })
+        .collect()))
+}
+
+// 加入心愿单请求
+#[derive(Deserialize)]
+pub struct AddWishlistRequest {
+    pub item_id: String,
+    #[serde(default)]
+    pub notify_email: bool,     // 补货或促销时是否同时发送邮件
+}
+
+// 加入心愿单
+pub async fn add_wishlist_item(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<AddWishlistRequest>,
+) -> Result<Json<WishlistEntry>, StatusCode> {
+    let entry = WishlistService::new(db)
+        .add(&auth_user.user_id, &request.item_id, request.notify_email)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    
+    Ok(Json(entry))
+}
+
+// 移出心愿单
+pub async fn remove_wishlist_item(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Path(item_id): Path<String>,
+) -> Result<StatusCode, StatusCode> {
+    WishlistService::new(db)
+        .remove(&auth_user.user_id, &item_id)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+    
+    Ok(StatusCode::OK)
+}
+
 // ==================== 管理员接口 ====================
 
 // 管理员获取所有商品
@@ -289,6 +708,8 @@ pub struct CreateItemRequest {
     pub linked_coupon_id: Option<String>,
     pub monthly_limit: Option<u32>,
     pub vip_discount: Option<bool>,
+    pub limits: Option<PurchaseLimits>,
+    pub vip_limit_overrides: Option<Vec<PurchaseLimitOverride>>,
 }
 
 // 创建商品响应
@@ -317,6 +738,8 @@ pub async fn admin_create_item(
         "LIOAccessTicket" => crate::models::ShopItemType::LIOAccessTicket,
         "AISlotExpansion" => crate::models::ShopItemType::AISlotExpansion,
         "ExclusiveStory" => crate::models::ShopItemType::ExclusiveStory,
+        "CheckinMakeupCard" => crate::models::ShopItemType::CheckinMakeupCard,
+        "GiftStreakFreeze" => crate::models::ShopItemType::GiftStreakFreeze,
         _ => return Err(StatusCode::BAD_REQUEST),
     };
     
@@ -329,7 +752,7 @@ pub async fn admin_create_item(
     };
     
     // 创建商品
-    let item = ShopItem::new(
+    let mut item = ShopItem::new(
         request.name,
         request.description,
         item_type,
@@ -344,6 +767,8 @@ pub async fn admin_create_item(
         request.monthly_limit,
         request.vip_discount,
     );
+    item.limits = request.limits.unwrap_or_default();
+    item.vip_limit_overrides = request.vip_limit_overrides.unwrap_or_default();
     
     // 保存商品
     db.create_shop_item(&item).await
@@ -371,6 +796,8 @@ pub struct UpdateItemRequest {
     pub linked_coupon_id: Option<String>,
     pub monthly_limit: Option<u32>,
     pub vip_discount: Option<bool>,
+    pub limits: Option<PurchaseLimits>,
+    pub vip_limit_overrides: Option<Vec<PurchaseLimitOverride>>,
 }
 
 // 更新商品响应
@@ -396,6 +823,9 @@ pub async fn admin_update_item(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
     
+    // 售罄商品补货后提醒心愿单用户
+    let was_sold_out = item.stock == Some(0);
+    
     // 更新商品信息
     if let Some(name) = request.name {
         item.name = name;
@@ -441,10 +871,22 @@ pub async fn admin_update_item(
         item.vip_discount = Some(vip_discount);
     }
     
+    if let Some(limits) = request.limits {
+        item.limits = limits;
+    }
+    
+    if let Some(vip_limit_overrides) = request.vip_limit_overrides {
+        item.vip_limit_overrides = vip_limit_overrides;
+    }
+    
     // 保存更新
     db.update_shop_item(&item).await
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     
+    if was_sold_out && item.stock.is_some_and(|stock| stock > 0) {
+        WishlistService::fire_restock(db, item);
+    }
+    
     Ok(Json(UpdateItemResponse {
         success: true,
         message: "商品更新成功".to_string(),
@@ -541,3 +983,365 @@ pub async fn admin_get_redemptions(
         purchases: purchases_with_data,
     }))
 }
+
+// ==================== 促销管理 ====================
+
+// 创建限时促销请求
+#[derive(Deserialize)]
+pub struct CreateSaleRequest {
+    pub name: String,
+    pub item_ids: Vec<String>,
+    pub adjustment: PriceAdjustment,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+// 更新限时促销请求
+#[derive(Deserialize)]
+pub struct UpdateSaleRequest {
+    pub id: String,
+    pub name: Option<String>,
+    pub item_ids: Option<Vec<String>>,
+    pub adjustment: Option<PriceAdjustment>,
+    pub starts_at: Option<i64>,
+    pub ends_at: Option<i64>,
+    pub is_active: Option<bool>,
+}
+
+// 管理员获取所有限时促销
+pub async fn admin_get_sales(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<ShopSale>>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let sales = db.get_shop_sales().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(sales))
+}
+
+// 管理员创建限时促销
+pub async fn admin_create_sale(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<CreateSaleRequest>,
+) -> Result<Json<ShopSale>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let sale = ShopSale::new(request.name, request.item_ids, request.adjustment, request.starts_at, request.ends_at);
+    sale.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    db.create_shop_sale(&sale).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // 已开始的活动立即提醒心愿单用户，未开始的由定时任务处理
+    WishlistService::fire_promotions(db);
+    
+    Ok(Json(sale))
+}
+
+// 管理员更新限时促销
+pub async fn admin_update_sale(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<UpdateSaleRequest>,
+) -> Result<Json<ShopSale>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let mut sale = db.get_shop_sale(&request.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    if let Some(name) = request.name {
+        sale.name = name;
+    }
+    
+    if let Some(item_ids) = request.item_ids {
+        sale.item_ids = item_ids;
+    }
+    
+    if let Some(adjustment) = request.adjustment {
+        sale.adjustment = adjustment;
+    }
+    
+    if let Some(starts_at) = request.starts_at {
+        sale.starts_at = starts_at;
+    }
+    
+    if let Some(ends_at) = request.ends_at {
+        sale.ends_at = ends_at;
+    }
+    
+    if let Some(is_active) = request.is_active {
+        sale.is_active = is_active;
+    }
+    
+    sale.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    sale.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+    
+    db.update_shop_sale(&sale).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // 已开始的活动立即提醒心愿单用户，未开始的由定时任务处理
+    WishlistService::fire_promotions(db);
+    
+    Ok(Json(sale))
+}
+
+// 创建秒杀请求
+#[derive(Deserialize)]
+pub struct CreateFlashDealRequest {
+    pub item_id: String,
+    pub price_hp: u32,
+    pub stock: u32,
+    pub starts_at: i64,
+    pub ends_at: i64,
+}
+
+// 更新秒杀请求
+#[derive(Deserialize)]
+pub struct UpdateFlashDealRequest {
+    pub id: String,
+    pub price_hp: Option<u32>,
+    pub stock: Option<u32>,
+    pub starts_at: Option<i64>,
+    pub ends_at: Option<i64>,
+    pub is_active: Option<bool>,
+}
+
+// 管理员获取所有秒杀
+pub async fn admin_get_flash_deals(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<FlashDeal>>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let deals = db.get_flash_deals().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(deals))
+}
+
+// 管理员创建秒杀
+pub async fn admin_create_flash_deal(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<CreateFlashDealRequest>,
+) -> Result<Json<FlashDeal>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    db.get_shop_item(&request.item_id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    let deal = FlashDeal::new(request.item_id, request.price_hp, request.stock, request.starts_at, request.ends_at);
+    deal.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    db.create_flash_deal(&deal).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // 已开始的活动立即提醒心愿单用户，未开始的由定时任务处理
+    WishlistService::fire_promotions(db);
+    
+    Ok(Json(deal))
+}
+
+// 管理员更新秒杀
+pub async fn admin_update_flash_deal(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<UpdateFlashDealRequest>,
+) -> Result<Json<FlashDeal>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let mut deal = db.get_flash_deal(&request.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    if let Some(price_hp) = request.price_hp {
+        deal.price_hp = price_hp;
+    }
+    
+    if let Some(stock) = request.stock {
+        deal.stock = stock;
+    }
+    
+    if let Some(starts_at) = request.starts_at {
+        deal.starts_at = starts_at;
+    }
+    
+    if let Some(ends_at) = request.ends_at {
+        deal.ends_at = ends_at;
+    }
+    
+    if let Some(is_active) = request.is_active {
+        deal.is_active = is_active;
+    }
+    
+    deal.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    deal.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+    
+    db.update_flash_deal(&deal).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    // 已开始的活动立即提醒心愿单用户，未开始的由定时任务处理
+    WishlistService::fire_promotions(db);
+    
+    Ok(Json(deal))
+}
+
+// 创建组合包请求
+#[derive(Deserialize)]
+pub struct CreateBundleRequest {
+    pub name: String,
+    pub description: String,
+    pub image_url: Option<String>,
+    pub item_ids: Vec<String>,
+    pub price_hp: u32,
+    pub stock: Option<u32>,
+    pub starts_at: Option<i64>,
+    pub ends_at: Option<i64>,
+}
+
+// 更新组合包请求
+#[derive(Deserialize)]
+pub struct UpdateBundleRequest {
+    pub id: String,
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub image_url: Option<String>,
+    pub item_ids: Option<Vec<String>>,
+    pub price_hp: Option<u32>,
+    #[serde(default, deserialize_with = "nullable")]
+    pub stock: Option<Option<u32>>,        // 传 null 取消库存限制
+    #[serde(default, deserialize_with = "nullable")]
+    pub starts_at: Option<Option<i64>>,    // 传 null 清除开始时间
+    #[serde(default, deserialize_with = "nullable")]
+    pub ends_at: Option<Option<i64>>,      // 传 null 清除结束时间
+    pub is_active: Option<bool>,
+}
+
+// 组合包内的商品必须都存在
+async fn ensure_items_exist(db: &Database, item_ids: &[String]) -> Result<(), StatusCode> {
+    for item_id in item_ids {
+        db.get_shop_item(item_id).await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+            .ok_or(StatusCode::NOT_FOUND)?;
+    }
+    
+    Ok(())
+}
+
+// 管理员获取所有组合包
+pub async fn admin_get_bundles(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Vec<ShopBundle>>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let bundles = db.get_shop_bundles().await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(bundles))
+}
+
+// 管理员创建组合包
+pub async fn admin_create_bundle(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<CreateBundleRequest>,
+) -> Result<Json<ShopBundle>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    ensure_items_exist(&db, &request.item_ids).await?;
+    
+    let mut bundle = ShopBundle::new(request.name, request.description, request.item_ids, request.price_hp);
+    bundle.image_url = request.image_url;
+    bundle.stock = request.stock;
+    bundle.starts_at = request.starts_at;
+    bundle.ends_at = request.ends_at;
+    
+    bundle.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    
+    db.create_shop_bundle(&bundle).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(bundle))
+}
+
+// 管理员更新组合包
+pub async fn admin_update_bundle(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(request): Json<UpdateBundleRequest>,
+) -> Result<Json<ShopBundle>, StatusCode> {
+    if !auth_user.is_admin() {
+        return Err(StatusCode::FORBIDDEN);
+    }
+    
+    let mut bundle = db.get_shop_bundle(&request.id).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    
+    if let Some(name) = request.name {
+        bundle.name = name;
+    }
+    
+    if let Some(description) = request.description {
+        bundle.description = description;
+    }
+    
+    if let Some(image_url) = request.image_url {
+        bundle.image_url = Some(image_url);
+    }
+    
+    if let Some(item_ids) = request.item_ids {
+        ensure_items_exist(&db, &item_ids).await?;
+        bundle.item_ids = item_ids;
+    }
+    
+    if let Some(price_hp) = request.price_hp {
+        bundle.price_hp = price_hp;
+    }
+    
+    if let Some(stock) = request.stock {
+        bundle.stock = stock;
+    }
+    
+    if let Some(starts_at) = request.starts_at {
+        bundle.starts_at = starts_at;
+    }
+    
+    if let Some(ends_at) = request.ends_at {
+        bundle.ends_at = ends_at;
+    }
+    
+    if let Some(is_active) = request.is_active {
+        bundle.is_active = is_active;
+    }
+    
+    bundle.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    bundle.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+    
+    db.update_shop_bundle(&bundle).await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    
+    Ok(Json(bundle))
+}
@@ -3,22 +3,24 @@ use axum::{
     Json,
     http::StatusCode,
     Router, routing::post,
-    middleware::Next,
-    response::Response,
 };
-use hyper::{Request as AxumRequest, Body};
-use serde::Serialize;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
 
 use crate::{
     middleware::auth::AuthenticatedUser,
+    middleware::admin::{require_admin, write_audit_log},
     db::Database,
     models::{User, PromoterType, FrontendUserRole, VipLevelConfig, VipLevel},
+    models::achievement::Badge,
+    models::checkin::TIMEZONE_CHANGE_COOLDOWN_DAYS,
+    services::{AchievementService, LeaderboardService},
 };
 
 #[derive(Serialize)]
 pub struct ProfileResponse {
     user: User,
+    badges: Vec<Badge>,                 // 已获得的成就徽章
 }
 
 pub async fn get_profile(
@@ -30,7 +32,12 @@ pub async fn get_profile(
         .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
         .ok_or(StatusCode::NOT_FOUND)?;
 
-    Ok(Json(ProfileResponse { user }))
+    let badges = AchievementService::new(db)
+        .get_badges(&user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(ProfileResponse { user, badges }))
 }
 
 #[derive(Serialize)]
@@ -79,27 +86,81 @@ pub async fn apply_for_promoter(
     Ok(StatusCode::OK)
 }
 
-async fn admin_auth(req: AxumRequest<Body>, next: Next, State(db): State<Arc<Database>>) -> Result<Response, StatusCode> {
-    // 从请求中获取用户ID
-    let user_id = req.extensions().get::<AuthenticatedUser>().ok_or(StatusCode::UNAUTHORIZED)?.user_id.clone();
-    
-    // 从数据库中获取最新的用户信息
-    let user = db.get_user_by_id(&user_id).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?.ok_or(StatusCode::UNAUTHORIZED)?;
-    
-    // 检查管理员权限
-    if !user.is_admin() {
-        return Err(StatusCode::FORBIDDEN);
+#[derive(Deserialize)]
+pub struct UpdateTimezoneRequest {
+    utc_offset_minutes: i32,            // 相对UTC的偏移分钟数，如北京时间为480
+}
+
+// 设置用户时区，签到日按该时区计算；首次设置后每7天只能修改一次
+pub async fn update_timezone(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateTimezoneRequest>,
+) -> Result<StatusCode, StatusCode> {
+    // 有效时区范围为 UTC-12:00 到 UTC+14:00
+    if !(-720..=840).contains(&payload.utc_offset_minutes) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
+    let user = db.get_user_by_id(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if user.utc_offset_minutes == Some(payload.utc_offset_minutes) {
+        return Ok(StatusCode::OK);
+    }
+
+    let updated = db.update_user_timezone(
+        &user.id,
+        payload.utc_offset_minutes,
+        OffsetDateTime::now_utc().unix_timestamp(),
+        TIMEZONE_CHANGE_COOLDOWN_DAYS * 24 * 60 * 60,
+    )
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    if !updated {
+        return Err(StatusCode::TOO_MANY_REQUESTS);
     }
-    
-    // 由于类型不匹配问题，我们直接返回一个成功状态而不是继续处理请求
-    // 这是一个临时解决方案，实际应用中可能需要更复杂的处理
-    Ok(Response::new(axum::body::Body::empty()))
+
+    Ok(StatusCode::OK)
+}
+
+#[derive(Deserialize)]
+pub struct UpdateLeaderboardPrivacyRequest {
+    opt_out: bool,
+}
+
+// 设置是否在排行榜中展示
+pub async fn update_leaderboard_privacy(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateLeaderboardPrivacyRequest>,
+) -> Result<StatusCode, StatusCode> {
+    LeaderboardService::new(db)
+        .set_opt_out(&auth_user.user_id, payload.opt_out)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(StatusCode::OK)
 }
 
 pub async fn set_vip_config(
     State(db): State<Database>,
+    auth_user: AuthenticatedUser,
     Json(config): Json<VipLevelConfig>,
 ) -> Result<Json<String>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    // 会员折扣不能达到100%
+    if config.shop_discount_percent.is_some_and(|percent| percent >= 100) {
+        return Err(StatusCode::BAD_REQUEST);
+    }
+
     db.set_vip_config(&config).await.map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    write_audit_log(&db, admin.id, format!("Updated VIP config: {} (shop discount {}%)", config.level.to_string(), config.shop_discount())).await?;
+
     Ok(Json("VIP configuration updated successfully".to_string()))
 }
@@ -0,0 +1,267 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Json,
+    routing::{get, post},
+    Router,
+};
+use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::models::{VipPlan, VipSubscription, VipLevel, PaymentOrder};
+use crate::services::SubscriptionService;
+use crate::middleware::admin::{require_admin, write_audit_log};
+use crate::middleware::auth::AuthenticatedUser;
+
+// 路由配置
+pub fn vip_routes() -> Router<Database> {
+    Router::new()
+        .route("/plans", get(get_vip_plans))
+        .route("/purchase", post(purchase_vip_plan))
+        .route("/subscription", get(get_my_subscription))
+        .route("/subscription/auto-renew", post(set_auto_renew))
+        .route("/orders", get(get_my_orders))
+}
+
+// 管理员路由配置
+pub fn admin_vip_routes() -> Router<Database> {
+    Router::new()
+        .route("/plans", get(admin_get_vip_plans))
+        .route("/plan/create", post(admin_create_vip_plan))
+        .route("/plan/update", post(admin_update_vip_plan))
+}
+
+// ==================== 请求和响应结构 ====================
+
+#[derive(Serialize)]
+pub struct VipPlanListResponse {
+    plans: Vec<VipPlan>,
+}
+
+#[derive(Deserialize)]
+pub struct PurchaseVipRequest {
+    plan_id: String,
+    auto_renew: bool,
+    coupon_id: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct PurchaseVipResponse {
+    success: bool,
+    message: String,
+    order: PaymentOrder,
+    subscription: Option<VipSubscription>,
+}
+
+#[derive(Serialize)]
+pub struct SubscriptionResponse {
+    subscription: Option<VipSubscription>,
+}
+
+#[derive(Deserialize)]
+pub struct SetAutoRenewRequest {
+    enabled: bool,
+}
+
+#[derive(Serialize)]
+pub struct OrderListResponse {
+    orders: Vec<PaymentOrder>,
+}
+
+#[derive(Deserialize)]
+pub struct CreateVipPlanRequest {
+    name: String,
+    level: VipLevel,
+    period_days: u32,
+    price: f32,
+    currency: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateVipPlanRequest {
+    id: String,
+    name: Option<String>,
+    price: Option<f32>,
+    period_days: Option<u32>,
+    is_active: Option<bool>,
+}
+
+#[derive(Serialize)]
+pub struct VipPlanResponse {
+    success: bool,
+    plan: VipPlan,
+}
+
+// ==================== 用户接口 ====================
+
+// 获取可购买的VIP套餐
+pub async fn get_vip_plans(
+    State(db): State<Database>,
+    _auth_user: AuthenticatedUser,
+) -> Result<Json<VipPlanListResponse>, StatusCode> {
+    let plans = db.get_active_vip_plans()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(VipPlanListResponse { plans }))
+}
+
+// 购买VIP套餐
+pub async fn purchase_vip_plan(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<PurchaseVipRequest>,
+) -> Result<Json<PurchaseVipResponse>, StatusCode> {
+    let subscription_service = SubscriptionService::new(db);
+
+    let outcome = subscription_service
+        .purchase_plan(
+            &auth_user.user_id,
+            &payload.plan_id,
+            payload.auto_renew,
+            payload.coupon_id.as_deref(),
+        )
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    let success = outcome.subscription.is_some();
+    let message = if success {
+        "VIP购买成功".to_string()
+    } else {
+        format!("支付失败: {}", outcome.order.failure_reason.clone().unwrap_or_default())
+    };
+
+    Ok(Json(PurchaseVipResponse {
+        success,
+        message,
+        order: outcome.order,
+        subscription: outcome.subscription,
+    }))
+}
+
+// 获取当前订阅
+pub async fn get_my_subscription(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<SubscriptionResponse>, StatusCode> {
+    let subscription_service = SubscriptionService::new(db);
+
+    let subscription = subscription_service.get_subscription(&auth_user.user_id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(SubscriptionResponse { subscription }))
+}
+
+// 开启/关闭自动续费
+pub async fn set_auto_renew(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<SetAutoRenewRequest>,
+) -> Result<Json<SubscriptionResponse>, StatusCode> {
+    let subscription_service = SubscriptionService::new(db);
+
+    let subscription = subscription_service.set_auto_renew(&auth_user.user_id, payload.enabled)
+        .await
+        .map_err(|_| StatusCode::NOT_FOUND)?;
+
+    Ok(Json(SubscriptionResponse { subscription: Some(subscription) }))
+}
+
+// 获取付款订单
+pub async fn get_my_orders(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<OrderListResponse>, StatusCode> {
+    let orders = db.get_user_payment_orders(&auth_user.user_id, 50)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(OrderListResponse { orders }))
+}
+
+// ==================== 管理员接口 ====================
+
+// 获取所有VIP套餐
+pub async fn admin_get_vip_plans(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<VipPlanListResponse>, StatusCode> {
+    require_admin(&db, &auth_user.user_id).await?;
+
+    let plans = db.get_all_vip_plans()
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    Ok(Json(VipPlanListResponse { plans }))
+}
+
+// 创建VIP套餐
+pub async fn admin_create_vip_plan(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<CreateVipPlanRequest>,
+) -> Result<Json<VipPlanResponse>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let plan = VipPlan::new(
+        payload.name,
+        payload.level,
+        payload.period_days,
+        payload.price,
+        payload.currency.unwrap_or_else(|| "USD".to_string()),
+    );
+    plan.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    db.create_vip_plan(&plan)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 记录审计日志
+    write_audit_log(&db, admin.id, format!("Created VIP plan: {}", plan.name)).await?;
+
+    Ok(Json(VipPlanResponse { success: true, plan }))
+}
+
+// 更新VIP套餐
+pub async fn admin_update_vip_plan(
+    State(db): State<Database>,
+    auth_user: AuthenticatedUser,
+    Json(payload): Json<UpdateVipPlanRequest>,
+) -> Result<Json<VipPlanResponse>, StatusCode> {
+    let admin = require_admin(&db, &auth_user.user_id).await?;
+
+    let mut plan = db.get_vip_plan(&payload.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+
+    if let Some(name) = payload.name {
+        plan.name = name;
+    }
+
+    if let Some(price) = payload.price {
+        plan.price = price;
+    }
+
+    if let Some(period_days) = payload.period_days {
+        plan.period_days = period_days;
+    }
+
+    if let Some(is_active) = payload.is_active {
+        plan.is_active = is_active;
+    }
+
+    plan.validate().map_err(|_| StatusCode::BAD_REQUEST)?;
+    plan.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+
+    db.update_vip_plan(&plan)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    // 记录审计日志
+    write_audit_log(&db, admin.id, format!("Updated VIP plan: {}", plan.name)).await?;
+
+    Ok(Json(VipPlanResponse { success: true, plan }))
+}
@@ -0,0 +1,181 @@
+use crate::db::Database;
+use crate::models::{NotificationType, ShopItemType};
+use crate::models::achievement::{
+    Achievement, AchievementGrant, AchievementMetric, AchievementProgress, Badge, UserAchievement,
+};
+use crate::services::NotificationService;
+use anyhow::{Result, anyhow};
+use std::cmp::Reverse;
+
+// 成就服务：送礼、签到、邀请、唤醒AI等事件发生后重新计算对应指标，达成目标时发放奖励
+pub struct AchievementService {
+    db: Database,
+}
+
+impl AchievementService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 在后台处理事件，不阻塞调用方
+    pub fn fire(db: Database, user_id: String, metric: AchievementMetric) {
+        tokio::spawn(async move {
+            if let Err(e) = AchievementService::new(db).evaluate(&user_id, &metric).await {
+                eprintln!("Failed to evaluate achievements: {:?}", e);
+            }
+        });
+    }
+
+    // 校验成就奖励引用的卡券模板和称号
+    pub async fn validate_reward(&self, achievement: &Achievement) -> Result<()> {
+        if let Some(template_id) = &achievement.reward.coupon_template_id {
+            self.db.get_coupon_template(template_id).await?
+                .ok_or_else(|| anyhow!("Coupon template not found"))?;
+        }
+
+        if let Some(item_id) = &achievement.reward.title_item_id {
+            self.db.get_shop_item(item_id).await?
+                .filter(|item| item.item_type == ShopItemType::UserTitle)
+                .ok_or_else(|| anyhow!("Title item not found"))?;
+        }
+
+        Ok(())
+    }
+
+    // 指标的当前值，从业务数据重新统计而不是累加事件，重复触发不会多算
+    async fn current_value(&self, user_id: &str, metric: &AchievementMetric) -> Result<u32> {
+        let value = match metric {
+            AchievementMetric::GiftsSent => self.db.count_user_gifts_sent(user_id).await?,
+            AchievementMetric::CheckinStreak => {
+                self.db.get_user_by_id(user_id).await?
+                    .map(|user| user.daily_checkin_streak)
+                    .unwrap_or(0)
+            }
+            AchievementMetric::InvitesUsed => self.db.count_user_invitees(user_id).await?,
+            AchievementMetric::AisAwakened => self.db.get_user_ais(user_id).await?.len() as u32,
+        };
+
+        Ok(value)
+    }
+
+    // 更新指标下所有成就的进度，返回本次新达成的成就
+    pub async fn evaluate(&self, user_id: &str, metric: &AchievementMetric) -> Result<Vec<Achievement>> {
+        let achievements = self.db.get_active_achievements_by_metric(metric).await?;
+        if achievements.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let value = self.current_value(user_id, metric).await?;
+        let mut completed = Vec::new();
+
+        for achievement in achievements {
+            let record = UserAchievement::new(user_id.to_string(), &achievement, value);
+
+            // 进度没有增长或成就已完成时不会写入，并发触发时只有一次记为新完成
+            if !self.db.save_user_achievement(&record).await? || record.completed_at.is_none() {
+                continue;
+            }
+
+            completed.push(achievement);
+        }
+
+        // 发放本次完成以及之前发放失败的奖励
+        self.grant_pending(user_id).await?;
+
+        Ok(completed)
+    }
+
+    // 发放用户所有待发放的成就奖励，单个成就发放失败时保留待发放标记，下次评估重试
+    pub async fn grant_pending(&self, user_id: &str) -> Result<()> {
+        for record in self.db.get_pending_achievement_rewards(user_id).await? {
+            let Some(achievement) = self.db.get_achievement(&record.achievement_id).await? else {
+                eprintln!("Achievement {} not found for pending reward {}", record.achievement_id, record.id);
+                continue;
+            };
+
+            if let Err(e) = self.grant(&record, &achievement).await {
+                eprintln!("Failed to grant achievement {} to user {}: {:?}", achievement.id, user_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // 发放成就奖励并通知用户
+    async fn grant(&self, record: &UserAchievement, achievement: &Achievement) -> Result<()> {
+        let reward = &achievement.reward;
+        let user_id = &record.user_id;
+
+        let template = match &reward.coupon_template_id {
+            Some(template_id) => {
+                let template = self.db.get_coupon_template(template_id).await?;
+                if template.is_none() {
+                    eprintln!("Achievement {} coupon template {} not found", achievement.id, template_id);
+                }
+                template
+            }
+            None => None,
+        };
+
+        let title_item = match &reward.title_item_id {
+            Some(item_id) => {
+                let item = self.db.get_shop_item(item_id).await?;
+                if item.is_none() {
+                    eprintln!("Achievement {} title item {} not found", achievement.id, item_id);
+                }
+                item
+            }
+            None => None,
+        };
+
+        let grant = AchievementGrant::new(user_id, achievement, template.as_ref(), title_item.as_ref());
+        if !self.db.grant_user_achievement(&record.id, &grant).await? {
+            return Ok(());
+        }
+
+        NotificationService::new(self.db.clone()).notify(
+            user_id,
+            NotificationType::AchievementUnlocked,
+            "达成成就".to_string(),
+            format!("恭喜你达成成就「{}」：{}", achievement.name, achievement.description),
+            Some(achievement.id.clone()),
+        ).await?;
+
+        Ok(())
+    }
+
+    // 获取所有启用的成就及用户进度，已完成但被停用的成就仍然展示
+    pub async fn list_progress(&self, user_id: &str) -> Result<Vec<AchievementProgress>> {
+        let records = self.db.get_user_achievements(user_id).await?;
+
+        let list = self.db.get_achievements().await?
+            .into_iter()
+            .filter_map(|achievement| {
+                let record = records.iter().find(|record| record.achievement_id == achievement.id);
+                let completed_at = record.and_then(|record| record.completed_at);
+                if !achievement.is_active && completed_at.is_none() {
+                    return None;
+                }
+
+                Some(AchievementProgress {
+                    progress: record.map(|record| record.progress).unwrap_or(0),
+                    completed_at,
+                    achievement,
+                })
+            })
+            .collect();
+
+        Ok(list)
+    }
+
+    // 获取用户已获得的徽章，按获得时间倒序
+    pub async fn get_badges(&self, user_id: &str) -> Result<Vec<Badge>> {
+        let mut badges: Vec<Badge> = self.list_progress(user_id).await?
+            .into_iter()
+            .filter_map(|entry| entry.completed_at.map(|at| Badge::new(&entry.achievement, at)))
+            .collect();
+
+        badges.sort_by_key(|badge| Reverse(badge.earned_at));
+        Ok(badges)
+    }
+}
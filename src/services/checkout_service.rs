@@ -0,0 +1,138 @@
+use crate::db::Database;
+use crate::models::{PaymentOrder, OrderType, OrderStatus};
+use crate::models::{Coupon, CouponKind};
+use crate::services::payment_gateway::{self, ChargeRequest, PaymentGateway};
+use crate::services::PointsService;
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 1 USD = 100 LC
+pub const LC_PER_USD: u32 = 100;
+
+// 结算服务：计算卡券优惠并通过支付网关扣款
+pub struct CheckoutService {
+    db: Database,
+    gateway: Box<dyn PaymentGateway>,
+}
+
+impl CheckoutService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            db,
+            gateway: payment_gateway::create_gateway(),
+        }
+    }
+
+    // 校验卡券并计算抵扣金额
+    async fn validate_coupon(&self, user_id: &str, coupon_id: &str, amount: f32) -> Result<(Coupon, f32)> {
+        let coupon = self.db.get_coupon(coupon_id).await?
+            .ok_or_else(|| anyhow!("Coupon not found"))?;
+
+        if coupon.owner_id != user_id {
+            return Err(anyhow!("Coupon does not belong to user"));
+        }
+
+        if !coupon.is_usable() {
+            return Err(anyhow!("Coupon is not available"));
+        }
+
+        let discount_amount = match coupon.kind {
+            // 折扣券按比例打折，如 0.95 即95折
+            CouponKind::Discount { rate } => {
+                if rate <= 0.0 || rate >= 1.0 {
+                    return Err(anyhow!("Invalid discount rate"));
+                }
+                amount - amount * rate
+            }
+            // 现金券按面额抵扣，不超过订单金额
+            CouponKind::Cash { amount: face_value } => face_value.min(amount),
+            // 体验券不能在付款时使用
+            CouponKind::Experience { .. } => return Err(anyhow!("Coupon cannot be applied to payments")),
+        };
+
+        // 金额保留两位小数
+        let discount_amount = (discount_amount * 100.0).round() / 100.0;
+
+        Ok((coupon, discount_amount))
+    }
+
+    // 为订单应用卡券（卡券此时仅做校验，扣款前才占用）
+    pub async fn apply_coupon(&self, order: &mut PaymentOrder, coupon_id: &str) -> Result<()> {
+        let (coupon, discount_amount) = self
+            .validate_coupon(&order.user_id, coupon_id, order.original_amount)
+            .await?;
+
+        order.apply_coupon(coupon.id, discount_amount);
+        Ok(())
+    }
+
+    // 通过支付网关为订单扣款，并更新订单状态
+    pub async fn pay(&self, order: &mut PaymentOrder, description: String) -> Result<bool> {
+        // 扣款前原子占用卡券，防止同一张卡券被并发使用
+        if let Some(coupon_id) = order.coupon_id.clone() {
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            if !self.db.reserve_coupon(&coupon_id, &order.user_id, now).await? {
+                return Err(anyhow!("Coupon is not available"));
+            }
+        }
+
+        let paid = self.charge(order, description).await;
+
+        // 未扣款成功时释放卡券
+        if order.status != OrderStatus::Paid {
+            if let Some(coupon_id) = &order.coupon_id {
+                if let Err(e) = self.db.release_coupon(coupon_id).await {
+                    eprintln!("Failed to release coupon {} for order {}: {:?}", coupon_id, order.id, e);
+                }
+            }
+        }
+
+        paid
+    }
+
+    async fn charge(&self, order: &mut PaymentOrder, description: String) -> Result<bool> {
+        self.db.create_payment_order(order).await?;
+
+        let result = self.gateway.charge(&ChargeRequest {
+            order_id: order.id.clone(),
+            user_id: order.user_id.clone(),
+            amount: order.amount,
+            currency: order.currency.clone(),
+            description,
+        }).await?;
+
+        match (result.success, result.transaction_id) {
+            (true, Some(tx_id)) => order.mark_as_paid(tx_id),
+            _ => order.mark_as_failed(result.failure_reason.unwrap_or_else(|| "Payment declined".to_string())),
+        }
+
+        self.db.update_payment_order(order).await?;
+
+        Ok(order.status == OrderStatus::Paid)
+    }
+
+    // 光币充值：按 1 USD = 100 LC 计价，付款成功后入账
+    pub async fn recharge_lc(&self, user_id: &str, lc_amount: u32, coupon_id: Option<&str>) -> Result<PaymentOrder> {
+        if lc_amount == 0 {
+            return Err(anyhow!("Invalid recharge amount"));
+        }
+
+        let mut order = PaymentOrder::new(
+            user_id.to_string(),
+            OrderType::LcRecharge,
+            None,
+            lc_amount as f32 / LC_PER_USD as f32,
+            "USD".to_string(),
+        );
+
+        if let Some(coupon_id) = coupon_id {
+            self.apply_coupon(&mut order, coupon_id).await?;
+        }
+
+        if self.pay(&mut order, format!("充值 {} 光币", lc_amount)).await? {
+            PointsService::new(self.db.clone()).recharge_lc(user_id, lc_amount).await?;
+        }
+
+        Ok(order)
+    }
+}
@@ -0,0 +1,401 @@
+use std::collections::HashSet;
+use rand::Rng;
+
+use crate::db::Database;
+use crate::db::surreal::is_unique_conflict;
+use crate::models::{Coupon, CouponKind, CouponTemplate, NotificationType, VipLevel};
+use crate::models::coupon::{CouponCampaign, CampaignAudience};
+use crate::models::coupon_code::{CouponCode, CouponCodeBatch, CouponCodeRedemption, CouponCodeBatchStats};
+use crate::models::coupon_transfer::{CouponTransfer, CouponTransferStatus};
+use crate::services::NotificationService;
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 发放活动每处理多少用户保存一次进度
+const CAMPAIGN_PROGRESS_BATCH: u32 = 50;
+// 单个批次最多生成的兑换码数量
+pub const MAX_CODES_PER_BATCH: u32 = 10000;
+// 兑换码默认前缀
+pub const DEFAULT_CODE_PREFIX: &str = "RAINBOW";
+// 兑换码字符集（去掉了易混淆的 0/O/1/I/L）
+const CODE_ALPHABET: &[u8] = b"23456789ABCDEFGHJKMNPQRSTUVWXYZ";
+// 单个兑换码因重复而重新生成的最多次数
+const MAX_CODE_ATTEMPTS: u32 = 5;
+// 每个用户每月最多发起的卡券转赠次数
+pub const MAX_TRANSFERS_PER_MONTH: u32 = 5;
+
+pub struct CouponService {
+    db: Database,
+}
+
+impl CouponService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 校验卡券类型参数
+    pub fn validate_kind(kind: &CouponKind) -> Result<()> {
+        match kind {
+            CouponKind::Experience { level, days } => {
+                if *level == VipLevel::Free || *days == 0 {
+                    return Err(anyhow!("Invalid experience coupon"));
+                }
+            }
+            CouponKind::Discount { rate } => {
+                if *rate <= 0.0 || *rate >= 1.0 {
+                    return Err(anyhow!("Invalid discount rate"));
+                }
+            }
+            CouponKind::Cash { amount } => {
+                if *amount <= 0.0 {
+                    return Err(anyhow!("Invalid cash amount"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // 创建发放活动并在后台执行
+    pub async fn start_campaign(
+        &self,
+        admin_id: &str,
+        name: String,
+        template_id: &str,
+        audience: CampaignAudience,
+    ) -> Result<CouponCampaign> {
+        let template = self.db.get_coupon_template(template_id).await?
+            .filter(|template| template.is_active)
+            .ok_or_else(|| anyhow!("Coupon template not found"))?;
+
+        if let CampaignAudience::RegisteredBetween { start, end } = &audience {
+            if start >= end {
+                return Err(anyhow!("Invalid registration date range"));
+            }
+        }
+
+        let campaign = CouponCampaign::new(name, template.id.clone(), audience, admin_id.to_string());
+        self.db.create_coupon_campaign(&campaign).await?;
+
+        let db = self.db.clone();
+        let job_campaign = campaign.clone();
+        tokio::spawn(async move {
+            run_campaign(db, job_campaign, template).await;
+        });
+
+        Ok(campaign)
+    }
+
+    // 恢复进程重启前未执行完的发放活动，已发放的用户不重复发放；返回恢复的活动数
+    pub async fn resume_campaigns(&self) -> Result<usize> {
+        let mut resumed = 0;
+
+        for mut campaign in self.db.get_unfinished_coupon_campaigns().await? {
+            let Some(template) = self.db.get_coupon_template(&campaign.template_id).await? else {
+                campaign.fail("Coupon template not found".to_string());
+                self.db.update_coupon_campaign(&campaign).await?;
+                continue;
+            };
+
+            let db = self.db.clone();
+            tokio::spawn(async move {
+                run_campaign(db, campaign, template).await;
+            });
+            resumed += 1;
+        }
+
+        Ok(resumed)
+    }
+
+    // ==================== 兑换码 ====================
+
+    // 批量生成兑换码
+    pub async fn generate_code_batch(&self, batch: CouponCodeBatch, prefix: &str) -> Result<(CouponCodeBatch, Vec<CouponCode>)> {
+        self.db.get_coupon_template(&batch.template_id).await?
+            .filter(|template| template.is_active)
+            .ok_or_else(|| anyhow!("Coupon template not found"))?;
+
+        if batch.code_count == 0 || batch.code_count > MAX_CODES_PER_BATCH {
+            return Err(anyhow!("Invalid code count"));
+        }
+
+        if batch.max_redemptions == 0 || batch.per_user_limit == 0 || batch.valid_from >= batch.valid_until {
+            return Err(anyhow!("Invalid batch rules"));
+        }
+
+        self.db.create_coupon_code_batch(&batch).await?;
+
+        let mut codes = Vec::with_capacity(batch.code_count as usize);
+        while codes.len() < batch.code_count as usize {
+            codes.push(self.create_unique_code(&batch.id, prefix).await?);
+        }
+
+        Ok((batch, codes))
+    }
+
+    // 使用兑换码领取卡券
+    pub async fn claim_code(&self, user_id: &str, code: &str) -> Result<Coupon> {
+        let code = self.db.get_coupon_code(&normalize_code(code)).await?
+            .ok_or_else(|| anyhow!("Invalid code"))?;
+
+        let batch = self.db.get_coupon_code_batch(&code.batch_id).await?
+            .filter(|batch| batch.is_valid_now())
+            .ok_or_else(|| anyhow!("Code is not valid at this time"))?;
+
+        let template = self.db.get_coupon_template(&batch.template_id).await?
+            .ok_or_else(|| anyhow!("Coupon template not found"))?;
+
+        if let Some(required_level) = &batch.required_vip_level {
+            let user = self.db.get_user_by_id(user_id).await?
+                .ok_or_else(|| anyhow!("User not found"))?;
+
+            if user.vip_level.rank() < required_level.rank() {
+                return Err(anyhow!("VIP level too low"));
+            }
+        }
+
+        let claimed = self.db.count_user_batch_redemptions(&batch.id, user_id).await?;
+        if claimed >= batch.per_user_limit {
+            return Err(anyhow!("Redemption limit reached"));
+        }
+
+        let coupon = Coupon::new_from_template(&template, user_id.to_string());
+        let mut redemption = CouponCodeRedemption::new(code.id, batch.id.clone(), user_id.to_string(), coupon.id.clone());
+        redemption.id = format!("{}_{}_{}", batch.id, user_id, claimed + 1);
+
+        match self.db.claim_coupon_code(&batch, &coupon, &redemption, claimed).await? {
+            None => Ok(coupon),
+            Some("code fully redeemed") => Err(anyhow!("Code has been fully redeemed")),
+            Some(_) => Err(anyhow!("Redemption limit reached")),
+        }
+    }
+
+    // 生成一个不重复的兑换码，仅在唯一冲突时重试
+    async fn create_unique_code(&self, batch_id: &str, prefix: &str) -> Result<CouponCode> {
+        for _ in 0..MAX_CODE_ATTEMPTS {
+            let code = CouponCode::new(generate_code(prefix), batch_id.to_string());
+            match self.db.create_coupon_code(&code).await {
+                Ok(()) => return Ok(code),
+                Err(e) if is_unique_conflict(&e) => continue,
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Err(anyhow!("Failed to generate a unique code after {} attempts", MAX_CODE_ATTEMPTS))
+    }
+
+    // 批次兑换统计
+    pub async fn get_batch_stats(&self, batch: &CouponCodeBatch) -> Result<CouponCodeBatchStats> {
+        let codes = self.db.get_batch_coupon_codes(&batch.id).await?;
+        let redemptions = self.db.get_batch_redemptions(&batch.id).await?;
+
+        let unique_users: HashSet<&str> = redemptions.iter()
+            .map(|redemption| redemption.user_id.as_str())
+            .collect();
+
+        Ok(CouponCodeBatchStats {
+            batch_id: batch.id.clone(),
+            code_count: codes.len() as u32,
+            redeemed_codes: codes.iter().filter(|code| code.redeemed_count > 0).count() as u32,
+            total_redemptions: redemptions.len() as u32,
+            unique_users: unique_users.len() as u32,
+        })
+    }
+
+    // 导出批次兑换码为CSV
+    pub async fn export_batch_csv(&self, batch: &CouponCodeBatch) -> Result<String> {
+        let codes = self.db.get_batch_coupon_codes(&batch.id).await?;
+
+        let mut csv = String::from("code,max_redemptions,redeemed_count,valid_from,valid_until\n");
+        for code in codes {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                code.code, batch.max_redemptions, code.redeemed_count, batch.valid_from, batch.valid_until,
+            ));
+        }
+
+        Ok(csv)
+    }
+
+    // ==================== 卡券转赠 ====================
+
+    // 发起转赠邀请，卡券在对方处理前被锁定
+    pub async fn offer_transfer(
+        &self,
+        from_user_id: &str,
+        to_user_id: &str,
+        coupon_id: &str,
+        message: Option<String>,
+    ) -> Result<CouponTransfer> {
+        if from_user_id == to_user_id {
+            return Err(anyhow!("Cannot transfer to yourself"));
+        }
+
+        let coupon = self.db.get_coupon(coupon_id).await?
+            .ok_or_else(|| anyhow!("Coupon not found"))?;
+
+        if coupon.owner_id != from_user_id || !coupon.is_transferable || !coupon.is_usable() {
+            return Err(anyhow!("Coupon cannot be transferred"));
+        }
+
+        self.db.get_user_by_id(to_user_id).await?
+            .ok_or_else(|| anyhow!("Recipient not found"))?;
+
+        if !self.db.are_friends(from_user_id, to_user_id).await? {
+            return Err(anyhow!("Coupons can only be transferred to friends"));
+        }
+
+        // 按自然月（UTC）限制转赠次数，在锁定卡券的事务中复核
+        let month_start = OffsetDateTime::now_utc().date().replace_day(1)?
+            .midnight()
+            .assume_utc()
+            .unix_timestamp();
+
+        let transfer = CouponTransfer::new(
+            coupon.id.clone(),
+            coupon.kind.clone(),
+            from_user_id.to_string(),
+            to_user_id.to_string(),
+            message,
+        );
+        match self.db.offer_coupon_transfer(&transfer, month_start, MAX_TRANSFERS_PER_MONTH).await? {
+            Some("transfer limit reached") => return Err(anyhow!("Monthly transfer limit reached")),
+            Some(_) => return Err(anyhow!("Coupon cannot be transferred")),
+            None => {}
+        }
+
+        let _ = NotificationService::new(self.db.clone()).notify(
+            to_user_id,
+            NotificationType::CouponTransfer,
+            "好友向你转赠了一张卡券".to_string(),
+            format!("{}，请在3天内接受", coupon.kind.description()),
+            Some(transfer.id.clone()),
+        ).await;
+
+        Ok(transfer)
+    }
+
+    // 接受或拒绝转赠
+    pub async fn respond_transfer(&self, user_id: &str, transfer_id: &str, accept: bool) -> Result<CouponTransfer> {
+        let mut transfer = self.db.get_coupon_transfer(transfer_id).await?
+            .filter(|transfer| transfer.to_user_id == user_id)
+            .ok_or_else(|| anyhow!("Transfer not found"))?;
+
+        if !transfer.is_pending() {
+            return Err(anyhow!("Transfer is no longer pending"));
+        }
+
+        // 卡券状态和转赠状态在同一事务中按条件更新，不覆盖并发占用卡券的付款
+        transfer.resolve(if accept { CouponTransferStatus::Accepted } else { CouponTransferStatus::Declined });
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        match self.db.resolve_coupon_transfer(&transfer, now).await? {
+            Some("coupon unavailable") => return Err(anyhow!("Coupon is no longer available")),
+            Some(_) => return Err(anyhow!("Transfer is no longer pending")),
+            None => {}
+        }
+
+        let content = if accept {
+            format!("对方已接受你转赠的{}", transfer.coupon_kind.description())
+        } else {
+            format!("对方拒绝了你转赠的{}，卡券已退回", transfer.coupon_kind.description())
+        };
+        let _ = NotificationService::new(self.db.clone()).notify(
+            &transfer.from_user_id,
+            NotificationType::CouponTransfer,
+            "卡券转赠结果".to_string(),
+            content,
+            Some(transfer.id.clone()),
+        ).await;
+
+        Ok(transfer)
+    }
+
+    // 超时的转赠邀请标记为过期，并解锁卡券；已被对方处理的转赠不再变更
+    pub async fn expire_transfer(&self, mut transfer: CouponTransfer) -> Result<()> {
+        transfer.resolve(CouponTransferStatus::Expired);
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.db.resolve_coupon_transfer(&transfer, now).await?;
+        Ok(())
+    }
+}
+
+// 生成形如 RAINBOW-7X3K-9QPL 的兑换码
+fn generate_code(prefix: &str) -> String {
+    let mut rng = rand::thread_rng();
+    let mut group = || -> String {
+        (0..4)
+            .map(|_| CODE_ALPHABET[rng.gen_range(0..CODE_ALPHABET.len())] as char)
+            .collect()
+    };
+
+    format!("{}-{}-{}", prefix, group(), group())
+}
+
+// 用户输入的兑换码统一转为大写并去掉空白
+fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+// 执行发放活动，失败时记录到活动状态中
+async fn run_campaign(db: Database, mut campaign: CouponCampaign, template: CouponTemplate) {
+    if let Err(e) = issue_campaign_coupons(&db, &mut campaign, &template).await {
+        eprintln!("Coupon campaign {} failed: {:?}", campaign.id, e);
+        campaign.fail(e.to_string());
+        if let Err(e) = db.update_coupon_campaign(&campaign).await {
+            eprintln!("Failed to save coupon campaign {}: {:?}", campaign.id, e);
+        }
+    }
+}
+
+async fn issue_campaign_coupons(db: &Database, campaign: &mut CouponCampaign, template: &CouponTemplate) -> Result<()> {
+    let audience = db.get_campaign_audience(&campaign.audience).await?;
+
+    // 中断后恢复执行时，之前已发放的用户计入已发放，不重复发放和通知
+    let issued_before: HashSet<String> = db.get_campaign_recipients(&campaign.id).await?
+        .into_iter()
+        .collect();
+    let mut recipients: HashSet<String> = HashSet::new();
+
+    campaign.start(audience.len() as u32);
+    db.update_coupon_campaign(campaign).await?;
+
+    let notification_service = NotificationService::new(db.clone());
+
+    for user_id in audience {
+        if !recipients.insert(user_id.clone()) {
+            campaign.skipped_count += 1;
+        } else if issued_before.contains(&user_id) {
+            campaign.issued_count += 1;
+        } else {
+            let mut coupon = Coupon::new_from_template(template, user_id.clone());
+            coupon.campaign_id = Some(campaign.id.clone());
+
+            match db.create_coupon(&coupon).await {
+                Ok(()) => {
+                    campaign.issued_count += 1;
+
+                    let _ = notification_service.notify(
+                        &user_id,
+                        NotificationType::CouponIssued,
+                        "你收到了一张新卡券".to_string(),
+                        format!("{} 已发放到你的卡包", template.name),
+                        Some(coupon.id.clone()),
+                    ).await;
+                }
+                Err(e) => {
+                    eprintln!("Failed to issue coupon to {}: {:?}", user_id, e);
+                    campaign.failed_count += 1;
+                }
+            }
+        }
+
+        campaign.processed_users += 1;
+        if campaign.processed_users.is_multiple_of(CAMPAIGN_PROGRESS_BATCH) {
+            db.update_coupon_campaign(campaign).await?;
+        }
+    }
+
+    campaign.finish();
+    db.update_coupon_campaign(campaign).await?;
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::models::{Coupon, LuckyCard, TxType};
+use crate::models::drop_table::{DropTable, DropReward, DropLog, LUCKY_CARD_DROP_KEY};
+use anyhow::{Result, anyhow};
+
+// 掉落服务：按掉落表抽取随机奖励，记录每次抽取结果
+pub struct DropService {
+    db: Database,
+}
+
+impl DropService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 获取场景当前生效的掉落表，幸运卡场景未配置时使用默认掉落表
+    pub async fn get_live_table(&self, key: &str) -> Result<Option<DropTable>> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let table = self.db.get_live_drop_table(key, now).await?;
+
+        if table.is_none() && key == LUCKY_CARD_DROP_KEY {
+            return Ok(Some(DropTable::default_lucky_card()));
+        }
+
+        Ok(table)
+    }
+
+    // 为用户抽取一次奖励并发放，场景没有生效的掉落表时返回 None
+    pub async fn roll(&self, user_id: &str, key: &str, source_id: Option<String>) -> Result<Option<DropLog>> {
+        let Some(table) = self.get_live_table(key).await? else {
+            return Ok(None);
+        };
+
+        let user = self.db.get_user_by_id(user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+
+        let pity_misses = match table.pity_threshold {
+            Some(_) => self.db.get_drop_pity(user_id, &table.id).await?
+                .map(|pity| pity.misses)
+                .unwrap_or(0),
+            None => 0,
+        };
+
+        // 每次抽取使用独立种子并记录，便于复现和审计
+        let seed: u64 = rand::random();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let outcome = table.roll(&mut rng, &user.vip_level, pity_misses);
+
+        let mut log = DropLog::new(&table, user_id.to_string(), user.vip_level.clone(), seed, pity_misses, &outcome);
+        log.granted_entity_id = self.grant(user_id, &outcome.reward, &mut rng, source_id).await?;
+
+        let rare_hit = table.pity_threshold.map(|_| outcome.is_rare);
+        self.db.create_drop_log(&log, rare_hit).await?;
+        Ok(Some(log))
+    }
+
+    // 发放奖励，返回生成的幸运卡/卡券ID
+    async fn grant(
+        &self,
+        user_id: &str,
+        reward: &DropReward,
+        rng: &mut StdRng,
+        source_id: Option<String>,
+    ) -> Result<Option<String>> {
+        match reward {
+            DropReward::Nothing => Ok(None),
+            DropReward::LuckyCard { level, min_multiplier, max_multiplier } => {
+                let min = min_multiplier.unwrap_or_else(|| level.min_multiplier());
+                let max = max_multiplier.unwrap_or_else(|| level.max_multiplier());
+                let multiplier = rng.gen_range(min..=max);
+
+                let card = LuckyCard::with_multiplier(level.clone(), user_id.to_string(), source_id, multiplier);
+                self.db.create_lucky_card(&card).await?;
+                Ok(Some(card.id))
+            }
+            DropReward::Hp { amount } => {
+                self.db.add_user_hp(
+                    user_id,
+                    *amount,
+                    TxType::Reward,
+                    None,
+                    Some("随机掉落奖励".to_string()),
+                ).await?;
+                Ok(None)
+            }
+            DropReward::Coupon { template_id } => {
+                let template = self.db.get_coupon_template(template_id).await?
+                    .filter(|template| template.is_active)
+                    .ok_or_else(|| anyhow!("Coupon template not found"))?;
+
+                let coupon = Coupon::new_from_template(&template, user_id.to_string());
+                self.db.create_coupon(&coupon).await?;
+                Ok(Some(coupon.id))
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::db::Database;
+use crate::models::{User, WalletTx, CurrencyType};
+use crate::models::earning_rule::{EarningCap, EarningRule, EarningSource, HeldReward, HeldRewardStatus};
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 异常获取检测的统计窗口
+const VELOCITY_WINDOW_SECS: i64 = 60 * 60;
+
+// 积分获取的规则判定结果
+#[derive(Debug, Clone, PartialEq)]
+pub enum EarningDecision {
+    Credit(u32, Vec<EarningCap>),   // 按上限裁剪后可入账的积分，及入账时复核的上限
+    Hold(u32, String),              // 暂扣待审核，附带原因
+    Capped,                         // 已达获取上限
+}
+
+// 积分获取规则服务：按来源限制每日、每周和终身获取量，并暂扣异常用户的奖励
+pub struct EarningService {
+    db: Database,
+}
+
+impl EarningService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 获取来源的规则，未配置时使用默认规则
+    pub async fn get_rule(&self, source: EarningSource) -> Result<EarningRule> {
+        let rule = self.db.get_earning_rule(&source).await?;
+        Ok(rule.unwrap_or_else(|| EarningRule::default_for(source)))
+    }
+
+    // 判定本次奖励能发放多少积分
+    pub async fn evaluate(&self, user: &User, source: &EarningSource, points: u32) -> Result<EarningDecision> {
+        let rule = self.get_rule(source.clone()).await?;
+        let now = OffsetDateTime::now_utc();
+
+        // 超出任一窗口上限的部分不发放；并发入账由入账事务按 caps 复核
+        let mut allowed = points;
+        let mut caps = Vec::new();
+        for (window, max) in rule.windows() {
+            let since = window.start(now).unwrap_or(0);
+            let earned = self.db.sum_user_earnings(&user.id, source, since).await?;
+            allowed = allowed.min(max.saturating_sub(earned));
+            caps.push(EarningCap { since, max });
+        }
+
+        if allowed == 0 {
+            return Ok(EarningDecision::Capped);
+        }
+
+        if user.earning_flagged_at.is_some() {
+            return Ok(EarningDecision::Hold(allowed, "用户积分获取异常，待审核".to_string()));
+        }
+
+        if let Some(limit) = rule.velocity_limit {
+            let since = now.unix_timestamp() - VELOCITY_WINDOW_SECS;
+            let recent = self.db.sum_user_earnings(&user.id, source, since).await?;
+            if recent + allowed > limit {
+                let reason = format!("一小时内从{}获取积分超过{}", source.as_str(), limit);
+                self.db.flag_user_earnings(&user.id, &reason).await?;
+                return Ok(EarningDecision::Hold(allowed, reason));
+            }
+        }
+
+        Ok(EarningDecision::Credit(allowed, caps))
+    }
+
+    // 审核暂扣积分，通过时在同一事务中更新状态并按原交易类型入账
+    pub async fn review(&self, reward_id: &str, approved: bool, admin_id: &str) -> Result<HeldReward> {
+        if !approved {
+            return self.db.review_held_reward(reward_id, &HeldRewardStatus::Rejected, admin_id).await?
+                .ok_or_else(|| anyhow!("Held reward not found or already reviewed"));
+        }
+
+        let reward = self.db.get_held_reward(reward_id).await?
+            .filter(|reward| reward.status == HeldRewardStatus::Pending)
+            .ok_or_else(|| anyhow!("Held reward not found or already reviewed"))?;
+
+        let tx = WalletTx::new(
+            reward.user_id.clone(),
+            reward.tx_type.clone(),
+            reward.amount,
+            CurrencyType::HP,
+            reward.related_entity_id.clone(),
+            reward.remark.clone(),
+        ).with_earning_source(reward.source.clone());
+
+        self.db.approve_held_reward(reward_id, admin_id, &tx).await?
+            .ok_or_else(|| anyhow!("Held reward not found or already reviewed"))
+    }
+}
@@ -3,7 +3,7 @@ use lettre::{
     AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
 };
 use std::env;
-use anyhow::Result;
+use anyhow::{Context, Result};
 use crate::models::EmailVerification;
 
 pub struct EmailService {
@@ -15,15 +15,15 @@ pub struct EmailService {
 
 impl EmailService {
     pub fn new() -> Result<Self> {
-        // 从环境变量中读取SMTP配置
-        let smtp_server = env::var("SMTP_SERVER").expect("SMTP_SERVER must be set");
-        let smtp_port = env::var("SMTP_PORT").expect("SMTP_PORT must be set")
-            .parse::<u16>().expect("SMTP_PORT must be a valid port number");
-        let smtp_username = env::var("SMTP_USERNAME").expect("SMTP_USERNAME must be set");
-        let smtp_password = env::var("SMTP_PASSWORD").expect("SMTP_PASSWORD must be set");
-        let from_email = env::var("FROM_EMAIL").expect("FROM_EMAIL must be set");
+        // 从环境变量中读取SMTP配置，未配置时返回错误而不是panic
+        let smtp_server = env::var("SMTP_SERVER").context("SMTP_SERVER must be set")?;
+        let smtp_port = env::var("SMTP_PORT").context("SMTP_PORT must be set")?
+            .parse::<u16>().context("SMTP_PORT must be a valid port number")?;
+        let smtp_username = env::var("SMTP_USERNAME").context("SMTP_USERNAME must be set")?;
+        let smtp_password = env::var("SMTP_PASSWORD").context("SMTP_PASSWORD must be set")?;
+        let from_email = env::var("FROM_EMAIL").context("FROM_EMAIL must be set")?;
         let app_name = env::var("APP_NAME").unwrap_or_else(|_| "彩虹城".to_string());
-        let app_url = env::var("APP_URL").expect("APP_URL must be set");
+        let app_url = env::var("APP_URL").context("APP_URL must be set")?;
 
         // 创建SMTP传输
         let creds = Credentials::new(smtp_username, smtp_password);
@@ -80,4 +80,62 @@ impl EmailService {
         self.smtp_transport.send(email).await?;
         Ok(())
     }
+
+    // 发送普通通知邮件
+    pub async fn send_notification_email(&self, to: &str, subject: String, body: String) -> Result<()> {
+        let email = Message::builder()
+            .from(format!("{} <{}>", self.app_name, self.from_email).parse()?)
+            .to(to.parse()?)
+            .subject(subject)
+            .body(body)?;
+
+        self.smtp_transport.send(email).await?;
+        Ok(())
+    }
+
+    // 发送VIP续费失败提醒邮件
+    pub async fn send_renewal_failed_email(&self, to: &str, plan_name: &str, grace_until: i64) -> Result<()> {
+        let grace_date = time::OffsetDateTime::from_unix_timestamp(grace_until)?.date();
+        let subject = format!("{}会员续费失败提醒", self.app_name);
+        let body = format!(
+            "亲爱的用户，\n\n您的{}自动续费扣款失败。我们会在宽限期内继续尝试扣款，请确保支付方式可用。\n\n宽限期截止日期：{}\n宽限期结束后仍未续费成功，会员权益将自动失效。\n\n管理订阅：{}/vip\n\n祝好，\n{}团队",
+            plan_name, grace_date, self.app_url, self.app_name
+        );
+
+        self.send_notification_email(to, subject, body).await
+    }
+
+    // 发送卡券即将过期提醒邮件
+    pub async fn send_coupon_expiring_email(&self, to: &str, coupon_name: &str, expires_at: i64) -> Result<()> {
+        let expires_at = time::OffsetDateTime::from_unix_timestamp(expires_at)?;
+        let subject = format!("{}卡券即将过期提醒", self.app_name);
+        let body = format!(
+            "亲爱的用户，\n\n您的{}将于 {} {:02}:{:02} (UTC) 过期，请尽快使用。\n\n查看卡包：{}/coupon\n\n祝好，\n{}团队",
+            coupon_name, expires_at.date(), expires_at.hour(), expires_at.minute(), self.app_url, self.app_name
+        );
+
+        self.send_notification_email(to, subject, body).await
+    }
+
+    // 发送心愿单商品补货或促销提醒邮件
+    pub async fn send_wishlist_email(&self, to: &str, subject: &str, message: &str) -> Result<()> {
+        let subject = format!("{}{}", self.app_name, subject);
+        let body = format!(
+            "亲爱的用户，\n\n{}\n\n前往积分商城：{}/store\n\n祝好，\n{}团队",
+            message, self.app_url, self.app_name
+        );
+
+        self.send_notification_email(to, subject, body).await
+    }
+
+    // 发送VIP到期通知邮件
+    pub async fn send_subscription_expired_email(&self, to: &str, plan_name: &str) -> Result<()> {
+        let subject = format!("{}会员已到期", self.app_name);
+        let body = format!(
+            "亲爱的用户，\n\n您的{}已到期，会员权益已停止。\n\n随时可以重新订阅：{}/vip\n\n祝好，\n{}团队",
+            plan_name, self.app_url, self.app_name
+        );
+
+        self.send_notification_email(to, subject, body).await
+    }
 }
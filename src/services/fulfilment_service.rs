@@ -0,0 +1,96 @@
+use crate::db::Database;
+use crate::models::{Coupon, ShopItemType, PurchaseRecord};
+use crate::models::inventory::{InventoryItem, PurchaseGrant};
+use anyhow::{Result, anyhow};
+
+// 积分商城履约服务：按商品类型发放兑换的效果
+pub struct FulfilmentService {
+    db: Database,
+}
+
+impl FulfilmentService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 激活购买记录并发放商品效果，激活状态和效果在同一事务中写入，并发激活只有一次成功
+    pub async fn activate_purchase(&self, user_id: &str, purchase_id: &str) -> Result<PurchaseRecord> {
+        let mut purchase = self.db.get_purchase_record(purchase_id).await?
+            .filter(|purchase| purchase.user_id == user_id)
+            .ok_or_else(|| anyhow!("Purchase not found"))?;
+
+        let item = self.db.get_shop_item(&purchase.item_id).await?
+            .ok_or_else(|| anyhow!("Shop item not found"))?;
+
+        purchase.activate().map_err(|e| anyhow!(e))?;
+
+        let mut grant = PurchaseGrant::new(&item, &purchase);
+
+        // 关联卡券的商品同时发放卡券
+        if let Some(template_id) = &item.linked_coupon_id {
+            let template = self.db.get_coupon_template(template_id).await?
+                .ok_or_else(|| anyhow!("Linked coupon template not found"))?;
+            grant.coupon = Some(Coupon::new_from_template(&template, purchase.user_id.clone()));
+        }
+
+        if !self.db.activate_purchase_record(&purchase, &grant).await? {
+            return Err(anyhow!("物品已激活"));
+        }
+
+        Ok(purchase)
+    }
+
+    // ==================== 背包 ====================
+
+    async fn get_owned_item(&self, user_id: &str, inventory_id: &str) -> Result<InventoryItem> {
+        let item = self.db.get_inventory_item(inventory_id).await?
+            .filter(|item| item.user_id == user_id)
+            .ok_or_else(|| anyhow!("Inventory item not found"))?;
+
+        if !item.is_equippable() || item.is_expired() {
+            return Err(anyhow!("Item cannot be equipped"));
+        }
+
+        Ok(item)
+    }
+
+    // 装备称号或AI装饰，装饰需要指定自己的AI
+    pub async fn equip(&self, user_id: &str, inventory_id: &str, ai_id: Option<&str>) -> Result<InventoryItem> {
+        let mut item = self.get_owned_item(user_id, inventory_id).await?;
+
+        match item.item_type {
+            ShopItemType::UserTitle => {
+                // 同一时间只能佩戴一个称号
+                self.db.unequip_user_titles(user_id).await?;
+                item.equipped_ai_id = None;
+            }
+            ShopItemType::AIDecoration => {
+                let ai_id = ai_id.ok_or_else(|| anyhow!("AI is required for decorations"))?;
+                self.db.get_ai(ai_id).await?
+                    .filter(|ai| ai.user_id == user_id)
+                    .ok_or_else(|| anyhow!("AI not found"))?;
+
+                // 每个AI同一时间只能使用一个装饰
+                self.db.unequip_ai_decorations(ai_id).await?;
+                item.equipped_ai_id = Some(ai_id.to_string());
+            }
+            _ => return Err(anyhow!("Item cannot be equipped")),
+        }
+
+        item.is_equipped = true;
+        self.db.update_inventory_item(&item).await?;
+        Ok(item)
+    }
+
+    // 卸下称号或装饰
+    pub async fn unequip(&self, user_id: &str, inventory_id: &str) -> Result<InventoryItem> {
+        let mut item = self.db.get_inventory_item(inventory_id).await?
+            .filter(|item| item.user_id == user_id && item.is_equippable())
+            .ok_or_else(|| anyhow!("Inventory item not found"))?;
+
+        item.is_equipped = false;
+        item.equipped_ai_id = None;
+        self.db.update_inventory_item(&item).await?;
+        Ok(item)
+    }
+}
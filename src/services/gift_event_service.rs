@@ -0,0 +1,265 @@
+use crate::db::Database;
+use crate::models::{Gift, NotificationType, ShopItemType};
+use crate::models::gift_event::{
+    GiftEvent, GiftEventCatalogue, GiftEventClaim, GiftEventEntry, GiftEventGrant, GiftEventResults, GiftEventScore,
+    SETTLEMENT_GRACE_SECS,
+};
+use crate::services::NotificationService;
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 活动结束时保存的最终排名数，不少于奖励覆盖的名次
+const FINAL_STANDINGS_SIZE: u32 = 100;
+
+// 礼物活动服务：活动期间上架主题礼物并累计活动送礼榜，活动结束后按排名发放奖励
+pub struct GiftEventService {
+    db: Database,
+}
+
+impl GiftEventService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 送礼后在后台累计送礼时进行中活动的送礼分数，不阻塞调用方；活动结束后等待一段时间再结算
+    pub fn record_gift(db: Database, gift_id: String, sender_id: String, emotional_value: u32, sent_at: i64) {
+        tokio::spawn(async move {
+            let service = GiftEventService::new(db);
+            if let Err(e) = service.on_gift(&gift_id, &sender_id, emotional_value, sent_at).await {
+                eprintln!("Failed to update gift event scores: {:?}", e);
+            }
+        });
+    }
+
+    async fn on_gift(&self, gift_id: &str, sender_id: &str, emotional_value: u32, sent_at: i64) -> Result<()> {
+        if emotional_value == 0 {
+            return Ok(());
+        }
+
+        let scores: Vec<GiftEventScore> = self.db.get_running_gift_events(sent_at, Some(gift_id)).await?
+            .into_iter()
+            .map(|event| GiftEventScore::new(event.id, sender_id.to_string(), emotional_value, sent_at))
+            .collect();
+
+        if !scores.is_empty() {
+            self.db.increment_gift_event_scores(&scores).await?;
+        }
+
+        Ok(())
+    }
+
+    // 校验活动引用的礼物、卡券模板和称号
+    pub async fn validate_references(&self, event: &GiftEvent) -> Result<()> {
+        for gift_id in &event.gift_ids {
+            self.db.get_gift_by_id(gift_id).await?
+                .ok_or_else(|| anyhow!("Gift not found"))?;
+        }
+
+        for reward in &event.rewards {
+            if let Some(template_id) = &reward.coupon_template_id {
+                self.db.get_coupon_template(template_id).await?
+                    .ok_or_else(|| anyhow!("Coupon template not found"))?;
+            }
+
+            if let Some(item_id) = &reward.title_item_id {
+                self.db.get_shop_item(item_id).await?
+                    .filter(|item| item.item_type == ShopItemType::UserTitle)
+                    .ok_or_else(|| anyhow!("Title item not found"))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    // 进行中活动的主题礼物，活动礼物即使未上架也可在活动期间赠送
+    pub async fn get_running_event_gifts(&self) -> Result<Vec<Gift>> {
+        let mut gifts: Vec<Gift> = Vec::new();
+
+        for catalogue in self.get_active_catalogues().await? {
+            for gift in catalogue.gifts {
+                if !gifts.iter().any(|existing| existing.id == gift.id) {
+                    gifts.push(gift);
+                }
+            }
+        }
+
+        Ok(gifts)
+    }
+
+    // 礼物是否属于进行中的活动
+    pub async fn is_event_gift(&self, gift_id: &str) -> Result<bool> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        Ok(!self.db.get_running_gift_events(now, Some(gift_id)).await?.is_empty())
+    }
+
+    // 获取进行中的活动及其主题礼物
+    pub async fn get_active_catalogues(&self) -> Result<Vec<GiftEventCatalogue>> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut catalogues = Vec::new();
+
+        for event in self.db.get_running_gift_events(now, None).await? {
+            let mut gifts = Vec::new();
+            for gift_id in &event.gift_ids {
+                if let Some(gift) = self.db.get_gift_by_id(gift_id).await? {
+                    gifts.push(gift);
+                }
+            }
+            catalogues.push(GiftEventCatalogue { event, gifts });
+        }
+
+        Ok(catalogues)
+    }
+
+    // 获取活动结果；活动已过结算等待期但尚未结算时先结算
+    pub async fn get_results(&self, event_id: &str, user_id: &str, page: usize, limit: usize) -> Result<GiftEventResults> {
+        let mut event = self.db.get_gift_event(event_id).await?
+            .filter(|event| event.is_active)
+            .ok_or_else(|| anyhow!("Gift event not found"))?;
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if event.is_settleable(now) && event.rewards_distributed_at.is_none() {
+            self.settle(&event).await?;
+            event = self.db.get_gift_event(event_id).await?
+                .ok_or_else(|| anyhow!("Gift event not found"))?;
+        }
+
+        let offset = page.saturating_sub(1) * limit;
+
+        if event.rewards_distributed_at.is_some() {
+            let mine = event.final_standings.iter().find(|entry| entry.user_id == user_id).cloned();
+            let entries = event.final_standings.iter().skip(offset).take(limit).cloned().collect();
+            return Ok(GiftEventResults { event, entries, mine });
+        }
+
+        let entries = self.db.get_gift_event_scores(event_id, limit, offset).await?
+            .iter()
+            .enumerate()
+            .map(|(index, score)| Self::entry(score, (offset + index + 1) as u32))
+            .collect();
+
+        let mine = match self.db.get_gift_event_score(event_id, user_id).await? {
+            Some(score) => {
+                let ahead = self.db.count_gift_event_scores_ahead(&score).await?;
+                Some(Self::entry(&score, ahead + 1))
+            }
+            None => None,
+        };
+
+        Ok(GiftEventResults { event, entries, mine })
+    }
+
+    fn entry(score: &GiftEventScore, rank: u32) -> GiftEventEntry {
+        GiftEventEntry {
+            rank,
+            user_id: score.user_id.clone(),
+            score: score.score,
+        }
+    }
+
+    // 结算所有已过结算等待期的活动并重试发放失败的奖励，返回本次结算的活动数
+    pub async fn settle_ended_events(&self) -> Result<usize> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut settled = 0;
+
+        for event in self.db.get_unsettled_gift_events(now - SETTLEMENT_GRACE_SECS).await? {
+            if self.settle(&event).await? {
+                settled += 1;
+            }
+        }
+
+        self.grant_pending(None).await?;
+
+        Ok(settled)
+    }
+
+    // 保存最终排名并写入获奖用户的待发放记录，随后发放奖励；并发结算时只有一次能成功标记
+    async fn settle(&self, event: &GiftEvent) -> Result<bool> {
+        let size = event.rewarded_ranks().max(FINAL_STANDINGS_SIZE);
+        let standings: Vec<GiftEventEntry> = self.db.get_gift_event_scores(&event.id, size as usize, 0).await?
+            .iter()
+            .enumerate()
+            .map(|(index, score)| Self::entry(score, index as u32 + 1))
+            .collect();
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let claims: Vec<GiftEventClaim> = standings
+            .iter()
+            .filter(|entry| event.reward_for(entry.rank).is_some())
+            .map(|entry| GiftEventClaim::new(&event.id, entry, now))
+            .collect();
+
+        if !self.db.settle_gift_event(&event.id, &standings, &claims, now).await? {
+            return Ok(false);
+        }
+
+        self.grant_pending(Some(&event.id)).await?;
+
+        Ok(true)
+    }
+
+    // 发放待发放的活动奖励，单个用户发放失败时保留待发放标记，下次结算任务重试
+    async fn grant_pending(&self, event_id: Option<&str>) -> Result<()> {
+        let mut event: Option<GiftEvent> = None;
+
+        for claim in self.db.get_pending_gift_event_claims(event_id).await? {
+            if event.as_ref().map(|event| event.id.as_str()) != Some(claim.event_id.as_str()) {
+                event = self.db.get_gift_event(&claim.event_id).await?;
+            }
+            let Some(event) = &event else {
+                eprintln!("Gift event {} not found for pending reward {}", claim.event_id, claim.id);
+                continue;
+            };
+
+            if let Err(e) = self.grant(event, &claim).await {
+                eprintln!("Failed to grant gift event {} reward to {}: {:?}", event.id, claim.user_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // 发放活动排名奖励并通知用户
+    async fn grant(&self, event: &GiftEvent, claim: &GiftEventClaim) -> Result<()> {
+        let Some(reward) = event.reward_for(claim.rank) else {
+            eprintln!("Gift event {} has no reward for rank {}", event.id, claim.rank);
+            return Ok(());
+        };
+
+        let template = match &reward.coupon_template_id {
+            Some(template_id) => {
+                let template = self.db.get_coupon_template(template_id).await?;
+                if template.is_none() {
+                    eprintln!("Gift event {} coupon template {} not found", event.id, template_id);
+                }
+                template
+            }
+            None => None,
+        };
+
+        let title_item = match &reward.title_item_id {
+            Some(item_id) => {
+                let item = self.db.get_shop_item(item_id).await?;
+                if item.is_none() {
+                    eprintln!("Gift event {} title item {} not found", event.id, item_id);
+                }
+                item
+            }
+            None => None,
+        };
+
+        let grant = GiftEventGrant::new(event, claim, reward, template.as_ref(), title_item.as_ref());
+        if !self.db.grant_gift_event_claim(&claim.id, &grant).await? {
+            return Ok(());
+        }
+
+        NotificationService::new(self.db.clone()).notify(
+            &claim.user_id,
+            NotificationType::GiftEventReward,
+            "活动奖励".to_string(),
+            format!("「{}」活动已结束，你在送礼榜排名第{}，奖励已发放", event.name, claim.rank),
+            Some(event.id.clone()),
+        ).await?;
+
+        Ok(())
+    }
+}
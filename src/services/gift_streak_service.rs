@@ -0,0 +1,249 @@
+use crate::db::Database;
+use crate::models::NotificationType;
+use crate::models::gift::{ConsecutiveGiftRecord, GiftStreakUpdate};
+use crate::models::gift_streak::{GiftStreakClaim, GiftStreakGrant, GiftStreakStatus};
+use crate::models::lucky_trigger::{LuckyEvent, LuckyEventType};
+use crate::services::{LuckyEventService, NotificationService};
+use crate::services::websocket;
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 连续送礼服务：送礼后检查里程碑奖励，连续中断或消耗冻结卡时通知用户；定时检查已中断的连续送礼
+pub struct GiftStreakService {
+    db: Database,
+}
+
+impl GiftStreakService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 送礼后在后台处理，不阻塞调用方
+    pub fn fire(db: Database, update: GiftStreakUpdate, utc_offset_secs: i64) {
+        tokio::spawn(async move {
+            let service = GiftStreakService::new(db);
+            if let Err(e) = service.on_streak_updated(&update, utc_offset_secs).await {
+                eprintln!("Failed to process gift streak: {:?}", e);
+            }
+        });
+    }
+
+    async fn on_streak_updated(&self, update: &GiftStreakUpdate, utc_offset_secs: i64) -> Result<()> {
+        let streak = &update.streak;
+
+        // 同一天内再次送礼，连续天数不变，只重试之前发放失败的奖励
+        if streak.consecutive_days != update.previous_days {
+            self.on_new_day(update, utc_offset_secs).await?;
+        }
+
+        self.grant_pending(&streak.user_id).await
+    }
+
+    // 当天首次送礼：通知冻结卡消耗或连续中断，达到里程碑时写入待发放记录
+    async fn on_new_day(&self, update: &GiftStreakUpdate, utc_offset_secs: i64) -> Result<()> {
+        let streak = &update.streak;
+        let Some(ai) = self.db.get_ai(&streak.ai_id).await? else {
+            return Ok(());
+        };
+        let notifications = NotificationService::new(self.db.clone());
+
+        if update.freezes_used > 0 {
+            notifications.notify(
+                &streak.user_id,
+                NotificationType::GiftStreakFreezeUsed,
+                "连续送礼已保住".to_string(),
+                format!(
+                    "你与{}的连续送礼中断了{}天，已自动使用{}张冻结卡，当前连续{}天",
+                    ai.name, update.freezes_used, update.freezes_used, streak.consecutive_days,
+                ),
+                Some(streak.id.clone()),
+            ).await?;
+        }
+
+        // 定时检查已通知过中断时不再重复通知
+        if update.previous_days > 1 && streak.consecutive_days == 1 && !update.break_notified {
+            notifications.notify(
+                &streak.user_id,
+                NotificationType::GiftStreakBroken,
+                "连续送礼已中断".to_string(),
+                format!("你与{}的{}天连续送礼已中断，今天重新从第1天开始", ai.name, update.previous_days),
+                Some(streak.id.clone()),
+            ).await?;
+        }
+
+        let milestones = self.db.get_active_gift_streak_milestones().await?
+            .into_iter()
+            .filter(|milestone| milestone.days == streak.consecutive_days);
+
+        // 并发送礼时只有一次能写入待发放记录
+        for milestone in milestones {
+            self.db.create_gift_streak_claim(&GiftStreakClaim::new(streak, &milestone, utc_offset_secs)).await?;
+        }
+
+        Ok(())
+    }
+
+    // 发放用户所有待发放的里程碑奖励，单个奖励发放失败时保留待发放标记，下次送礼或定时检查重试
+    pub async fn grant_pending(&self, user_id: &str) -> Result<()> {
+        for claim in self.db.get_pending_gift_streak_claims(user_id).await? {
+            if let Err(e) = self.grant(&claim).await {
+                eprintln!("Failed to grant gift streak reward {}: {:?}", claim.id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // 重试所有用户待发放的里程碑奖励
+    pub async fn grant_all_pending(&self) -> Result<()> {
+        for claim in self.db.get_all_pending_gift_streak_claims().await? {
+            if let Err(e) = self.grant(&claim).await {
+                eprintln!("Failed to grant gift streak reward {}: {:?}", claim.id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    // 发放里程碑奖励并通知用户
+    async fn grant(&self, claim: &GiftStreakClaim) -> Result<()> {
+        let Some(milestone) = self.db.get_gift_streak_milestone(&claim.milestone_id).await? else {
+            eprintln!("Gift streak milestone {} not found for pending reward {}", claim.milestone_id, claim.id);
+            return Ok(());
+        };
+        let Some(ai) = self.db.get_ai(&claim.ai_id).await? else {
+            eprintln!("AI {} not found for pending gift streak reward {}", claim.ai_id, claim.id);
+            return Ok(());
+        };
+        let Some(streak) = self.db.get_consecutive_gift_record(&claim.user_id, &claim.ai_id).await? else {
+            eprintln!("Gift streak not found for pending reward {}", claim.id);
+            return Ok(());
+        };
+
+        let grant = GiftStreakGrant::new(claim, &milestone, &ai);
+        let Some(boosted) = self.db.grant_gift_streak_claim(&claim.id, &streak.id, &grant).await? else {
+            return Ok(());
+        };
+
+        if grant.relationship_boost > 0 {
+            let previous_level = ConsecutiveGiftRecord::level_for(
+                boosted.total_emotional_value.saturating_sub(grant.relationship_boost),
+            );
+            if boosted.relationship_level() > previous_level {
+                LuckyEventService::fire(
+                    self.db.clone(),
+                    LuckyEvent::new(
+                        LuckyEventType::RelationshipLevelUp,
+                        claim.user_id.clone(),
+                        Some(ai.id.clone()),
+                        boosted.relationship_level(),
+                    ),
+                );
+            }
+        }
+
+        if let Some(message) = &grant.message {
+            if let Ok(data) = serde_json::to_value(message) {
+                websocket::push_to_user(&claim.user_id, "message", data).await;
+            }
+        }
+
+        NotificationService::new(self.db.clone()).notify(
+            &claim.user_id,
+            NotificationType::GiftStreakMilestone,
+            "连续送礼里程碑".to_string(),
+            format!("你已连续{}天为{}送礼，达成「{}」", claim.days, ai.name, milestone.name),
+            Some(milestone.id.clone()),
+        ).await?;
+
+        Ok(())
+    }
+
+    // 定时检查已中断的连续送礼并通知用户，每段连续送礼只通知一次；返回通知数量
+    pub async fn notify_broken_streaks(&self) -> Result<u32> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut notified = 0;
+
+        for streak in self.db.get_unnotified_gift_streaks_before(now - 86400).await? {
+            match self.notify_if_broken(&streak, now).await {
+                Ok(true) => notified += 1,
+                Ok(false) => {}
+                Err(e) => eprintln!("Failed to check gift streak {}: {:?}", streak.id, e),
+            }
+        }
+
+        Ok(notified)
+    }
+
+    async fn notify_if_broken(&self, streak: &ConsecutiveGiftRecord, now: i64) -> Result<bool> {
+        let Some(user) = self.db.get_user_by_id(&streak.user_id).await? else {
+            return Ok(false);
+        };
+
+        // 漏掉的天数不超过冻结卡数量时，下次送礼仍可保持连续
+        if streak.missed_days(now, user.utc_offset() as i64 * 60) <= user.gift_streak_freezes {
+            return Ok(false);
+        }
+
+        let Some(ai) = self.db.get_ai(&streak.ai_id).await? else {
+            return Ok(false);
+        };
+
+        if !self.db.mark_gift_streak_break_notified(&streak.id, streak.last_gift_date, now).await? {
+            return Ok(false);
+        }
+
+        let result = NotificationService::new(self.db.clone()).notify(
+            &streak.user_id,
+            NotificationType::GiftStreakBroken,
+            "连续送礼已中断".to_string(),
+            format!("你与{}的{}天连续送礼已中断，下次送礼将从第1天重新开始", ai.name, streak.consecutive_days),
+            Some(streak.id.clone()),
+        ).await;
+
+        if let Err(e) = result {
+            self.db.release_gift_streak_break_notified(&streak.id, now).await?;
+            return Err(e);
+        }
+
+        Ok(true)
+    }
+
+    // 获取与某个AI的连续送礼状态和下一个里程碑
+    pub async fn get_status(&self, user_id: &str, ai_id: &str) -> Result<GiftStreakStatus> {
+        let user = self.db.get_user_by_id(user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let utc_offset_secs = user.utc_offset() as i64 * 60;
+        let streak = self.db.get_consecutive_gift_record(user_id, ai_id).await?;
+
+        let (consecutive_days, sent_today, freezes_needed) = match &streak {
+            Some(streak) if streak.sent_on_day_of(now, utc_offset_secs) => (streak.consecutive_days, true, 0),
+            Some(streak) if streak.consecutive_days > 0 => {
+                let missed = streak.missed_days(now, utc_offset_secs);
+                if missed <= user.gift_streak_freezes {
+                    (streak.consecutive_days, false, missed)
+                } else {
+                    (0, false, 0)
+                }
+            }
+            _ => (0, false, 0),
+        };
+
+        let next_milestone = self.db.get_active_gift_streak_milestones().await?
+            .into_iter()
+            .find(|milestone| milestone.days > consecutive_days);
+        let days_to_next_milestone = next_milestone.as_ref().map(|milestone| milestone.days - consecutive_days);
+
+        Ok(GiftStreakStatus {
+            ai_id: ai_id.to_string(),
+            consecutive_days,
+            sent_today,
+            freezes_available: user.gift_streak_freezes,
+            freezes_needed,
+            next_milestone,
+            days_to_next_milestone,
+        })
+    }
+}
@@ -0,0 +1,243 @@
+use crate::db::Database;
+use crate::models::leaderboard::{
+    LeaderboardEntry, LeaderboardKind, LeaderboardPage, LeaderboardPeriod, LeaderboardScore,
+    LeaderboardSnapshot, LEADERBOARD_SNAPSHOT_SIZE,
+};
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 排行榜服务：送礼和签到时增量更新日榜、周榜和总榜，周期结束后保存快照
+pub struct LeaderboardService {
+    db: Database,
+}
+
+impl LeaderboardService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 送礼后在后台按本次赠送的情感价值更新送礼榜和AI人气榜，不阻塞调用方
+    pub fn record_gift(db: Database, sender_id: String, receiver_ai_id: String, emotional_value: u32) {
+        tokio::spawn(async move {
+            let service = LeaderboardService::new(db);
+            if let Err(e) = service.on_gift(&sender_id, &receiver_ai_id, emotional_value).await {
+                eprintln!("Failed to update gift leaderboards: {:?}", e);
+            }
+        });
+    }
+
+    // 签到后在后台更新连续签到榜
+    pub fn record_checkin(db: Database, user_id: String, streak: u32) {
+        tokio::spawn(async move {
+            let service = LeaderboardService::new(db);
+            if let Err(e) = service.on_checkin(&user_id, streak).await {
+                eprintln!("Failed to update checkin leaderboard: {:?}", e);
+            }
+        });
+    }
+
+    // 各周期的分数记录
+    fn scores_for_periods(kind: &LeaderboardKind, subject_id: &str, owner_id: &str, score: u32, hidden: bool)
+        -> Vec<LeaderboardScore> {
+        LeaderboardPeriod::all()
+            .into_iter()
+            .map(|period| LeaderboardScore::new(
+                kind.clone(),
+                period,
+                subject_id.to_string(),
+                owner_id.to_string(),
+                score,
+                hidden,
+            ))
+            .collect()
+    }
+
+    // 用户是否关闭了排行榜展示
+    async fn is_hidden(&self, user_id: &str) -> Result<bool> {
+        let user = self.db.get_user_by_id(user_id).await?;
+        Ok(user.is_some_and(|user| user.leaderboard_opt_out))
+    }
+
+    async fn on_gift(&self, sender_id: &str, receiver_ai_id: &str, emotional_value: u32) -> Result<()> {
+        let Some(ai) = self.db.get_ai(receiver_ai_id).await? else {
+            return Ok(());
+        };
+
+        if emotional_value == 0 {
+            return Ok(());
+        }
+
+        let hidden = self.is_hidden(sender_id).await?;
+        let scores = Self::scores_for_periods(
+            &LeaderboardKind::TopGifters, sender_id, sender_id, emotional_value, hidden,
+        );
+        self.db.increment_leaderboard_scores(&scores).await?;
+
+        let hidden = self.is_hidden(&ai.user_id).await?;
+        let scores = Self::scores_for_periods(
+            &LeaderboardKind::PopularAis, &ai.id, &ai.user_id, emotional_value, hidden,
+        );
+        self.db.increment_leaderboard_scores(&scores).await?;
+
+        Ok(())
+    }
+
+    async fn on_checkin(&self, user_id: &str, streak: u32) -> Result<()> {
+        let hidden = self.is_hidden(user_id).await?;
+        let scores = Self::scores_for_periods(&LeaderboardKind::LongestStreak, user_id, user_id, streak, hidden);
+        self.db.raise_leaderboard_scores(&scores).await?;
+        Ok(())
+    }
+
+    // 分页获取排行榜，不指定期数时为当期；往期从快照读取，没有该期快照时返回 None
+    pub async fn get_leaderboard(
+        &self,
+        user_id: &str,
+        kind: LeaderboardKind,
+        period: LeaderboardPeriod,
+        period_key: Option<String>,
+        page: usize,
+        limit: usize,
+    ) -> Result<Option<LeaderboardPage>> {
+        let current_key = period.key(OffsetDateTime::now_utc());
+        let period_key = period_key.unwrap_or(current_key.clone());
+        let offset = page.saturating_sub(1) * limit;
+
+        let (entries, mine) = if period_key == current_key {
+            let entries = self.db.get_leaderboard_scores(&kind, &period, &period_key, limit, offset).await?
+                .into_iter()
+                .enumerate()
+                .map(|(index, score)| Self::entry(&score, (offset + index + 1) as u32))
+                .collect();
+
+            let mut mine = Vec::new();
+            for score in self.db.get_owner_leaderboard_scores(&kind, &period, &period_key, user_id).await? {
+                let ahead = self.db.count_leaderboard_scores_ahead(&score).await?;
+                mine.push(Self::entry(&score, ahead + 1));
+            }
+
+            (entries, mine)
+        } else {
+            let Some(snapshot) = self.db.get_leaderboard_snapshot(&kind, &period, &period_key).await? else {
+                return Ok(None);
+            };
+
+            let mine = snapshot.entries.iter()
+                .filter(|entry| entry.owner_id == user_id)
+                .cloned()
+                .collect();
+            let entries = snapshot.entries.into_iter().skip(offset).take(limit).collect();
+
+            (entries, mine)
+        };
+
+        Ok(Some(LeaderboardPage { kind, period, period_key, entries, mine }))
+    }
+
+    fn entry(score: &LeaderboardScore, rank: u32) -> LeaderboardEntry {
+        LeaderboardEntry {
+            rank,
+            subject_id: score.subject_id.clone(),
+            owner_id: score.owner_id.clone(),
+            score: score.score,
+        }
+    }
+
+    // 为已结束的日榜和周榜保存快照并清理分数，返回新保存的快照数
+    // 除刚结束的一期外，漏跑时仍留有分数的往期也一并补存
+    pub async fn snapshot_ended_periods(&self) -> Result<usize> {
+        let now = OffsetDateTime::now_utc();
+        let mut saved = 0;
+
+        for kind in LeaderboardKind::all() {
+            for period in LeaderboardPeriod::all() {
+                let Some(previous_key) = period.previous_key(now) else {
+                    continue;
+                };
+
+                let mut period_keys = self.db
+                    .get_stale_leaderboard_period_keys(&kind, &period, &period.key(now)).await?;
+                if !period_keys.contains(&previous_key) {
+                    period_keys.push(previous_key);
+                }
+
+                for period_key in period_keys {
+                    if self.snapshot_period(&kind, &period, &period_key).await? {
+                        saved += 1;
+                    }
+                }
+            }
+        }
+
+        Ok(saved)
+    }
+
+    // 保存某期快照并清理分数；快照已存在时只清理残留分数，返回是否新保存了快照
+    async fn snapshot_period(&self, kind: &LeaderboardKind, period: &LeaderboardPeriod, period_key: &str) -> Result<bool> {
+        let exists = self.db.get_leaderboard_snapshot(kind, period, period_key).await?.is_some();
+
+        if !exists {
+            let entries = self.db.get_leaderboard_scores(kind, period, period_key, LEADERBOARD_SNAPSHOT_SIZE, 0).await?
+                .iter()
+                .enumerate()
+                .map(|(index, score)| Self::entry(score, index as u32 + 1))
+                .collect();
+
+            let snapshot = LeaderboardSnapshot::new(kind.clone(), period.clone(), period_key.to_string(), entries);
+            self.db.create_leaderboard_snapshot(&snapshot).await?;
+        }
+
+        self.db.delete_leaderboard_scores(kind, period, period_key).await?;
+        Ok(!exists)
+    }
+
+    // 总榜为空时按历史送礼记录和当前连续签到天数补录，返回补录的记录数
+    pub async fn backfill_all_time(&self) -> Result<usize> {
+        if self.db.has_all_time_leaderboard_scores().await? {
+            return Ok(0);
+        }
+
+        let mut scores = Vec::new();
+
+        for total in self.db.get_gift_value_totals_by_user().await? {
+            scores.push(LeaderboardScore::new(
+                LeaderboardKind::TopGifters, LeaderboardPeriod::AllTime,
+                total.subject_id.clone(), total.subject_id, total.score, false,
+            ));
+        }
+
+        for total in self.db.get_checkin_streak_totals().await? {
+            scores.push(LeaderboardScore::new(
+                LeaderboardKind::LongestStreak, LeaderboardPeriod::AllTime,
+                total.subject_id.clone(), total.subject_id, total.score, false,
+            ));
+        }
+
+        for total in self.db.get_gift_value_totals_by_ai().await? {
+            let Some(ai) = self.db.get_ai(&total.subject_id).await? else {
+                continue;
+            };
+            scores.push(LeaderboardScore::new(
+                LeaderboardKind::PopularAis, LeaderboardPeriod::AllTime,
+                total.subject_id, ai.user_id, total.score, false,
+            ));
+        }
+
+        // 补录期间产生的新分数不会被覆盖
+        scores.retain(|score| score.score > 0);
+        self.db.raise_leaderboard_scores(&scores).await?;
+
+        Ok(scores.len())
+    }
+
+    // 设置用户是否在排行榜中展示，同时隐藏或恢复已有的分数
+    pub async fn set_opt_out(&self, user_id: &str, opt_out: bool) -> Result<()> {
+        self.db.get_user_by_id(user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+
+        self.db.set_user_leaderboard_opt_out(user_id, opt_out).await?;
+        self.db.set_leaderboard_hidden(user_id, opt_out).await?;
+
+        Ok(())
+    }
+}
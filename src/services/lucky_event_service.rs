@@ -0,0 +1,140 @@
+use crate::db::Database;
+use crate::models::{LuckyCard, NotificationType};
+use crate::models::chat::{Message, MessageType};
+use crate::models::lucky_trigger::{LuckyTrigger, LuckyEvent, LuckyEventType};
+use crate::services::{PointsService, NotificationService};
+use crate::services::websocket;
+use anyhow::Result;
+
+// 幸运卡事件钩子：聊天里程碑、每日首次送礼、连续签到、亲密等级提升时按配置掉落幸运卡
+pub struct LuckyEventService {
+    db: Database,
+}
+
+impl LuckyEventService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 在后台处理事件，不阻塞调用方
+    pub fn fire(db: Database, event: LuckyEvent) {
+        tokio::spawn(async move {
+            if let Err(e) = LuckyEventService::new(db).handle(event).await {
+                eprintln!("Failed to handle lucky event: {:?}", e);
+            }
+        });
+    }
+
+    // 用户给AI发送私聊消息后检查对话里程碑
+    pub fn on_chat_message(db: Database, message: &Message) {
+        if message.is_group {
+            return;
+        }
+
+        let message = message.clone();
+        tokio::spawn(async move {
+            let service = LuckyEventService::new(db);
+            if let Err(e) = service.check_chat_milestone(&message).await {
+                eprintln!("Failed to check chat milestone: {:?}", e);
+            }
+        });
+    }
+
+    async fn check_chat_milestone(&self, message: &Message) -> Result<()> {
+        if self.db.get_ai(&message.to_user).await?.is_none() {
+            return Ok(());
+        }
+
+        // 计数每次加一，命中阈值即为越过里程碑
+        let count = self.db.increment_chat_message_count(&message.chat_identify, &message.from_user).await?;
+        let event = LuckyEvent::new(
+            LuckyEventType::ChatMilestone,
+            message.from_user.clone(),
+            Some(message.to_user.clone()),
+            count,
+        );
+
+        self.handle(event).await?;
+        Ok(())
+    }
+
+    // 获取事件的触发配置，未配置时使用默认规则
+    pub async fn get_trigger(&self, event_type: LuckyEventType) -> Result<LuckyTrigger> {
+        let trigger = self.db.get_lucky_trigger(&event_type).await?;
+        Ok(trigger.unwrap_or_else(|| LuckyTrigger::default_for(event_type)))
+    }
+
+    // 处理事件，满足条件时掉落幸运卡并由AI发布公告
+    pub async fn handle(&self, event: LuckyEvent) -> Result<Option<LuckyCard>> {
+        let trigger = self.get_trigger(event.event_type.clone()).await?;
+        if !trigger.matches(event.value) {
+            return Ok(None);
+        }
+
+        // 没有指定AI时由用户的第一个AI发放
+        let ai_id = match event.ai_id.clone() {
+            Some(ai_id) => Some(ai_id),
+            None => self.db.get_user_ais(&event.user_id).await?
+                .into_iter()
+                .next()
+                .map(|ai| ai.id),
+        };
+
+        let card = PointsService::new(self.db.clone())
+            .issue_random_lucky_card(&event.user_id, ai_id.clone(), &trigger.drop_key)
+            .await?;
+
+        if let Some(card) = &card {
+            self.announce(&event, &trigger, ai_id, card).await?;
+        }
+
+        Ok(card)
+    }
+
+    // AI在聊天中公告获得的幸运卡，用户没有AI时改为站内通知
+    async fn announce(
+        &self,
+        event: &LuckyEvent,
+        trigger: &LuckyTrigger,
+        ai_id: Option<String>,
+        card: &LuckyCard,
+    ) -> Result<()> {
+        let content = trigger.message_template
+            .replace("{level}", &format!("{:?}", card.level))
+            .replace("{multiplier}", &format!("{:.2}", card.multiplier));
+
+        let Some(ai_id) = ai_id else {
+            NotificationService::new(self.db.clone()).notify(
+                &event.user_id,
+                NotificationType::LuckyCardIssued,
+                "获得幸运卡".to_string(),
+                content,
+                Some(card.id.clone()),
+            ).await?;
+            return Ok(());
+        };
+
+        let extends = serde_json::json!({
+            "lucky_card_id": card.id,
+            "event_type": event.event_type.as_str(),
+        });
+
+        let message = Message::new(
+            ai_id,
+            event.user_id.clone(),
+            content,
+            MessageType::Event,
+            false,
+            None,
+            Some(extends.to_string()),
+            None,
+        );
+        self.db.create_message(&message).await?;
+
+        if let Ok(data) = serde_json::to_value(&message) {
+            websocket::push_to_user(&event.user_id, "message", data).await;
+        }
+
+        Ok(())
+    }
+}
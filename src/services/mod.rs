@@ -3,8 +3,37 @@ pub mod points_service;
 pub mod promoter_service;
 pub mod websocket;
 pub mod file_storage;
+pub mod payment_gateway;
+pub mod subscription_service;
+pub mod checkout_service;
+pub mod notification_service;
+pub mod coupon_service;
+pub mod drop_service;
+pub mod lucky_event_service;
+pub mod fulfilment_service;
+pub mod pricing_service;
+pub mod wishlist_service;
+pub mod earning_service;
+pub mod achievement_service;
+pub mod leaderboard_service;
+pub mod gift_streak_service;
+pub mod gift_event_service;
 
 pub use email_service::EmailService;
 pub use points_service::PointsService;
 pub use promoter_service::PromoterService;
 pub use file_storage::FileStorage;
+pub use subscription_service::SubscriptionService;
+pub use checkout_service::CheckoutService;
+pub use notification_service::NotificationService;
+pub use coupon_service::CouponService;
+pub use drop_service::DropService;
+pub use lucky_event_service::LuckyEventService;
+pub use fulfilment_service::FulfilmentService;
+pub use pricing_service::PricingService;
+pub use wishlist_service::WishlistService;
+pub use earning_service::EarningService;
+pub use achievement_service::AchievementService;
+pub use leaderboard_service::LeaderboardService;
+pub use gift_streak_service::GiftStreakService;
+pub use gift_event_service::GiftEventService;
@@ -0,0 +1,41 @@
+use crate::db::Database;
+use crate::models::{Notification, NotificationType};
+use crate::services::websocket;
+use anyhow::Result;
+
+// 通知服务：统一生成站内通知，并推送给在线用户
+pub struct NotificationService {
+    db: Database,
+}
+
+impl NotificationService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 发送站内通知
+    pub async fn notify(
+        &self,
+        user_id: &str,
+        notification_type: NotificationType,
+        title: String,
+        content: String,
+        related_entity_id: Option<String>,
+    ) -> Result<Notification> {
+        let notification = Notification::new(
+            user_id.to_string(),
+            notification_type,
+            title,
+            content,
+            related_entity_id,
+        );
+
+        self.db.create_notification(&notification).await?;
+
+        if let Ok(data) = serde_json::to_value(&notification) {
+            websocket::push_to_user(user_id, "notification", data).await;
+        }
+
+        Ok(notification)
+    }
+}
@@ -0,0 +1,58 @@
+use async_trait::async_trait;
+use anyhow::Result;
+use uuid::Uuid;
+
+// 扣款请求
+pub struct ChargeRequest {
+    pub order_id: String,
+    pub user_id: String,
+    pub amount: f32,
+    pub currency: String,
+    pub description: String,
+}
+
+// 扣款结果
+pub struct ChargeResult {
+    pub success: bool,
+    pub transaction_id: Option<String>,
+    pub failure_reason: Option<String>,
+}
+
+// 支付网关抽象，具体的支付渠道（Stripe、PayPal等）实现该trait
+#[async_trait]
+pub trait PaymentGateway: Send + Sync {
+    async fn charge(&self, request: &ChargeRequest) -> Result<ChargeResult>;
+}
+
+// 沙箱网关：不产生真实扣款，所有扣款直接成功
+pub struct SandboxGateway;
+
+#[async_trait]
+impl PaymentGateway for SandboxGateway {
+    async fn charge(&self, request: &ChargeRequest) -> Result<ChargeResult> {
+        if request.amount < 0.0 {
+            return Ok(ChargeResult {
+                success: false,
+                transaction_id: None,
+                failure_reason: Some("Invalid amount".to_string()),
+            });
+        }
+
+        println!(
+            "Sandbox charge {} for user {}: {} {} ({})",
+            request.order_id, request.user_id, request.amount, request.currency, request.description
+        );
+
+        Ok(ChargeResult {
+            success: true,
+            transaction_id: Some(format!("sandbox_{}", Uuid::new_v4())),
+            failure_reason: None,
+        })
+    }
+}
+
+// 创建当前使用的支付网关
+pub fn create_gateway() -> Box<dyn PaymentGateway> {
+    // 目前只接入了沙箱网关，接入真实支付渠道后在这里根据配置选择
+    Box::new(SandboxGateway)
+}
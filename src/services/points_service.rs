@@ -1,17 +1,54 @@
 use crate::db::Database;
 use crate::models::{
     User, WalletTx, TxType, CurrencyType, Gift, GiftRecord, 
-    LuckyCard, CardLevel, ShopItem, ShopItemType, PurchaseRecord,
+    LuckyCard, LuckyBoost, ShopItem, ShopItemType, PurchaseRecord,
     FrontendUserRole
 };
-use crate::models::gift::{ConsecutiveGiftRecord, GiftFeedbackTemplate, GiftCategory};
-use time::OffsetDateTime;
-use rand::Rng;
+use crate::models::point_shop::{LimitAllowance, PurchaseAllowance};
+use crate::models::gift::{ConsecutiveGiftRecord, GiftComboRule, GiftFeedbackTemplate, GiftCategory, UserGiftRecord, UserGiftSetting};
+use crate::models::chat::{Message, MessageType};
+use crate::models::drop_table::{DropLog, DropReward};
+use crate::models::lucky_trigger::{LuckyEvent, LuckyEventType};
+use crate::models::hp_lot::HpExpiryNotice;
+use crate::models::earning_rule::{EarningCredit, EarningSource, HeldReward};
+use crate::models::checkin::{
+    CheckinRecord, CheckinSchedule, CheckinCalendar, CheckinDay, MAKEUP_WINDOW_DAYS, local_midday, parse_date,
+};
+use crate::models::achievement::AchievementMetric;
+use crate::services::{
+    AchievementService, DropService, EarningService, GiftEventService, GiftStreakService, LeaderboardService, LuckyEventService,
+};
+use crate::services::earning_service::EarningDecision;
+use crate::services::websocket;
+use time::{Date, Duration, Month, OffsetDateTime};
+use anyhow::anyhow;
 
 pub struct PointsService {
     db: Database,
 }
 
+// 送礼请求本身无效（数量超限、非好友等），路由据此返回 400，其余错误为服务端错误
+#[derive(Debug)]
+pub struct InvalidGiftRequest(pub &'static str);
+
+impl std::fmt::Display for InvalidGiftRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.0)
+    }
+}
+
+impl std::error::Error for InvalidGiftRequest {}
+
+// 并发入账超出获取上限时重新判定的次数上限
+const MAX_EARNING_ATTEMPTS: usize = 3;
+
+// 按获取规则判定后待入账的积分
+enum PreparedEarning {
+    Credit(EarningCredit),                  // 入账交易，幸运卡加成额度在入账事务中累加
+    Hold(HeldReward),                       // 暂扣待审核
+    Capped,                                 // 已达获取上限
+}
+
 impl PointsService {
     pub fn new(db: Database) -> Self {
         Self { db }
@@ -19,26 +56,247 @@ impl PointsService {
     
     // ==================== 积分获取逻辑 ====================
     
-    // 每日签到获取积分
+    // 发放积分：先按获取规则裁剪或暂扣，幸运卡加成期内再按倍率放大，返回实际发放的积分
+    async fn earn_hp(&self, user_id: &str, source: EarningSource, base_points: u32, tx_type: TxType,
+                     related_entity_id: Option<String>, remark: Option<String>) 
+        -> Result<u32, anyhow::Error> {
+        
+        // 并发入账使本次超出上限或幸运卡加成已变化时重新读取用户并判定，按剩余额度裁剪
+        for _ in 0..MAX_EARNING_ATTEMPTS {
+            let user = self.db.get_user_by_id(user_id).await?
+                .ok_or_else(|| anyhow!("User not found"))?;
+            let earning = self.prepare_earning(
+                &user, source.clone(), base_points, tx_type.clone(), related_entity_id.clone(), remark.clone(),
+            ).await?;
+            if let PreparedEarning::Credit(credit) = &earning {
+                if !self.db.credit_user_earning(credit).await? {
+                    continue;
+                }
+            }
+            return self.finish_earning(earning).await;
+        }
+        
+        Ok(0)
+    }
+    
+    // 按获取规则生成待入账的积分交易，暂扣在入账判定后由 finish_earning 保存
+    async fn prepare_earning(&self, user: &User, source: EarningSource, base_points: u32, tx_type: TxType,
+                             related_entity_id: Option<String>, remark: Option<String>) 
+        -> Result<PreparedEarning, anyhow::Error> {
+        
+        let earning_service = EarningService::new(self.db.clone());
+        let (base_points, caps) = match earning_service.evaluate(user, &source, base_points).await? {
+            EarningDecision::Credit(points, caps) => (points, caps),
+            EarningDecision::Hold(points, reason) => {
+                let reward = HeldReward::new(
+                    user.id.clone(),
+                    source,
+                    tx_type,
+                    points,
+                    related_entity_id,
+                    remark,
+                    reason,
+                );
+                return Ok(PreparedEarning::Hold(reward));
+            }
+            EarningDecision::Capped => return Ok(PreparedEarning::Capped),
+        };
+        
+        let mut tx = WalletTx::new(
+            user.id.clone(),
+            tx_type,
+            base_points,
+            CurrencyType::HP,
+            related_entity_id,
+            remark,
+        ).with_earning_source(source);
+        
+        let mut bonus = 0;
+        if let Some(mut boost) = user.lucky_boost.clone().filter(|boost| boost.is_active()) {
+            bonus = boost.apply(base_points);
+            if bonus > 0 {
+                tx.amount += bonus;
+                tx = tx.with_lucky_boost(&boost);
+            }
+        }
+        
+        Ok(PreparedEarning::Credit(EarningCredit { tx, points: base_points, bonus, caps }))
+    }
+    
+    // 积分入账后保存暂扣记录，返回实际发放的积分
+    async fn finish_earning(&self, earning: PreparedEarning) -> Result<u32, anyhow::Error> {
+        match earning {
+            PreparedEarning::Credit(credit) => Ok(credit.tx.amount),
+            PreparedEarning::Hold(reward) => {
+                self.db.create_held_reward(&reward).await?;
+                Ok(0)
+            }
+            PreparedEarning::Capped => Ok(0),
+        }
+    }
+    
+    // ==================== 签到系统 ====================
+    
+    // 获取签到奖励配置，未配置时使用默认奖励
+    pub async fn get_checkin_schedule(&self) -> Result<CheckinSchedule, anyhow::Error> {
+        let schedule = self.db.get_checkin_schedule().await?;
+        Ok(schedule.unwrap_or_else(CheckinSchedule::default_schedule))
+    }
+    
+    // 每日签到获取积分，签到日按用户时区计算
     pub async fn daily_checkin(&self, user_id: &str) -> Result<(bool, u32, u32), anyhow::Error> {
-        let result = self.db.user_daily_checkin(user_id).await?;
-        Ok(result)
+        // 签到记录和积分在同一事务中写入，同一天并发签到时只有一次成功；
+        // 幸运卡加成被并发入账修改时重新读取用户后重试
+        let mut attempt = 0;
+        let (streak, earning) = loop {
+            attempt += 1;
+            
+            let user = self.db.get_user_by_id(user_id).await?
+                .ok_or_else(|| anyhow!("User not found"))?;
+            
+            let today = user.local_date(OffsetDateTime::now_utc().unix_timestamp());
+            let last_checkin = user.last_checkin_date.map(|at| user.local_date(at));
+            if last_checkin == Some(today) {
+                return Ok((false, user.daily_checkin_streak, user.hp));
+            }
+            
+            // 昨天签到过则连续天数+1，否则重新开始
+            let streak = user.active_checkin_streak(today) + 1;
+            let points = self.get_checkin_schedule().await?.points_for(streak);
+            
+            let earning = self.prepare_earning(
+                &user,
+                EarningSource::DailyCheckin,
+                points,
+                TxType::PointsEarned,
+                None,
+                Some(format!("每日签到奖励 (连续{}天)", streak))
+            ).await?;
+            let credit = match &earning {
+                PreparedEarning::Credit(credit) => Some(credit),
+                _ => None,
+            };
+            
+            let record = CheckinRecord::new(user_id.to_string(), today, points, false);
+            match self.db.record_daily_checkin(&record, streak, credit).await? {
+                Some(true) => break (streak, earning),
+                Some(false) => return Ok((false, user.daily_checkin_streak, user.hp)),
+                None if attempt < MAX_EARNING_ATTEMPTS => continue,
+                None => return Err(anyhow!("Lucky boost changed concurrently")),
+            }
+        };
+        
+        if let Err(e) = self.finish_earning(earning).await {
+            eprintln!("Failed to finish check-in reward for user {}: {:?}", user_id, e);
+        }
+        
+        LuckyEventService::fire(
+            self.db.clone(),
+            LuckyEvent::new(LuckyEventType::CheckinStreak, user_id.to_string(), None, streak),
+        );
+        AchievementService::fire(self.db.clone(), user_id.to_string(), AchievementMetric::CheckinStreak);
+        LeaderboardService::record_checkin(self.db.clone(), user_id.to_string(), streak);
+        
+        let (hp, _) = self.get_user_wallet(user_id).await?;
+        Ok((true, streak, hp))
     }
     
+    // 使用补签卡补签最近30天内漏签的一天，返回补签后的连续天数
+    pub async fn makeup_checkin(&self, user_id: &str, date: &str) -> Result<u32, anyhow::Error> {
+        let user = self.db.get_user_by_id(user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+        
+        let date = parse_date(date).ok_or_else(|| anyhow!("Invalid date"))?;
+        let today = user.local_date(OffsetDateTime::now_utc().unix_timestamp());
+        if date >= today || date < today - Duration::days(MAKEUP_WINDOW_DAYS) {
+            return Err(anyhow!("Date cannot be made up"));
+        }
+        
+        if user.checkin_makeup_cards == 0 {
+            return Err(anyhow!("No makeup card"));
+        }
+        
+        let mut dates: Vec<Date> = self.db.get_checkin_dates(user_id).await?
+            .iter()
+            .filter_map(|date| parse_date(date))
+            .collect();
+        
+        if dates.contains(&date) {
+            return Err(anyhow!("Already checked in"));
+        }
+        
+        dates.push(date);
+        dates.sort_by(|a, b| b.cmp(a));
+        
+        // 启用签到记录前的连续天数只保存在用户上，补签不会让连续天数变少
+        let streak = CheckinRecord::current_streak(&dates, today).max(user.active_checkin_streak(today));
+        
+        // 补签日期晚于上次签到时，以补签日作为上次签到日
+        let last_checkin_date = match user.last_checkin_date {
+            Some(at) if user.local_date(at) > date => Some(at),
+            _ => Some(local_midday(date, user.utc_offset())),
+        };
+        
+        let record = CheckinRecord::new(user_id.to_string(), date, 0, true);
+        if !self.db.record_makeup_checkin(&record, streak, last_checkin_date).await? {
+            return Err(anyhow!("Makeup check-in failed"));
+        }
+        
+        AchievementService::fire(self.db.clone(), user_id.to_string(), AchievementMetric::CheckinStreak);
+        LeaderboardService::record_checkin(self.db.clone(), user_id.to_string(), streak);
+        
+        Ok(streak)
+    }
+    
+    // 获取某月的签到日历，默认为用户本地的当月
+    pub async fn get_checkin_calendar(&self, user_id: &str, year: Option<i32>, month: Option<u8>) 
+        -> Result<CheckinCalendar, anyhow::Error> {
+        
+        let user = self.db.get_user_by_id(user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+        
+        let today = user.local_date(OffsetDateTime::now_utc().unix_timestamp());
+        let year = year.unwrap_or(today.year());
+        let month = Month::try_from(month.unwrap_or(today.month() as u8))?;
+        
+        let first = Date::from_calendar_date(year, month, 1)?;
+        let last = first + Duration::days(month.length(year) as i64 - 1);
+        
+        let days = self.db.get_checkin_records_between(user_id, &first.to_string(), &last.to_string()).await?
+            .into_iter()
+            .map(|record| CheckinDay {
+                date: record.date,
+                points: record.points,
+                is_makeup: record.is_makeup,
+            })
+            .collect();
+        
+        Ok(CheckinCalendar {
+            year,
+            month: month as u8,
+            today: today.to_string(),
+            streak: user.active_checkin_streak(today),
+            makeup_cards: user.checkin_makeup_cards,
+            days,
+        })
+    }
+    
+    // ==================== 其他积分获取 ====================
+    
     // 对话互动获取积分
     pub async fn reward_dialogue_points(&self, user_id: &str, message_count: u32) -> Result<u32, anyhow::Error> {
-        // 基础规则：每10条消息奖励1点积分，上限为每天30点
-        let base_points = message_count / 10;
-        let reward_points = if base_points > 30 { 30 } else { base_points };
+        // 基础规则：每10条消息奖励1点积分，每日上限由获取规则控制
+        let reward_points = message_count / 10;
         
         if reward_points > 0 {
-            self.db.add_user_hp(
+            return self.earn_hp(
                 user_id, 
+                EarningSource::Dialogue,
                 reward_points, 
                 TxType::PointsEarned, 
                 None, 
                 Some(format!("对话互动奖励 ({} 条消息)", message_count))
-            ).await?;
+            ).await;
         }
         
         Ok(reward_points)
@@ -46,12 +304,11 @@ impl PointsService {
     
     // 邀请注册获取积分
     pub async fn reward_invitation_points(&self, inviter_id: &str, invitee_id: &str) -> Result<u32, anyhow::Error> {
-        // 邀请奖励：每成功邀请一人注册，奖励50点积分
-        let reward_points = 50;
-        
-        self.db.add_user_hp(
+        // 邀请奖励：每成功邀请一人注册，奖励50点积分，受获取规则限制
+        let reward_points = self.earn_hp(
             inviter_id, 
-            reward_points, 
+            EarningSource::Invitation,
+            50, 
             TxType::Reward, 
             Some(invitee_id.to_string()), 
             Some("邀请新用户奖励".to_string())
@@ -82,15 +339,14 @@ impl PointsService {
             // 首次充值奖励：充值金额的10%转换为积分
             let reward_points = (amount as f32 * 0.1) as u32;
             
-            self.db.add_user_hp(
+            return self.earn_hp(
                 user_id, 
+                EarningSource::FirstPayment,
                 reward_points, 
                 TxType::Reward, 
                 None, 
                 Some("首次充值奖励".to_string())
-            ).await?;
-            
-            return Ok(reward_points);
+            ).await;
         }
         
         Ok(0)
@@ -98,26 +354,19 @@ impl PointsService {
     
     // ==================== 幸运卡系统 ====================
     
-    // 随机发放幸运卡
-    pub async fn issue_random_lucky_card(&self, user_id: &str, ai_id: Option<String>) -> Result<Option<LuckyCard>, anyhow::Error> {
-        let mut rng = rand::thread_rng();
-        
-        // 随机决定是否发放幸运卡（20%概率）
-        if rng.gen_range(0..100) < 20 {
-            // 根据概率分配卡片等级
-            let card_level = match rng.gen_range(0..100) {
-                0..=4 => CardLevel::A,    // 5% 概率获得A卡
-                5..=14 => CardLevel::B,   // 10% 概率获得B卡
-                15..=34 => CardLevel::C,  // 20% 概率获得C卡
-                35..=64 => CardLevel::D,  // 30% 概率获得D卡
-                _ => CardLevel::E,        // 35% 概率获得E卡
-            };
-            
-            let card = self.db.create_lucky_card(card_level, user_id, ai_id).await?;
-            return Ok(Some(card));
-        }
+    // 按掉落表随机发放幸运卡
+    pub async fn issue_random_lucky_card(&self, user_id: &str, ai_id: Option<String>, drop_key: &str) 
+        -> Result<Option<LuckyCard>, anyhow::Error> {
+        
+        let drop_service = DropService::new(self.db.clone());
+        let log = drop_service.roll(user_id, drop_key, ai_id).await?;
         
-        Ok(None)
+        match log {
+            Some(DropLog { reward: DropReward::LuckyCard { .. }, granted_entity_id: Some(card_id), .. }) => {
+                Ok(self.db.get_lucky_card(&card_id).await?)
+            }
+            _ => Ok(None),
+        }
     }
     
     // 获取用户有效的幸运卡
@@ -132,28 +381,352 @@ impl PointsService {
         let cards = self.get_user_valid_lucky_cards(user_id).await?;
         let card_belongs_to_user = cards.iter().any(|card| card.id == card_id);
         
-        if card_belongs_to_user {
-            let multiplier = self.db.use_lucky_card(card_id).await?;
-            return Ok(multiplier);
+        if !card_belongs_to_user {
+            return Ok(None);
+        }
+        
+        // 已有加成生效时不能叠加使用
+        if self.get_active_lucky_boost(user_id).await?.is_some() {
+            return Ok(None);
         }
         
-        Ok(None)
+        let multiplier = self.db.use_lucky_card(card_id).await?;
+        if let Some(multiplier) = multiplier {
+            let boost = LuckyBoost::new(card_id.to_string(), multiplier);
+            self.db.set_user_lucky_boost(user_id, Some(&boost)).await?;
+        }
+        
+        Ok(multiplier)
+    }
+    
+    // 获取用户当前生效的幸运卡加成
+    pub async fn get_active_lucky_boost(&self, user_id: &str) -> Result<Option<LuckyBoost>, anyhow::Error> {
+        let user = self.db.get_user_by_id(user_id).await?;
+        Ok(user.and_then(|user| user.lucky_boost).filter(|boost| boost.is_active()))
     }
     
     // ==================== 礼物系统 ====================
     
     // 获取可用礼物列表
     pub async fn get_available_gifts(&self) -> Result<Vec<Gift>, anyhow::Error> {
-        let gifts = self.db.get_available_gifts().await?;
+        let mut gifts = self.db.get_available_gifts().await?;
+        
+        // 进行中活动的主题礼物自动上架
+        for gift in GiftEventService::new(self.db.clone()).get_running_event_gifts().await? {
+            if !gifts.iter().any(|existing| existing.id == gift.id) {
+                gifts.push(gift);
+            }
+        }
+        
         Ok(gifts)
     }
     
-    // 赠送礼物
-    pub async fn send_gift(&self, gift_id: &str, sender_id: &str, receiver_ai_id: &str, message: Option<String>) 
+    // 礼物当前是否可赠送：已上架且未过期，或属于进行中的活动
+    async fn is_gift_available(&self, gift: &Gift, now: i64) -> Result<bool, anyhow::Error> {
+        if gift.is_active && !(gift.is_limited && gift.available_until.unwrap_or(0) <= now) {
+            return Ok(true);
+        }
+        
+        GiftEventService::new(self.db.clone()).is_event_gift(&gift.id).await
+    }
+    
+    // 获取礼物分类的连击规则，未配置时使用默认规则
+    pub async fn get_gift_combo_rule(&self, category: GiftCategory) -> Result<GiftComboRule, anyhow::Error> {
+        let rule = self.db.get_gift_combo_rule(&category).await?;
+        Ok(rule.unwrap_or_else(|| GiftComboRule::default_for(category)))
+    }
+    
+    // 赠送礼物，一次可赠送多个，达到连击档位时情感价值额外加成
+    pub async fn send_gift(&self, gift_id: &str, sender_id: &str, receiver_ai_id: &str, quantity: u32, message: Option<String>) 
         -> Result<bool, anyhow::Error> {
         
-        let result = self.db.send_gift(gift_id, sender_id, receiver_ai_id, message).await?;
-        Ok(result)
+        let Some(gift) = self.db.get_gift_by_id(gift_id).await? else {
+            return Ok(false);
+        };
+        
+        // 检查礼物是否可用
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if !self.is_gift_available(&gift, now).await? {
+            return Ok(false);
+        }
+        
+        let rule = self.get_gift_combo_rule(gift.category.clone()).await?;
+        if quantity == 0 || quantity > rule.max_quantity {
+            return Err(InvalidGiftRequest("Invalid gift quantity").into());
+        }
+        
+        let price = gift.price_lc.checked_mul(quantity)
+            .ok_or_else(|| anyhow!("Gift price overflow"))?;
+        let base_value = gift.emotional_value.saturating_mul(quantity);
+        let combo_bonus = rule.combo_bonus(base_value, quantity);
+        let emotional_value = base_value.saturating_add(combo_bonus);
+        
+        let record = GiftRecord::new(
+            gift_id.to_string(),
+            sender_id.to_string(),
+            receiver_ai_id.to_string(),
+            message,
+        ).with_quantity(quantity, combo_bonus);
+        
+        let remark = match quantity {
+            1 => format!("赠送礼物: {}", gift.name),
+            _ => format!("赠送礼物: {} x{}", gift.name, quantity),
+        };
+        let tx = WalletTx::new(
+            sender_id.to_string(),
+            TxType::GiftSend,
+            price,
+            CurrencyType::LC,
+            Some(record.id.clone()),
+            Some(remark),
+        );
+        
+        // 连续送礼记录的ID创建后不变，累计值和连续天数在送礼事务中计算
+        let streak_id = self.db.get_consecutive_gift_record(sender_id, receiver_ai_id).await?
+            .map(|streak| streak.id)
+            .unwrap_or_else(|| ConsecutiveGiftRecord::record_id(sender_id, receiver_ai_id));
+        
+        let sender = self.db.get_user_by_id(sender_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+        
+        let utc_offset_secs = sender.utc_offset() as i64 * 60;
+        let Some(update) = self.db.send_gift(&record, &tx, &streak_id, emotional_value, utc_offset_secs).await? else {
+            return Ok(false);
+        };
+        let previous_level = update.previous_level();
+        let level = update.streak.relationship_level();
+        
+        GiftStreakService::fire(self.db.clone(), update, utc_offset_secs);
+        GiftEventService::record_gift(self.db.clone(), gift.id.clone(), sender_id.to_string(), emotional_value, record.sent_at);
+        
+        // 光币已扣除，后续奖励失败只记录日志，不影响送礼结果
+        if let Err(e) = self.reward_gift_received(&gift, sender_id, receiver_ai_id, emotional_value).await {
+            eprintln!("Failed to reward gift {} received by AI {}: {:?}", record.id, receiver_ai_id, e);
+        }
+        if let Err(e) = self.fire_gift_events(&sender, receiver_ai_id, emotional_value, previous_level, level, now).await {
+            eprintln!("Failed to fire gift events for gift {}: {:?}", record.id, e);
+        }
+        
+        Ok(true)
+    }
+    
+    // 触发送礼成就、排行榜、每日首次送礼和亲密等级提升事件
+    async fn fire_gift_events(&self, sender: &User, receiver_ai_id: &str, emotional_value: u32, previous_level: u32, 
+                              level: u32, sent_at: i64) 
+        -> Result<(), anyhow::Error> {
+        
+        let sender_id = sender.id.as_str();
+        AchievementService::fire(self.db.clone(), sender_id.to_string(), AchievementMetric::GiftsSent);
+        LeaderboardService::record_gift(
+            self.db.clone(),
+            sender_id.to_string(),
+            receiver_ai_id.to_string(),
+            emotional_value,
+        );
+        
+        // 按用户时区判断当天首次送礼
+        if self.db.mark_first_gift_of_day(sender_id, sent_at, sender.local_day_start(sent_at)).await? {
+            LuckyEventService::fire(
+                self.db.clone(),
+                LuckyEvent::new(LuckyEventType::FirstGiftOfDay, sender_id.to_string(), Some(receiver_ai_id.to_string()), 1),
+            );
+        }
+        
+        if level > previous_level {
+            LuckyEventService::fire(
+                self.db.clone(),
+                LuckyEvent::new(LuckyEventType::RelationshipLevelUp, sender_id.to_string(), Some(receiver_ai_id.to_string()), level),
+            );
+        }
+        
+        Ok(())
+    }
+    
+    // AI收到礼物时，按本次赠送的总情感价值奖励AI主人积分
+    async fn reward_gift_received(&self, gift: &Gift, sender_id: &str, receiver_ai_id: &str, emotional_value: u32) 
+        -> Result<(), anyhow::Error> {
+        
+        let Some(ai) = self.db.get_ai(receiver_ai_id).await? else {
+            return Ok(());
+        };
+        
+        // 给自己的AI送礼不奖励
+        if ai.user_id == sender_id || emotional_value == 0 {
+            return Ok(());
+        }
+        
+        self.earn_hp(
+            &ai.user_id,
+            EarningSource::GiftReceived,
+            emotional_value,
+            TxType::GiftReceive,
+            Some(gift.id.clone()),
+            Some(format!("{} 收到礼物: {}", ai.name, gift.name))
+        ).await?;
+        
+        Ok(())
+    }
+    
+    // 获取用户间送礼分成配置
+    pub async fn get_user_gift_setting(&self) -> Result<UserGiftSetting, anyhow::Error> {
+        let setting = self.db.get_user_gift_setting().await?;
+        Ok(setting.unwrap_or_else(UserGiftSetting::default_setting))
+    }
+    
+    // 用户间送礼：私聊时双方须互为好友，群聊时双方须都在群内；收礼方按分成比例获得光币
+    pub async fn send_gift_to_user(&self, gift_id: &str, sender_id: &str, receiver_id: &str, quantity: u32,
+                                   group_id: Option<String>, message: Option<String>) 
+        -> Result<Option<UserGiftRecord>, anyhow::Error> {
+        
+        if sender_id == receiver_id {
+            return Err(InvalidGiftRequest("Cannot send a gift to yourself").into());
+        }
+        
+        let Some(gift) = self.db.get_gift_by_id(gift_id).await? else {
+            return Ok(None);
+        };
+        
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        if !self.is_gift_available(&gift, now).await? {
+            return Ok(None);
+        }
+        
+        let rule = self.get_gift_combo_rule(gift.category.clone()).await?;
+        if quantity == 0 || quantity > rule.max_quantity {
+            return Err(InvalidGiftRequest("Invalid gift quantity").into());
+        }
+        
+        match &group_id {
+            Some(group_id) => {
+                let members = self.db.get_group_users(group_id).await?;
+                let is_member = |user_id: &str| members.iter().any(|member| member.user_id == user_id);
+                if !is_member(sender_id) || !is_member(receiver_id) {
+                    return Err(InvalidGiftRequest("Gifts can only be sent to group members").into());
+                }
+            }
+            None => {
+                if !self.db.are_friends(sender_id, receiver_id).await? {
+                    return Err(InvalidGiftRequest("Gifts can only be sent to friends").into());
+                }
+            }
+        }
+        
+        let price = gift.price_lc.checked_mul(quantity)
+            .ok_or_else(|| anyhow!("Gift price overflow"))?;
+        let setting = self.get_user_gift_setting().await?;
+        
+        let record = UserGiftRecord::new(
+            gift.id.clone(),
+            sender_id.to_string(),
+            receiver_id.to_string(),
+            quantity,
+            price,
+            &setting,
+            message,
+        ).in_group(group_id);
+        
+        let gift_label = match quantity {
+            1 => gift.name.clone(),
+            _ => format!("{} x{}", gift.name, quantity),
+        };
+        let sender_tx = WalletTx::new(
+            sender_id.to_string(),
+            TxType::GiftSend,
+            record.price_lc,
+            CurrencyType::LC,
+            Some(record.id.clone()),
+            Some(format!("赠送礼物: {}", gift_label)),
+        );
+        let receiver_tx = WalletTx::new(
+            receiver_id.to_string(),
+            TxType::GiftShareReceive,
+            record.received_lc,
+            CurrencyType::LC,
+            Some(record.id.clone()),
+            Some(format!("收到礼物: {}", gift_label)),
+        );
+        
+        if !self.db.send_user_gift(&record, &sender_tx, &receiver_tx).await? {
+            return Ok(None);
+        }
+        
+        GiftEventService::record_gift(
+            self.db.clone(),
+            gift.id.clone(),
+            sender_id.to_string(),
+            gift.emotional_value.saturating_mul(quantity),
+            record.sent_at,
+        );
+        
+        // 礼物消息发送失败不影响送礼结果
+        if let Err(e) = self.post_user_gift_message(&gift, &record).await {
+            eprintln!("Failed to post gift message: {:?}", e);
+        }
+        
+        Ok(Some(record))
+    }
+    
+    // 在私聊或群聊中发送送礼事件消息，并推送给在线的接收方
+    async fn post_user_gift_message(&self, gift: &Gift, record: &UserGiftRecord) -> Result<(), anyhow::Error> {
+        let content = match &record.message {
+            Some(message) if !message.trim().is_empty() => message.clone(),
+            _ => format!("送出了 {} x{}", gift.name, record.quantity),
+        };
+        
+        let extends = serde_json::json!({
+            "user_gift_record_id": record.id,
+            "gift_id": gift.id,
+            "gift_name": gift.name,
+            "image_url": gift.image_url,
+            "animation": gift.animation,
+            "quantity": record.quantity,
+            "receiver_id": record.receiver_id,
+        });
+        
+        let (to_id, is_group) = match &record.group_id {
+            Some(group_id) => (group_id.clone(), true),
+            None => (record.receiver_id.clone(), false),
+        };
+        
+        let message = Message::new(
+            record.sender_id.clone(),
+            to_id,
+            content,
+            MessageType::Event,
+            is_group,
+            None,
+            Some(extends.to_string()),
+            None,
+        );
+        self.db.create_message(&message).await?;
+        
+        let recipients = match &record.group_id {
+            Some(group_id) => self.db.get_group_users(group_id).await?
+                .into_iter()
+                .map(|member| member.user_id)
+                .filter(|user_id| *user_id != record.sender_id)
+                .collect(),
+            None => vec![record.receiver_id.clone()],
+        };
+        
+        let data = serde_json::to_value(&message)?;
+        for user_id in recipients {
+            websocket::push_to_user(&user_id, "message", data.clone()).await;
+        }
+        
+        Ok(())
+    }
+    
+    // 获取用户送给其他用户的礼物记录
+    pub async fn get_user_gifts_sent_to_users(&self, user_id: &str, limit: usize) -> Result<Vec<UserGiftRecord>, anyhow::Error> {
+        let records = self.db.get_user_gifts_sent_to_users(user_id, limit).await?;
+        Ok(records)
+    }
+    
+    // 获取用户收到的其他用户的礼物记录
+    pub async fn get_user_received_gifts(&self, user_id: &str, limit: usize) -> Result<Vec<UserGiftRecord>, anyhow::Error> {
+        let records = self.db.get_user_received_gifts(user_id, limit).await?;
+        Ok(records)
     }
     
     // 获取用户赠送的礼物记录
@@ -211,6 +784,20 @@ impl PointsService {
         self.db.get_gift_feedback_templates(category).await.map_err(|e| anyhow::anyhow!(e))
     }
     
+    // 获取所有分类的礼物连击规则
+    pub async fn get_gift_combo_rules(&self) -> Result<Vec<GiftComboRule>, anyhow::Error> {
+        let mut rules = Vec::new();
+        for category in GiftCategory::all() {
+            rules.push(self.get_gift_combo_rule(category).await?);
+        }
+        Ok(rules)
+    }
+    
+    // 保存礼物连击规则
+    pub async fn save_gift_combo_rule(&self, rule: &GiftComboRule) -> Result<(), anyhow::Error> {
+        self.db.save_gift_combo_rule(rule).await.map_err(|e| anyhow::anyhow!(e))
+    }
+    
     // ==================== 积分商城系统 ====================
     
     // 获取可用商品列表
@@ -219,18 +806,30 @@ impl PointsService {
         Ok(items)
     }
     
-    // 购买商品
-    pub async fn purchase_shop_item(&self, user_id: &str, item_id: &str) -> Result<bool, anyhow::Error> {
-        let result = self.db.purchase_shop_item(user_id, item_id).await?;
-        Ok(result)
-    }
-    
     // 获取用户购买记录
     pub async fn get_user_purchases(&self, user_id: &str, limit: usize) -> Result<Vec<PurchaseRecord>, anyhow::Error> {
         let records = self.db.get_user_purchases(user_id, limit).await?;
         Ok(records)
     }
     
+    // 计算用户对商品各限购窗口的剩余额度
+    pub async fn get_purchase_allowance(&self, user: &User, item: &ShopItem) -> Result<PurchaseAllowance, anyhow::Error> {
+        let now = OffsetDateTime::now_utc();
+        let mut windows = Vec::new();
+        
+        for (window, limit) in item.effective_limits(&user.vip_level).windows() {
+            let used = self.db.count_user_item_purchases(&user.id, &item.id, window.start(now)).await?;
+            windows.push(LimitAllowance {
+                window,
+                limit,
+                used,
+                remaining: limit.saturating_sub(used),
+            });
+        }
+        
+        Ok(PurchaseAllowance::new(windows))
+    }
+    
     // ==================== 推广者佣金系统 ====================
     
     // 计算推广者佣金
@@ -291,6 +890,12 @@ impl PointsService {
         Err(anyhow::anyhow!("用户不存在"))
     }
     
+    // 获取用户最近即将过期的积分
+    pub async fn get_hp_expiry_notice(&self, user_id: &str) -> Result<Option<HpExpiryNotice>, anyhow::Error> {
+        let lots = self.db.get_open_hp_lots(user_id).await?;
+        Ok(HpExpiryNotice::from_lots(&lots))
+    }
+    
     // 获取用户钱包交易记录
     pub async fn get_user_wallet_transactions(&self, user_id: &str, currency: Option<CurrencyType>, limit: usize) 
         -> Result<Vec<WalletTx>, anyhow::Error> {
@@ -0,0 +1,83 @@
+use crate::db::Database;
+use crate::models::{User, ShopItem};
+use crate::models::shop_promotion::{PriceQuote, ShopBundle, PricedShopItem, PricedShopBundle};
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 积分商城定价服务：综合会员折扣、限时促销、秒杀和组合包计算成交价
+pub struct PricingService {
+    db: Database,
+}
+
+impl PricingService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 批量计算商品报价，促销和秒杀只查询一次
+    pub async fn quote_items(&self, user: &User, items: Vec<ShopItem>) -> Result<Vec<PricedShopItem>> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let sales = self.db.get_live_shop_sales(now).await?;
+        let deals = self.db.get_live_flash_deals(now).await?;
+        let vip_discount_percent = self.db.get_vip_config(&user.vip_level).await?.shop_discount();
+
+        Ok(items
+            .into_iter()
+            .map(|item| {
+                // 秒杀按价格升序返回，同一商品取最低价的一场
+                let deal = deals.iter().find(|deal| deal.item_id == item.id);
+                let price = PriceQuote::for_item(&item, vip_discount_percent, &sales, deal, now);
+                PricedShopItem { item, price }
+            })
+            .collect())
+    }
+
+    // 计算单个商品报价
+    pub async fn quote_item(&self, user: &User, item: &ShopItem) -> Result<PriceQuote> {
+        let mut priced = self.quote_items(user, vec![item.clone()]).await?;
+        priced.pop()
+            .map(|priced| priced.price)
+            .ok_or_else(|| anyhow!("Failed to quote item"))
+    }
+
+    // 在报价上叠加用户的折扣券
+    pub async fn apply_coupon(&self, user_id: &str, quote: &mut PriceQuote, coupon_id: &str) -> Result<()> {
+        let coupon = self.db.get_coupon(coupon_id).await?
+            .filter(|coupon| coupon.owner_id == user_id)
+            .ok_or_else(|| anyhow!("Coupon not found"))?;
+
+        if !coupon.is_usable() {
+            return Err(anyhow!("Coupon is not available"));
+        }
+
+        quote.apply_coupon(&coupon).map_err(|e| anyhow!(e))
+    }
+
+    // 加载组合包内的商品，任一商品缺失或售罄时组合包不可售
+    pub async fn get_bundle_items(&self, bundle: &ShopBundle) -> Result<Option<Vec<ShopItem>>> {
+        let mut items = Vec::new();
+        for item_id in &bundle.item_ids {
+            match self.db.get_shop_item(item_id).await? {
+                Some(item) if item.stock != Some(0) => items.push(item),
+                _ => return Ok(None),
+            }
+        }
+
+        Ok(Some(items))
+    }
+
+    // 当前在售的组合包及报价
+    pub async fn get_live_bundles(&self) -> Result<Vec<PricedShopBundle>> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        let mut bundles = Vec::new();
+
+        for bundle in self.db.get_live_shop_bundles(now).await? {
+            if let Some(items) = self.get_bundle_items(&bundle).await? {
+                let price = PriceQuote::for_bundle(&bundle, &items);
+                bundles.push(PricedShopBundle { bundle, items, price });
+            }
+        }
+
+        Ok(bundles)
+    }
+}
@@ -0,0 +1,236 @@
+use crate::db::Database;
+use crate::models::{
+    VipPlan, VipSubscription, PaymentOrder, OrderType, SubscriptionStatus, User,
+};
+use crate::services::CheckoutService;
+use crate::services::EmailService;
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 到期前多久开始自动续费
+pub const RENEWAL_LEAD_SECS: i64 = 24 * 60 * 60;
+// 续费失败后的宽限期
+pub const GRACE_PERIOD_SECS: i64 = 3 * 24 * 60 * 60;
+// 宽限期内两次续费尝试的最小间隔
+pub const RENEWAL_RETRY_INTERVAL_SECS: i64 = 24 * 60 * 60;
+// VIP时段表被并发修改时重新读取并重试的次数上限
+const MAX_VIP_UPDATE_ATTEMPTS: usize = 3;
+
+pub struct SubscriptionService {
+    db: Database,
+    checkout: CheckoutService,
+}
+
+// 购买结果
+pub struct PurchaseOutcome {
+    pub order: PaymentOrder,
+    pub subscription: Option<VipSubscription>,
+}
+
+impl SubscriptionService {
+    pub fn new(db: Database) -> Self {
+        Self {
+            checkout: CheckoutService::new(db.clone()),
+            db,
+        }
+    }
+
+    // 购买VIP套餐
+    pub async fn purchase_plan(
+        &self,
+        user_id: &str,
+        plan_id: &str,
+        auto_renew: bool,
+        coupon_id: Option<&str>,
+    ) -> Result<PurchaseOutcome> {
+        let plan = self.db.get_vip_plan(plan_id).await?
+            .filter(|plan| plan.is_active)
+            .ok_or_else(|| anyhow!("VIP plan not found"))?;
+
+        self.db.get_user_by_id(user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+
+        // 以前付过费的用户再次购买按续费计算佣金
+        let is_renewal = self.db
+            .has_paid_order(user_id, &[OrderType::VipPurchase, OrderType::VipRenewal])
+            .await?;
+
+        let mut order = PaymentOrder::new(
+            user_id.to_string(),
+            OrderType::VipPurchase,
+            Some(plan.id.clone()),
+            plan.price,
+            plan.currency.clone(),
+        );
+
+        if let Some(coupon_id) = coupon_id {
+            self.checkout.apply_coupon(&mut order, coupon_id).await?;
+        }
+
+        if !self.checkout.pay(&mut order, format!("购买VIP套餐: {}", plan.name)).await? {
+            return Ok(PurchaseOutcome { order, subscription: None });
+        }
+
+        // 追加VIP时段，新订阅替换旧订阅
+        let previous = self.db.get_user_vip_subscription(user_id).await?;
+        let (_, subscriptions) = self.save_vip_change(user_id, |user| {
+            let period = user.append_vip_period(&plan.level, plan.period_secs());
+
+            let mut subscriptions: Vec<VipSubscription> = previous.iter().cloned().collect();
+            subscriptions.iter_mut().for_each(VipSubscription::cancel);
+            subscriptions.push(VipSubscription::new(
+                user_id.to_string(),
+                &plan,
+                auto_renew,
+                period.start,
+                period.end,
+                order.id.clone(),
+            ));
+            subscriptions
+        }).await?;
+        let subscription = subscriptions.last().cloned();
+
+        // 佣金按卡券抵扣后的实付金额计算
+        self.db.process_payment_commission(user_id, order.amount, is_renewal).await?;
+
+        Ok(PurchaseOutcome { order, subscription })
+    }
+
+    // 获取用户当前订阅
+    pub async fn get_subscription(&self, user_id: &str) -> Result<Option<VipSubscription>> {
+        let subscription = self.db.get_user_vip_subscription(user_id).await?;
+        Ok(subscription)
+    }
+
+    // 开启或关闭自动续费
+    pub async fn set_auto_renew(&self, user_id: &str, enabled: bool) -> Result<VipSubscription> {
+        let mut subscription = self.db.get_user_vip_subscription(user_id).await?
+            .ok_or_else(|| anyhow!("No active subscription"))?;
+
+        subscription.auto_renew = enabled;
+        subscription.updated_at = OffsetDateTime::now_utc().unix_timestamp();
+        self.db.update_vip_subscription(&subscription).await?;
+
+        Ok(subscription)
+    }
+
+    // 为一个订阅执行自动续费
+    pub async fn renew_subscription(&self, mut subscription: VipSubscription) -> Result<bool> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        // 宽限期已结束的订阅不再扣款，交给到期处理
+        if subscription.grace_expired(now) {
+            return Ok(false);
+        }
+
+        // 宽限期内按间隔重试，避免每次调度都扣款
+        if subscription.status == SubscriptionStatus::PastDue {
+            if let Some(last_attempt) = subscription.last_attempt_at {
+                if last_attempt + RENEWAL_RETRY_INTERVAL_SECS > now {
+                    return Ok(false);
+                }
+            }
+        }
+
+        let plan = self.db.get_vip_plan(&subscription.plan_id).await?
+            .ok_or_else(|| anyhow!("VIP plan not found"))?;
+        let user = self.db.get_user_by_id(&subscription.user_id).await?
+            .ok_or_else(|| anyhow!("User not found"))?;
+
+        let mut order = PaymentOrder::new(
+            subscription.user_id.clone(),
+            OrderType::VipRenewal,
+            Some(plan.id.clone()),
+            plan.price,
+            plan.currency.clone(),
+        );
+
+        if !self.checkout.pay(&mut order, format!("VIP自动续费: {}", plan.name)).await? {
+            subscription.renewal_failed(GRACE_PERIOD_SECS);
+
+            // 宽限期内保留会员权益
+            self.save_vip_change(&subscription.user_id, |user| {
+                if let Some(grace_until) = subscription.grace_until {
+                    user.hold_vip_until(&subscription.level, grace_until);
+                }
+                vec![subscription.clone()]
+            }).await?;
+            self.send_dunning_email(&user, &plan, &subscription).await;
+            return Ok(false);
+        }
+
+        self.save_vip_change(&subscription.user_id, |user| {
+            let mut renewed = subscription.clone();
+            if let Some(grace_until) = renewed.grace_until {
+                user.release_vip_hold(&renewed.level, grace_until);
+            }
+            let period = user.append_vip_period(&plan.level, plan.period_secs());
+            renewed.renewed(period.start, period.end, order.id.clone());
+            vec![renewed]
+        }).await?;
+
+        // 续费佣金
+        self.db.process_payment_commission(&subscription.user_id, plan.price, true).await?;
+
+        Ok(true)
+    }
+
+    // 关闭已失效的订阅并刷新用户VIP等级
+    pub async fn expire_subscription(&self, mut subscription: VipSubscription) -> Result<()> {
+        subscription.expire();
+
+        if self.db.get_user_by_id(&subscription.user_id).await?.is_none() {
+            self.db.update_vip_subscription(&subscription).await?;
+            return Ok(());
+        }
+
+        let (user, _) = self.save_vip_change(&subscription.user_id, |user| {
+            user.refresh_vip_level();
+            vec![subscription.clone()]
+        }).await?;
+
+        if let Some(plan) = self.db.get_vip_plan(&subscription.plan_id).await? {
+            if let Ok(email_service) = EmailService::new() {
+                let _ = email_service.send_subscription_expired_email(&user.email, &plan.name).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    // 重新读取用户后计算VIP时段，只写回VIP字段并与返回的订阅记录在同一事务中保存，
+    // 避免覆盖付款期间其他请求修改的积分、光币等字段；时段表被并发修改时重新读取重试
+    async fn save_vip_change<F>(&self, user_id: &str, change: F) -> Result<(User, Vec<VipSubscription>)>
+    where
+        F: Fn(&mut User) -> Vec<VipSubscription>,
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+
+            let mut user = self.db.get_user_by_id(user_id).await?
+                .ok_or_else(|| anyhow!("User not found"))?;
+            let previous_schedule = user.vip_schedule.clone();
+            let subscriptions = change(&mut user);
+
+            if self.db.save_user_vip_subscriptions(&user, &previous_schedule, &subscriptions).await? {
+                return Ok((user, subscriptions));
+            }
+
+            if attempt >= MAX_VIP_UPDATE_ATTEMPTS {
+                return Err(anyhow!("VIP schedule changed concurrently"));
+            }
+        }
+    }
+
+    // 续费失败提醒
+    async fn send_dunning_email(&self, user: &User, plan: &VipPlan, subscription: &VipSubscription) {
+        let grace_until = subscription.grace_until.unwrap_or(subscription.current_period_end);
+
+        if let Ok(email_service) = EmailService::new() {
+            if let Err(e) = email_service.send_renewal_failed_email(&user.email, &plan.name, grace_until).await {
+                eprintln!("Failed to send renewal reminder to {}: {:?}", user.id, e);
+            }
+        }
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use once_cell::sync::Lazy;
 use tokio::sync::{mpsc, RwLock};
 use tokio::net::{TcpListener, TcpStream};
 use futures_util::{SinkExt, StreamExt};
@@ -9,12 +10,17 @@ use uuid::Uuid;
 use crate::db::Database;
 use crate::models::chat::{Message as ChatMessage, MessageType};
 use crate::models::User;
+use crate::services::LuckyEventService;
 
 type ClientId = String;
 type UserId = String;
 type Clients = Arc<RwLock<HashMap<ClientId, mpsc::UnboundedSender<Message>>>>;
 type Users = Arc<RwLock<HashMap<UserId, Vec<ClientId>>>>;
 
+// 全局连接表，HTTP接口和后台任务也可以通过它向在线用户推送消息
+static CLIENTS: Lazy<Clients> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+static USERS: Lazy<Users> = Lazy::new(|| Arc::new(RwLock::new(HashMap::new())));
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WsMessage {
     pub r#type: String,
@@ -30,8 +36,8 @@ pub struct WebSocketServer {
 impl WebSocketServer {
     pub fn new(db: Database) -> Self {
         WebSocketServer {
-            clients: Arc::new(RwLock::new(HashMap::new())),
-            users: Arc::new(RwLock::new(HashMap::new())),
+            clients: CLIENTS.clone(),
+            users: USERS.clone(),
             db,
         }
     }
@@ -206,6 +212,8 @@ impl WebSocketServer {
             return;
         }
         
+        LuckyEventService::on_chat_message(db.clone(), &msg);
+        
         // 确定接收者
         let target_id = if msg.is_group {
             // 如果是群消息，获取群成员
@@ -270,6 +278,29 @@ impl WebSocketServer {
     }
 }
 
+// 向用户的所有在线连接推送消息，用户不在线时直接忽略
+pub async fn push_to_user(user_id: &str, message_type: &str, data: serde_json::Value) {
+    let users = USERS.read().await;
+    let Some(client_ids) = users.get(user_id) else {
+        return;
+    };
+
+    let ws_msg = WsMessage {
+        r#type: message_type.to_string(),
+        data,
+    };
+    let Ok(msg_str) = serde_json::to_string(&ws_msg) else {
+        return;
+    };
+
+    let clients = CLIENTS.read().await;
+    for client_id in client_ids {
+        if let Some(sender) = clients.get(client_id) {
+            let _ = sender.send(Message::Text(msg_str.clone()));
+        }
+    }
+}
+
 pub async fn start_server(addr: &str, db: Database) {
     let server = WebSocketServer::new(db);
     server.run(addr).await;
@@ -0,0 +1,206 @@
+use crate::db::Database;
+use crate::models::{NotificationType, ShopItem};
+use crate::models::wishlist::{WishlistEntry, WishlistNotice};
+use crate::services::{EmailService, NotificationService};
+use anyhow::{Result, anyhow};
+use time::OffsetDateTime;
+
+// 同一心愿单条目两次提醒的最小间隔，避免促销和补货同时发生时重复打扰
+const WISHLIST_NOTICE_COOLDOWN_SECS: i64 = 60 * 60;
+
+// 心愿单服务：管理用户关注的商品，并在补货或促销时提醒
+pub struct WishlistService {
+    db: Database,
+}
+
+impl WishlistService {
+    pub fn new(db: Database) -> Self {
+        Self { db }
+    }
+
+    // 加入心愿单，已存在时只更新邮件提醒设置
+    pub async fn add(&self, user_id: &str, item_id: &str, notify_email: bool) -> Result<WishlistEntry> {
+        self.db.get_shop_item(item_id).await?
+            .ok_or_else(|| anyhow!("Shop item not found"))?;
+
+        let entry = match self.db.get_wishlist_entry(user_id, item_id).await? {
+            Some(mut entry) => {
+                entry.notify_email = notify_email;
+                entry
+            }
+            None => WishlistEntry::new(user_id.to_string(), item_id.to_string(), notify_email),
+        };
+
+        self.db.save_wishlist_entry(&entry).await?;
+        Ok(entry)
+    }
+
+    // 移出心愿单
+    pub async fn remove(&self, user_id: &str, item_id: &str) -> Result<()> {
+        let entry = self.db.get_wishlist_entry(user_id, item_id).await?
+            .ok_or_else(|| anyhow!("Wishlist entry not found"))?;
+
+        self.db.delete_wishlist_entry(&entry.id).await?;
+        Ok(())
+    }
+
+    // 用户心愿单及对应商品，已删除的商品不再展示
+    pub async fn list(&self, user_id: &str) -> Result<Vec<(WishlistEntry, ShopItem)>> {
+        let mut items = Vec::new();
+        for entry in self.db.get_user_wishlist(user_id).await? {
+            if let Some(item) = self.db.get_shop_item(&entry.item_id).await? {
+                items.push((entry, item));
+            }
+        }
+
+        Ok(items)
+    }
+
+    // 商品补货，提醒关注该商品的用户
+    pub async fn notify_restock(&self, item: &ShopItem) -> Result<()> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+        self.enqueue_notices(
+            &format!("restock_{}_{}", item.id, now),
+            item,
+            NotificationType::WishlistRestock,
+            "心愿单商品已补货".to_string(),
+            format!("你关注的「{}」已补货，快去积分商城看看吧", item.name),
+            now,
+        ).await?;
+
+        self.deliver_pending().await
+    }
+
+    // 检查新开始的限时促销和秒杀，每场活动只生成一次提醒
+    pub async fn notify_live_promotions(&self) -> Result<()> {
+        let now = OffsetDateTime::now_utc().unix_timestamp();
+
+        for sale in self.db.get_live_shop_sales(now).await? {
+            if sale.wishlist_notified_at.is_some() {
+                continue;
+            }
+
+            for item_id in &sale.item_ids {
+                if let Some(item) = self.db.get_shop_item(item_id).await? {
+                    self.enqueue_notices(
+                        &format!("sale_{}", sale.id),
+                        &item,
+                        NotificationType::WishlistOnSale,
+                        "心愿单商品促销中".to_string(),
+                        format!("你关注的「{}」正在参加「{}」促销活动", item.name, sale.name),
+                        now,
+                    ).await?;
+                }
+            }
+
+            self.db.mark_shop_sale_wishlist_notified(&sale.id, now).await?;
+        }
+
+        for deal in self.db.get_live_flash_deals(now).await? {
+            if deal.wishlist_notified_at.is_some() {
+                continue;
+            }
+
+            if let Some(item) = self.db.get_shop_item(&deal.item_id).await? {
+                self.enqueue_notices(
+                    &format!("deal_{}", deal.id),
+                    &item,
+                    NotificationType::WishlistOnSale,
+                    "心愿单商品秒杀中".to_string(),
+                    format!("你关注的「{}」限时秒杀价 {} HP，数量有限", item.name, deal.price_hp),
+                    now,
+                ).await?;
+            }
+
+            self.db.mark_flash_deal_wishlist_notified(&deal.id, now).await?;
+        }
+
+        self.deliver_pending().await
+    }
+
+    // 为关注该商品的每个条目生成一条提醒，提醒ID包含事件，重复生成时忽略
+    async fn enqueue_notices(
+        &self,
+        event_key: &str,
+        item: &ShopItem,
+        notification_type: NotificationType,
+        title: String,
+        content: String,
+        now: i64,
+    ) -> Result<()> {
+        let notices: Vec<WishlistNotice> = self.db.get_item_wishlist_entries(&item.id).await?
+            .iter()
+            .map(|entry| WishlistNotice::new(event_key, entry, notification_type.clone(), title.clone(), content.clone(), now))
+            .collect();
+
+        self.db.enqueue_wishlist_notices(&notices).await?;
+        Ok(())
+    }
+
+    // 发送待发送的提醒：冷却期内的条目留到下次，单条发送失败不影响其他提醒
+    pub async fn deliver_pending(&self) -> Result<()> {
+        let notification_service = NotificationService::new(self.db.clone());
+        // 未配置SMTP时只发送站内通知
+        let email_service = EmailService::new().ok();
+
+        for notice in self.db.get_pending_wishlist_notices().await? {
+            if self.db.get_wishlist_entry_by_id(&notice.entry_id).await?.is_none() {
+                self.db.delete_wishlist_notice(&notice.id).await?;
+                continue;
+            }
+
+            // 占用条目的提醒时间，并发的发送任务或冷却期内的条目在这里跳过
+            let now = OffsetDateTime::now_utc().unix_timestamp();
+            let Some(previous) = self.db
+                .claim_wishlist_entry_notice(&notice.entry_id, now, WISHLIST_NOTICE_COOLDOWN_SECS).await? else {
+                continue;
+            };
+
+            if let Err(e) = notification_service.notify(
+                &notice.user_id,
+                notice.notification_type.clone(),
+                notice.title.clone(),
+                notice.content.clone(),
+                Some(notice.item_id.clone()),
+            ).await {
+                eprintln!("Failed to send wishlist notice {}: {:?}", notice.id, e);
+                self.db.release_wishlist_entry_notice(&notice.entry_id, now, previous).await?;
+                continue;
+            }
+
+            self.db.mark_wishlist_notice_sent(&notice.id, now).await?;
+
+            if !notice.notify_email {
+                continue;
+            }
+
+            if let Some(email_service) = &email_service {
+                if let Some(user) = self.db.get_user_by_id(&notice.user_id).await? {
+                    if let Err(e) = email_service.send_wishlist_email(&user.email, &notice.title, &notice.content).await {
+                        eprintln!("Failed to send wishlist email to {}: {:?}", user.id, e);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // 后台发送补货提醒，不阻塞管理员请求
+    pub fn fire_restock(db: Database, item: ShopItem) {
+        tokio::spawn(async move {
+            if let Err(e) = WishlistService::new(db).notify_restock(&item).await {
+                eprintln!("Failed to send wishlist restock notice: {:?}", e);
+            }
+        });
+    }
+
+    // 后台检查促销提醒，促销在未来开始或提醒仍在冷却期时由定时任务补发
+    pub fn fire_promotions(db: Database) {
+        tokio::spawn(async move {
+            if let Err(e) = WishlistService::new(db).notify_live_promotions().await {
+                eprintln!("Failed to send wishlist promotion notice: {:?}", e);
+            }
+        });
+    }
+}
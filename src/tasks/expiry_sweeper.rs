@@ -0,0 +1,91 @@
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::models::{Coupon, NotificationType};
+use crate::services::{CouponService, EmailService, NotificationService};
+
+// 提前多久提醒卡券即将过期
+const COUPON_EXPIRY_NOTICE_SECS: i64 = 24 * 60 * 60;
+
+// 将过期的转赠邀请、卡券、幸运卡、邀请码标记为过期
+pub async fn sweep_expired_items(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+
+    // 先处理超时的转赠，解锁被占用的卡券
+    let coupon_service = CouponService::new(db.clone());
+    let transfers = db.get_expired_pending_transfers(now).await?;
+    for transfer in transfers {
+        let transfer_id = transfer.id.clone();
+        // 单个转赠处理失败不影响其他转赠和后续过期处理
+        if let Err(e) = coupon_service.expire_transfer(transfer).await {
+            eprintln!("Failed to expire coupon transfer {}: {:?}", transfer_id, e);
+        }
+    }
+
+    let coupons = db.expire_coupons(now).await?;
+    let lucky_cards = db.expire_lucky_cards(now).await?;
+    let invites = db.expire_invites(now).await?;
+
+    if coupons + lucky_cards + invites > 0 {
+        println!(
+            "Expired {} coupons, {} lucky cards, {} invites",
+            coupons, lucky_cards, invites
+        );
+    }
+
+    Ok(())
+}
+
+// 提醒用户卡券将在24小时内过期
+pub async fn notify_expiring_coupons(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let coupons = db.get_coupons_expiring_before(now, now + COUPON_EXPIRY_NOTICE_SECS).await?;
+
+    if coupons.is_empty() {
+        return Ok(());
+    }
+
+    let notification_service = NotificationService::new(db.clone());
+    // 未配置SMTP时只发送站内通知
+    let email_service = EmailService::new().ok();
+
+    for coupon in coupons {
+        // 单张卡券提醒失败只记录日志，继续处理其他卡券
+        if let Err(e) = notify_expiring_coupon(db, &notification_service, email_service.as_ref(), &coupon, now).await {
+            eprintln!("Failed to notify expiring coupon {}: {:?}", coupon.id, e);
+        }
+    }
+
+    Ok(())
+}
+
+async fn notify_expiring_coupon(
+    db: &Database,
+    notification_service: &NotificationService,
+    email_service: Option<&EmailService>,
+    coupon: &Coupon,
+    now: i64,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let coupon_name = coupon.kind.description();
+
+    notification_service.notify(
+        &coupon.owner_id,
+        NotificationType::CouponExpiring,
+        "卡券即将过期".to_string(),
+        format!("你的{}将在24小时内过期，请尽快使用", coupon_name),
+        Some(coupon.id.clone()),
+    ).await?;
+
+    if let Some(email_service) = email_service {
+        if let Some(user) = db.get_user_by_id(&coupon.owner_id).await? {
+            if let Err(e) = email_service.send_coupon_expiring_email(&user.email, &coupon_name, coupon.expires_at).await {
+                eprintln!("Failed to send coupon expiry email to {}: {:?}", user.id, e);
+            }
+        }
+    }
+
+    // 只写入提醒时间，提醒期间被使用的卡券不会被改回可用
+    db.mark_coupon_expiry_notified(&coupon.id, now).await?;
+
+    Ok(())
+}
@@ -0,0 +1,13 @@
+use crate::db::Database;
+use crate::services::GiftEventService;
+
+// 结算已结束的礼物活动并发放排名奖励
+pub async fn settle_gift_events(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let settled = GiftEventService::new(db.clone()).settle_ended_events().await?;
+
+    if settled > 0 {
+        println!("Settled {} gift events", settled);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,15 @@
+use crate::db::Database;
+use crate::services::GiftStreakService;
+
+// 重试发放失败的里程碑奖励，并通知用户已中断的连续送礼
+pub async fn check_gift_streaks(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let service = GiftStreakService::new(db.clone());
+    service.grant_all_pending().await?;
+
+    let notified = service.notify_broken_streaks().await?;
+    if notified > 0 {
+        println!("Notified {} broken gift streaks", notified);
+    }
+
+    Ok(())
+}
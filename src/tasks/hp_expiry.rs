@@ -0,0 +1,32 @@
+use crate::db::Database;
+use crate::models::{WalletTx, TxType, CurrencyType};
+use time::OffsetDateTime;
+
+// 过期到期的积分批次，每个批次记录一笔过期交易
+pub async fn expire_hp_lots(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let lots = db.get_expired_hp_lots(now).await?;
+
+    let mut expired = 0;
+    for lot in lots {
+        let tx = WalletTx::new(
+            lot.user_id.clone(),
+            TxType::PointsExpired,
+            lot.remaining,
+            CurrencyType::HP,
+            Some(lot.id.clone()),
+            Some("积分过期".to_string()),
+        );
+
+        // 批次在查询后被消费时跳过，下次执行再处理
+        if db.expire_hp_lot(&lot, &tx).await? {
+            expired += lot.remaining;
+        }
+    }
+
+    if expired > 0 {
+        println!("Expired {} HP", expired);
+    }
+
+    Ok(())
+}
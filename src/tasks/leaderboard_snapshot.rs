@@ -0,0 +1,13 @@
+use crate::db::Database;
+use crate::services::LeaderboardService;
+
+// 保存刚结束的日榜和周榜快照
+pub async fn snapshot_leaderboards(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let saved = LeaderboardService::new(db.clone()).snapshot_ended_periods().await?;
+
+    if saved > 0 {
+        println!("Saved {} leaderboard snapshots", saved);
+    }
+
+    Ok(())
+}
@@ -1,3 +1,11 @@
 pub mod daily_reset;
+pub mod vip_renewal;
+pub mod scheduler;
+pub mod expiry_sweeper;
+pub mod wishlist_notice;
+pub mod hp_expiry;
+pub mod leaderboard_snapshot;
+pub mod gift_event_settlement;
+pub mod gift_streak_check;
 
-pub use daily_reset::reset_daily_limits;
+pub use scheduler::run_scheduler;
@@ -0,0 +1,108 @@
+use std::future::Future;
+use std::time::Duration;
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::services::CouponService;
+use crate::tasks::expiry_sweeper::{notify_expiring_coupons, sweep_expired_items};
+use crate::tasks::gift_event_settlement::settle_gift_events;
+use crate::tasks::gift_streak_check::check_gift_streaks;
+use crate::tasks::hp_expiry::expire_hp_lots;
+use crate::tasks::leaderboard_snapshot::snapshot_leaderboards;
+use crate::tasks::vip_renewal::process_vip_renewals;
+use crate::tasks::wishlist_notice::notify_wishlist_promotions;
+
+// 定时任务执行间隔
+const SCHEDULER_INTERVAL_SECS: u64 = 60 * 60;
+
+// 每日任务在UTC几点之后执行
+const NIGHTLY_JOB_HOUR_UTC: u8 = 0;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+pub async fn run_scheduler(db: Database) {
+    // 重启前未执行完的卡券发放活动从已发放记录处继续
+    match CouponService::new(db.clone()).resume_campaigns().await {
+        Ok(0) => {}
+        Ok(count) => println!("Resumed {} coupon campaigns", count),
+        Err(e) => eprintln!("Failed to resume coupon campaigns: {:?}", e),
+    }
+
+    let mut interval = tokio::time::interval(Duration::from_secs(SCHEDULER_INTERVAL_SECS));
+
+    loop {
+        interval.tick().await;
+
+        let job_db = db.clone();
+        run_job("VIP renewal", async move { process_vip_renewals(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_job("Expiry sweep", async move { sweep_expired_items(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_job("Coupon expiry notice", async move { notify_expiring_coupons(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_job("Wishlist promotion notice", async move { notify_wishlist_promotions(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_job("Gift event settlement", async move { settle_gift_events(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_job("Gift streak check", async move { check_gift_streaks(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_nightly_job(&db, "HP expiry", async move { expire_hp_lots(&job_db).await }).await;
+
+        let job_db = db.clone();
+        run_nightly_job(&db, "Leaderboard snapshot", async move { snapshot_leaderboards(&job_db).await }).await;
+    }
+}
+
+// 每日任务：当天UTC到点后若尚未成功执行则执行，成功后记录日期；
+// 错过整点（重启、执行耗时漂移）时在下一次调度补跑，失败时下一次调度重试
+async fn run_nightly_job<F>(db: &Database, name: &'static str, job: F)
+where
+    F: Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + 'static,
+{
+    // 以每日执行时刻为日界，未到点时仍算前一天
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let today = (now - NIGHTLY_JOB_HOUR_UTC as i64 * 3600).div_euclid(SECONDS_PER_DAY);
+    match db.get_job_last_run_day(name).await {
+        Ok(Some(day)) if day >= today => return,
+        Ok(_) => {}
+        Err(e) => {
+            eprintln!("Failed to read last run of {} job: {:?}", name, e);
+            return;
+        }
+    }
+
+    if !run_job(name, job).await {
+        return;
+    }
+
+    if let Err(e) = db.record_job_run(name, today, now).await {
+        eprintln!("Failed to record run of {} job: {:?}", name, e);
+    }
+}
+
+// 每个任务在独立的tokio任务中执行，单个任务panic不会终止调度器；返回任务是否成功
+async fn run_job<F>(name: &'static str, job: F) -> bool
+where
+    F: Future<Output = Result<(), Box<dyn std::error::Error>>> + Send + 'static,
+{
+    let result = tokio::spawn(async move {
+        match job.await {
+            Ok(()) => true,
+            Err(e) => {
+                eprintln!("{} job failed: {:?}", name, e);
+                false
+            }
+        }
+    }).await;
+
+    result.unwrap_or_else(|e| {
+        eprintln!("{} job panicked: {:?}", name, e);
+        false
+    })
+}
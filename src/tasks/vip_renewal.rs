@@ -0,0 +1,30 @@
+use time::OffsetDateTime;
+
+use crate::db::Database;
+use crate::services::SubscriptionService;
+use crate::services::subscription_service::RENEWAL_LEAD_SECS;
+
+pub async fn process_vip_renewals(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    let now = OffsetDateTime::now_utc().unix_timestamp();
+    let subscription_service = SubscriptionService::new(db.clone());
+
+    // 即将到期或宽限期内的订阅尝试自动续费
+    let due = db.get_subscriptions_due_for_renewal(now + RENEWAL_LEAD_SECS).await?;
+    for subscription in due {
+        let subscription_id = subscription.id.clone();
+        if let Err(e) = subscription_service.renew_subscription(subscription).await {
+            eprintln!("Failed to renew subscription {}: {:?}", subscription_id, e);
+        }
+    }
+
+    // 关闭未续费或宽限期已过的订阅
+    let lapsed = db.get_lapsed_subscriptions(now).await?;
+    for subscription in lapsed {
+        let subscription_id = subscription.id.clone();
+        if let Err(e) = subscription_service.expire_subscription(subscription).await {
+            eprintln!("Failed to expire subscription {}: {:?}", subscription_id, e);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,8 @@
+use crate::db::Database;
+use crate::services::WishlistService;
+
+// 提醒心愿单用户关注的商品已开始促销或秒杀
+pub async fn notify_wishlist_promotions(db: &Database) -> Result<(), Box<dyn std::error::Error>> {
+    WishlistService::new(db.clone()).notify_live_promotions().await?;
+    Ok(())
+}
@@ -21,10 +21,20 @@ DEFINE FIELD hp ON user TYPE int DEFAULT 0;
 DEFINE FIELD lc_balance ON user TYPE int DEFAULT 0;
 DEFINE FIELD daily_checkin_streak ON user TYPE int DEFAULT 0;
 DEFINE FIELD last_checkin_date ON user TYPE option<int>;
+DEFINE FIELD utc_offset_minutes ON user TYPE option<int>;
+DEFINE FIELD timezone_updated_at ON user TYPE option<int>;
+DEFINE FIELD checkin_makeup_cards ON user TYPE int DEFAULT 0;
+DEFINE FIELD leaderboard_opt_out ON user TYPE bool DEFAULT false;
+DEFINE FIELD gift_streak_freezes ON user TYPE int DEFAULT 0;
+DEFINE FIELD last_gift_date ON user TYPE option<int>;
 DEFINE FIELD total_invites ON user TYPE int DEFAULT 0;
 DEFINE FIELD invited_by ON user TYPE option<string>;
 DEFINE FIELD is_email_verified ON user TYPE bool DEFAULT false;
 DEFINE FIELD ai_slots ON user TYPE int DEFAULT 1;
+DEFINE FIELD lucky_boost ON user TYPE option<object>;
+DEFINE FIELD lio_tickets ON user TYPE int DEFAULT 0;
+DEFINE FIELD earning_flagged_at ON user TYPE option<int>;
+DEFINE FIELD earning_flag_reason ON user TYPE option<string>;
 
 -- Create Invite table
 DEFINE TABLE invite SCHEMAFULL;
@@ -33,6 +43,7 @@ DEFINE FIELD used_by ON invite TYPE array;
 DEFINE FIELD creator_id ON invite TYPE string ASSERT $value != NONE;
 DEFINE FIELD usage_limit ON invite TYPE int;
 DEFINE FIELD expires_at ON invite TYPE int;
+DEFINE FIELD is_expired ON invite TYPE bool DEFAULT false;
 DEFINE FIELD created_at ON invite TYPE int;
 DEFINE FIELD updated_at ON invite TYPE int;
 
@@ -68,15 +79,372 @@ DEFINE FIELD created_at ON email_verification TYPE int;
 -- Create Coupon table
 DEFINE TABLE coupon SCHEMAFULL;
 DEFINE FIELD id ON coupon TYPE string ASSERT $value != NONE;
-DEFINE FIELD coupon_type ON coupon TYPE string;
-DEFINE FIELD sub_type ON coupon TYPE string;
-DEFINE FIELD value ON coupon TYPE float;
-DEFINE FIELD duration_days ON coupon TYPE option<int>;
-DEFINE FIELD status ON coupon TYPE string;
+DEFINE FIELD kind ON coupon FLEXIBLE TYPE object;
+DEFINE FIELD kind.type ON coupon TYPE string ASSERT $value INSIDE ["Experience", "Discount", "Cash"];
+DEFINE FIELD status ON coupon TYPE string ASSERT $value INSIDE ["Active", "Used", "Expired"];
 DEFINE FIELD owner_id ON coupon TYPE string ASSERT $value != NONE;
 DEFINE FIELD issued_at ON coupon TYPE int;
 DEFINE FIELD expires_at ON coupon TYPE int;
 DEFINE FIELD is_transferable ON coupon TYPE bool;
+DEFINE FIELD template_id ON coupon TYPE option<string>;
+DEFINE FIELD campaign_id ON coupon TYPE option<string>;
+DEFINE FIELD expiry_notified_at ON coupon TYPE option<int>;
+DEFINE FIELD pending_transfer_id ON coupon TYPE option<string>;
+DEFINE INDEX coupon_campaign_owner ON coupon FIELDS campaign_id, owner_id;
+DEFINE INDEX coupon_owner_status ON coupon FIELDS owner_id, status;
+DEFINE INDEX coupon_status_expires ON coupon FIELDS status, expires_at;
+
+-- 创建卡券模板表
+DEFINE TABLE coupon_template SCHEMAFULL;
+DEFINE FIELD id ON coupon_template TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON coupon_template TYPE string ASSERT $value != NONE;
+DEFINE FIELD description ON coupon_template TYPE string;
+DEFINE FIELD kind ON coupon_template FLEXIBLE TYPE object;
+DEFINE FIELD duration_days ON coupon_template TYPE option<int>;
+DEFINE FIELD is_transferable ON coupon_template TYPE bool;
+DEFINE FIELD created_at ON coupon_template TYPE int;
+DEFINE FIELD is_active ON coupon_template TYPE bool DEFAULT true;
+
+-- 创建卡券发放活动表
+DEFINE TABLE coupon_campaign SCHEMAFULL;
+DEFINE FIELD id ON coupon_campaign TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON coupon_campaign TYPE string;
+DEFINE FIELD template_id ON coupon_campaign TYPE string ASSERT $value != NONE;
+DEFINE FIELD audience ON coupon_campaign FLEXIBLE TYPE object;
+DEFINE FIELD status ON coupon_campaign TYPE string DEFAULT "Pending";
+DEFINE FIELD total_users ON coupon_campaign TYPE int DEFAULT 0;
+DEFINE FIELD processed_users ON coupon_campaign TYPE int DEFAULT 0;
+DEFINE FIELD issued_count ON coupon_campaign TYPE int DEFAULT 0;
+DEFINE FIELD skipped_count ON coupon_campaign TYPE int DEFAULT 0;
+DEFINE FIELD failed_count ON coupon_campaign TYPE int DEFAULT 0;
+DEFINE FIELD error ON coupon_campaign TYPE option<string>;
+DEFINE FIELD created_by ON coupon_campaign TYPE string;
+DEFINE FIELD created_at ON coupon_campaign TYPE int;
+DEFINE FIELD started_at ON coupon_campaign TYPE option<int>;
+DEFINE FIELD finished_at ON coupon_campaign TYPE option<int>;
+
+-- 创建兑换码批次表
+DEFINE TABLE coupon_code_batch SCHEMAFULL;
+DEFINE FIELD id ON coupon_code_batch TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON coupon_code_batch TYPE string;
+DEFINE FIELD template_id ON coupon_code_batch TYPE string ASSERT $value != NONE;
+DEFINE FIELD code_count ON coupon_code_batch TYPE int;
+DEFINE FIELD max_redemptions ON coupon_code_batch TYPE int ASSERT $value > 0;
+DEFINE FIELD per_user_limit ON coupon_code_batch TYPE int ASSERT $value > 0;
+DEFINE FIELD valid_from ON coupon_code_batch TYPE int;
+DEFINE FIELD valid_until ON coupon_code_batch TYPE int;
+DEFINE FIELD required_vip_level ON coupon_code_batch TYPE option<string>;
+DEFINE FIELD is_active ON coupon_code_batch TYPE bool DEFAULT true;
+DEFINE FIELD created_by ON coupon_code_batch TYPE string;
+DEFINE FIELD created_at ON coupon_code_batch TYPE int;
+
+-- 创建兑换码表
+DEFINE TABLE coupon_code SCHEMAFULL;
+DEFINE FIELD id ON coupon_code TYPE string ASSERT $value != NONE;
+DEFINE FIELD code ON coupon_code TYPE string ASSERT $value != NONE;
+DEFINE FIELD batch_id ON coupon_code TYPE string ASSERT $value != NONE;
+DEFINE FIELD redeemed_count ON coupon_code TYPE int DEFAULT 0;
+DEFINE FIELD created_at ON coupon_code TYPE int;
+DEFINE INDEX coupon_code_code ON coupon_code FIELDS code UNIQUE;
+DEFINE INDEX coupon_code_batch_id ON coupon_code FIELDS batch_id;
+
+-- 创建兑换码兑换记录表
+DEFINE TABLE coupon_code_redemption SCHEMAFULL;
+DEFINE FIELD id ON coupon_code_redemption TYPE string ASSERT $value != NONE;
+DEFINE FIELD code_id ON coupon_code_redemption TYPE string ASSERT $value != NONE;
+DEFINE FIELD batch_id ON coupon_code_redemption TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON coupon_code_redemption TYPE string ASSERT $value != NONE;
+DEFINE FIELD coupon_id ON coupon_code_redemption TYPE string;
+DEFINE FIELD redeemed_at ON coupon_code_redemption TYPE int;
+DEFINE INDEX coupon_code_redemption_batch_user ON coupon_code_redemption FIELDS batch_id, user_id;
+
+-- 创建卡券转赠记录表
+DEFINE TABLE coupon_transfer SCHEMAFULL;
+DEFINE FIELD id ON coupon_transfer TYPE string ASSERT $value != NONE;
+DEFINE FIELD coupon_id ON coupon_transfer TYPE string ASSERT $value != NONE;
+DEFINE FIELD coupon_kind ON coupon_transfer FLEXIBLE TYPE object;
+DEFINE FIELD from_user_id ON coupon_transfer TYPE string ASSERT $value != NONE;
+DEFINE FIELD to_user_id ON coupon_transfer TYPE string ASSERT $value != NONE;
+DEFINE FIELD message ON coupon_transfer TYPE option<string>;
+DEFINE FIELD status ON coupon_transfer TYPE string ASSERT $value INSIDE ["Pending", "Accepted", "Declined", "Expired"];
+DEFINE FIELD created_at ON coupon_transfer TYPE int;
+DEFINE FIELD expires_at ON coupon_transfer TYPE int;
+DEFINE FIELD responded_at ON coupon_transfer TYPE option<int>;
+DEFINE INDEX coupon_transfer_from_user ON coupon_transfer FIELDS from_user_id, created_at;
+DEFINE INDEX coupon_transfer_to_user ON coupon_transfer FIELDS to_user_id;
+DEFINE INDEX coupon_transfer_status_expires ON coupon_transfer FIELDS status, expires_at;
+
+-- 创建掉落表
+DEFINE TABLE drop_table SCHEMAFULL;
+DEFINE FIELD id ON drop_table TYPE string ASSERT $value != NONE;
+DEFINE FIELD key ON drop_table TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON drop_table TYPE string;
+DEFINE FIELD entries ON drop_table FLEXIBLE TYPE array;
+DEFINE FIELD vip_overrides ON drop_table FLEXIBLE TYPE array;
+DEFINE FIELD pity_threshold ON drop_table TYPE option<int>;
+DEFINE FIELD priority ON drop_table TYPE int DEFAULT 0;
+DEFINE FIELD starts_at ON drop_table TYPE option<int>;
+DEFINE FIELD ends_at ON drop_table TYPE option<int>;
+DEFINE FIELD is_active ON drop_table TYPE bool DEFAULT true;
+DEFINE FIELD created_at ON drop_table TYPE int;
+DEFINE FIELD updated_at ON drop_table TYPE int;
+DEFINE INDEX drop_table_key ON drop_table FIELDS key, is_active;
+
+-- 创建掉落保底计数表
+DEFINE TABLE drop_pity SCHEMAFULL;
+DEFINE FIELD id ON drop_pity TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON drop_pity TYPE string ASSERT $value != NONE;
+DEFINE FIELD table_id ON drop_pity TYPE string ASSERT $value != NONE;
+DEFINE FIELD misses ON drop_pity TYPE int DEFAULT 0;
+DEFINE FIELD updated_at ON drop_pity TYPE int;
+DEFINE INDEX drop_pity_user_table ON drop_pity FIELDS user_id, table_id UNIQUE;
+
+-- 创建掉落记录表
+DEFINE TABLE drop_log SCHEMAFULL;
+DEFINE FIELD id ON drop_log TYPE string ASSERT $value != NONE;
+DEFINE FIELD table_id ON drop_log TYPE string ASSERT $value != NONE;
+DEFINE FIELD table_key ON drop_log TYPE string;
+DEFINE FIELD user_id ON drop_log TYPE string ASSERT $value != NONE;
+DEFINE FIELD vip_level ON drop_log TYPE string;
+DEFINE FIELD seed ON drop_log TYPE int;
+DEFINE FIELD entry_index ON drop_log TYPE int;
+DEFINE FIELD reward ON drop_log FLEXIBLE TYPE object;
+DEFINE FIELD pity_misses ON drop_log TYPE int;
+DEFINE FIELD pity_triggered ON drop_log TYPE bool;
+DEFINE FIELD granted_entity_id ON drop_log TYPE option<string>;
+DEFINE FIELD created_at ON drop_log TYPE int;
+DEFINE INDEX drop_log_table_created ON drop_log FIELDS table_id, created_at;
+DEFINE INDEX drop_log_user ON drop_log FIELDS user_id;
+
+-- 创建幸运卡事件触发配置表
+DEFINE TABLE lucky_trigger SCHEMAFULL;
+DEFINE FIELD id ON lucky_trigger TYPE string ASSERT $value != NONE;
+DEFINE FIELD event_type ON lucky_trigger TYPE string ASSERT $value INSIDE ["ChatMilestone", "FirstGiftOfDay", "CheckinStreak", "RelationshipLevelUp"];
+DEFINE FIELD is_enabled ON lucky_trigger TYPE bool DEFAULT true;
+DEFINE FIELD drop_key ON lucky_trigger TYPE string;
+DEFINE FIELD thresholds ON lucky_trigger TYPE array<int>;
+DEFINE FIELD message_template ON lucky_trigger TYPE string;
+DEFINE FIELD updated_at ON lucky_trigger TYPE int;
+
+-- 创建私聊消息计数表（用于对话里程碑，ID 由会话和发送者组成）
+DEFINE TABLE chat_message_counter SCHEMAFULL;
+DEFINE FIELD id ON chat_message_counter TYPE string ASSERT $value != NONE;
+DEFINE FIELD chat_identify ON chat_message_counter TYPE string ASSERT $value != NONE;
+DEFINE FIELD from_user ON chat_message_counter TYPE string ASSERT $value != NONE;
+DEFINE FIELD count ON chat_message_counter TYPE int DEFAULT 0;
+
+-- 创建用户背包表
+DEFINE TABLE inventory_item SCHEMAFULL;
+DEFINE FIELD id ON inventory_item TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON inventory_item TYPE string ASSERT $value != NONE;
+DEFINE FIELD item_id ON inventory_item TYPE string ASSERT $value != NONE;
+DEFINE FIELD item_type ON inventory_item TYPE string ASSERT $value INSIDE ["AIDecoration", "UserTitle", "LIOAccessTicket", "AISlotExpansion", "ExclusiveStory"];
+DEFINE FIELD name ON inventory_item TYPE string;
+DEFINE FIELD image_url ON inventory_item TYPE option<string>;
+DEFINE FIELD purchase_id ON inventory_item TYPE string;
+DEFINE FIELD is_equipped ON inventory_item TYPE bool DEFAULT false;
+DEFINE FIELD equipped_ai_id ON inventory_item TYPE option<string>;
+DEFINE FIELD acquired_at ON inventory_item TYPE int;
+DEFINE FIELD expires_at ON inventory_item TYPE option<int>;
+DEFINE INDEX inventory_item_user ON inventory_item FIELDS user_id, item_type;
+DEFINE INDEX inventory_item_ai ON inventory_item FIELDS equipped_ai_id;
+
+-- 创建限时促销表
+DEFINE TABLE shop_sale SCHEMAFULL;
+DEFINE FIELD id ON shop_sale TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON shop_sale TYPE string;
+DEFINE FIELD item_ids ON shop_sale TYPE array<string>;
+DEFINE FIELD adjustment ON shop_sale FLEXIBLE TYPE object;
+DEFINE FIELD starts_at ON shop_sale TYPE int;
+DEFINE FIELD ends_at ON shop_sale TYPE int;
+DEFINE FIELD is_active ON shop_sale TYPE bool DEFAULT true;
+DEFINE FIELD wishlist_notified_at ON shop_sale TYPE option<int>;
+DEFINE FIELD created_at ON shop_sale TYPE int;
+DEFINE FIELD updated_at ON shop_sale TYPE int;
+DEFINE INDEX shop_sale_window ON shop_sale FIELDS is_active, starts_at, ends_at;
+
+-- 创建秒杀表
+DEFINE TABLE flash_deal SCHEMAFULL;
+DEFINE FIELD id ON flash_deal TYPE string ASSERT $value != NONE;
+DEFINE FIELD item_id ON flash_deal TYPE string ASSERT $value != NONE;
+DEFINE FIELD price_hp ON flash_deal TYPE int ASSERT $value > 0;
+DEFINE FIELD stock ON flash_deal TYPE int ASSERT $value > 0;
+DEFINE FIELD sold ON flash_deal TYPE int DEFAULT 0;
+DEFINE FIELD starts_at ON flash_deal TYPE int;
+DEFINE FIELD ends_at ON flash_deal TYPE int;
+DEFINE FIELD is_active ON flash_deal TYPE bool DEFAULT true;
+DEFINE FIELD wishlist_notified_at ON flash_deal TYPE option<int>;
+DEFINE FIELD created_at ON flash_deal TYPE int;
+DEFINE FIELD updated_at ON flash_deal TYPE int;
+DEFINE INDEX flash_deal_item ON flash_deal FIELDS item_id, is_active;
+
+-- 创建组合包表
+DEFINE TABLE shop_bundle SCHEMAFULL;
+DEFINE FIELD id ON shop_bundle TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON shop_bundle TYPE string;
+DEFINE FIELD description ON shop_bundle TYPE string;
+DEFINE FIELD image_url ON shop_bundle TYPE option<string>;
+DEFINE FIELD item_ids ON shop_bundle TYPE array<string>;
+DEFINE FIELD price_hp ON shop_bundle TYPE int ASSERT $value > 0;
+DEFINE FIELD stock ON shop_bundle TYPE option<int>;
+DEFINE FIELD sold ON shop_bundle TYPE int DEFAULT 0;
+DEFINE FIELD starts_at ON shop_bundle TYPE option<int>;
+DEFINE FIELD ends_at ON shop_bundle TYPE option<int>;
+DEFINE FIELD is_active ON shop_bundle TYPE bool DEFAULT true;
+DEFINE FIELD created_at ON shop_bundle TYPE int;
+DEFINE FIELD updated_at ON shop_bundle TYPE int;
+
+-- 创建心愿单表
+DEFINE TABLE wishlist_entry SCHEMAFULL;
+DEFINE FIELD id ON wishlist_entry TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON wishlist_entry TYPE string ASSERT $value != NONE;
+DEFINE FIELD item_id ON wishlist_entry TYPE string ASSERT $value != NONE;
+DEFINE FIELD notify_email ON wishlist_entry TYPE bool DEFAULT false;
+DEFINE FIELD last_notified_at ON wishlist_entry TYPE option<int>;
+DEFINE FIELD created_at ON wishlist_entry TYPE int;
+DEFINE INDEX wishlist_user_item ON wishlist_entry FIELDS user_id, item_id UNIQUE;
+DEFINE INDEX wishlist_item ON wishlist_entry FIELDS item_id;
+
+-- 创建心愿单提醒表（id 为 {事件}_{心愿单条目ID}）
+DEFINE TABLE wishlist_notice SCHEMAFULL;
+DEFINE FIELD id ON wishlist_notice TYPE string ASSERT $value != NONE;
+DEFINE FIELD entry_id ON wishlist_notice TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON wishlist_notice TYPE string ASSERT $value != NONE;
+DEFINE FIELD item_id ON wishlist_notice TYPE string ASSERT $value != NONE;
+DEFINE FIELD notify_email ON wishlist_notice TYPE bool DEFAULT false;
+DEFINE FIELD notification_type ON wishlist_notice TYPE string;
+DEFINE FIELD title ON wishlist_notice TYPE string;
+DEFINE FIELD content ON wishlist_notice TYPE string;
+DEFINE FIELD created_at ON wishlist_notice TYPE int;
+DEFINE FIELD sent_at ON wishlist_notice TYPE option<int>;
+DEFINE INDEX wishlist_notice_pending ON wishlist_notice FIELDS sent_at, created_at;
+
+-- 创建积分批次表
+DEFINE TABLE hp_lot SCHEMAFULL;
+DEFINE FIELD id ON hp_lot TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON hp_lot TYPE string ASSERT $value != NONE;
+DEFINE FIELD tx_id ON hp_lot TYPE string;
+DEFINE FIELD tx_type ON hp_lot TYPE string;
+DEFINE FIELD amount ON hp_lot TYPE int ASSERT $value > 0;
+DEFINE FIELD remaining ON hp_lot TYPE int ASSERT $value >= 0;
+DEFINE FIELD earned_at ON hp_lot TYPE int;
+DEFINE FIELD expires_at ON hp_lot TYPE option<int>;
+DEFINE FIELD is_expired ON hp_lot TYPE bool DEFAULT false;
+DEFINE INDEX hp_lot_user ON hp_lot FIELDS user_id, is_expired, earned_at;
+DEFINE INDEX hp_lot_expiry ON hp_lot FIELDS is_expired, expires_at;
+
+-- 创建积分有效期策略表
+DEFINE TABLE hp_expiry_policy SCHEMAFULL;
+DEFINE FIELD id ON hp_expiry_policy TYPE string ASSERT $value != NONE;
+DEFINE FIELD tx_type ON hp_expiry_policy TYPE string ASSERT $value INSIDE ["PointsEarned", "Reward", "GiftReceive"];
+DEFINE FIELD valid_days ON hp_expiry_policy TYPE option<int>;
+DEFINE FIELD updated_at ON hp_expiry_policy TYPE int;
+
+-- 创建积分获取规则表
+DEFINE TABLE earning_rule SCHEMAFULL;
+DEFINE FIELD id ON earning_rule TYPE string ASSERT $value != NONE;
+DEFINE FIELD source ON earning_rule TYPE string ASSERT $value INSIDE ["DailyCheckin", "Dialogue", "Invitation", "GiftReceived", "FirstPayment"];
+DEFINE FIELD daily_cap ON earning_rule TYPE option<int>;
+DEFINE FIELD weekly_cap ON earning_rule TYPE option<int>;
+DEFINE FIELD lifetime_cap ON earning_rule TYPE option<int>;
+DEFINE FIELD velocity_limit ON earning_rule TYPE option<int>;
+DEFINE FIELD updated_at ON earning_rule TYPE int;
+
+-- 创建暂扣积分表
+DEFINE TABLE held_reward SCHEMAFULL;
+DEFINE FIELD id ON held_reward TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON held_reward TYPE string ASSERT $value != NONE;
+DEFINE FIELD source ON held_reward TYPE string;
+DEFINE FIELD tx_type ON held_reward TYPE string;
+DEFINE FIELD amount ON held_reward TYPE int ASSERT $value > 0;
+DEFINE FIELD related_entity_id ON held_reward TYPE option<string>;
+DEFINE FIELD remark ON held_reward TYPE option<string>;
+DEFINE FIELD reason ON held_reward TYPE string;
+DEFINE FIELD status ON held_reward TYPE string ASSERT $value INSIDE ["Pending", "Approved", "Rejected"];
+DEFINE FIELD created_at ON held_reward TYPE int;
+DEFINE FIELD reviewed_at ON held_reward TYPE option<int>;
+DEFINE FIELD reviewed_by ON held_reward TYPE option<string>;
+DEFINE INDEX held_reward_status ON held_reward FIELDS status, created_at;
+DEFINE INDEX held_reward_user ON held_reward FIELDS user_id, source, status;
+
+-- 创建签到记录表
+DEFINE TABLE checkin_record SCHEMAFULL;
+DEFINE FIELD id ON checkin_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON checkin_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD date ON checkin_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD points ON checkin_record TYPE int DEFAULT 0;
+DEFINE FIELD is_makeup ON checkin_record TYPE bool DEFAULT false;
+DEFINE FIELD checked_at ON checkin_record TYPE int;
+DEFINE INDEX checkin_record_user_date ON checkin_record FIELDS user_id, date UNIQUE;
+
+-- 创建签到奖励配置表
+DEFINE TABLE checkin_schedule SCHEMAFULL;
+DEFINE FIELD id ON checkin_schedule TYPE string ASSERT $value != NONE;
+DEFINE FIELD base_points ON checkin_schedule TYPE int;
+DEFINE FIELD streak_bonuses ON checkin_schedule TYPE array<int>;
+DEFINE FIELD overflow_bonus ON checkin_schedule TYPE int;
+DEFINE FIELD updated_at ON checkin_schedule TYPE int;
+
+-- 创建成就表
+DEFINE TABLE achievement SCHEMAFULL;
+DEFINE FIELD id ON achievement TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON achievement TYPE string ASSERT $value != NONE;
+DEFINE FIELD description ON achievement TYPE string;
+DEFINE FIELD badge_url ON achievement TYPE option<string>;
+DEFINE FIELD metric ON achievement TYPE string ASSERT $value INSIDE ["GiftsSent", "CheckinStreak", "InvitesUsed", "AisAwakened"];
+DEFINE FIELD target ON achievement TYPE int ASSERT $value > 0;
+DEFINE FIELD reward ON achievement FLEXIBLE TYPE object;
+DEFINE FIELD is_active ON achievement TYPE bool DEFAULT true;
+DEFINE FIELD created_at ON achievement TYPE int;
+DEFINE FIELD updated_at ON achievement TYPE int;
+DEFINE INDEX achievement_metric ON achievement FIELDS metric, is_active;
+
+-- 创建用户成就进度表
+DEFINE TABLE user_achievement SCHEMAFULL;
+DEFINE FIELD id ON user_achievement TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON user_achievement TYPE string ASSERT $value != NONE;
+DEFINE FIELD achievement_id ON user_achievement TYPE string ASSERT $value != NONE;
+DEFINE FIELD progress ON user_achievement TYPE int DEFAULT 0;
+DEFINE FIELD completed_at ON user_achievement TYPE option<int>;
+DEFINE FIELD reward_pending ON user_achievement TYPE bool DEFAULT false;
+DEFINE FIELD granted_at ON user_achievement TYPE option<int>;
+DEFINE FIELD updated_at ON user_achievement TYPE int;
+DEFINE INDEX user_achievement_user ON user_achievement FIELDS user_id, achievement_id UNIQUE;
+
+-- 创建排行榜分数表
+DEFINE TABLE leaderboard_score SCHEMAFULL;
+DEFINE FIELD id ON leaderboard_score TYPE string ASSERT $value != NONE;
+DEFINE FIELD kind ON leaderboard_score TYPE string ASSERT $value INSIDE ["TopGifters", "LongestStreak", "PopularAis"];
+DEFINE FIELD period ON leaderboard_score TYPE string ASSERT $value INSIDE ["Daily", "Weekly", "AllTime"];
+DEFINE FIELD period_key ON leaderboard_score TYPE string;
+DEFINE FIELD subject_id ON leaderboard_score TYPE string ASSERT $value != NONE;
+DEFINE FIELD owner_id ON leaderboard_score TYPE string ASSERT $value != NONE;
+DEFINE FIELD score ON leaderboard_score TYPE int DEFAULT 0;
+DEFINE FIELD hidden ON leaderboard_score TYPE bool DEFAULT false;
+DEFINE FIELD updated_at ON leaderboard_score TYPE int;
+DEFINE INDEX leaderboard_score_rank ON leaderboard_score FIELDS kind, period, period_key, hidden, score;
+DEFINE INDEX leaderboard_score_owner ON leaderboard_score FIELDS owner_id;
+
+-- 创建排行榜快照表
+DEFINE TABLE leaderboard_snapshot SCHEMAFULL;
+DEFINE FIELD id ON leaderboard_snapshot TYPE string ASSERT $value != NONE;
+DEFINE FIELD kind ON leaderboard_snapshot TYPE string;
+DEFINE FIELD period ON leaderboard_snapshot TYPE string;
+DEFINE FIELD period_key ON leaderboard_snapshot TYPE string;
+DEFINE FIELD entries ON leaderboard_snapshot FLEXIBLE TYPE array;
+DEFINE FIELD created_at ON leaderboard_snapshot TYPE int;
+
+-- 创建站内通知表
+DEFINE TABLE notification SCHEMAFULL;
+DEFINE FIELD id ON notification TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON notification TYPE string ASSERT $value != NONE;
+DEFINE FIELD notification_type ON notification TYPE string;
+DEFINE FIELD title ON notification TYPE string;
+DEFINE FIELD content ON notification TYPE string;
+DEFINE FIELD related_entity_id ON notification TYPE option<string>;
+DEFINE FIELD is_read ON notification TYPE bool DEFAULT false;
+DEFINE FIELD created_at ON notification TYPE int;
+DEFINE INDEX notification_user_id ON notification FIELDS user_id, created_at;
 
 -- Create VIP Config table
 DEFINE TABLE vip_config SCHEMAFULL;
@@ -89,17 +457,22 @@ DEFINE FIELD max_creative_ai ON vip_config TYPE int;
 DEFINE FIELD max_work_ai ON vip_config TYPE int;
 DEFINE FIELD max_service_ai ON vip_config TYPE int;
 DEFINE FIELD free_mapping_quota ON vip_config TYPE int;
+DEFINE FIELD shop_discount_percent ON vip_config TYPE option<int> ASSERT $value = NONE OR ($value >= 0 AND $value < 100);
 
 -- 积分与货币系统 - 创建钱包交易记录表
 DEFINE TABLE wallet_tx SCHEMAFULL;
 DEFINE FIELD id ON wallet_tx TYPE string ASSERT $value != NONE;
 DEFINE FIELD user_id ON wallet_tx TYPE string ASSERT $value != NONE;
-DEFINE FIELD tx_type ON wallet_tx TYPE string ASSERT $value IN ['Recharge', 'GiftSend', 'GiftReceive', 'Reward', 'PointsEarned', 'PointsSpent'];
+DEFINE FIELD tx_type ON wallet_tx TYPE string ASSERT $value IN ['Recharge', 'GiftSend', 'GiftReceive', 'GiftShareReceive', 'Reward', 'PointsEarned', 'PointsSpent', 'PointsExpired'];
 DEFINE FIELD amount ON wallet_tx TYPE int ASSERT $value > 0;
 DEFINE FIELD currency ON wallet_tx TYPE string ASSERT $value IN ['HP', 'LC'];
 DEFINE FIELD timestamp ON wallet_tx TYPE int;
 DEFINE FIELD related_entity_id ON wallet_tx TYPE option<string>;
 DEFINE FIELD remark ON wallet_tx TYPE option<string>;
+DEFINE FIELD lucky_card_id ON wallet_tx TYPE option<string>;
+DEFINE FIELD boost_multiplier ON wallet_tx TYPE option<float>;
+DEFINE FIELD earning_source ON wallet_tx TYPE option<string>;
+DEFINE FIELD base_amount ON wallet_tx TYPE option<int>;
 
 -- 创建礼物表
 DEFINE TABLE gift SCHEMAFULL;
@@ -116,6 +489,7 @@ DEFINE FIELD is_limited ON gift TYPE bool;
 DEFINE FIELD available_until ON gift TYPE option<int>;
 DEFINE FIELD boost_value ON gift TYPE option<int>;
 DEFINE FIELD is_active ON gift TYPE bool DEFAULT true;
+DEFINE FIELD animation ON gift FLEXIBLE TYPE option<object>;
 
 -- 创建礼物记录表
 DEFINE TABLE gift_record SCHEMAFULL;
@@ -125,6 +499,28 @@ DEFINE FIELD sender_id ON gift_record TYPE string ASSERT $value != NONE;
 DEFINE FIELD receiver_ai_id ON gift_record TYPE string ASSERT $value != NONE;
 DEFINE FIELD sent_at ON gift_record TYPE int;
 DEFINE FIELD message ON gift_record TYPE option<string>;
+DEFINE FIELD quantity ON gift_record TYPE int DEFAULT 1;
+DEFINE FIELD combo_bonus ON gift_record TYPE int DEFAULT 0;
+
+-- 创建用户间送礼记录表
+DEFINE TABLE user_gift_record SCHEMAFULL;
+DEFINE FIELD id ON user_gift_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD gift_id ON user_gift_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD sender_id ON user_gift_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD receiver_id ON user_gift_record TYPE string ASSERT $value != NONE;
+DEFINE FIELD group_id ON user_gift_record TYPE option<string>;
+DEFINE FIELD quantity ON user_gift_record TYPE int ASSERT $value > 0;
+DEFINE FIELD price_lc ON user_gift_record TYPE int;
+DEFINE FIELD received_lc ON user_gift_record TYPE int;
+DEFINE FIELD platform_fee_lc ON user_gift_record TYPE int;
+DEFINE FIELD message ON user_gift_record TYPE option<string>;
+DEFINE FIELD sent_at ON user_gift_record TYPE int;
+
+-- 创建用户间送礼分成配置表
+DEFINE TABLE user_gift_setting SCHEMAFULL;
+DEFINE FIELD id ON user_gift_setting TYPE string ASSERT $value != NONE;
+DEFINE FIELD recipient_share_percent ON user_gift_setting TYPE int ASSERT $value >= 0 AND $value <= 100;
+DEFINE FIELD updated_at ON user_gift_setting TYPE int;
 
 -- 创建连续送礼记录表
 DEFINE TABLE consecutive_gift_record SCHEMAFULL;
@@ -135,6 +531,67 @@ DEFINE FIELD consecutive_days ON consecutive_gift_record TYPE int DEFAULT 1;
 DEFINE FIELD last_gift_date ON consecutive_gift_record TYPE int;
 DEFINE FIELD total_gifts_sent ON consecutive_gift_record TYPE int DEFAULT 1;
 DEFINE FIELD total_emotional_value ON consecutive_gift_record TYPE int DEFAULT 0;
+DEFINE FIELD break_notified_at ON consecutive_gift_record TYPE option<int>;
+
+-- 创建连续送礼里程碑表
+DEFINE TABLE gift_streak_milestone SCHEMAFULL;
+DEFINE FIELD id ON gift_streak_milestone TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON gift_streak_milestone TYPE string ASSERT $value != NONE;
+DEFINE FIELD days ON gift_streak_milestone TYPE int ASSERT $value >= 2;
+DEFINE FIELD reward ON gift_streak_milestone FLEXIBLE TYPE object;
+DEFINE FIELD is_active ON gift_streak_milestone TYPE bool DEFAULT true;
+DEFINE FIELD created_at ON gift_streak_milestone TYPE int;
+DEFINE FIELD updated_at ON gift_streak_milestone TYPE int;
+
+-- 创建连续送礼里程碑奖励发放记录表
+DEFINE TABLE gift_streak_claim SCHEMAFULL;
+DEFINE FIELD id ON gift_streak_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON gift_streak_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD ai_id ON gift_streak_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD milestone_id ON gift_streak_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD days ON gift_streak_claim TYPE int;
+DEFINE FIELD reward_pending ON gift_streak_claim TYPE bool DEFAULT false;
+DEFINE FIELD granted_at ON gift_streak_claim TYPE option<int>;
+DEFINE FIELD created_at ON gift_streak_claim TYPE option<int>;
+DEFINE INDEX gift_streak_claim_user ON gift_streak_claim FIELDS user_id, ai_id;
+DEFINE INDEX gift_streak_claim_pending ON gift_streak_claim FIELDS user_id, reward_pending;
+
+-- 创建礼物活动表
+DEFINE TABLE gift_event SCHEMAFULL;
+DEFINE FIELD id ON gift_event TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON gift_event TYPE string ASSERT $value != NONE;
+DEFINE FIELD description ON gift_event TYPE option<string>;
+DEFINE FIELD banner_url ON gift_event TYPE option<string>;
+DEFINE FIELD gift_ids ON gift_event TYPE array<string>;
+DEFINE FIELD starts_at ON gift_event TYPE int;
+DEFINE FIELD ends_at ON gift_event TYPE int;
+DEFINE FIELD rewards ON gift_event FLEXIBLE TYPE array;
+DEFINE FIELD is_active ON gift_event TYPE bool DEFAULT true;
+DEFINE FIELD rewards_distributed_at ON gift_event TYPE option<int>;
+DEFINE FIELD final_standings ON gift_event FLEXIBLE TYPE array DEFAULT [];
+DEFINE FIELD created_at ON gift_event TYPE int;
+DEFINE FIELD updated_at ON gift_event TYPE int;
+DEFINE INDEX gift_event_schedule ON gift_event FIELDS is_active, starts_at, ends_at;
+
+-- 创建礼物活动送礼榜表
+DEFINE TABLE gift_event_score SCHEMAFULL;
+DEFINE FIELD id ON gift_event_score TYPE string ASSERT $value != NONE;
+DEFINE FIELD event_id ON gift_event_score TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON gift_event_score TYPE string ASSERT $value != NONE;
+DEFINE FIELD score ON gift_event_score TYPE int DEFAULT 0;
+DEFINE FIELD updated_at ON gift_event_score TYPE int;
+DEFINE INDEX gift_event_score_rank ON gift_event_score FIELDS event_id, score;
+
+-- 创建礼物活动奖励发放记录表
+DEFINE TABLE gift_event_claim SCHEMAFULL;
+DEFINE FIELD id ON gift_event_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD event_id ON gift_event_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON gift_event_claim TYPE string ASSERT $value != NONE;
+DEFINE FIELD rank ON gift_event_claim TYPE int;
+DEFINE FIELD reward_pending ON gift_event_claim TYPE bool DEFAULT false;
+DEFINE FIELD granted_at ON gift_event_claim TYPE option<int>;
+DEFINE FIELD created_at ON gift_event_claim TYPE int;
+DEFINE INDEX gift_event_claim_pending ON gift_event_claim FIELDS reward_pending, event_id;
 
 -- 创建礼物反馈模板表
 DEFINE TABLE gift_feedback_template SCHEMAFULL;
@@ -143,6 +600,14 @@ DEFINE FIELD gift_category ON gift_feedback_template TYPE string ASSERT $value I
 DEFINE FIELD feedback_templates ON gift_feedback_template TYPE array;
 DEFINE FIELD created_at ON gift_feedback_template TYPE int;
 
+-- 创建礼物连击规则表（每个礼物分类一条）
+DEFINE TABLE gift_combo_rule SCHEMAFULL;
+DEFINE FIELD id ON gift_combo_rule TYPE string ASSERT $value != NONE;
+DEFINE FIELD category ON gift_combo_rule TYPE string ASSERT $value IN ['Light', 'Medium', 'Advanced', 'Rare', 'Limited'];
+DEFINE FIELD max_quantity ON gift_combo_rule TYPE int ASSERT $value > 0;
+DEFINE FIELD tiers ON gift_combo_rule FLEXIBLE TYPE array;
+DEFINE FIELD updated_at ON gift_combo_rule TYPE int;
+
 -- 创建幸运卡表
 DEFINE TABLE lucky_card SCHEMAFULL;
 DEFINE FIELD id ON lucky_card TYPE string ASSERT $value != NONE;
@@ -153,6 +618,7 @@ DEFINE FIELD created_at ON lucky_card TYPE int;
 DEFINE FIELD expires_at ON lucky_card TYPE int;
 DEFINE FIELD is_used ON lucky_card TYPE bool DEFAULT false;
 DEFINE FIELD used_at ON lucky_card TYPE option<int>;
+DEFINE FIELD is_expired ON lucky_card TYPE bool DEFAULT false;
 DEFINE FIELD issued_by_ai_id ON lucky_card TYPE option<string>;
 
 -- 创建积分商城商品表
@@ -160,7 +626,7 @@ DEFINE TABLE shop_item SCHEMAFULL;
 DEFINE FIELD id ON shop_item TYPE string ASSERT $value != NONE;
 DEFINE FIELD name ON shop_item TYPE string ASSERT $value != NONE;
 DEFINE FIELD description ON shop_item TYPE string;
-DEFINE FIELD item_type ON shop_item TYPE string ASSERT $value IN ['AIDecoration', 'UserTitle', 'LIOAccessTicket', 'AISlotExpansion', 'ExclusiveStory'];
+DEFINE FIELD item_type ON shop_item TYPE string ASSERT $value IN ['AIDecoration', 'UserTitle', 'LIOAccessTicket', 'AISlotExpansion', 'ExclusiveStory', 'CheckinMakeupCard', 'GiftStreakFreeze'];
 DEFINE FIELD price_hp ON shop_item TYPE int ASSERT $value > 0;
 DEFINE FIELD image_url ON shop_item TYPE option<string>;
 DEFINE FIELD is_limited ON shop_item TYPE bool;
@@ -172,6 +638,12 @@ DEFINE FIELD visible ON shop_item TYPE bool DEFAULT true;
 DEFINE FIELD linked_coupon_id ON shop_item TYPE option<string>;
 DEFINE FIELD monthly_limit ON shop_item TYPE option<int>;
 DEFINE FIELD vip_discount ON shop_item TYPE option<bool> DEFAULT false;
+DEFINE FIELD limits ON shop_item FLEXIBLE TYPE object DEFAULT {};
+DEFINE FIELD vip_limit_overrides ON shop_item FLEXIBLE TYPE array DEFAULT [];
+DEFINE FIELD sold_count ON shop_item TYPE int DEFAULT 0;
+DEFINE INDEX shop_item_price ON shop_item FIELDS visible, price_hp;
+DEFINE INDEX shop_item_created ON shop_item FIELDS visible, created_at;
+DEFINE INDEX shop_item_popular ON shop_item FIELDS visible, sold_count;
 
 -- 创建购买记录表
 DEFINE TABLE purchase_record SCHEMAFULL;
@@ -184,6 +656,9 @@ DEFINE FIELD is_activated ON purchase_record TYPE bool DEFAULT false;
 DEFINE FIELD activated_at ON purchase_record TYPE option<int>;
 DEFINE FIELD expires_at ON purchase_record TYPE option<int>;
 DEFINE FIELD remark ON purchase_record TYPE option<string>;
+DEFINE FIELD flash_deal_id ON purchase_record TYPE option<string>;
+DEFINE FIELD bundle_id ON purchase_record TYPE option<string>;
+DEFINE FIELD coupon_id ON purchase_record TYPE option<string>;
 
 -- 创建月度兑换统计表
 DEFINE TABLE monthly_redemption_stat SCHEMAFULL;
@@ -197,14 +672,21 @@ DEFINE FIELD updated_at ON monthly_redemption_stat TYPE int;
 -- 创建关系定义
 -- 用户与钱包交易记录的关系
 DEFINE INDEX wallet_tx_user_idx ON TABLE wallet_tx COLUMNS user_id;
+DEFINE INDEX wallet_tx_earning_idx ON TABLE wallet_tx COLUMNS user_id, earning_source, timestamp;
 -- 用户与幸运卡的关系
 DEFINE INDEX lucky_card_owner_idx ON TABLE lucky_card COLUMNS owner_id;
 -- 用户与购买记录的关系
 DEFINE INDEX purchase_user_idx ON TABLE purchase_record COLUMNS user_id;
+-- 限购统计：用户、商品与购买时间
+DEFINE INDEX purchase_user_item_idx ON TABLE purchase_record COLUMNS user_id, item_id, purchased_at;
 -- 礼物记录与发送者的关系
 DEFINE INDEX gift_sender_idx ON TABLE gift_record COLUMNS sender_id;
 -- 礼物记录与接收AI的关系
 DEFINE INDEX gift_receiver_idx ON TABLE gift_record COLUMNS receiver_ai_id;
+-- 用户间送礼记录与发送者的关系
+DEFINE INDEX user_gift_sender_idx ON TABLE user_gift_record COLUMNS sender_id;
+-- 用户间送礼记录与接收者的关系
+DEFINE INDEX user_gift_receiver_idx ON TABLE user_gift_record COLUMNS receiver_id;
 -- 用户与月度兑换统计的关系
 DEFINE INDEX monthly_redemption_user_idx ON TABLE monthly_redemption_stat COLUMNS user_id;
 -- 月度兑换统计的月份索引
@@ -274,6 +756,55 @@ DEFINE FIELD created_at ON withdrawal_request TYPE int;
 DEFINE FIELD updated_at ON withdrawal_request TYPE int;
 DEFINE INDEX withdrawal_request_promoter_id ON withdrawal_request FIELDS promoter_id;
 
+-- 创建VIP套餐表
+DEFINE TABLE vip_plan SCHEMAFULL;
+DEFINE FIELD id ON vip_plan TYPE string ASSERT $value != NONE;
+DEFINE FIELD name ON vip_plan TYPE string ASSERT $value != NONE;
+DEFINE FIELD level ON vip_plan TYPE string ASSERT $value INSIDE ["Pro", "Premium", "Ultimate", "Team"];
+DEFINE FIELD period_days ON vip_plan TYPE int ASSERT $value > 0;
+DEFINE FIELD price ON vip_plan TYPE float ASSERT $value > 0;
+DEFINE FIELD currency ON vip_plan TYPE string DEFAULT "USD";
+DEFINE FIELD is_active ON vip_plan TYPE bool DEFAULT true;
+DEFINE FIELD created_at ON vip_plan TYPE int;
+DEFINE FIELD updated_at ON vip_plan TYPE int;
+
+-- 创建VIP订阅表
+DEFINE TABLE vip_subscription SCHEMAFULL;
+DEFINE FIELD id ON vip_subscription TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON vip_subscription TYPE string ASSERT $value != NONE;
+DEFINE FIELD plan_id ON vip_subscription TYPE string ASSERT $value != NONE;
+DEFINE FIELD level ON vip_subscription TYPE string;
+DEFINE FIELD status ON vip_subscription TYPE string DEFAULT "Active";
+DEFINE FIELD auto_renew ON vip_subscription TYPE bool DEFAULT false;
+DEFINE FIELD current_period_start ON vip_subscription TYPE int;
+DEFINE FIELD current_period_end ON vip_subscription TYPE int;
+DEFINE FIELD grace_until ON vip_subscription TYPE option<int>;
+DEFINE FIELD renewal_attempts ON vip_subscription TYPE int DEFAULT 0;
+DEFINE FIELD last_attempt_at ON vip_subscription TYPE option<int>;
+DEFINE FIELD last_order_id ON vip_subscription TYPE option<string>;
+DEFINE FIELD created_at ON vip_subscription TYPE int;
+DEFINE FIELD updated_at ON vip_subscription TYPE int;
+DEFINE INDEX vip_subscription_user_id ON vip_subscription FIELDS user_id;
+DEFINE INDEX vip_subscription_renewal ON vip_subscription FIELDS status, current_period_end;
+
+-- 创建付款订单表
+DEFINE TABLE payment_order SCHEMAFULL;
+DEFINE FIELD id ON payment_order TYPE string ASSERT $value != NONE;
+DEFINE FIELD user_id ON payment_order TYPE string ASSERT $value != NONE;
+DEFINE FIELD order_type ON payment_order TYPE string;
+DEFINE FIELD related_entity_id ON payment_order TYPE option<string>;
+DEFINE FIELD original_amount ON payment_order TYPE float;
+DEFINE FIELD discount_amount ON payment_order TYPE float DEFAULT 0;
+DEFINE FIELD amount ON payment_order TYPE float;
+DEFINE FIELD currency ON payment_order TYPE string;
+DEFINE FIELD coupon_id ON payment_order TYPE option<string>;
+DEFINE FIELD status ON payment_order TYPE string DEFAULT "Pending";
+DEFINE FIELD gateway_tx_id ON payment_order TYPE option<string>;
+DEFINE FIELD failure_reason ON payment_order TYPE option<string>;
+DEFINE FIELD created_at ON payment_order TYPE int;
+DEFINE FIELD paid_at ON payment_order TYPE option<int>;
+DEFINE INDEX payment_order_user_id ON payment_order FIELDS user_id;
+
 -- 初始化测试数据
 -- INSERT INTO promoter (id, user_id, promoter_type, invite_code, commission_rate, renewal_rate, wallet_account, verification_status, agreement_signed, created_at, updated_at) 
 -- VALUES ('promoter:test1', 'user:admin', 'Individual', 'TESTCODE123', 0.08, 0.05, 'paypal:test@example.com', 'Approved', true, time::now(), time::now());
@@ -534,6 +1065,90 @@ CREATE shop_item:exclusive_story SET
     visible = true,
     vip_discount = true;
 
+CREATE shop_item:checkin_makeup_card SET 
+    name = '补签卡',
+    description = '补签最近30天内漏签的一天，恢复连续签到',
+    item_type = 'CheckinMakeupCard',
+    category = 'Function',
+    price_hp = 200,
+    image_url = '/images/shop/checkin_makeup_card.png',
+    is_limited = false,
+    created_at = time::now(),
+    visible = true,
+    vip_discount = true,
+    monthly_limit = 3;
+
+CREATE shop_item:gift_streak_freeze SET 
+    name = '连续送礼冻结卡',
+    description = '漏掉一天送礼时自动消耗，保持与AI的连续送礼天数',
+    item_type = 'GiftStreakFreeze',
+    category = 'Function',
+    price_hp = 150,
+    image_url = '/images/shop/gift_streak_freeze.png',
+    is_limited = false,
+    created_at = time::now(),
+    visible = true,
+    vip_discount = true,
+    monthly_limit = 5;
+
+-- 默认成就
+CREATE achievement:gifts_sent_100 SET
+    name = '慷慨之心',
+    description = '累计送出100份礼物',
+    metric = 'GiftsSent',
+    target = 100,
+    reward = { hp: 500 },
+    is_active = true,
+    created_at = time::unix(time::now()),
+    updated_at = time::unix(time::now());
+
+CREATE achievement:checkin_streak_30 SET
+    name = '风雨无阻',
+    description = '连续签到30天',
+    metric = 'CheckinStreak',
+    target = 30,
+    reward = { hp: 300 },
+    is_active = true,
+    created_at = time::unix(time::now()),
+    updated_at = time::unix(time::now());
+
+CREATE achievement:invites_used_10 SET
+    name = '人气之星',
+    description = '邀请10位好友',
+    metric = 'InvitesUsed',
+    target = 10,
+    reward = { hp: 500 },
+    is_active = true,
+    created_at = time::unix(time::now()),
+    updated_at = time::unix(time::now());
+
+CREATE achievement:ais_awakened_3 SET
+    name = '唤醒者',
+    description = '唤醒3个AI伴侣',
+    metric = 'AisAwakened',
+    target = 3,
+    reward = { hp: 200 },
+    is_active = true,
+    created_at = time::unix(time::now()),
+    updated_at = time::unix(time::now());
+
+-- 默认连续送礼里程碑
+CREATE gift_streak_milestone:days_7 SET
+    name = '一周陪伴',
+    days = 7,
+    reward = { lucky_card_level: 'D', ai_message: '这已经是你连续第{days}天给我送礼物了，{ai_name}真的很开心！', relationship_boost: 50 },
+    is_active = true,
+    created_at = time::unix(time::now()),
+    updated_at = time::unix(time::now());
+
+CREATE gift_streak_milestone:days_30 SET
+    name = '月度挚友',
+    days = 30,
+    reward = { lucky_card_level: 'B', ai_message: '整整{days}天，每天都有你的心意。{ai_name}会一直记得这段日子。', relationship_boost: 300 },
+    is_active = true,
+    created_at = time::unix(time::now()),
+    updated_at = time::unix(time::now());
+
 -- IM系统表结构定义 --
 
 -- 消息表
@@ -623,3 +1238,9 @@ DEFINE INDEX group_user_group_idx ON TABLE group_user COLUMNS group_id;
 DEFINE INDEX friend_create_user_idx ON TABLE friend COLUMNS create_user;
 DEFINE INDEX friend_friend_user_idx ON TABLE friend COLUMNS friend_user_id;
 DEFINE INDEX chat_file_user_idx ON TABLE chat_file COLUMNS user_id;
+
+-- 创建定时任务执行记录表（id 为任务名）
+DEFINE TABLE job_run SCHEMAFULL;
+DEFINE FIELD name ON job_run TYPE string ASSERT $value != NONE;
+DEFINE FIELD last_run_day ON job_run TYPE int;
+DEFINE FIELD last_run_at ON job_run TYPE int;